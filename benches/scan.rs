@@ -0,0 +1,91 @@
+//! Entry boundary scanning, compared with the `str::find` it replaces, and
+//! the whole parser on top of it, with and without `with_arena`. The plain text
+//! group compares the ways of finding the end of a run of text within an entry.
+
+#[macro_use]
+extern crate criterion;
+extern crate gcide;
+extern crate memchr;
+
+use criterion::{black_box, Criterion, Throughput};
+use gcide::EntryParser;
+use gcide::scan::{EntryBoundaries, ENTRY_CLOSE, ENTRY_OPEN};
+
+/// Entries shaped like those of the dictionary files, with a stretch of
+/// comments between them.
+fn sample() -> String {
+    let mut text = String::new();
+    for n in 0..2000 {
+        text.push_str(&format!(
+            "<entry main-word=\"Word{0}\" source=\"1913 Webster\">\n<p><ent>Word{0}</ent><br/\n\
+             <hw>Word\"{0}</hw> (<pr>w<ucr/rd</pr>), <pos>n.</pos> <ety>[L. <ets>verbum</ets>.]</ety> \
+             <sn>1.</sn> <def>A sound or combination of sounds, or its representative in writing or \
+             printing, used as the sign of an idea; a term.</def></p>\n\n<p><sn>2.</sn> <def>Talk; \
+             discourse; speech.</def> <q>Why should calamity be full of words?</q> <qau>Shak.</qau></p>\n\
+             </entry>\n\n<-- {0} --> <-- a comment between entries, as in the files -->\n\n", n));
+    }
+    text
+}
+
+fn boundaries(c: &mut Criterion) {
+    let text = sample();
+    let mut group = c.benchmark_group("entry boundaries");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    group.bench_function("str::find", |b| b.iter(|| {
+        let (mut rest, mut count) = (black_box(&text[..]), 0);
+        while let Some(start) = rest.find(ENTRY_OPEN) {
+            let end = start + rest[start..].find(ENTRY_CLOSE).unwrap();
+            rest = &rest[end..];
+            count += 1;
+        }
+        count
+    }));
+    group.bench_function("memchr", |b| {
+        let bounds = EntryBoundaries::new();
+        b.iter(|| {
+            let (mut rest, mut count) = (black_box(&text[..]), 0);
+            while let Some(start) = bounds.find_open(rest) {
+                let end = start + bounds.find_close(&rest[start..]).unwrap();
+                rest = &rest[end..];
+                count += 1;
+            }
+            count
+        })
+    });
+    group.finish();
+}
+
+fn plain_text(c: &mut Criterion) {
+    let text = sample();
+    let mut group = c.benchmark_group("plain text");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    group.bench_function("chars", |b| b.iter(|| {
+        let (mut rest, mut count) = (black_box(&text[..]), 0);
+        while let Some(idx) = rest.find(['<', '>']) {
+            rest = &rest[idx + 1..];
+            count += 1;
+        }
+        count
+    }));
+    group.bench_function("memchr2", |b| b.iter(|| {
+        let (mut rest, mut count) = (black_box(&text[..]), 0);
+        while let Some(idx) = memchr::memchr2(b'<', b'>', rest.as_bytes()) {
+            rest = &rest[idx + 1..];
+            count += 1;
+        }
+        count
+    }));
+    group.finish();
+}
+
+fn parse(c: &mut Criterion) {
+    let text = sample();
+    let mut group = c.benchmark_group("parse");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    group.bench_function("EntryParser", |b| b.iter(|| EntryParser::new(black_box(&text)).count()));
+    group.bench_function("with_arena", |b| b.iter(|| EntryParser::new(black_box(&text)).with_arena().count()));
+    group.finish();
+}
+
+criterion_group!(benches, boundaries, plain_text, parse);
+criterion_main!(benches);
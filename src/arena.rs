@@ -0,0 +1,202 @@
+//! Entries whose items all live in one `Vec`, in document order, instead of a
+//! `Vec` per tag. Batch jobs over the whole dictionary spend less time in the
+//! allocator this way; see `EntryParser::with_arena`.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use std::{iter, vec};
+
+use diagnostics::ParseWarning;
+use parser::{Entry, EntryItem, PairingRules, ALLOWED_TO_DANGLE};
+use tags::TagName;
+
+#[derive(Debug, PartialEq)]
+pub enum Node<'a> {
+    /// A paired tag, followed in `ArenaEntry::nodes` by its `len` descendants.
+    Tag { name: TagName<'a>, source: Option<&'a str>, len: usize },
+    /// Any other item; never `EntryItem::Tagged`.
+    Item(EntryItem<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ArenaEntry<'a> {
+    pub main_word: &'a str,
+    pub source: &'a str,
+    /// Items in document order, each tag before its contents.
+    pub nodes: Vec<Node<'a>>,
+}
+
+impl<'a> ArenaEntry<'a> {
+    /// Indices of the top-level items in `nodes`.
+    pub fn roots(&self) -> impl Iterator<Item=usize> + '_ {
+        self.siblings(0, self.nodes.len())
+    }
+
+    /// Indices of the items directly inside the tag at `idx`; none for other
+    /// items.
+    pub fn children(&self, idx: usize) -> impl Iterator<Item=usize> + '_ {
+        let len = match self.nodes[idx] {
+            Node::Tag { len, .. } => len,
+            Node::Item(_) => 0,
+        };
+        self.siblings(idx + 1, idx + 1 + len)
+    }
+
+    fn siblings(&self, start: usize, end: usize) -> impl Iterator<Item=usize> + '_ {
+        let mut next = start;
+        iter::from_fn(move || {
+            if next >= end {
+                return None;
+            }
+            let idx = next;
+            next += match self.nodes[idx] {
+                Node::Tag { len, .. } => 1 + len,
+                Node::Item(_) => 1,
+            };
+            Some(idx)
+        })
+    }
+
+    /// The same entry as a tree of `EntryItem`s.
+    pub fn into_entry(self) -> Entry<'a> {
+        let len = self.nodes.len();
+        Entry {
+            main_word: self.main_word,
+            items: tree(&mut self.nodes.into_iter(), len),
+            source: self.source,
+            raw: None,
+            id: None,
+        }
+    }
+}
+
+/// Takes `len` nodes from `nodes`, as the items containing them.
+fn tree<'a>(nodes: &mut vec::IntoIter<Node<'a>>, mut len: usize) -> Vec<EntryItem<'a>> {
+    let mut items = Vec::new();
+    while len > 0 {
+        match nodes.next() {
+            Some(Node::Tag { name, source, len: inner }) => {
+                items.push(EntryItem::Tagged { name, items: tree(nodes, inner), source });
+                len -= 1 + inner;
+            }
+            Some(Node::Item(item)) => {
+                items.push(item);
+                len -= 1;
+            }
+            None => break,
+        }
+    }
+    items
+}
+
+/// Pairs up tags like `parser::pair_up_items`, with the same results and
+/// warnings, but into a flat list.
+pub(crate) fn pair_up_nodes<'a>(items: Vec<EntryItem<'a>>,
+                                rules: &PairingRules,
+                                warnings: &mut Vec<ParseWarning<'a>>) -> Vec<Node<'a>> {
+    use parser::EntryItem::*;
+
+    let mut nodes = Vec::with_capacity(items.len());
+    // Indices of the open tags not yet closed nor inside a closed tag.
+    let mut open: Vec<usize> = Vec::new();
+    for item in items {
+        match item {
+            UnpairedTagOpen(name, _) => {
+                for (tag, _) in rules.auto_close.iter().filter(|(_, opener)| name == opener.as_str()) {
+                    if let Some(pos) = find_open_tag(&nodes, &open, TagName::new(tag)) {
+                        let tag = close_open_tag(&mut nodes, &mut open, pos);
+                        warnings.push(ParseWarning::ImplicitlyClosed(tag.as_str()));
+                    }
+                }
+                open.push(nodes.len());
+                nodes.push(Node::Item(item));
+            }
+            UnpairedTagClose(name) => {
+                if let Some(pos) = find_open_tag(&nodes, &open, name) {
+                    if rules.close_at_parent_end {
+                        while let Some(inner) = open[pos + 1..].iter().rposition(|&idx| {
+                            open_tag_name(&nodes[idx]).is_some_and(|name| !ALLOWED_TO_DANGLE.contains(&name))
+                        }) {
+                            let inner = close_open_tag(&mut nodes, &mut open, pos + 1 + inner);
+                            warnings.push(ParseWarning::ImplicitlyClosed(inner.as_str()));
+                        }
+                    }
+                    close_open_tag(&mut nodes, &mut open, pos);
+                } else {
+                    nodes.push(Node::Item(item));
+                }
+            }
+            _ => nodes.push(Node::Item(item)),
+        }
+    }
+    nodes
+}
+
+/// Position in `open` of the innermost open tag `name`.
+fn find_open_tag(nodes: &[Node], open: &[usize], name: TagName) -> Option<usize> {
+    open.iter().rposition(|&idx| open_tag_name(&nodes[idx]).is_some_and(|open| open == name))
+}
+
+/// Turns the open tag at `open[pos]` into a tag holding every node after it,
+/// and returns its name.
+fn close_open_tag<'a>(nodes: &mut [Node<'a>], open: &mut Vec<usize>, pos: usize) -> TagName<'a> {
+    let idx = open[pos];
+    let len = nodes.len() - idx - 1;
+    let (name, source) = match nodes[idx] {
+        Node::Item(EntryItem::UnpairedTagOpen(name, source)) => (name, source),
+        _ => unreachable!("only open tags are kept in `open`"),
+    };
+    nodes[idx] = Node::Tag { name, source, len };
+    open.truncate(pos);
+    name
+}
+
+fn open_tag_name<'a>(node: &Node<'a>) -> Option<TagName<'a>> {
+    match *node {
+        Node::Item(EntryItem::UnpairedTagOpen(name, _)) => Some(name),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use exporter::CIDE;
+    use parser::EntryParser;
+
+    #[test]
+    fn same_as_tree() {
+        let text = "<entry main-word=\"A\" source=\"B\"><p><hw>A</hw> <def>one <i>two</def></i> \
+                    <note>three</p> <p>four <p>five</b></p></entry>\n\
+                    <entry main-word=\"C\" source=\"D\"><p><sn>1.</sn> <def>six</def></p></entry>";
+        let rules = [
+            PairingRules::default(),
+            PairingRules { auto_close: vec![("p".to_string(), "p".to_string())], close_at_parent_end: true },
+        ];
+        for rules in &rules {
+            let mut parser = EntryParser::new(text).with_rules(rules.clone());
+            let mut arena = EntryParser::new(text).with_rules(rules.clone()).with_arena();
+            while let Some(entry) = parser.next() {
+                let entry = entry.unwrap();
+                let arena_entry = arena.next().unwrap().unwrap();
+                let pairing: Vec<_> = parser.warnings().iter()
+                    .filter(|warning| matches!(warning, ParseWarning::ImplicitlyClosed(_)))
+                    .collect();
+                assert_eq!(arena.parser().warnings().iter().collect::<Vec<_>>(), pairing);
+                assert_eq!(CIDE(&arena_entry.into_entry()).to_string(), CIDE(&entry).to_string());
+            }
+            assert!(arena.next().is_none());
+        }
+
+        let entry = EntryParser::new(text).with_arena().nth(1).unwrap().unwrap();
+        let roots: Vec<_> = entry.roots().collect();
+        assert_eq!(roots, [0]);
+        let children: Vec<_> = entry.children(0).map(|idx| &entry.nodes[idx]).collect();
+        assert_eq!(children, [
+            &Node::Tag { name: TagName::Sn, source: None, len: 1 },
+            &Node::Item(EntryItem::PlainText(" ")),
+            &Node::Tag { name: TagName::Def, source: None, len: 1 },
+        ]);
+    }
+}
@@ -0,0 +1,104 @@
+//! Canonical names for the authors cited in `<qau>` and `<au>`.
+
+use model;
+use parser::Entry;
+
+/// Spelling variants and abbreviations used in the dictionary, keyed by the
+/// lowercased citation without its final period.
+const AUTHOR_VARIANTS: &[(&str, &str)] = &[
+    ("shak", "Shakespeare"),
+    ("shaks", "Shakespeare"),
+    ("shakesp", "Shakespeare"),
+    ("b. jonson", "Ben Jonson"),
+    ("beau. & fl", "Beaumont and Fletcher"),
+    ("beau. and fl", "Beaumont and Fletcher"),
+    ("beaumont & fletcher", "Beaumont and Fletcher"),
+    ("sir w. scott", "Walter Scott"),
+    ("w. scott", "Walter Scott"),
+    ("jer. taylor", "Jeremy Taylor"),
+    ("bp. taylor", "Jeremy Taylor"),
+    ("sir t. browne", "Thomas Browne"),
+    ("sir thomas browne", "Thomas Browne"),
+    ("bp. hall", "Joseph Hall"),
+    ("sir p. sidney", "Philip Sidney"),
+    ("mrs. browning", "Elizabeth Barrett Browning"),
+    ("e. b. browning", "Elizabeth Barrett Browning"),
+    ("r. browning", "Robert Browning"),
+    ("sir w. hamilton", "William Hamilton"),
+    ("dr. h. more", "Henry More"),
+    ("h. more", "Henry More"),
+    ("bp. burnet", "Gilbert Burnet"),
+    ("dr. johnson", "Samuel Johnson"),
+    ("johnson", "Samuel Johnson"),
+    ("wycliffe", "Wyclif"),
+    ("wickliffe", "Wyclif"),
+    ("sir w. temple", "William Temple"),
+    ("sir t. more", "Thomas More"),
+    ("sir m. hale", "Matthew Hale"),
+    ("sir j. davies", "John Davies"),
+    ("abp. tillotson", "Tillotson"),
+    ("bp. butler", "Joseph Butler"),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Citation {
+    pub quote: String,
+    /// The author as written in the entry.
+    pub raw_author: String,
+    /// Canonical author name, the same for all spellings of an author.
+    pub author: String,
+    /// The work, if given in parentheses after the author (`Jowett (Thucyd.)`).
+    pub work: Option<String>,
+}
+
+/// Splits a citation such as `Jowett (Thucyd.)` into author and work.
+pub fn split_work(raw: &str) -> (&str, Option<&str>) {
+    let raw = raw.trim();
+    match (raw.find('('), raw.rfind(')')) {
+        (Some(open), Some(close)) if open < close => {
+            (raw[..open].trim(), Some(raw[open + 1..close].trim()))
+        }
+        _ => (raw, None),
+    }
+}
+
+/// Canonical name of the author in a citation; unknown authors are returned
+/// with whitespace collapsed and the final period removed.
+pub fn normalize_author(raw: &str) -> String {
+    let (author, _) = split_work(raw);
+    let cleaned = author.split_whitespace().collect::<Vec<_>>().join(" ");
+    let cleaned = cleaned.trim_end_matches(['.', ',', ';']);
+    let key = cleaned.to_lowercase();
+    AUTHOR_VARIANTS.iter()
+        .find(|&&(variant, _)| variant == key)
+        .map_or_else(|| cleaned.to_string(), |&(_, canonical)| canonical.to_string())
+}
+
+/// Attributed quotations of an entry, in order.
+pub fn citations(entry: &Entry) -> Vec<Citation> {
+    model::senses(entry).into_iter()
+        .flat_map(|sense| sense.quotes)
+        .filter_map(|quote| {
+            let raw_author = quote.author?;
+            let work = split_work(&raw_author).1.map(|work| work.to_string());
+            Some(Citation { author: normalize_author(&raw_author), quote: quote.text, raw_author, work })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn variants() {
+        for raw in &["Shak.", "Shakespeare", "Shakespeare.", " Shak. "] {
+            assert_eq!(normalize_author(raw), "Shakespeare");
+        }
+        assert_eq!(normalize_author("Sir  W. Scott."), "Walter Scott");
+        assert_eq!(normalize_author("Milton."), "Milton");
+        assert_eq!(split_work("Jowett (Thucyd.)"), ("Jowett", Some("Thucyd.")));
+        assert_eq!(normalize_author("Jowett (Thucyd.)"), "Jowett");
+    }
+}
@@ -0,0 +1,61 @@
+use gcide::{binutils, parallel, EntryParser};
+use gcide::binutils::{SourceOpt, UnwrapOrFail};
+use gcide::exporter::{ExportOptions, HeadwordStyle};
+use gcide::exporter::apple::{self, AppleEntry};
+use gcide::exporter::html;
+use gcide::sources;
+use std::path::PathBuf;
+use std::fs;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct AppleOpt {
+    #[structopt(name = "INFILE", help = "GNU CIDE file", parse(from_os_str))]
+    infile: PathBuf,
+    #[structopt(name = "OUTDIR", help = "directory to write the Dictionary Development Kit project to",
+                parse(from_os_str))]
+    out_dir: PathBuf,
+    #[structopt(flatten)]
+    sources: SourceOpt,
+    #[structopt(long = "bundle-id", default_value = "org.gnu.gcide", help = "identifier of the dictionary bundle")]
+    bundle_id: String,
+    #[structopt(long = "name", default_value = "GCIDE", help = "name of the dictionary in Dictionary.app")]
+    name: String,
+    #[structopt(long = "manifest", parse(from_os_str),
+                help = "write sizes, SHA-256 digests and entry counts of INFILE and the outputs to this JSON file")]
+    manifest: Option<PathBuf>,
+}
+
+pub fn run(opt: AppleOpt, jobs: usize) {
+    let filter = opt.sources.filter();
+    let export_opts = ExportOptions { headwords: HeadwordStyle::Clean, ..ExportOptions::default() };
+    let contents = binutils::map_file(&opt.infile).unwrap_or_fail(&opt.infile);
+    let mut xml = String::from(apple::DICTIONARY_HEAD);
+    let entries = EntryParser::new(&contents).with_ids().filter_map(Result::ok);
+    parallel::ordered_map(entries, jobs, |mut entry| {
+        if !sources::retain_sources(&mut entry, &filter) {
+            return String::new();
+        }
+        let id = entry.id.clone().unwrap_or_default();
+        AppleEntry(&entry, &export_opts, &id).to_string()
+    }, |text| xml.push_str(&text));
+    xml.push_str(apple::FRONT_MATTER);
+    xml.push_str(apple::DICTIONARY_TAIL);
+
+    let out_dir = &opt.out_dir;
+    let files = [
+        ("GCIDE.xml", xml),
+        ("GCIDE.css", html::default_stylesheet(&export_opts)),
+        ("GCIDE.plist", apple::info_plist(&opt.bundle_id, &opt.name)),
+        ("Makefile", apple::MAKEFILE.to_string()),
+    ];
+    fs::create_dir_all(out_dir)
+        .and_then(|_| files.iter().try_for_each(|&(name, ref text)| fs::write(out_dir.join(name), text)))
+        .unwrap_or_fail(out_dir);
+    if let Some(ref manifest) = opt.manifest {
+        let outputs: Vec<_> = files.iter()
+            .map(|&(name, ref text)| (out_dir.join(name).display().to_string(), text.as_bytes()))
+            .collect();
+        binutils::write_manifest(manifest, &opt.infile, &contents, &outputs).unwrap_or_fail(manifest);
+    }
+}
@@ -0,0 +1,31 @@
+use gcide::binutils;
+use gcide::binutils::{ErrorRecord, PatchOpt, UnwrapOrFail};
+use gcide::rules::RuleSet;
+use std::cell::RefCell;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct ApplyRulesOpt {
+    #[structopt(flatten)]
+    patch: PatchOpt,
+    #[structopt(long = "rules", parse(from_os_str),
+                help = "TOML file of [[rule]] tables with match, replace and optionally name, source, from and to")]
+    rules: PathBuf,
+}
+
+/// Applies the rewrite rules to every entry, then reports how often each rule
+/// was applied.
+pub fn run(opt: ApplyRulesOpt) {
+    let text = binutils::read_file(&opt.rules).unwrap_or_fail(&opt.rules);
+    let rules = RuleSet::parse(&text).unwrap_or_else(|err| {
+        binutils::fail(ErrorRecord::new("invalid-input", err).in_file(&opt.rules), binutils::EXIT_INVALID_INPUT)
+    });
+    let report = RefCell::new(rules.new_report());
+    binutils::patch_entries_with(&opt.patch, |raw, entry_res| {
+        rules.apply(raw, &entry_res.ok()?, &mut report.borrow_mut())
+    });
+    let report = report.into_inner();
+    eprint!("{}", report);
+    eprintln!("{} replacements", report.total());
+}
@@ -0,0 +1,42 @@
+use gcide::{binutils, EntryParser};
+use gcide::binutils::{SourceOpt, UnwrapOrFail};
+use gcide::exporter::concordance::Concordance;
+use gcide::sources;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct ConcordanceOpt {
+    #[structopt(name = "FILE", help = "GNU CIDE files", parse(from_os_str), required = true)]
+    files: Vec<PathBuf>,
+    #[structopt(short = "o", long = "output", default_value = "-", parse(from_os_str),
+                help = "write the TSV lines (headword, entry, sense numbers) to this file")]
+    output: PathBuf,
+    #[structopt(flatten)]
+    sources: SourceOpt,
+    #[structopt(long = "max-uses", default_value = "1000",
+                help = "leave out headwords used by more entries than this, such as \"the\"")]
+    max_uses: usize,
+}
+
+pub fn run(opt: ConcordanceOpt) {
+    let filter = opt.sources.filter();
+    let contents: Vec<_> = opt.files.iter()
+        .map(|path| binutils::map_file(path).unwrap_or_fail(path))
+        .collect();
+    let mut concordance = Concordance::new();
+    for text in &contents {
+        for entry in EntryParser::new(text).filter_map(Result::ok) {
+            concordance.add_headword(entry.main_word);
+        }
+    }
+    for text in &contents {
+        for mut entry in EntryParser::new(text).filter_map(Result::ok) {
+            if sources::retain_sources(&mut entry, &filter) {
+                concordance.add_entry(&entry);
+            }
+        }
+    }
+    concordance.drop_frequent(opt.max_uses);
+    binutils::write_file(&opt.output, concordance.tsv().as_bytes()).unwrap_or_fail(&opt.output);
+}
@@ -0,0 +1,131 @@
+use gcide::{binutils, parallel, Entry, EntryParser};
+use gcide::authors::normalize_author;
+use gcide::binutils::PipeOpt;
+use gcide::model::{self, Sense, UsageLabel};
+use std::borrow::Cow;
+use std::str::FromStr;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct CsvOpt {
+    #[structopt(flatten)]
+    pipe: PipeOpt,
+    #[structopt(short = "c", long = "columns", default_value = "headword,pos,sense,definition,source,quote",
+                help = "comma-separated columns: id, headword, pos, sense, definition, source, quote, author, canonical_author, labels")]
+    columns: Columns,
+    #[structopt(long = "tsv", help = "separate fields with tabs instead of commas")]
+    tsv: bool,
+    #[structopt(long = "no-header", help = "omit the header row")]
+    no_header: bool,
+    #[structopt(long = "modern-only", help = "skip senses marked obsolete or archaic")]
+    modern_only: bool,
+    #[structopt(long = "collocations",
+                help = "emit one row per collocation (headword, phrase, definition) instead of per sense")]
+    collocations: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Column {
+    Id,
+    Headword,
+    Pos,
+    Sense,
+    Definition,
+    Source,
+    Quote,
+    Author,
+    CanonicalAuthor,
+    Labels,
+}
+
+#[derive(Debug)]
+struct Columns(Vec<(String, Column)>);
+
+impl FromStr for Columns {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Columns, String> {
+        s.split(',').map(|name| {
+            let column = match name.trim() {
+                "id" => Column::Id,
+                "headword" => Column::Headword,
+                "pos" => Column::Pos,
+                "sense" => Column::Sense,
+                "definition" => Column::Definition,
+                "source" => Column::Source,
+                "quote" => Column::Quote,
+                "author" => Column::Author,
+                "canonical_author" => Column::CanonicalAuthor,
+                "labels" => Column::Labels,
+                other => return Err(format!("unknown column: {}", other)),
+            };
+            Ok((name.trim().to_string(), column))
+        }).collect::<Result<_, _>>().map(Columns)
+    }
+}
+
+pub fn run(opt: CsvOpt, jobs: usize) {
+    let delimiter = if opt.tsv { '\t' } else { ',' };
+    binutils::pipe_through_with(&opt.pipe, |contents| {
+        let mut output = String::with_capacity(contents.len() / 2);
+        if opt.collocations {
+            if !opt.no_header {
+                binutils::write_csv_row(&mut output, &["headword", "phrase", "definition"], delimiter);
+            }
+            let entries = EntryParser::new(contents).filter_map(Result::ok);
+            parallel::ordered_map(entries, jobs, |entry| {
+                let mut rows = String::new();
+                for col in model::collocations(&entry) {
+                    binutils::write_csv_row(&mut rows, &[entry.main_word, &col.phrase, &col.definition], delimiter);
+                }
+                rows
+            }, |rows| output.push_str(&rows));
+            return output;
+        }
+        if !opt.no_header {
+            let header: Vec<&str> = opt.columns.0.iter().map(|(name, _)| &name[..]).collect();
+            binutils::write_csv_row(&mut output, &header, delimiter);
+        }
+        let entries = EntryParser::new(contents).with_ids().filter_map(Result::ok);
+        parallel::ordered_map(entries, jobs, |entry| {
+            let mut rows = String::new();
+            for sense in model::senses(&entry) {
+                if opt.modern_only && sense.is_dated() {
+                    continue;
+                }
+                let row: Vec<Cow<str>> = opt.columns.0.iter()
+                    .map(|&(_, column)| field(&entry, &sense, column))
+                    .collect();
+                let row: Vec<&str> = row.iter().map(|field| &field[..]).collect();
+                binutils::write_csv_row(&mut rows, &row, delimiter);
+            }
+            rows
+        }, |rows| output.push_str(&rows));
+        output
+    });
+}
+
+fn field<'s>(entry: &'s Entry, sense: &'s Sense, column: Column) -> Cow<'s, str> {
+    let first_quote = sense.quotes.first();
+    Cow::Borrowed(match column {
+        Column::Id => entry.id.as_deref().unwrap_or(""),
+        Column::Headword => entry.main_word,
+        Column::Pos => sense.pos.as_ref().map_or("", |s| &s[..]),
+        Column::Sense => sense.number.as_ref().map_or("", |s| &s[..]),
+        Column::Definition => &sense.definition,
+        Column::Source => &sense.source,
+        Column::Quote => first_quote.map_or("", |q| &q.text[..]),
+        Column::Author => first_quote.and_then(|q| q.author.as_ref()).map_or("", |s| &s[..]),
+        Column::CanonicalAuthor => return Cow::Owned(first_quote.and_then(|q| q.author.as_ref())
+                                                     .map_or_else(String::new, |a| normalize_author(a))),
+        Column::Labels => return Cow::Owned(sense.labels.iter().map(label_name).collect::<Vec<_>>().join(";")),
+    })
+}
+
+fn label_name(label: &UsageLabel) -> String {
+    match *label {
+        UsageLabel::Regional(ref region) => format!("regional:{}", region),
+        UsageLabel::Other(ref other) => other.clone(),
+        ref label => format!("{:?}", label).to_lowercase(),
+    }
+}
@@ -0,0 +1,63 @@
+use gcide::{binutils, dictzip, forms, parallel, EntryParser};
+use gcide::binutils::{ErrorRecord, SourceOpt, UnwrapOrFail};
+use gcide::exporter::{ExportOptions, HeadwordStyle, Plain};
+use gcide::exporter::dictd::DictDatabase;
+use gcide::sources;
+use std::fs;
+use std::path::{Path, PathBuf};
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct DictOpt {
+    #[structopt(name = "INFILE", help = "GNU CIDE file", parse(from_os_str))]
+    infile: PathBuf,
+    #[structopt(name = "OUTBASE", help = "write OUTBASE.index and OUTBASE.dict (or OUTBASE.dict.dz)")]
+    out_base: String,
+    #[structopt(flatten)]
+    sources: SourceOpt,
+    #[structopt(long = "dictzip", help = "compress the .dict file with dictzip")]
+    dictzip: bool,
+    #[structopt(long = "name", default_value = "GNU Collaborative International Dictionary of English",
+                help = "short name of the database")]
+    name: String,
+    #[structopt(long = "url", default_value = "https://gcide.gnu.org.ua/", help = "URL of the database")]
+    url: String,
+    #[structopt(long = "manifest", parse(from_os_str),
+                help = "write sizes, SHA-256 digests and entry counts of INFILE and the outputs to this JSON file")]
+    manifest: Option<PathBuf>,
+}
+
+pub fn run(opt: DictOpt, jobs: usize) {
+    let filter = opt.sources.filter();
+    let export_opts = ExportOptions { headwords: HeadwordStyle::Clean, ..ExportOptions::default() };
+    let contents = binutils::map_file(&opt.infile).unwrap_or_fail(&opt.infile);
+    let mut db = DictDatabase::new(&opt.name, &opt.url);
+    let entries = EntryParser::new(&contents).filter_map(Result::ok);
+    parallel::ordered_map(entries, jobs, |mut entry| {
+        if sources::retain_sources(&mut entry, &filter) {
+            Some((forms::lookup_terms(&entry), Plain(&entry, &export_opts).to_string()))
+        } else {
+            None
+        }
+    }, |rendered| if let Some((headwords, text)) = rendered {
+        db.add_text(&headwords, text.trim());
+    });
+
+    let index_path = format!("{}.index", opt.out_base);
+    let index = db.index();
+    fs::write(&index_path, &index).unwrap_or_fail(Path::new(&index_path));
+    let (dict_path, body) = if opt.dictzip {
+        let compressed = dictzip::compress(db.body().as_bytes()).unwrap_or_else(|err| {
+            binutils::fail(ErrorRecord::new("invalid-input", err), binutils::EXIT_INVALID_INPUT)
+        });
+        (format!("{}.dict.dz", opt.out_base), compressed)
+    } else {
+        (format!("{}.dict", opt.out_base), db.body().as_bytes().to_vec())
+    };
+    fs::write(&dict_path, &body).unwrap_or_fail(Path::new(&dict_path));
+    if let Some(ref manifest) = opt.manifest {
+        let outputs = [(index_path, index.as_bytes()), (dict_path, &body[..])];
+        binutils::write_manifest(manifest, &opt.infile, &contents, &outputs)
+            .unwrap_or_fail(manifest);
+    }
+}
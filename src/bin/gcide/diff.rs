@@ -0,0 +1,81 @@
+use gcide::binutils::{self, UnwrapOrFail};
+use gcide::diff::{self, Compare, EntryChange, EntryKey};
+use std::path::PathBuf;
+use std::process;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct DiffOpt {
+    #[structopt(name = "OLD", help = "old version of a GNU CIDE file", parse(from_os_str))]
+    old: PathBuf,
+    #[structopt(name = "NEW", help = "new version of a GNU CIDE file", parse(from_os_str))]
+    new: PathBuf,
+    #[structopt(short = "s", long = "semantic",
+                help = "compare parsed entries, ignoring layout, and show the changed paragraphs")]
+    semantic: bool,
+    #[structopt(long = "stat", help = "only print the number of added, removed and changed entries")]
+    stat: bool,
+}
+
+pub fn run(opt: DiffOpt) {
+    let read = |path: &PathBuf| binutils::read_file(path).unwrap_or_fail(path);
+    let (old, new) = (read(&opt.old), read(&opt.new));
+    let mode = if opt.semantic { Compare::Semantic } else { Compare::Bytes };
+    let changes = diff::diff(&old, &new, mode);
+
+    if opt.stat {
+        let count = |f: fn(&EntryChange) -> bool| changes.iter().filter(|c| f(c)).count();
+        println!("{} added, {} removed, {} changed",
+                 count(|c| matches!(*c, EntryChange::Added { .. })),
+                 count(|c| matches!(*c, EntryChange::Removed { .. })),
+                 count(|c| matches!(*c, EntryChange::Changed { .. })));
+    } else {
+        for change in &changes {
+            match *change {
+                EntryChange::Added { ref key, new } => println!("+ {}", display_key(key, new)),
+                EntryChange::Removed { ref key, old } => println!("- {}", display_key(key, old)),
+                EntryChange::Changed { ref key, old, new } => {
+                    println!("~ {}", display_key(key, new));
+                    if opt.semantic {
+                        print_block_diff(old, new);
+                    }
+                }
+            }
+        }
+    }
+    process::exit(if changes.is_empty() { 0 } else { binutils::EXIT_FINDINGS });
+}
+
+fn display_key(key: &EntryKey, raw: &str) -> String {
+    match key.occurrence {
+        0 => diff::raw_main_word(raw).to_string(),
+        n => format!("{} ({})", diff::raw_main_word(raw), n + 1),
+    }
+}
+
+fn print_block_diff(old: &str, new: &str) {
+    let (old, new) = match (diff::semantic_blocks(old), diff::semantic_blocks(new)) {
+        (Some(old), Some(new)) => (old, new),
+        _ => return println!("    (entry does not parse; compared as text)"),
+    };
+    // longest common subsequence of blocks, filled in from the back
+    let mut lcs = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            println!("    - {}", old[i]);
+            i += 1;
+        } else {
+            println!("    + {}", new[j]);
+            j += 1;
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use gcide::{binutils, EntryParser};
+use gcide::binutils::UnwrapOrFail;
+use gcide::duplicates::{DuplicateFinder, Shingles};
+use std::path::{Path, PathBuf};
+use std::process;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct DuplicatesOpt {
+    #[structopt(name = "FILE", help = "GNU CIDE files", parse(from_os_str), required = true)]
+    files: Vec<PathBuf>,
+    #[structopt(long = "threshold", default_value = "0.8",
+                help = "least share of definition wording in common, from 0 to 1, to report a pair")]
+    threshold: f64,
+}
+
+/// Reports entries that repeat an earlier one with the same headword as
+/// `FILE:LINE: MAIN-WORD: duplicates FILE:LINE (SIMILARITY)`; exits with 1 if
+/// there are any.
+pub fn run(opt: DuplicatesOpt, jobs: usize) {
+    let entries = binutils::map_files(&opt.files, jobs, file_shingles);
+    let mut finder = DuplicateFinder::new(opt.threshold);
+    let mut found = 0;
+    for (path, entries) in opt.files.iter().zip(entries) {
+        for (line, main_word, shingles) in entries {
+            for ((other_path, other_line), resemblance) in finder.add((path, line), &main_word, shingles) {
+                let similarity = if resemblance == 1.0 { "same definitions".to_string() }
+                                 else { format!("{:.0}% alike", resemblance * 100.0) };
+                println!("{}:{}: {}: duplicates {}:{} ({})", path.display(), line, main_word,
+                         other_path.display(), other_line, similarity);
+                found += 1;
+            }
+        }
+    }
+    if found > 0 {
+        process::exit(binutils::EXIT_FINDINGS);
+    }
+}
+
+/// Line, main word and shingles of each parsable entry.
+fn file_shingles(path: &Path) -> Vec<(usize, String, Shingles)> {
+    let contents = binutils::map_file(path).unwrap_or_fail(path);
+    let mut entries = Vec::new();
+    let mut entry_iter = EntryParser::new(&contents);
+    while let Some(entry_res) = entry_iter.next() {
+        if let Ok(entry) = entry_res {
+            let span = entry_iter.last_span().unwrap();
+            let line = contents[..span.start].matches('\n').count() + 1;
+            entries.push((line, entry.main_word.to_string(), Shingles::of_entry(&entry)));
+        }
+    }
+    entries
+}
@@ -0,0 +1,128 @@
+use gcide::{binutils, Entry, EntryParser, CIDE};
+use gcide::binutils::{ErrorRecord, PatchOpt, SourceOpt, UnwrapOrFail};
+use gcide::headword::collation_key;
+use gcide::normalize::normalize_spacing;
+use gcide::sort;
+use gcide::sources::{self, SourceFilter};
+use std::collections::BTreeMap;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct FilterOpt {
+    #[structopt(flatten)]
+    patch: PatchOpt,
+    #[structopt(flatten)]
+    sources: SourceOpt,
+    #[structopt(long = "list-sources", help = "print how many entries credit each source and exit")]
+    list_sources: bool,
+    #[structopt(long = "sort", help = "sort entries by headword")]
+    sort: bool,
+    #[structopt(long = "merge-duplicates", help = "merge consecutive entries with the same main word")]
+    merge_duplicates: bool,
+    #[structopt(long = "split-pos", help = "split entries into one entry per part of speech")]
+    split_pos: bool,
+    #[structopt(long = "normalize-spacing", help = "separate entries with one blank line")]
+    normalize_spacing: bool,
+}
+
+pub fn run(opt: FilterOpt) {
+    if opt.list_sources {
+        let contents = binutils::load_file(&opt.patch.infile, opt.patch.encoding).unwrap_or_fail(&opt.patch.infile);
+        let mut counts = BTreeMap::new();
+        for entry in EntryParser::new(&contents).filter_map(Result::ok) {
+            for source in sources::sources(&entry) {
+                *counts.entry(source.to_string()).or_insert(0) += 1;
+            }
+        }
+        for (source, count) in counts {
+            println!("{:8} {}", count, source);
+        }
+        return;
+    }
+
+    let filter = opt.sources.filter();
+    binutils::patch_with(&opt.patch, |contents| {
+        let filtered = if opt.sort || opt.merge_duplicates || opt.split_pos {
+            reorder(contents, &opt, &filter)
+        } else {
+            binutils::patch_entries(contents, |raw, entry_res| {
+                let mut entry = entry_res.ok()?;
+                if !sources::retain_sources(&mut entry, &filter) {
+                    return Some(String::new());
+                }
+                let filtered = CIDE(&entry).to_string();
+                if filtered != raw { Some(filtered) } else { None }
+            })
+        };
+        if opt.normalize_spacing { normalize_spacing(&filtered, "\n\n") } else { filtered }
+    });
+}
+
+/// Applies the source filter, then sorts, merges and/or splits entries. Text before the
+/// first and after the last entry is kept; text between entries is not, except
+/// that each entry is followed by the whitespace that followed it in `contents`.
+fn reorder(contents: &str, opt: &FilterOpt, filter: &SourceFilter) -> String {
+    let mut entries = Vec::new();
+    let mut spans = Vec::new();
+    let mut entry_iter = EntryParser::new(contents);
+    while let Some(entry_res) = entry_iter.next() {
+        let span = entry_iter.last_span().unwrap();
+        match entry_res {
+            Ok(mut entry) => if sources::retain_sources(&mut entry, filter) {
+                entries.push((entry, spans.len()));
+            },
+            Err(err) => {
+                let message = format!("{}; refusing to reorder a file with broken entries", err.hint());
+                let record = ErrorRecord::new("unparsable-entry", message)
+                    .in_file(&opt.patch.infile)
+                    .at_entry(contents, span.start, spans.len());
+                binutils::fail(record, binutils::EXIT_INVALID_INPUT);
+            }
+        }
+        spans.push(span);
+    }
+    let (first, last) = match (spans.first(), spans.last()) {
+        (Some(first), Some(last)) => (first.clone(), last.clone()),
+        _ => return contents.to_string(),
+    };
+    // the whitespace after each entry, or for the last, the whitespace before it
+    let default_separator = spans.get(1).map_or("\n\n", |second| &contents[first.end..second.start]);
+    let separators: Vec<&str> = (0..spans.len()).map(|idx| {
+        let gap = match spans.get(idx + 1) {
+            Some(next) => &contents[spans[idx].end..next.start],
+            None if idx > 0 => &contents[spans[idx - 1].end..spans[idx].start],
+            None => default_separator,
+        };
+        if gap.trim().is_empty() { gap } else { default_separator }
+    }).collect();
+    if opt.sort {
+        entries.sort_by_cached_key(|(entry, _)| collation_key(entry.main_word));
+    }
+    if opt.merge_duplicates {
+        let mut merged: Vec<(Entry, usize)> = Vec::with_capacity(entries.len());
+        for (entry, idx) in entries {
+            match merged.last_mut() {
+                Some(last) if last.0.main_word == entry.main_word => {
+                    sort::merge_entry(&mut last.0, entry);
+                    last.1 = idx;
+                }
+                _ => merged.push((entry, idx)),
+            }
+        }
+        entries = merged;
+    }
+    if opt.split_pos {
+        entries = entries.into_iter()
+            .flat_map(|(entry, idx)| sort::split_by_pos(entry).into_iter().map(move |part| (part, idx)))
+            .collect();
+    }
+    let mut reordered = contents[..first.start].to_string();
+    for (pos, (entry, _)) in entries.iter().enumerate() {
+        if pos > 0 {
+            reordered.push_str(separators[entries[pos - 1].1]);
+        }
+        reordered.push_str(&CIDE(entry).to_string());
+    }
+    reordered.push_str(&contents[last.end..]);
+    reordered
+}
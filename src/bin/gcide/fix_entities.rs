@@ -0,0 +1,22 @@
+use gcide::{binutils, CIDE};
+use gcide::binutils::PatchOpt;
+use gcide::diagnostics::use_entities;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct FixEntitiesOpt {
+    #[structopt(flatten)]
+    patch: PatchOpt,
+}
+
+pub fn run(opt: FixEntitiesOpt) {
+    binutils::patch_entries_with(&opt.patch, |_, entry_res| {
+        // leave broken entries alone
+        let mut entry = entry_res.ok()?;
+        if use_entities(&mut entry.items) {
+            Some(CIDE(&entry).to_string())
+        } else {
+            None
+        }
+    });
+}
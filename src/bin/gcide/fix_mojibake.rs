@@ -0,0 +1,20 @@
+use gcide::binutils::{self, PatchOpt};
+use gcide::mojibake::{find_mojibake, repair_mojibake};
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct FixMojibakeOpt {
+    #[structopt(flatten)]
+    patch: PatchOpt,
+}
+
+/// Repairs doubly encoded UTF-8 and reports the U+FFFD characters left, whose
+/// original text has to be restored by hand.
+pub fn run(opt: FixMojibakeOpt) {
+    binutils::patch_with(&opt.patch, |contents| {
+        let garbled = find_mojibake(contents);
+        let lost = garbled.iter().filter(|g| g.repair.is_none()).count();
+        eprintln!("repaired {} characters, {} replacement characters left", garbled.len() - lost, lost);
+        repair_mojibake(contents).unwrap_or_else(|| contents.to_string())
+    });
+}
@@ -0,0 +1,36 @@
+use gcide::{binutils, CIDE};
+use gcide::binutils::{ErrorRecord, PatchOpt, UnwrapOrFail};
+use gcide::todos::Replacements;
+use std::cell::Cell;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct FixUnknownsOpt {
+    #[structopt(flatten)]
+    patch: PatchOpt,
+    #[structopt(long = "table", parse(from_os_str),
+                help = "replacements, one per line: MAIN-WORD<tab>CONTEXT<tab>REPLACEMENT, \
+                        with contexts as listed by `gcide todos`")]
+    table: PathBuf,
+}
+
+/// Puts the characters from the table in place of matching `<?/` markers.
+pub fn run(opt: FixUnknownsOpt) {
+    let table = binutils::read_file(&opt.table).unwrap_or_fail(&opt.table);
+    let replacements = Replacements::parse(&table).unwrap_or_else(|err| {
+        binutils::fail(ErrorRecord::new("invalid-input", err).in_file(&opt.table), binutils::EXIT_INVALID_INPUT)
+    });
+    let replaced = Cell::new(0);
+    binutils::patch_entries_with(&opt.patch, |_, entry_res| {
+        let mut entry = entry_res.ok()?;
+        match replacements.apply(&mut entry) {
+            0 => None,
+            count => {
+                replaced.set(replaced.get() + count);
+                Some(CIDE(&entry).to_string())
+            }
+        }
+    });
+    eprintln!("replaced {} unknown characters", replaced.get());
+}
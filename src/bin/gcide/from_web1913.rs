@@ -0,0 +1,37 @@
+use gcide::{binutils, CIDE};
+use gcide::binutils::{ErrorRecord, PipeOpt};
+use gcide::web1913::{self, Web1913Parser};
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct FromWeb1913Opt {
+    #[structopt(flatten)]
+    pipe: PipeOpt,
+}
+
+/// Writes each entry as a GCIDE entry block; entries that cannot be parsed are
+/// reported and written with their error marked, as `identity` does.
+pub fn run(opt: FromWeb1913Opt) {
+    binutils::pipe_through_with(&opt.pipe, |contents| {
+        let mut output = String::with_capacity(contents.len() + contents.len() / 8);
+        let mut entries = Web1913Parser::new(contents);
+        let mut index = 0;
+        while let Some(entry_res) = entries.next() {
+            let span = entries.last_span().unwrap();
+            match entry_res {
+                Ok(entry) => output.push_str(&CIDE(&entry).to_string()),
+                Err(err) => {
+                    let record = ErrorRecord::new("unparsable-entry", err.hint())
+                        .in_file(&opt.pipe.infile)
+                        .at_entry(contents, span.start, index);
+                    binutils::report(&record);
+                    output.push_str(&format!("<entry main-word=\"{}\" source=\"1913 Webster\">{}</entry>",
+                                             web1913::main_word(&contents[span]), err));
+                }
+            }
+            output.push_str("\n\n");
+            index += 1;
+        }
+        output
+    });
+}
@@ -0,0 +1,52 @@
+use gcide::{binutils, EntryParser, CIDE};
+use gcide::binutils::UnwrapOrFail;
+use gcide::model::plain_text;
+use gcide::query::Query;
+use std::path::PathBuf;
+use std::process;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct GrepOpt {
+    #[structopt(name = "QUERY", help = "selector such as `entry > p > def` or `p[source=\"PJC\"] grk`")]
+    query: Query,
+    #[structopt(name = "FILE", help = "GNU CIDE files", parse(from_os_str), required = true)]
+    files: Vec<PathBuf>,
+    #[structopt(long = "entries", help = "print the source of the matching entries instead of what matched")]
+    entries: bool,
+}
+
+/// Prints what matches the query as `FILE:LINE:MAIN-WORD: TEXT`, with the
+/// line of the entry. Exits with `EXIT_FINDINGS` if nothing matched.
+pub fn run(opt: GrepOpt) {
+    let mut output = String::new();
+    let mut found = false;
+    for path in &opt.files {
+        let contents = binutils::map_file(path).unwrap_or_fail(path);
+        let mut entry_iter = EntryParser::new(&contents);
+        while let Some(entry_res) = entry_iter.next() {
+            let entry = match entry_res {
+                Ok(entry) => entry,
+                Err(_) => continue,
+            };
+            let matches = opt.query.find_all(&entry);
+            if matches.is_empty() {
+                continue;
+            }
+            found = true;
+            if opt.entries {
+                output.push_str(&format!("{}\n", CIDE(&entry)));
+                continue;
+            }
+            let span = entry_iter.last_span().unwrap();
+            let line = contents[..span.start].matches('\n').count() + 1;
+            for items in matches {
+                output.push_str(&format!("{}:{}:{}: {}\n", path.display(), line, entry.main_word, plain_text(items)));
+            }
+        }
+    }
+    print!("{}", output);
+    if !found {
+        process::exit(binutils::EXIT_FINDINGS);
+    }
+}
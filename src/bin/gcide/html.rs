@@ -0,0 +1,164 @@
+use gcide::{binutils, parallel, EntryParser};
+use gcide::binutils::{ErrorRecord, PipeOpt, ProgressBar, SourceOpt, UnwrapOrFail};
+use gcide::exporter::{CommentPolicy, ExportOptions, HeadwordStyle, UnknownTagPolicy};
+use gcide::exporter::html::{self, HTML};
+use gcide::exporter::site::{self, Site, SplitMode};
+use gcide::greek::GreekStyle;
+use gcide::sources;
+use std::fs;
+use std::iter;
+use std::sync::Arc;
+use structopt::StructOpt;
+
+const TITLE: &str = "Webster's Unabridged Dictionary 1913";
+
+#[derive(StructOpt, Debug)]
+pub struct HtmlOpt {
+    #[structopt(flatten)]
+    pipe: PipeOpt,
+    #[structopt(long = "greek", default_value = "unicode",
+                help = "rendering of Greek text: unicode, ascii, betacode or ala-lc")]
+    greek: GreekStyle,
+    #[structopt(flatten)]
+    sources: SourceOpt,
+    #[structopt(long = "unknown-tags", default_value = "placeholder",
+                help = "handling of unknown tags: placeholder, skip, span or error")]
+    unknown_tags: UnknownTagPolicy,
+    #[structopt(long = "comments", default_value = "drop", parse(try_from_str = "parse_comments"),
+                help = "handling of <-- comments -->: drop, keep (as HTML comments) or report (to stderr)")]
+    comments: CommentPolicy,
+    #[structopt(long = "css", number_of_values = 1, help = "link the stylesheet at this URL (repeatable)")]
+    css: Vec<String>,
+    #[structopt(long = "embed-css", help = "embed the default stylesheet")]
+    embed_css: bool,
+    #[structopt(long = "class", number_of_values = 1, parse(try_from_str = "parse_class"),
+                help = "use a custom CSS class instead of a built-in one, e.g. hw=headword (repeatable)")]
+    classes: Vec<(String, String)>,
+    #[structopt(long = "split",
+                help = "write one page per letter or entry, plus index.html, into the directory OUTFILE")]
+    split: Option<SplitMode>,
+    #[structopt(long = "headwords", default_value = "raw",
+                help = "rendering of syllable marks in headwords: raw, clean or dotted")]
+    headwords: HeadwordStyle,
+    #[structopt(long = "page-images",
+                help = "link page breaks to this URL, with {} replaced by the page number of the 1913 edition")]
+    page_images: Option<String>,
+    #[structopt(long = "progress", help = "show progress on stderr")]
+    progress: bool,
+}
+
+fn parse_class(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((name, class)) if !name.is_empty() && !class.is_empty() => Ok((name.into(), class.into())),
+        _ => Err(format!("expected NAME=CLASS, got {}", arg)),
+    }
+}
+
+fn parse_comments(arg: &str) -> Result<CommentPolicy, String> {
+    match arg {
+        "report" => Ok(CommentPolicy::Callback(Arc::new(|word, text| eprintln!("{}: {}", word, text.trim())))),
+        _ => arg.parse(),
+    }
+}
+
+pub fn run(opt: HtmlOpt, jobs: usize) {
+    let mut export_opts = ExportOptions {
+        greek: opt.greek,
+        on_warning: Some(Arc::new(|warning| eprintln!("{}", warning))),
+        unknown_tags: opt.unknown_tags.clone(),
+        comments: opt.comments.clone(),
+        class_names: opt.classes.iter().cloned().collect(),
+        headwords: opt.headwords,
+        ..ExportOptions::default()
+    };
+    if let Some(ref template) = opt.page_images {
+        let template = template.clone();
+        export_opts.page_href = Some(Arc::new(move |page| template.replace("{}", &page.to_string())));
+    }
+    match opt.split {
+        Some(mode) => {
+            let out_dir = opt.pipe.outfile.as_ref().unwrap_or_else(|| {
+                binutils::fail(ErrorRecord::new("invalid-input", "--split needs an output directory"),
+                               binutils::EXIT_INVALID_INPUT)
+            });
+            export_opts.link_href = Some(Arc::new(move |word| site::href(word, mode)));
+            let contents = binutils::load_file(&opt.pipe.infile, opt.pipe.encoding).unwrap_or_fail(&opt.pipe.infile);
+            let mut site = Site::new(mode);
+            render_entries(&contents, &export_opts, &opt, jobs, |main_word, html| {
+                if let Some(main_word) = main_word {
+                    site.add(main_word, html);
+                }
+            });
+            let styled_head = |title: &str| html::page_head(title, &opt.css, opt.embed_css, &export_opts);
+            let pages = site.pages(styled_head);
+            fs::create_dir_all(out_dir)
+                .and_then(|_| pages.iter().try_for_each(|(name, page)| fs::write(out_dir.join(name), page)))
+                .unwrap_or_fail(out_dir);
+            if let Some(ref manifest) = opt.pipe.manifest {
+                let outputs: Vec<_> = pages.iter()
+                    .map(|(name, page)| (out_dir.join(name).display().to_string(), page.as_bytes()))
+                    .collect();
+                binutils::write_manifest(manifest, &opt.pipe.infile, &contents, &outputs).unwrap_or_fail(manifest);
+            }
+        }
+        None => {
+            let head = html::page_head(TITLE, &opt.css, opt.embed_css, &export_opts);
+            binutils::pipe_through_with(&opt.pipe, |contents| {
+                let mut output = String::with_capacity(contents.len()/3);
+                output.push_str(&head);
+                render_entries(contents, &export_opts, &opt, jobs, |_, html| {
+                    output.push('\n');
+                    output.push_str(html);
+                    output.push('\n');
+                });
+                output.push_str(html::PAGE_TAIL);
+                output
+            });
+        }
+    }
+}
+
+/// Calls `sink` with the main word and HTML of each entry, or with no main word
+/// and an error comment for entries that fail to parse or render.
+fn render_entries<F>(contents: &str, opts: &ExportOptions, opt: &HtmlOpt, jobs: usize, mut sink: F)
+where F: FnMut(Option<&str>, &str) {
+    use std::fmt::Write;
+    let filter = opt.sources.filter();
+    let mut entries = EntryParser::new(contents).with_ids();
+    if opt.progress {
+        let mut bar = ProgressBar::new(&opt.pipe.infile.display().to_string());
+        entries = entries.on_progress(move |progress| bar.update(progress));
+    }
+    let mut index = 0;
+    let located_entries = iter::from_fn(|| {
+        let entry_res = entries.next()?;
+        index += 1;
+        Some((index - 1, entries.last_span().unwrap().start, entry_res))
+    });
+    parallel::ordered_map(located_entries, jobs, |(index, span_start, entry_res)| {
+        let located = |record: ErrorRecord| record.in_file(&opt.pipe.infile).at_entry(contents, span_start, index);
+        match entry_res {
+            Ok(mut entry) => {
+                if !sources::retain_sources(&mut entry, &filter) {
+                    return None;
+                }
+                let mut output = String::new();
+                if write!(output, "{}", HTML(&entry, opts)).is_ok() {
+                    Some(Ok((entry.main_word, output)))
+                } else {
+                    Some(Err((located(ErrorRecord::new("render", "could not render entry")),
+                              format!("<!-- ERROR while rendering {} -->", entry.main_word))))
+                }
+            }
+            Err(err) => Some(Err((located(ErrorRecord::new("unparsable-entry", err.hint())),
+                                  "<!-- ERROR while parsing an entry -->".to_string()))),
+        }
+    }, |rendered| match rendered {
+        Some(Ok((main_word, html))) => sink(Some(main_word), &html),
+        Some(Err((record, comment))) => {
+            binutils::report(&record);
+            sink(None, &comment);
+        }
+        None => (),
+    });
+}
@@ -0,0 +1,33 @@
+use gcide::{binutils, Entry, CIDE};
+use gcide::binutils::PatchOpt;
+use gcide::normalize::normalize_spacing;
+use gcide::parser::ParserError;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct IdentityOpt {
+    #[structopt(flatten)]
+    patch: PatchOpt,
+    #[structopt(long = "normalize-spacing", help = "separate entries with one blank line")]
+    normalize_spacing: bool,
+}
+
+fn patch(raw: &str, entry_res: Result<Entry, ParserError>) -> Option<String> {
+    let patched = match entry_res {
+        Ok(entry) => CIDE(&entry).to_string(),
+        Err(err) => err.to_string(),
+    };
+    if patched != raw {
+        Some(patched)
+    } else {
+        None
+    }
+}
+
+pub fn run(opt: IdentityOpt) {
+    if opt.normalize_spacing {
+        binutils::patch_with(&opt.patch, |contents| normalize_spacing(&binutils::patch_entries(contents, patch), "\n\n"));
+    } else {
+        binutils::patch_entries_with(&opt.patch, patch);
+    }
+}
@@ -0,0 +1,44 @@
+use gcide::{binutils, parallel, EntryParser};
+use gcide::binutils::{PipeOpt, SourceOpt};
+use gcide::exporter::ExportOptions;
+use gcide::exporter::latex::{self, LaTeX};
+use gcide::greek::GreekStyle;
+use gcide::sources;
+use std::sync::Arc;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct LatexOpt {
+    #[structopt(flatten)]
+    pipe: PipeOpt,
+    #[structopt(long = "greek", default_value = "unicode",
+                help = "rendering of Greek text: unicode, ascii, betacode or ala-lc")]
+    greek: GreekStyle,
+    #[structopt(flatten)]
+    sources: SourceOpt,
+    #[structopt(long = "body-only", help = "omit the preamble and \\end{document}")]
+    body_only: bool,
+}
+
+pub fn run(opt: LatexOpt, jobs: usize) {
+    let export_opts = ExportOptions {
+        greek: opt.greek,
+        on_warning: Some(Arc::new(|warning| eprintln!("{}", warning))),
+        ..ExportOptions::default()
+    };
+    let filter = opt.sources.filter();
+    binutils::pipe_through_with(&opt.pipe, |contents| {
+        let mut output = String::with_capacity(contents.len());
+        if !opt.body_only {
+            output.push_str(latex::PREAMBLE);
+        }
+        let entries = EntryParser::new(contents).filter_map(Result::ok);
+        parallel::ordered_map(entries, jobs, |mut entry| {
+            if sources::retain_sources(&mut entry, &filter) { LaTeX(&entry, &export_opts).to_string() } else { String::new() }
+        }, |text| output.push_str(&text));
+        if !opt.body_only {
+            output.push_str(latex::POSTAMBLE);
+        }
+        output
+    });
+}
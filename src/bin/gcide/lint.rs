@@ -0,0 +1,102 @@
+use gcide::{binutils, EntryParser};
+use gcide::binutils::UnwrapOrFail;
+use gcide::model::check_sense_numbers;
+use gcide::parser::Block;
+use gcide::sort::{check_letter_file, file_letter, Misplaced};
+use gcide::tags::validate_nesting;
+use std::path::{Path, PathBuf};
+use std::process;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct LintOpt {
+    #[structopt(name = "FILE", help = "GNU CIDE files", parse(from_os_str), required = true)]
+    files: Vec<PathBuf>,
+    #[structopt(long = "letter-files",
+                help = "check that entries of files named like CIDE.A begin with that letter and are in order")]
+    letter_files: bool,
+}
+
+/// Reports unparsable entries, parse warnings, misplaced tags, gaps in sense
+/// numbers, text outside entries and, if asked, misplaced entries; exits with 1 if there are any.
+pub fn run(opt: LintOpt, jobs: usize) {
+    let mut problems = 0;
+    for lines in binutils::map_files(&opt.files, jobs, |path| lint_file(path, opt.letter_files)) {
+        problems += lines.len();
+        for line in lines {
+            println!("{}", line);
+        }
+    }
+    process::exit(if problems == 0 { 0 } else { binutils::EXIT_FINDINGS });
+}
+
+fn lint_file(path: &Path, letter_files: bool) -> Vec<String> {
+    let contents = binutils::map_file(path).unwrap_or_fail(path);
+    let mut lines = Vec::new();
+    let mut main_words = Vec::new();
+    let mut block_iter = EntryParser::new(&contents).with_gaps();
+    while let Some(block) = block_iter.next() {
+        let entry_res = match block {
+            Block::Gap { offset, text } => {
+                if let Some(stray) = stray_text(text) {
+                    let line = contents[..offset + text.len() - stray.len()].matches('\n').count() + 1;
+                    lines.push(format!("{}:{}: text outside entries: {}", path.display(), line,
+                                       stray.lines().next().unwrap_or_default()));
+                }
+                continue;
+            }
+            Block::Entry(entry_res) => entry_res,
+        };
+        let entry_iter = block_iter.parser();
+        let span = entry_iter.last_span().unwrap();
+        let line = contents[..span.start].matches('\n').count() + 1;
+        match entry_res {
+            Ok(entry) => {
+                main_words.push((entry.main_word, line));
+                for warning in entry_iter.warnings() {
+                    lines.push(format!("{}:{}: {}: {}", path.display(), line, entry.main_word, warning));
+                }
+                let raw = &contents[span];
+                for violation in validate_nesting(&entry) {
+                    let line = violation.offset_in(raw).map_or(line, |idx| line + raw[..idx].matches('\n').count());
+                    lines.push(format!("{}:{}: {}: <{}> may not appear inside <{}>",
+                                       path.display(), line, entry.main_word, violation.tag, violation.ancestor));
+                }
+                for error in check_sense_numbers(&entry) {
+                    lines.push(format!("{}:{}: {}: {}", path.display(), line, entry.main_word, error));
+                }
+            }
+            Err(err) => lines.push(format!("{}:{}: unparsable entry: {}", path.display(), line, err.hint())),
+        }
+    }
+    if letter_files {
+        let letter = path.file_name().and_then(|name| name.to_str()).and_then(file_letter);
+        for (idx, misplaced) in check_letter_file(letter, main_words.iter().map(|&(word, _)| word)) {
+            let (word, line) = main_words[idx];
+            let problem = match misplaced {
+                Misplaced::WrongLetter(_) => {
+                    format!("entry does not begin with {}", letter.unwrap().to_ascii_uppercase())
+                }
+                Misplaced::OutOfOrder(_, before) => format!("entry out of order after {}", before),
+            };
+            lines.push(format!("{}:{}: {}: {}", path.display(), line, word, problem));
+        }
+    }
+    lines
+}
+
+/// The end of a gap between entries from its first text that is neither
+/// whitespace nor a comment.
+fn stray_text(mut gap: &str) -> Option<&str> {
+    loop {
+        gap = gap.trim_start();
+        if !gap.starts_with("<--") {
+            break;
+        }
+        match gap.find("-->") {
+            Some(end) => gap = &gap[end + 3..],
+            None => break,
+        }
+    }
+    if gap.is_empty() { None } else { Some(gap) }
+}
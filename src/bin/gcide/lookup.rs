@@ -0,0 +1,195 @@
+use gcide::{binutils, Entry, EntryParser};
+use gcide::binutils::{ErrorRecord, UnwrapOrFail};
+use gcide::exporter::{ExportOptions, HeadwordStyle, Plain};
+use gcide::exporter::groff::{self, Groff};
+use gcide::exporter::html::HTML;
+use gcide::exporter::term::{self, Term};
+use gcide::fulltext::FullTextIndex;
+use gcide::headword::normalize_headword;
+use gcide::index::{self, HeadwordIndex, IndexBuilder};
+use gcide::phonetic::PhoneticIndex;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+use std::process;
+use std::str::FromStr;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct LookupOpt {
+    #[structopt(name = "WORD", help = "word to look up")]
+    word: String,
+    #[structopt(name = "FILES", help = "GNU CIDE files (in the order used to build the index)",
+                parse(from_os_str))]
+    files: Vec<PathBuf>,
+    #[structopt(short = "i", long = "index", help = "prebuilt headword index", parse(from_os_str))]
+    index: Option<PathBuf>,
+    #[structopt(long = "build-index", parse(from_os_str),
+                help = "write a headword index and exit (all positional arguments are then FILES)")]
+    build_index: Option<PathBuf>,
+    #[structopt(short = "p", long = "prefix", help = "match all headwords starting with WORD")]
+    prefix: bool,
+    #[structopt(short = "t", long = "fulltext",
+                help = "treat WORD as a query over definitions and quotations (`word*` matches prefixes)")]
+    fulltext: bool,
+    #[structopt(long = "phonetic",
+                help = "match headwords that sound like WORD, which may be spelled as pronounced, e.g. nee-mon-ik")]
+    phonetic: bool,
+    #[structopt(long = "suggestions", default_value = "5",
+                help = "number of similar headwords to list when WORD is not found (0 for none)")]
+    suggestions: usize,
+    #[structopt(short = "f", long = "format", default_value = "auto",
+                help = "output format: plain, term, html, json, man, or auto (term if stdout is a terminal, else plain)")]
+    format: Format,
+    #[structopt(long = "headwords", default_value = "raw",
+                help = "rendering of syllable marks in headwords: raw, clean or dotted")]
+    headwords: HeadwordStyle,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Format {
+    Auto,
+    Plain,
+    /// Plain text with ANSI styles, wrapped to `$COLUMNS`
+    Term,
+    Html,
+    Json,
+    /// `-man` macros, for `man -l -`
+    Man,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s {
+            "auto" => Ok(Format::Auto),
+            "plain" => Ok(Format::Plain),
+            "term" => Ok(Format::Term),
+            "html" => Ok(Format::Html),
+            "json" => Ok(Format::Json),
+            "man" => Ok(Format::Man),
+            _ => Err(format!("unknown format: {}", s)),
+        }
+    }
+}
+
+pub fn run(opt: LookupOpt) {
+    if opt.index.is_some() && opt.build_index.is_none() && opt.files.is_empty() {
+        binutils::fail(ErrorRecord::new("invalid-input", "--index needs the FILES it was built from"),
+                       binutils::EXIT_INVALID_INPUT)
+    }
+    let mut files = opt.files.clone();
+    if opt.build_index.is_some() {
+        files.insert(0, PathBuf::from(&opt.word));
+    }
+    let contents: Vec<String> = files.iter().map(|path| {
+        binutils::read_file(path).unwrap_or_fail(path)
+    }).collect();
+
+    if let Some(ref index_path) = opt.build_index {
+        build_index(&contents).save(index_path).unwrap_or_fail(index_path);
+        return;
+    }
+
+    let index = match opt.index {
+        Some(ref index_path) if !opt.fulltext && !opt.phonetic => Some(HeadwordIndex::load(index_path).unwrap_or_fail(index_path)),
+        _ => None,
+    };
+    let entries = match index {
+        _ if opt.fulltext => {
+            let mut index = FullTextIndex::new();
+            for (file_no, text) in contents.iter().enumerate() {
+                index.add_file(file_no as u32, text);
+            }
+            entries_at(&contents, index.search(&opt.word))
+        }
+        _ if opt.phonetic => {
+            let mut index = PhoneticIndex::new();
+            for (file_no, text) in contents.iter().enumerate() {
+                let mut entry_iter = EntryParser::new(text);
+                while let Some(entry_res) = entry_iter.next() {
+                    if let Ok(entry) = entry_res {
+                        let span = entry_iter.last_span().unwrap();
+                        index.insert(entry.main_word, index::location(file_no as u32, span.start));
+                    }
+                }
+            }
+            entries_at(&contents, index.get(&opt.word))
+        }
+        Some(ref index) => lookup_indexed(index, &contents, &opt),
+        None => lookup_linear(&contents, &opt),
+    };
+    if entries.is_empty() {
+        let suggestions = if opt.fulltext || opt.phonetic || opt.suggestions == 0 {
+            Vec::new()
+        } else {
+            index.unwrap_or_else(|| build_index(&contents)).suggest(&opt.word, opt.suggestions)
+        };
+        if suggestions.is_empty() {
+            eprintln!("{}: not found", opt.word);
+        } else {
+            eprintln!("{}: not found; did you mean: {}?", opt.word, suggestions.join(", "));
+        }
+        process::exit(binutils::EXIT_FINDINGS);
+    }
+    let export_opts = ExportOptions { headwords: opt.headwords, ..ExportOptions::default() };
+    print_entries(&entries, opt.format, &export_opts);
+}
+
+fn build_index(contents: &[String]) -> HeadwordIndex {
+    let mut builder = IndexBuilder::new();
+    for (file_no, text) in contents.iter().enumerate() {
+        builder.add_file(file_no as u32, text);
+    }
+    builder.finish()
+}
+
+fn lookup_indexed<'a>(index: &HeadwordIndex, contents: &'a [String], opt: &LookupOpt) -> Vec<Entry<'a>> {
+    let locations: Vec<u64> = if opt.prefix {
+        index.prefix(&opt.word).into_iter().flat_map(|(_, locs)| locs.iter().cloned()).collect()
+    } else {
+        index.get(&opt.word).to_vec()
+    };
+    entries_at(contents, locations)
+}
+
+fn entries_at(contents: &[String], locations: Vec<u64>) -> Vec<Entry<'_>> {
+    locations.into_iter().filter_map(|loc| {
+        let (file_no, offset) = index::split_location(loc);
+        let text = contents.get(file_no as usize)?;
+        EntryParser::new(text.get(offset..)?).next()?.ok()
+    }).collect()
+}
+
+fn lookup_linear<'a>(contents: &'a [String], opt: &LookupOpt) -> Vec<Entry<'a>> {
+    let word = normalize_headword(&opt.word);
+    contents.iter()
+        .flat_map(|text| EntryParser::new(text).with_ids().filter_map(Result::ok))
+        .filter(|entry| {
+            let main_word = normalize_headword(entry.main_word);
+            if opt.prefix { main_word.starts_with(&word) } else { main_word == word }
+        })
+        .collect()
+}
+
+fn print_entries(entries: &[Entry], format: Format, export_opts: &ExportOptions) {
+    match format {
+        Format::Auto if io::stdout().is_terminal() => print_entries(entries, Format::Term, export_opts),
+        Format::Auto | Format::Plain => for entry in entries {
+            println!("{}\n", Plain(entry, export_opts).to_string().trim());
+        },
+        Format::Term => for entry in entries {
+            println!("{}\n", Term::with_options(entry, export_opts, term::terminal_width()));
+        },
+        Format::Html => for entry in entries {
+            println!("{}", HTML(entry, export_opts));
+        },
+        Format::Json => println!("{}", serde_json::to_string_pretty(entries).unwrap()),
+        Format::Man => {
+            print!("{}", groff::page_header(entries[0].main_word));
+            for entry in entries {
+                print!("{}", Groff(entry, export_opts));
+            }
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use gcide::{Entry, ParseWarning};
+use gcide::diagnostics::check_entry;
+use gcide::exporter::{entity_to_unicode, is_known_entity};
+use gcide::headword::normalize_headword;
+use gcide::incremental::{Document, TextEdit};
+use gcide::parser::{EntryItem, ParserError};
+use gcide::tags::validate_nesting;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::ops::Range;
+
+const SEVERITY_ERROR: u8 = 1;
+const SEVERITY_WARNING: u8 = 2;
+const SYMBOL_KIND_KEY: u8 = 20;
+
+struct OpenFile {
+    text: String,
+    doc: Document,
+}
+
+struct Server {
+    files: HashMap<String, OpenFile>,
+}
+
+pub fn run() {
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut server = Server { files: HashMap::new() };
+    while let Some(message) = read_message(&mut input) {
+        let id = message.get("id").cloned();
+        let method = message["method"].as_str().unwrap_or("");
+        let params = &message["params"];
+        match (method, id) {
+            ("exit", _) => break,
+            (_, Some(id)) => {
+                let response = match server.handle_request(method, params) {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err(msg) => json!({ "jsonrpc": "2.0", "id": id,
+                                        "error": { "code": -32601, "message": msg } }),
+                };
+                write_message(&response);
+            }
+            (_, None) => server.handle_notification(method, params),
+        }
+    }
+}
+
+impl Server {
+    fn handle_request(&mut self, method: &str, params: &Value) -> Result<Value, String> {
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": 2,
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "documentSymbolProvider": true,
+                },
+                "serverInfo": { "name": "gcide" },
+            })),
+            "shutdown" => Ok(Value::Null),
+            "textDocument/hover" => Ok(self.with_position(params, hover).unwrap_or(Value::Null)),
+            "textDocument/definition" => Ok(self.with_position(params, definition).unwrap_or(Value::Null)),
+            "textDocument/documentSymbol" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                Ok(self.files.get(uri).map(document_symbols).unwrap_or(Value::Null))
+            }
+            _ => Err(format!("unsupported method: {}", method)),
+        }
+    }
+
+    fn handle_notification(&mut self, method: &str, params: &Value) {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or("").to_string();
+        match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or("").to_string();
+                let doc = Document::parse(&text).0;
+                self.files.insert(uri.clone(), OpenFile { text, doc });
+            }
+            "textDocument/didChange" => {
+                let file = match self.files.get_mut(&uri) {
+                    Some(file) => file,
+                    None => return,
+                };
+                for change in params["contentChanges"].as_array().into_iter().flatten() {
+                    let new_text = change["text"].as_str().unwrap_or("");
+                    if change.get("range").is_some() {
+                        let start = position_to_offset(&file.text, &change["range"]["start"]);
+                        let end = position_to_offset(&file.text, &change["range"]["end"]);
+                        file.text.replace_range(start..end, new_text);
+                        file.doc.reparse(&file.text, &TextEdit { range: start..end, new_len: new_text.len() });
+                    } else {
+                        file.text = new_text.to_string();
+                        file.doc = Document::parse(&file.text).0;
+                    }
+                }
+            }
+            "textDocument/didClose" => {
+                self.files.remove(&uri);
+                publish_diagnostics(&uri, Vec::new());
+                return;
+            }
+            _ => return,
+        }
+        if let Some(file) = self.files.get(&uri) {
+            publish_diagnostics(&uri, diagnostics(file));
+        }
+    }
+
+    fn with_position<F>(&self, params: &Value, handler: F) -> Option<Value>
+    where F: Fn(&str, &OpenFile, usize) -> Option<Value> {
+        let uri = params["textDocument"]["uri"].as_str()?;
+        let file = self.files.get(uri)?;
+        let offset = position_to_offset(&file.text, &params["position"]);
+        handler(uri, file, offset)
+    }
+}
+
+fn parse_entry<'a>(file: &'a OpenFile, span: &Range<usize>) -> Result<Entry<'a>, ParserError<'a>> {
+    gcide::EntryParser::new(&file.text[span.clone()]).next().unwrap()
+}
+
+fn diagnostics(file: &OpenFile) -> Vec<Value> {
+    let mut diags = Vec::new();
+    for span in file.doc.spans() {
+        let raw = &file.text[span.clone()];
+        match parse_entry(file, span) {
+            Ok(entry) => {
+                for warning in check_entry(&entry) {
+                    let start = warning_anchor(raw, &warning).map(|idx| span.start + idx).unwrap_or(span.start);
+                    let end = match warning {
+                        ParseWarning::LiteralCharacter(c, _) => start + c.len_utf8(),
+                        ParseWarning::Mojibake(text, _) => start + text.len(),
+                        _ => raw.get(start - span.start + 2..).and_then(|rest| rest.find(['>', '/', '\n']))
+                            .map(|idx| start + 2 + idx + 1)
+                            .unwrap_or(span.end),
+                    };
+                    diags.push(diagnostic(&file.text, start..end, SEVERITY_WARNING, &warning.to_string()));
+                }
+                for violation in validate_nesting(&entry) {
+                    let start = violation.offset_in(raw).map(|idx| span.start + idx).unwrap_or(span.start);
+                    let end = start + violation.tag.len() + 2;
+                    let message = format!("<{}> may not appear inside <{}>", violation.tag, violation.ancestor);
+                    diags.push(diagnostic(&file.text, start..end.min(span.end), SEVERITY_WARNING, &message));
+                }
+            }
+            Err(err) => {
+                let start = span.start + err.leading.len();
+                let end = (start + err.trailing.find('\n').unwrap_or(err.trailing.len())).max(start + 1);
+                diags.push(diagnostic(&file.text, start..end.min(span.end), SEVERITY_ERROR, &err.hint()));
+            }
+        }
+    }
+    diags
+}
+
+/// Where in the raw entry text a warning most likely comes from.
+fn warning_anchor(raw: &str, warning: &ParseWarning) -> Option<usize> {
+    match *warning {
+        ParseWarning::UnknownEntity(name) => raw.find(&format!("<{}/", name)),
+        ParseWarning::DanglingTagOpen(name) | ParseWarning::ImplicitlyClosed(name) => raw
+            .find(&format!("<{}>", name))
+            .or_else(|| raw.find(&format!("<{} ", name))),
+        ParseWarning::DanglingTagClose(name) => raw.find(&format!("</{}>", name)),
+        ParseWarning::SuspiciousGreek(..) | ParseWarning::UnknownGreekChar(_) => raw.find("<grk>"),
+        ParseWarning::NestedParagraph => raw.find("<p").and_then(|i| raw[i + 2..].find("<p").map(|j| i + 2 + j)),
+        ParseWarning::UnknownTag(name) | ParseWarning::HandlerPanicked(name) => raw.find(&format!("<{}", name)),
+        ParseWarning::LiteralCharacter(c, _) => raw.find(c),
+        ParseWarning::Mojibake(text, _) | ParseWarning::LooseTag(text) | ParseWarning::StrayBracket(text) => raw.find(text),
+    }
+}
+
+fn diagnostic(text: &str, range: Range<usize>, severity: u8, message: &str) -> Value {
+    json!({
+        "range": lsp_range(text, range),
+        "severity": severity,
+        "source": "gcide",
+        "message": message,
+    })
+}
+
+fn publish_diagnostics(uri: &str, diags: Vec<Value>) {
+    write_message(&json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diags },
+    }));
+}
+
+fn hover(_uri: &str, file: &OpenFile, offset: usize) -> Option<Value> {
+    let text = &file.text;
+    let start = text[..offset.min(text.len())].rfind('<')?;
+    let len = text[start + 1..].find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == ':'))?;
+    let end = start + 1 + len;
+    if !text[end..].starts_with('/') || offset > end {
+        return None;
+    }
+    let name = &text[start + 1..end];
+    let contents = if is_known_entity(name) {
+        format!("`<{}/` \u{2192} {}", name, entity_to_unicode(name))
+    } else {
+        format!("`<{}/` is not a known entity", name)
+    };
+    Some(json!({
+        "contents": { "kind": "markdown", "value": contents },
+        "range": lsp_range(text, start..end + 1),
+    }))
+}
+
+fn definition(uri: &str, file: &OpenFile, offset: usize) -> Option<Value> {
+    let text = &file.text;
+    let open = text[..offset.min(text.len())].rfind("<er>")?;
+    let close = open + text[open..].find("</er>")?;
+    if offset > close {
+        return None;
+    }
+    let target = normalize_headword(&text[open + 4..close]);
+    for span in file.doc.spans() {
+        if let Ok(entry) = parse_entry(file, span) {
+            if normalize_headword(entry.main_word) == target {
+                return Some(json!({ "uri": uri, "range": lsp_range(text, span.start..span.start) }));
+            }
+        }
+    }
+    None
+}
+
+fn document_symbols(file: &OpenFile) -> Value {
+    let symbols: Vec<Value> = file.doc.spans().iter().map(|span| {
+        let name = match parse_entry(file, span) {
+            Ok(entry) => entry.main_word.to_string(),
+            Err(_) => "(unparsable entry)".to_string(),
+        };
+        let head_end = file.text[span.clone()].find('>').map(|i| span.start + i + 1).unwrap_or(span.end);
+        json!({
+            "name": name,
+            "kind": SYMBOL_KIND_KEY,
+            "range": lsp_range(&file.text, span.clone()),
+            "selectionRange": lsp_range(&file.text, span.start..head_end),
+            "detail": first_headword(&file.text[span.clone()]),
+        })
+    }).collect();
+    Value::Array(symbols)
+}
+
+fn first_headword(raw: &str) -> String {
+    match gcide::EntryParser::new(raw).next() {
+        Some(Ok(entry)) => find_tag(&entry.items, "hw").map(|items| {
+            items.iter().map(|i| i.to_string()).collect()
+        }).unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+fn find_tag<'e, 'a>(items: &'e [EntryItem<'a>], tag: &str) -> Option<&'e [EntryItem<'a>]> {
+    items.iter().filter_map(|item| match *item {
+        EntryItem::Tagged { name, ref items, .. } if name == tag => Some(&items[..]),
+        EntryItem::Tagged { ref items, .. } => find_tag(items, tag),
+        _ => None,
+    }).next()
+}
+
+fn lsp_range(text: &str, range: Range<usize>) -> Value {
+    json!({ "start": offset_to_position(text, range.start), "end": offset_to_position(text, range.end) })
+}
+
+fn offset_to_position(text: &str, offset: usize) -> Value {
+    let offset = offset.min(text.len());
+    let line_start = text[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line = text[..line_start].matches('\n').count();
+    let character: usize = text[line_start..offset].chars().map(char::len_utf16).sum();
+    json!({ "line": line, "character": character })
+}
+
+fn position_to_offset(text: &str, position: &Value) -> usize {
+    let line = position["line"].as_u64().unwrap_or(0) as usize;
+    let character = position["character"].as_u64().unwrap_or(0) as usize;
+    let line_start = match line {
+        0 => 0,
+        n => match text.match_indices('\n').nth(n - 1) {
+            Some((idx, _)) => idx + 1,
+            None => return text.len(),
+        },
+    };
+    let mut utf16 = 0;
+    for (idx, c) in text[line_start..].char_indices() {
+        if utf16 >= character || c == '\n' {
+            return line_start + idx;
+        }
+        utf16 += c.len_utf16();
+    }
+    text.len()
+}
+
+fn read_message<R: BufRead>(input: &mut R) -> Option<Value> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header).ok()? == 0 {
+            return None;
+        }
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(len) = header.strip_prefix("Content-Length:") {
+            content_length = len.trim().parse::<usize>().ok();
+        }
+    }
+    let mut body = vec![0; content_length?];
+    input.read_exact(&mut body).ok()?;
+    serde_json::from_slice(&body).ok()
+}
+
+fn write_message(message: &Value) {
+    let body = message.to_string();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let _ = write!(out, "Content-Length: {}\r\n\r\n{}", body.len(), body);
+    let _ = out.flush();
+}
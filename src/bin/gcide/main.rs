@@ -0,0 +1,198 @@
+extern crate gcide;
+#[macro_use]
+extern crate serde_json;
+extern crate structopt;
+
+use gcide::binutils;
+use structopt::StructOpt;
+
+mod apple;
+mod apply_rules;
+mod concordance;
+mod csv;
+mod dict;
+mod diff;
+mod duplicates;
+mod filter;
+mod fix_entities;
+mod fix_mojibake;
+mod fix_unknowns;
+mod from_web1913;
+mod grep;
+mod html;
+mod identity;
+mod latex;
+mod lint;
+mod lookup;
+mod lsp;
+mod merge;
+mod normalize;
+mod ontolex;
+mod reflow;
+mod revert;
+mod serve;
+mod stats;
+mod taxa;
+mod template;
+mod thesaurus;
+mod todos;
+mod verify;
+mod wiki;
+
+/// Tools for the GNU Collaborative International Dictionary of English.
+///
+/// Exit status: 0 on success, 1 if something was found (lint problems,
+/// differences, merge conflicts, words not found), 2 if a file cannot be
+/// read or written, 3 if the input cannot be used.
+#[derive(StructOpt, Debug)]
+#[structopt(name = "gcide")]
+struct Opt {
+    #[structopt(short = "j", long = "jobs", default_value = "0",
+                help = "threads for commands that read several files or convert entries (default: one per CPU)")]
+    jobs: usize,
+    #[structopt(long = "json-errors",
+                help = "report errors on stderr as JSON lines with file, line, entry, headword, kind and message")]
+    json_errors: bool,
+    #[structopt(subcommand)]
+    command: Command,
+}
+
+#[derive(StructOpt, Debug)]
+enum Command {
+    /// Rewrite entries as parsed, marking the unparsable ones
+    #[structopt(name = "identity")]
+    Identity(identity::IdentityOpt),
+    /// Check that entries are written back byte for byte
+    #[structopt(name = "verify")]
+    Verify(verify::VerifyOpt),
+    /// Report unparsable entries, parse warnings, misplaced tags and gaps in sense numbers
+    #[structopt(name = "lint")]
+    Lint(lint::LintOpt),
+    /// Count entries, unparsable entries and warnings
+    #[structopt(name = "stats")]
+    Stats(stats::StatsOpt),
+    /// Report entries that repeat another with the same headword
+    #[structopt(name = "duplicates")]
+    Duplicates(duplicates::DuplicatesOpt),
+    /// Print the parts of entries matching a selector
+    #[structopt(name = "grep")]
+    Grep(grep::GrepOpt),
+    /// List comments and unknown characters for proofreading
+    #[structopt(name = "todos")]
+    Todos(todos::TodosOpt),
+    /// Look up words
+    #[structopt(name = "lookup")]
+    Lookup(lookup::LookupOpt),
+    /// Serve lookups as JSON over HTTP
+    #[structopt(name = "serve")]
+    Serve(serve::ServeOpt),
+    /// Run a language server on stdin and stdout
+    #[structopt(name = "lsp")]
+    Lsp,
+    /// Compare two versions of a file entry by entry
+    #[structopt(name = "diff")]
+    Diff(diff::DiffOpt),
+    /// Merge entry by entry, as a git merge driver
+    #[structopt(name = "merge")]
+    Merge(merge::MergeOpt),
+    /// Filter entries by source, sort, merge or split them
+    #[structopt(name = "filter")]
+    Filter(filter::FilterOpt),
+    /// Rewrap long lines
+    #[structopt(name = "reflow")]
+    Reflow(reflow::ReflowOpt),
+    /// Normalize dashes and apostrophes
+    #[structopt(name = "normalize")]
+    Normalize(normalize::NormalizeOpt),
+    /// Replace literal characters with entities
+    #[structopt(name = "fix-entities")]
+    FixEntities(fix_entities::FixEntitiesOpt),
+    /// Repair doubly encoded UTF-8
+    #[structopt(name = "fix-mojibake")]
+    FixMojibake(fix_mojibake::FixMojibakeOpt),
+    /// Undo the changes recorded with --journal
+    #[structopt(name = "revert")]
+    Revert(revert::RevertOpt),
+    /// Apply rewrite rules from a TOML file
+    #[structopt(name = "apply-rules")]
+    ApplyRules(apply_rules::ApplyRulesOpt),
+    /// Replace unknown characters from a table
+    #[structopt(name = "fix-unknowns")]
+    FixUnknowns(fix_unknowns::FixUnknownsOpt),
+    /// Convert the original web1913 transcription to GCIDE entries
+    #[structopt(name = "from-web1913")]
+    FromWeb1913(from_web1913::FromWeb1913Opt),
+    /// Convert to HTML
+    #[structopt(name = "html")]
+    Html(html::HtmlOpt),
+    /// Convert to Wiktionary-style wikitext
+    #[structopt(name = "wiki")]
+    Wiki(wiki::WikiOpt),
+    /// Convert to OntoLex-Lemon Turtle
+    #[structopt(name = "ontolex")]
+    OntoLex(ontolex::OntoLexOpt),
+    /// Convert to a dictd database
+    #[structopt(name = "dict")]
+    Dict(dict::DictOpt),
+    /// Convert to the sources of an Apple Dictionary.app bundle
+    #[structopt(name = "apple")]
+    Apple(apple::AppleOpt),
+    /// Convert senses or collocations to CSV
+    #[structopt(name = "csv")]
+    Csv(csv::CsvOpt),
+    /// Extract taxonomic names as CSV
+    #[structopt(name = "taxa")]
+    Taxa(taxa::TaxaOpt),
+    /// Build a Moby or MyThes thesaurus from synonym lists and antonyms
+    #[structopt(name = "thesaurus")]
+    Thesaurus(thesaurus::ThesaurusOpt),
+    /// List, for each headword, the entries whose definitions use it
+    #[structopt(name = "concordance")]
+    Concordance(concordance::ConcordanceOpt),
+    /// Render entries with a Handlebars template
+    #[structopt(name = "template")]
+    Template(template::TemplateOpt),
+    /// Convert to LaTeX
+    #[structopt(name = "latex")]
+    Latex(latex::LatexOpt),
+}
+
+fn main() {
+    let opt = Opt::from_args();
+    binutils::restore_sigpipe();
+    binutils::set_json_errors(opt.json_errors);
+    match opt.command {
+        Command::Identity(cmd) => identity::run(cmd),
+        Command::Verify(cmd) => verify::run(cmd),
+        Command::Lint(cmd) => lint::run(cmd, opt.jobs),
+        Command::Stats(cmd) => stats::run(cmd, opt.jobs),
+        Command::Duplicates(cmd) => duplicates::run(cmd, opt.jobs),
+        Command::Grep(cmd) => grep::run(cmd),
+        Command::Todos(cmd) => todos::run(cmd),
+        Command::Lookup(cmd) => lookup::run(cmd),
+        Command::Serve(cmd) => serve::run(cmd),
+        Command::Lsp => lsp::run(),
+        Command::Diff(cmd) => diff::run(cmd),
+        Command::Merge(cmd) => merge::run(cmd),
+        Command::Filter(cmd) => filter::run(cmd),
+        Command::Reflow(cmd) => reflow::run(cmd),
+        Command::Normalize(cmd) => normalize::run(cmd),
+        Command::FixEntities(cmd) => fix_entities::run(cmd),
+        Command::FixMojibake(cmd) => fix_mojibake::run(cmd),
+        Command::FixUnknowns(cmd) => fix_unknowns::run(cmd),
+        Command::ApplyRules(cmd) => apply_rules::run(cmd),
+        Command::Revert(cmd) => revert::run(cmd),
+        Command::FromWeb1913(cmd) => from_web1913::run(cmd),
+        Command::Html(cmd) => html::run(cmd, opt.jobs),
+        Command::Wiki(cmd) => wiki::run(cmd, opt.jobs),
+        Command::OntoLex(cmd) => ontolex::run(cmd, opt.jobs),
+        Command::Dict(cmd) => dict::run(cmd, opt.jobs),
+        Command::Apple(cmd) => apple::run(cmd, opt.jobs),
+        Command::Csv(cmd) => csv::run(cmd, opt.jobs),
+        Command::Taxa(cmd) => taxa::run(cmd, opt.jobs),
+        Command::Thesaurus(cmd) => thesaurus::run(cmd),
+        Command::Concordance(cmd) => concordance::run(cmd),
+        Command::Template(cmd) => template::run(cmd, opt.jobs),
+        Command::Latex(cmd) => latex::run(cmd, opt.jobs),
+    }
+}
@@ -0,0 +1,30 @@
+use gcide::binutils::{self, UnwrapOrFail};
+use gcide::merge::merge3;
+use std::path::PathBuf;
+use std::process;
+use structopt::StructOpt;
+
+/// Usable as a git merge driver: `gcide merge %O %A %B`.
+#[derive(StructOpt, Debug)]
+pub struct MergeOpt {
+    #[structopt(name = "BASE", help = "common ancestor", parse(from_os_str))]
+    base: PathBuf,
+    #[structopt(name = "OURS", help = "our version (overwritten with the result by default)", parse(from_os_str))]
+    ours: PathBuf,
+    #[structopt(name = "THEIRS", help = "their version", parse(from_os_str))]
+    theirs: PathBuf,
+    #[structopt(short = "o", long = "output", help = "write the result here instead", parse(from_os_str))]
+    output: Option<PathBuf>,
+}
+
+pub fn run(opt: MergeOpt) {
+    let read = |path: &PathBuf| binutils::read_file(path).unwrap_or_fail(path);
+    let merged = merge3(&read(&opt.base), &read(&opt.ours), &read(&opt.theirs));
+
+    let outfile = opt.output.as_ref().unwrap_or(&opt.ours);
+    binutils::write_file(outfile, merged.text.as_bytes()).unwrap_or_fail(outfile);
+    for key in &merged.conflicts {
+        eprintln!("conflict: {}", key.headword);
+    }
+    process::exit(if merged.conflicts.is_empty() { 0 } else { binutils::EXIT_FINDINGS });
+}
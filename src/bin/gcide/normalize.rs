@@ -0,0 +1,28 @@
+use gcide::{binutils, CIDE};
+use gcide::binutils::PatchOpt;
+use gcide::normalize::{normalize_symbols, NormalizeRules, SymbolStyle};
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct NormalizeOpt {
+    #[structopt(flatten)]
+    patch: PatchOpt,
+    #[structopt(long = "dashes", default_value = "ascii", help = "write dashes as: keep, ascii (--) or entity (<mdash/)")]
+    dashes: SymbolStyle,
+    #[structopt(long = "apostrophes", default_value = "ascii",
+                help = "write apostrophes as: keep, ascii (') or entity (<rsquo/)")]
+    apostrophes: SymbolStyle,
+}
+
+pub fn run(opt: NormalizeOpt) {
+    let rules = NormalizeRules { dashes: opt.dashes, apostrophes: opt.apostrophes };
+    binutils::patch_entries_with(&opt.patch, |_, entry_res| {
+        // leave broken entries alone
+        let mut entry = entry_res.ok()?;
+        if normalize_symbols(&mut entry.items, &rules) {
+            Some(CIDE(&entry).to_string())
+        } else {
+            None
+        }
+    });
+}
@@ -0,0 +1,28 @@
+use gcide::{binutils, parallel, EntryParser};
+use gcide::binutils::{PipeOpt, SourceOpt};
+use gcide::exporter::ontolex::{self, OntoLex};
+use gcide::sources;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct OntoLexOpt {
+    #[structopt(flatten)]
+    pipe: PipeOpt,
+    #[structopt(flatten)]
+    sources: SourceOpt,
+    #[structopt(long = "base", default_value = "http://example.org/gcide/",
+                help = "base IRI of the lexical entries")]
+    base: String,
+}
+
+pub fn run(opt: OntoLexOpt, jobs: usize) {
+    let filter = opt.sources.filter();
+    binutils::pipe_through_with(&opt.pipe, |contents| {
+        let mut output = ontolex::header(&opt.base);
+        let entries = EntryParser::new(contents).with_ids().filter_map(Result::ok);
+        parallel::ordered_map(entries, jobs, |mut entry| {
+            if sources::retain_sources(&mut entry, &filter) { OntoLex(&entry).to_string() } else { String::new() }
+        }, |text| output.push_str(&text));
+        output
+    });
+}
@@ -0,0 +1,27 @@
+use gcide::binutils::{self, PatchOpt};
+use gcide::exporter::reflow_cide;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct ReflowOpt {
+    #[structopt(flatten)]
+    patch: PatchOpt,
+    #[structopt(short = "w", long = "width", default_value = "78", help = "maximum line width")]
+    width: usize,
+}
+
+pub fn run(opt: ReflowOpt) {
+    let width = opt.width;
+    binutils::patch_entries_with(&opt.patch, |raw, entry_res| {
+        // leave broken entries alone; reflowing them would only hide the error
+        if entry_res.is_err() {
+            return None;
+        }
+        let reflowed = reflow_cide(raw, width);
+        if reflowed != raw {
+            Some(reflowed)
+        } else {
+            None
+        }
+    });
+}
@@ -0,0 +1,41 @@
+use gcide::binutils;
+use gcide::binutils::{ErrorRecord, JournalRecord, JournalUndo, PatchOpt, UnwrapOrFail};
+use std::cell::Cell;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct RevertOpt {
+    #[structopt(flatten)]
+    patch: PatchOpt,
+    #[structopt(long = "using", parse(from_os_str), help = "journal written with --journal")]
+    using: PathBuf,
+    #[structopt(long = "headword", number_of_values = 1,
+                help = "only revert the changes to entries with this main word (repeatable)")]
+    headwords: Vec<String>,
+}
+
+/// Puts back the entries a journal records as changed or added, wherever their
+/// current text is the one the journal has after the change. Changes recorded
+/// one after the other are undone together; removed entries are not restored.
+pub fn run(opt: RevertOpt) {
+    let text = binutils::read_file(&opt.using).unwrap_or_fail(&opt.using);
+    let records = JournalRecord::read_all(&text).unwrap_or_else(|err| {
+        binutils::fail(ErrorRecord::new("invalid-input", err).in_file(&opt.using), binutils::EXIT_INVALID_INPUT)
+    });
+    let selected: Vec<_> = records.iter()
+        .filter(|record| opt.headwords.is_empty() || opt.headwords.contains(&record.headword))
+        .collect();
+    let undo = JournalUndo::new(selected.iter().cloned());
+    let reverted = Cell::new(0);
+    binutils::patch_entries_with(&opt.patch, |raw, _| {
+        let original = undo.original(raw)?;
+        reverted.set(reverted.get() + 1);
+        Some(original.unwrap_or("").to_string())
+    });
+    let removed = selected.iter().filter(|record| record.after.is_none()).count();
+    eprintln!("reverted {} entries", reverted.get());
+    if removed > 0 {
+        eprintln!("{} removed entries not restored", removed);
+    }
+}
@@ -0,0 +1,159 @@
+use gcide::{binutils, Dictionary};
+use gcide::binutils::ErrorRecord;
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::fs;
+use std::sync::Arc;
+use std::thread;
+use structopt::StructOpt;
+
+const SEARCH_LIMIT: usize = 50;
+const SUGGESTION_LIMIT: usize = 5;
+
+#[derive(StructOpt, Debug)]
+pub struct ServeOpt {
+    #[structopt(name = "FILES", help = "GNU CIDE files", parse(from_os_str), required = true)]
+    files: Vec<PathBuf>,
+    #[structopt(short = "a", long = "addr", default_value = "127.0.0.1:8080", help = "address to listen on")]
+    addr: String,
+    #[structopt(long = "cache", parse(from_os_str),
+                help = "load the parsed FILES from this file; it is (re)written if missing or older than FILES")]
+    cache: Option<PathBuf>,
+}
+
+pub fn run(opt: ServeOpt) {
+    let dict = Arc::new(load_dictionary(&opt));
+    eprintln!("indexed {} headwords; listening on http://{}", dict.index().len(), opt.addr);
+
+    let listener = TcpListener::bind(&opt.addr)
+        .unwrap_or_else(|err| binutils::fail(ErrorRecord::new("io", format!("{}: {}", opt.addr, err)), binutils::EXIT_IO));
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let dict = Arc::clone(&dict);
+                thread::spawn(move || {
+                    if let Err(err) = handle_connection(stream, &dict) {
+                        eprintln!("connection error: {}", err);
+                    }
+                });
+            }
+            Err(err) => eprintln!("accept failed: {}", err),
+        }
+    }
+}
+
+fn load_dictionary(opt: &ServeOpt) -> Dictionary {
+    let modified = |path: &PathBuf| fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    if let Some(ref cache) = opt.cache {
+        let cache_time = modified(cache);
+        if cache_time.is_some() && opt.files.iter().all(|path| modified(path) <= cache_time) {
+            match Dictionary::load(cache) {
+                Ok(dict) => return dict,
+                Err(err) => eprintln!("{}: {}; rebuilding", cache.display(), err),
+            }
+        }
+    }
+    let dict = Dictionary::from_files(&opt.files)
+        .unwrap_or_else(|err| binutils::fail(ErrorRecord::new("io", err), binutils::EXIT_IO));
+    if let Some(ref cache) = opt.cache {
+        if let Err(err) = dict.save(cache) {
+            eprintln!("{}: {}", cache.display(), err);
+        }
+    }
+    dict
+}
+
+fn handle_connection(stream: TcpStream, dict: &Dictionary) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    loop { // skip headers; requests carry no body we care about
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+    let mut parts = request_line.split_whitespace();
+    let (status, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) => route(target, dict),
+        (Some(_), Some(_)) => (405, json!({ "error": "method not allowed" })),
+        _ => (400, json!({ "error": "bad request" })),
+    };
+    write_response(stream, status, &body)
+}
+
+fn route(target: &str, dict: &Dictionary) -> (u16, Value) {
+    let (path, query) = match target.find('?') {
+        Some(idx) => (&target[..idx], &target[idx + 1..]),
+        None => (target, ""),
+    };
+    if let Some(word) = path.strip_prefix("/define/") {
+        let word = percent_decode(word);
+        let entries = dict.define(&word);
+        if entries.is_empty() {
+            let suggestions = dict.index().suggest(&word, SUGGESTION_LIMIT);
+            (404, json!({ "error": "not found", "word": word, "suggestions": suggestions }))
+        } else {
+            (200, json!({ "word": word, "entries": entries }))
+        }
+    } else if path == "/search" {
+        let q = query.split('&')
+            .filter_map(|pair| pair.strip_prefix("q="))
+            .map(percent_decode)
+            .next();
+        match q {
+            Some(ref q) if !q.is_empty() => {
+                let results: Vec<Value> = dict.index().prefix(q).into_iter()
+                    .take(SEARCH_LIMIT)
+                    .map(|(word, locs)| json!({ "word": word, "entries": dict.entries_at(locs) }))
+                    .collect();
+                (200, json!({ "query": q, "results": results }))
+            }
+            _ => (400, json!({ "error": "missing query parameter q" })),
+        }
+    } else {
+        (404, json!({ "error": "no such endpoint" }))
+    }
+}
+
+fn write_response(mut stream: TcpStream, status: u16, body: &Value) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    let body = body.to_string();
+    write!(stream, "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=utf-8\r\n\
+                    Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+           status, reason, body.len(), body)?;
+    stream.flush()
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'%' if idx + 2 < bytes.len() => {
+                let hex = |b: u8| (b as char).to_digit(16);
+                match (hex(bytes[idx + 1]), hex(bytes[idx + 2])) {
+                    (Some(hi), Some(lo)) => {
+                        decoded.push((hi * 16 + lo) as u8);
+                        idx += 3;
+                        continue;
+                    }
+                    _ => decoded.push(b'%'),
+                }
+            }
+            b'+' => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        idx += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
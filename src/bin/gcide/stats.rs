@@ -0,0 +1,64 @@
+use gcide::{binutils, EntryParser};
+use gcide::binutils::UnwrapOrFail;
+use gcide::manifest::source_version;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct StatsOpt {
+    #[structopt(name = "FILE", help = "GNU CIDE files", parse(from_os_str), required = true)]
+    files: Vec<PathBuf>,
+    #[structopt(long = "json", help = "print the counts as JSON")]
+    json: bool,
+}
+
+#[derive(Default)]
+struct FileStats {
+    version: Option<String>,
+    entries: usize,
+    unparsable: usize,
+    warnings: usize,
+}
+
+/// Counts entries, unparsable entries and warnings per file.
+pub fn run(opt: StatsOpt, jobs: usize) {
+    let stats = binutils::map_files(&opt.files, jobs, file_stats);
+    if opt.json {
+        let files: Vec<Value> = opt.files.iter().zip(&stats).map(|(path, s)| json!({
+            "file": path.display().to_string(),
+            "version": s.version,
+            "entries": s.entries,
+            "unparsable": s.unparsable,
+            "warnings": s.warnings,
+        })).collect();
+        println!("{}", serde_json::to_string_pretty(&files).unwrap());
+        return;
+    }
+    let mut total = FileStats::default();
+    println!("{:>8} {:>10} {:>8}  file", "entries", "unparsable", "warnings");
+    for (path, s) in opt.files.iter().zip(&stats) {
+        println!("{:8} {:10} {:8}  {}{}", s.entries, s.unparsable, s.warnings, path.display(),
+                 s.version.as_ref().map_or(String::new(), |v| format!(" (version {})", v)));
+        total.entries += s.entries;
+        total.unparsable += s.unparsable;
+        total.warnings += s.warnings;
+    }
+    if stats.len() > 1 {
+        println!("{:8} {:10} {:8}  total", total.entries, total.unparsable, total.warnings);
+    }
+}
+
+fn file_stats(path: &Path) -> FileStats {
+    let contents = binutils::map_file(path).unwrap_or_fail(path);
+    let mut stats = FileStats { version: source_version(&contents), ..FileStats::default() };
+    let mut entry_iter = EntryParser::new(&contents);
+    while let Some(entry_res) = entry_iter.next() {
+        stats.entries += 1;
+        match entry_res {
+            Ok(_) => stats.warnings += entry_iter.warnings().len(),
+            Err(_) => stats.unparsable += 1,
+        }
+    }
+    stats
+}
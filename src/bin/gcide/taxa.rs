@@ -0,0 +1,31 @@
+use gcide::{binutils, parallel, EntryParser};
+use gcide::binutils::PipeOpt;
+use gcide::taxa;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct TaxaOpt {
+    #[structopt(flatten)]
+    pipe: PipeOpt,
+    #[structopt(long = "tsv", help = "separate fields with tabs instead of commas")]
+    tsv: bool,
+}
+
+pub fn run(opt: TaxaOpt, jobs: usize) {
+    let delimiter = if opt.tsv { '\t' } else { ',' };
+    binutils::pipe_through_with(&opt.pipe, |contents| {
+        let mut output = String::new();
+        binutils::write_csv_row(&mut output, &["headword", "sense", "rank", "name"], delimiter);
+        let entries = EntryParser::new(contents).filter_map(Result::ok);
+        parallel::ordered_map(entries, jobs, |entry| {
+            let mut rows = String::new();
+            for taxon in taxa::taxa(&entry) {
+                let sense = taxon.sense.as_ref().map_or("", |s| &s[..]);
+                binutils::write_csv_row(&mut rows, &[entry.main_word, sense, taxon.rank.as_str(), &taxon.name],
+                                        delimiter);
+            }
+            rows
+        }, |rows| output.push_str(&rows));
+        output
+    });
+}
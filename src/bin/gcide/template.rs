@@ -0,0 +1,57 @@
+use gcide::{binutils, parallel, EntryParser};
+use gcide::binutils::{ErrorRecord, PipeOpt, UnwrapOrFail};
+use gcide::exporter::template::TemplateExporter;
+use gcide::model::entry_model;
+use gcide::wordnet::WordNet;
+use std::iter;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct TemplateOpt {
+    #[structopt(flatten)]
+    pipe: PipeOpt,
+    #[structopt(short = "t", long = "template", parse(from_os_str),
+                help = "Handlebars template rendered for each entry")]
+    template: PathBuf,
+    #[structopt(long = "escape-html", help = "HTML-escape values inserted with {{...}}")]
+    escape_html: bool,
+    #[structopt(long = "wordnet", parse(from_os_str),
+                help = "set the synset of senses from the WordNet data files in this directory")]
+    wordnet: Option<PathBuf>,
+}
+
+pub fn run(opt: TemplateOpt, jobs: usize) {
+    let template = binutils::read_file(&opt.template).unwrap_or_fail(&opt.template);
+    let exporter = TemplateExporter::new(&template)
+        .unwrap_or_else(|err| {
+            binutils::fail(ErrorRecord::new("invalid-input", err).in_file(&opt.template), binutils::EXIT_INVALID_INPUT)
+        })
+        .escape_html(opt.escape_html);
+    let wordnet = opt.wordnet.as_ref().map(|dir| WordNet::load(dir).unwrap_or_fail(dir));
+    binutils::pipe_through_with(&opt.pipe, |contents| {
+        let mut output = String::with_capacity(contents.len());
+        let mut entries = EntryParser::new(contents).with_ids();
+        let mut index = 0;
+        let located_entries = iter::from_fn(|| {
+            let entry_res = entries.next()?;
+            index += 1;
+            Some((index - 1, entries.last_span().unwrap().start, entry_res))
+        });
+        parallel::ordered_map(located_entries, jobs, |(index, span_start, entry_res)| {
+            let entry = entry_res.ok()?;
+            let mut model = entry_model(&entry);
+            if let Some(ref wordnet) = wordnet {
+                wordnet.annotate(&mut model);
+            }
+            Some(exporter.render_model(&model).map_err(|err| {
+                ErrorRecord::new("render", err).in_file(&opt.pipe.infile).at_entry(contents, span_start, index)
+            }))
+        }, |rendered| match rendered {
+            Some(Ok(text)) => output.push_str(&text),
+            Some(Err(record)) => binutils::report(&record),
+            None => (),
+        });
+        output
+    });
+}
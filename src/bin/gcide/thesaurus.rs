@@ -0,0 +1,38 @@
+use gcide::{binutils, EntryParser};
+use gcide::binutils::{ErrorRecord, PipeOpt, SourceOpt, UnwrapOrFail};
+use gcide::exporter::thesaurus::Thesaurus;
+use gcide::sources;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct ThesaurusOpt {
+    #[structopt(flatten)]
+    pipe: PipeOpt,
+    #[structopt(flatten)]
+    sources: SourceOpt,
+    #[structopt(long = "mythes",
+                help = "write OUTFILE as a MyThes .dat file, with its index next to it (default: Moby format)")]
+    mythes: bool,
+}
+
+pub fn run(opt: ThesaurusOpt) {
+    let filter = opt.sources.filter();
+    let contents = binutils::load_file(&opt.pipe.infile, opt.pipe.encoding).unwrap_or_fail(&opt.pipe.infile);
+    let mut thesaurus = Thesaurus::new();
+    for mut entry in EntryParser::new(&contents).filter_map(Result::ok) {
+        if sources::retain_sources(&mut entry, &filter) {
+            thesaurus.add_entry(&entry);
+        }
+    }
+    if !opt.mythes {
+        let outfile = opt.pipe.outfile.clone().unwrap_or_else(|| "-".into());
+        return binutils::write_file(&outfile, thesaurus.moby().as_bytes()).unwrap_or_fail(&outfile);
+    }
+    let dat_path = opt.pipe.outfile.as_ref().filter(|path| !binutils::is_std_stream(path)).unwrap_or_else(|| {
+        binutils::fail(ErrorRecord::new("invalid-input", "--mythes needs an output file"), binutils::EXIT_INVALID_INPUT)
+    });
+    let (dat, idx) = thesaurus.mythes();
+    let idx_path = dat_path.with_extension("idx");
+    binutils::write_file(dat_path, dat.as_bytes()).unwrap_or_fail(dat_path);
+    binutils::write_file(&idx_path, idx.as_bytes()).unwrap_or_fail(&idx_path);
+}
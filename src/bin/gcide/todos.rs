@@ -0,0 +1,58 @@
+use gcide::{binutils, EntryParser};
+use gcide::binutils::UnwrapOrFail;
+use gcide::todos::{self, Todo};
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct TodosOpt {
+    #[structopt(name = "FILE", help = "GNU CIDE files", parse(from_os_str), required = true)]
+    files: Vec<PathBuf>,
+    #[structopt(long = "csv", help = "write CSV rows (file, line, headword, kind, text) instead of a text list")]
+    csv: bool,
+}
+
+/// Lists the `<-- comments -->` and `<?/` unknown characters in the files,
+/// grouped by file and headword, as a work list for proofreaders.
+pub fn run(opt: TodosOpt) {
+    let mut output = String::new();
+    if opt.csv {
+        binutils::write_csv_row(&mut output, &["file", "line", "headword", "kind", "text"], ',');
+    }
+    let mut total = 0;
+    for path in &opt.files {
+        let contents = binutils::map_file(path).unwrap_or_fail(path);
+        let file_name = path.display().to_string();
+        let mut file_header = !opt.csv;
+        let mut entry_iter = EntryParser::new(&contents);
+        while let Some(entry_res) = entry_iter.next() {
+            let entry = match entry_res {
+                Ok(entry) => entry,
+                Err(_) => continue,
+            };
+            let todos = todos::entry_todos(&entry);
+            if todos.is_empty() {
+                continue;
+            }
+            total += todos.len();
+            let span = entry_iter.last_span().unwrap();
+            let line = (contents[..span.start].matches('\n').count() + 1).to_string();
+            if opt.csv {
+                for Todo { kind, text } in &todos {
+                    binutils::write_csv_row(&mut output, &[&file_name, &line, entry.main_word, kind.name(), text], ',');
+                }
+                continue;
+            }
+            if file_header {
+                output.push_str(&format!("{}\n", file_name));
+                file_header = false;
+            }
+            output.push_str(&format!("  {} (line {})\n", entry.main_word, line));
+            for Todo { kind, text } in &todos {
+                output.push_str(&format!("    {}: {}\n", kind.name(), text));
+            }
+        }
+    }
+    print!("{}", output);
+    eprintln!("{} items to review", total);
+}
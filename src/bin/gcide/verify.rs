@@ -0,0 +1,54 @@
+use gcide::{binutils, EntryParser, CIDE};
+use gcide::binutils::UnwrapOrFail;
+use std::path::PathBuf;
+use std::process;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct VerifyOpt {
+    #[structopt(name = "FILE", help = "GNU CIDE files", parse(from_os_str), required = true)]
+    files: Vec<PathBuf>,
+}
+
+/// Checks that every entry parses and is written back byte for byte by `CIDE`;
+/// exits with 1 if any does not.
+pub fn run(opt: VerifyOpt) {
+    let mut failures = 0;
+    for path in &opt.files {
+        let contents = binutils::map_file(path).unwrap_or_fail(path);
+        let mut entry_iter = EntryParser::new(&contents);
+        while let Some(entry_res) = entry_iter.next() {
+            let span = entry_iter.last_span().unwrap();
+            let raw = &contents[span.clone()];
+            let line = contents[..span.start].matches('\n').count() + 1;
+            let entry = match entry_res {
+                Ok(entry) => entry,
+                Err(err) => {
+                    failures += 1;
+                    println!("{}:{}: unparsable entry: {}", path.display(), line, err.hint());
+                    continue;
+                }
+            };
+            let written = CIDE(&entry).to_string();
+            if written != raw {
+                failures += 1;
+                let (diff_line, expected, got) = first_difference(raw, &written);
+                println!("{}:{}: {}: lossy output\n  source: {}\n  output: {}",
+                         path.display(), line + diff_line, entry.main_word, expected, got);
+            }
+        }
+    }
+    process::exit(if failures == 0 { 0 } else { binutils::EXIT_FINDINGS });
+}
+
+/// Index and contents of the first line that differs between `a` and `b`.
+fn first_difference<'s>(a: &'s str, b: &'s str) -> (usize, &'s str, &'s str) {
+    let (mut a_lines, mut b_lines) = (a.split('\n'), b.split('\n'));
+    let mut idx = 0;
+    loop {
+        match (a_lines.next(), b_lines.next()) {
+            (Some(x), Some(y)) if x == y => idx += 1,
+            (x, y) => return (idx, x.unwrap_or(""), y.unwrap_or("")),
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use gcide::{binutils, parallel, EntryParser};
+use gcide::binutils::{PipeOpt, SourceOpt};
+use gcide::exporter::wiki::Wikitext;
+use gcide::sources;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct WikiOpt {
+    #[structopt(flatten)]
+    pipe: PipeOpt,
+    #[structopt(flatten)]
+    sources: SourceOpt,
+}
+
+pub fn run(opt: WikiOpt, jobs: usize) {
+    let filter = opt.sources.filter();
+    binutils::pipe_through_with(&opt.pipe, |contents| {
+        let mut output = String::with_capacity(contents.len());
+        let entries = EntryParser::new(contents).with_ids().filter_map(Result::ok);
+        parallel::ordered_map(entries, jobs, |mut entry| {
+            if !sources::retain_sources(&mut entry, &filter) {
+                return String::new();
+            }
+            // marks where the page for the main word starts
+            format!("<!-- {} -->\n{}\n", entry.main_word.replace("--", ""), Wikitext(&entry))
+        }, |text| output.push_str(&text));
+        output
+    });
+}
@@ -1,72 +1,619 @@
-use std::{fmt, process};
-use std::fs::File;
-use std::io::{Error, Read};
+use std::{fmt, process, str};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Error, Read, Write};
+use std::ops::Deref;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use structopt::StructOpt;
 
+use encoding_rs::{Encoding, UTF_8};
+#[cfg(feature = "mmap")]
+use memmap2::Mmap;
+
+use decode::{self, Decoded};
+use diff::{self, Compare, EntryChange};
+use manifest::{FileInfo, Manifest, SourceInfo};
+use parallel;
+use parser::{Entry, EntryParser, ParserError, Progress};
+use sources::SourceFilter;
+
 #[derive(StructOpt, Debug)]
-struct PatchOpt {
-    #[structopt(name = "INFILE", help = "GNU CIDE file", parse(from_os_str))]
-    infile: PathBuf,
-    #[structopt(name = "OUTFILE", help = "output file (default: overwrite)", parse(from_os_str))]
-    outfile: Option<PathBuf>,
+pub struct PatchOpt {
+    #[structopt(name = "INFILE", default_value = "-", help = "GNU CIDE file, or - for stdin", parse(from_os_str))]
+    pub infile: PathBuf,
+    #[structopt(name = "OUTFILE", help = "output file, or - for stdout (default: overwrite INFILE, or stdout)",
+                parse(from_os_str))]
+    pub outfile: Option<PathBuf>,
+    #[structopt(long = "encoding", parse(try_from_str = "parse_encoding"),
+                help = "encoding of INFILE, e.g. latin1 (default: utf-8)")]
+    pub encoding: Option<&'static Encoding>,
+    #[structopt(long = "dry-run", help = "list the entries that would change instead of writing anything")]
+    pub dry_run: bool,
+    #[structopt(long = "check", help = "write nothing, and exit with 1 if anything would change")]
+    pub check: bool,
+    #[structopt(long = "backup", raw(require_equals = "true"),
+                help = "copy the file about to be overwritten to its name plus this suffix (default: ~)")]
+    pub backup: Option<Option<String>>,
+    #[structopt(long = "journal", parse(from_os_str),
+                help = "append each changed entry to this file as a JSON line, for review or `gcide revert`")]
+    pub journal: Option<PathBuf>,
 }
 
 #[derive(StructOpt, Debug)]
-struct PipeOpt {
-    #[structopt(name = "INFILE", help = "GNU CIDE file", parse(from_os_str))]
-    infile: PathBuf,
-    #[structopt(name = "OUTFILE", help = "output file (default: stdout)", parse(from_os_str))]
-    outfile: Option<PathBuf>,
+pub struct PipeOpt {
+    #[structopt(name = "INFILE", default_value = "-", help = "GNU CIDE file, or - for stdin", parse(from_os_str))]
+    pub infile: PathBuf,
+    #[structopt(name = "OUTFILE", help = "output file, or - for stdout (default: stdout)", parse(from_os_str))]
+    pub outfile: Option<PathBuf>,
+    #[structopt(long = "encoding", parse(try_from_str = "parse_encoding"),
+                help = "encoding of INFILE, e.g. latin1 (default: utf-8)")]
+    pub encoding: Option<&'static Encoding>,
+    #[structopt(long = "manifest", parse(from_os_str),
+                help = "write sizes, SHA-256 digests and entry counts of INFILE and the output to this JSON file")]
+    pub manifest: Option<PathBuf>,
+}
+
+impl PatchOpt {
+    /// Where the patched text goes: OUTFILE, or else INFILE, or stdout if
+    /// INFILE is stdin.
+    pub fn patch_target(&self) -> &Path {
+        self.outfile.as_ref().unwrap_or(&self.infile)
+    }
+}
+
+fn parse_encoding(label: &str) -> Result<&'static Encoding, String> {
+    decode::encoding_for_label(label).ok_or_else(|| format!("unknown encoding: {}", label))
+}
+
+#[derive(StructOpt, Debug)]
+pub struct SourceOpt {
+    #[structopt(long = "include-source", number_of_values = 1,
+                help = "only keep content from this source, e.g. \"1913 Webster\" (repeatable)")]
+    pub include: Vec<String>,
+    #[structopt(long = "exclude-source", number_of_values = 1, help = "drop content from this source (repeatable)")]
+    pub exclude: Vec<String>,
+}
+
+impl SourceOpt {
+    pub fn filter(&self) -> SourceFilter {
+        SourceFilter { include: self.include.clone(), exclude: self.exclude.clone() }
+    }
+}
+
+/// Whether `path` is `-`, which stands for stdin or stdout.
+pub fn is_std_stream(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+fn read_bytes(path: &Path) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::with_capacity(2 << 20);
+    if is_std_stream(path) {
+        io::stdin().read_to_end(&mut bytes)?;
+    } else {
+        File::open(path)?.read_to_end(&mut bytes)?;
+    }
+    Ok(bytes)
 }
 
+/// Reads `path`, or stdin if it is `-`, replacing invalid UTF-8.
 pub fn read_file<P: AsRef<Path>>(path: P) -> Result<String, Error> {
-    let mut contents = Vec::with_capacity(2 << 20);
-    File::open(path)?.read_to_end(&mut contents)?;
-    Ok(String::from_utf8_lossy(&contents).into_owned())
+    Ok(String::from_utf8_lossy(&read_bytes(path.as_ref())?).into_owned())
+}
+
+/// Writes `output` to `path`, or to stdout if it is `-`.
+///
+/// Files are written to a temporary file next to `path` that is then renamed
+/// over it, so that `path` is never left truncated or half written, even when
+/// it is also the input.
+pub fn write_file(path: &Path, output: &[u8]) -> Result<(), Error> {
+    if is_std_stream(path) {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        stdout.write_all(output)?;
+        return stdout.flush();
+    }
+    let file_name = path.file_name()
+        .ok_or_else(|| Error::new(io::ErrorKind::InvalidInput, "not a file name"))?;
+    let mut temp_name = OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".{}.tmp", process::id()));
+    let temp_path = path.with_file_name(temp_name);
+    let written = File::create(&temp_path).and_then(|mut file| {
+        file.write_all(output)?;
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.sync_all()
+    }).and_then(|_| fs::rename(&temp_path, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    written
+}
+
+/// Restores the default action of SIGPIPE, so that writing to a closed pipe
+/// ends the process quietly, as it does for other Unix tools, instead of
+/// failing with an error or panicking in `println!`.
+pub fn restore_sigpipe() {
+    #[cfg(unix)]
+    unsafe {
+        ::libc::signal(::libc::SIGPIPE, ::libc::SIG_DFL);
+    }
+}
+
+/// Contents of a CIDE file: with the `mmap` feature, memory-mapped if the file
+/// is valid UTF-8; otherwise read into memory with invalid bytes replaced.
+pub enum FileContents {
+    #[cfg(feature = "mmap")]
+    Mapped(Mmap),
+    Owned(String),
+}
+
+impl Deref for FileContents {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match *self {
+            // checked in `map_file`
+            #[cfg(feature = "mmap")]
+            FileContents::Mapped(ref map) => unsafe { str::from_utf8_unchecked(map) },
+            FileContents::Owned(ref contents) => contents,
+        }
+    }
 }
 
-pub fn patch_using<F>(patcher: F)
+/// Like `read_file`, but avoids copying the file with the `mmap` feature, and
+/// reports bytes that are not valid UTF-8 on stderr. The file must not be
+/// modified while the contents are in use.
+pub fn map_file<P: AsRef<Path>>(path: P) -> Result<FileContents, Error> {
+    load_file(path.as_ref(), None)
+}
+
+/// Reads `path` decoded from `encoding` (UTF-8 by default), reporting bytes
+/// that could not be decoded on stderr.
+pub fn load_file(path: &Path, encoding: Option<&'static Encoding>) -> Result<FileContents, Error> {
+    let encoding = encoding.unwrap_or(UTF_8);
+    #[cfg(feature = "mmap")]
+    {
+        if !is_std_stream(path) {
+            let map = unsafe { Mmap::map(&File::open(path)?)? };
+            if encoding == UTF_8 && str::from_utf8(&map).is_ok() {
+                return Ok(FileContents::Mapped(map));
+            }
+            return Ok(FileContents::Owned(decode_reporting(path, &map, encoding)));
+        }
+    }
+    Ok(FileContents::Owned(decode_reporting(path, &read_bytes(path)?, encoding)))
+}
+
+fn decode_reporting(path: &Path, bytes: &[u8], encoding: &'static Encoding) -> String {
+    const SHOWN: usize = 10;
+    let Decoded { text, replaced } = decode::decode(bytes, encoding);
+    if !replaced.is_empty() {
+        let lines: Vec<_> = replaced.iter().take(SHOWN).map(|&offset| {
+            let line = bytes[..offset].iter().filter(|&&b| b == b'\n').count() + 1;
+            format!("{} (byte {})", line, offset)
+        }).collect();
+        eprintln!("{}: replaced {} invalid {} byte sequence(s) with U+FFFD, at line {}{}",
+                  path.display(), replaced.len(), encoding.name(), lines.join(", "),
+                  if replaced.len() > SHOWN { ", ..." } else { "" });
+    }
+    text
+}
+
+pub fn patch_with<F>(opt: &PatchOpt, patcher: F)
 where F: Fn(&str) -> String {
-    use std::io::Write;
-    let opt = PatchOpt::from_args();
-    let outfile = opt.outfile.as_ref().unwrap_or(&opt.infile);
-    let contents = read_file(&opt.infile).unwrap_abort();
+    let contents = load_file(&opt.infile, opt.encoding).unwrap_or_fail(&opt.infile);
     let patched = patcher(&contents);
-    let mut output_file = File::create(outfile).unwrap_abort();
-    output_file.write_all(patched.as_bytes()).unwrap_abort();
+    finish_patch(opt, contents, &patched);
+}
+
+/// Like `patch_with`, but the patcher is called once per entry block with its
+/// raw source and parse result. Returning `None` keeps the original bytes.
+pub fn patch_entries_with<F>(opt: &PatchOpt, patcher: F)
+where F: Fn(&str, Result<Entry, ParserError>) -> Option<String> {
+    let contents = load_file(&opt.infile, opt.encoding).unwrap_or_fail(&opt.infile);
+    let patched = patch_entries(&contents, patcher);
+    finish_patch(opt, contents, &patched);
+}
+
+/// Writes `patched`, or with `--dry-run` or `--check`, reports how it differs
+/// from `contents`. A file patched in place is left alone if nothing changed.
+fn finish_patch(opt: &PatchOpt, contents: FileContents, patched: &str) {
+    if opt.dry_run || opt.check {
+        let changes = diff::diff(&contents, patched, Compare::Bytes);
+        if opt.dry_run {
+            for change in &changes {
+                let (mark, raw) = match *change {
+                    EntryChange::Added { new, .. } => ('+', new),
+                    EntryChange::Removed { old, .. } => ('-', old),
+                    EntryChange::Changed { new, .. } => ('~', new),
+                };
+                println!("{} {}", mark, diff::raw_main_word(raw));
+            }
+        }
+        if opt.check && *contents != *patched {
+            match changes.len() {
+                0 => eprintln!("{}: text between entries would change", opt.infile.display()),
+                count => eprintln!("{}: {} entries would change", opt.infile.display(), count),
+            }
+            process::exit(EXIT_FINDINGS);
+        }
+        return;
+    }
+    if *contents == *patched && opt.outfile.is_none() && !is_std_stream(&opt.infile) {
+        return;
+    }
+    let target = opt.patch_target();
+    if let Some(ref suffix) = opt.backup {
+        if !is_std_stream(target) && target.exists() {
+            let mut backup = target.as_os_str().to_owned();
+            backup.push(suffix.as_ref().map_or("~", |s| &s[..]));
+            fs::copy(target, &backup).unwrap_or_fail(Path::new(&backup));
+        }
+    }
+    if let Some(ref journal) = opt.journal {
+        let changes = diff::diff(&contents, patched, Compare::Bytes);
+        append_journal(journal, target, &changes).unwrap_or_fail(journal);
+    }
+    // unmap before the file is overwritten
+    drop(contents);
+    write_file(target, patched.as_bytes()).unwrap_or_fail(target);
+}
+
+/// A change to an entry as written by `--journal`: the block before and after,
+/// without `before` for an added entry or `after` for a removed one.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct JournalRecord {
+    pub file: String,
+    pub headword: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl JournalRecord {
+    pub fn new(file: &Path, change: &EntryChange) -> JournalRecord {
+        let (before, after) = match *change {
+            EntryChange::Added { new, .. } => (None, Some(new)),
+            EntryChange::Removed { old, .. } => (Some(old), None),
+            EntryChange::Changed { old, new, .. } => (Some(old), Some(new)),
+        };
+        JournalRecord {
+            file: file.display().to_string(),
+            headword: diff::raw_main_word(after.or(before).unwrap_or("")).to_string(),
+            before: before.map(str::to_string),
+            after: after.map(str::to_string),
+        }
+    }
+
+    /// Reads a journal, one record per line.
+    pub fn read_all(text: &str) -> Result<Vec<JournalRecord>, String> {
+        text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()).map(|(idx, line)| {
+            serde_json::from_str(line).map_err(|err| format!("line {}: {}", idx + 1, err))
+        }).collect()
+    }
+}
+
+/// The entries a journal records as changed or added, by their text after the
+/// change, for undoing the changes.
+pub struct JournalUndo<'r> {
+    befores: HashMap<&'r str, Option<&'r str>>,
+}
+
+impl<'r> JournalUndo<'r> {
+    pub fn new<I: IntoIterator<Item = &'r JournalRecord>>(records: I) -> JournalUndo<'r> {
+        let befores = records.into_iter()
+            .filter_map(|record| record.after.as_ref().map(|after| (&after[..], record.before.as_deref())))
+            .collect();
+        JournalUndo { befores }
+    }
+
+    /// The block `raw` was before the changes that led to it, following changes
+    /// recorded one after the other back to the first: `None` if no recorded
+    /// change resulted in `raw`, and `Some(None)` if the entry was added.
+    pub fn original(&self, raw: &str) -> Option<Option<&'r str>> {
+        let mut original = *self.befores.get(raw)?;
+        for _ in 0..self.befores.len() {
+            match original.and_then(|before| self.befores.get(before)) {
+                Some(&earlier) => original = earlier,
+                None => break,
+            }
+        }
+        Some(original)
+    }
+}
+
+fn append_journal(path: &Path, file: &Path, changes: &[EntryChange]) -> Result<(), Error> {
+    let mut lines = String::new();
+    for change in changes {
+        lines.push_str(&serde_json::to_string(&JournalRecord::new(file, change)).unwrap());
+        lines.push('\n');
+    }
+    fs::OpenOptions::new().create(true).append(true).open(path)?.write_all(lines.as_bytes())
+}
+
+/// Applies `patcher` to each entry block of `contents`, keeping the text
+/// between blocks as it is. An entry replaced with nothing takes the
+/// whitespace that separated it from the entry kept before it (or, if there is
+/// none, after it) along.
+pub fn patch_entries<F>(contents: &str, patcher: F) -> String
+where F: Fn(&str, Result<Entry, ParserError>) -> Option<String> {
+    let mut patched = String::with_capacity(contents.len());
+    let mut copied_upto = 0;
+    let mut kept_any = false;
+    let mut entry_iter = EntryParser::new(contents);
+    while let Some(entry_res) = entry_iter.next() {
+        let span = entry_iter.last_span().unwrap();
+        let replacement = match patcher(&contents[span.clone()], entry_res) {
+            Some(replacement) => replacement,
+            None => {
+                kept_any = true;
+                continue;
+            }
+        };
+        let before = &contents[copied_upto..span.start];
+        copied_upto = span.end;
+        if !replacement.is_empty() {
+            patched.push_str(before);
+            kept_any = true;
+        } else if kept_any {
+            patched.push_str(before.trim_end());
+        } else {
+            patched.push_str(before);
+            let after = &contents[copied_upto..];
+            copied_upto += after.len() - after.trim_start().len();
+        }
+        patched.push_str(&replacement);
+    }
+    patched.push_str(&contents[copied_upto..]);
+    patched
+}
+
+/// Appends one CSV (or, with a tab delimiter, TSV) record to `output`.
+pub fn write_csv_row(output: &mut String, fields: &[&str], delimiter: char) {
+    for (idx, field) in fields.iter().enumerate() {
+        if idx > 0 {
+            output.push(delimiter);
+        }
+        if delimiter == '\t' {
+            // TSV has no quoting; fields are single-line already
+            output.push_str(&field.replace('\t', " "));
+        } else if field.contains([delimiter, '"', '\n']) {
+            output.push('"');
+            output.push_str(&field.replace('"', "\"\""));
+            output.push('"');
+        } else {
+            output.push_str(field);
+        }
+    }
+    output.push('\n');
 }
 
-pub fn pipe_through<F>(processor: F)
+pub fn pipe_through_with<F>(opt: &PipeOpt, processor: F)
 where F: Fn(&str) -> String {
-    use std::io::Write;
-    let opt = PipeOpt::from_args();
-    let contents = read_file(&opt.infile).unwrap_abort();
+    let contents = load_file(&opt.infile, opt.encoding).unwrap_or_fail(&opt.infile);
     let output = processor(&contents);
-    if let Some(outfile) = opt.outfile {
-        let mut output_file = File::create(outfile).unwrap_abort();
-        output_file.write_all(output.as_bytes()).unwrap_abort();
+    let outfile = opt.outfile.as_ref().map_or(Path::new("-"), |path| path);
+    write_file(outfile, output.as_bytes()).unwrap_or_fail(outfile);
+    if let Some(ref manifest) = opt.manifest {
+        let name = opt.outfile.as_ref().map_or("-".to_string(), |path| path.display().to_string());
+        write_manifest(manifest, &opt.infile, &contents, &[(name, output.as_bytes())]).unwrap_or_fail(manifest);
+    }
+}
+
+/// Writes a `Manifest` of an export from the CIDE file `infile` as JSON.
+pub fn write_manifest(path: &Path, infile: &Path, contents: &str, outputs: &[(String, &[u8])]) -> Result<(), Error> {
+    let program = ::std::env::args().next()
+        .and_then(|arg| Path::new(&arg).file_name().map(|name| name.to_string_lossy().into_owned()))
+        .unwrap_or_default();
+    let manifest = Manifest {
+        generator: format!("{} {} ({})", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"), program),
+        source: SourceInfo::new(&infile.display().to_string(), contents),
+        outputs: outputs.iter().map(|&(ref name, bytes)| FileInfo::new(name, bytes)).collect(),
+    };
+    let json = ::serde_json::to_string_pretty(&manifest).map_err(Error::from)?;
+    fs::write(path, json + "\n")
+}
+
+/// Calls `f` on each of `paths` using up to `jobs` threads (one per CPU if 0),
+/// returning the results in the order of `paths`.
+pub fn map_files<T, F>(paths: &[PathBuf], jobs: usize, f: F) -> Vec<T>
+where T: Send, F: Fn(&Path) -> T + Sync {
+    let chunk_len = paths.len().div_ceil(parallel::thread_count(jobs)).max(1);
+    ::std::thread::scope(|scope| {
+        let f = &f;
+        let handles: Vec<_> = paths.chunks(chunk_len)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(|path| f(path)).collect::<Vec<T>>()))
+            .collect();
+        handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+    })
+}
+
+/// A one-line progress display on stderr, for `EntryParser::on_progress`.
+/// Finishes the line when dropped.
+pub struct ProgressBar {
+    label: String,
+    shown_percent: Option<usize>,
+}
+
+impl ProgressBar {
+    pub fn new(label: &str) -> ProgressBar {
+        ProgressBar { label: label.into(), shown_percent: None }
+    }
+
+    pub fn update(&mut self, progress: Progress) {
+        let percent = progress.bytes * 100 / progress.total_bytes.max(1);
+        if self.shown_percent != Some(percent) {
+            self.shown_percent = Some(percent);
+            eprint!("\r{}: {:3}% ({} entries)", self.label, percent, progress.entries);
+        }
+    }
+}
+
+impl Drop for ProgressBar {
+    fn drop(&mut self) {
+        if self.shown_percent.is_some() {
+            eprintln!();
+        }
+    }
+}
+
+/// Exit status when the command worked but found something: lint problems,
+/// differences, merge conflicts or a word that is not in the dictionary.
+pub const EXIT_FINDINGS: i32 = 1;
+/// Exit status when a file cannot be read or written.
+pub const EXIT_IO: i32 = 2;
+/// Exit status when the input cannot be used, such as a broken entry where
+/// none are allowed, or a bad replacement table or template.
+pub const EXIT_INVALID_INPUT: i32 = 3;
+
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// Makes `report` write errors as JSON lines.
+pub fn set_json_errors(on: bool) {
+    JSON_ERRORS.store(on, Ordering::Relaxed);
+}
+
+/// An error to report on stderr, as text or as a JSON line.
+#[derive(Debug, Serialize)]
+pub struct ErrorRecord {
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    /// Position of the entry in the file, from 0.
+    pub entry: Option<usize>,
+    pub headword: Option<String>,
+    /// What went wrong: `io`, `invalid-input`, `unparsable-entry` or `render`.
+    pub kind: &'static str,
+    pub message: String,
+}
+
+impl ErrorRecord {
+    pub fn new<M: Display>(kind: &'static str, message: M) -> ErrorRecord {
+        ErrorRecord { file: None, line: None, entry: None, headword: None, kind, message: message.to_string() }
+    }
+
+    pub fn io<E: Display>(path: &Path, err: E) -> ErrorRecord {
+        ErrorRecord::new("io", err).in_file(path)
+    }
+
+    pub fn in_file(mut self, path: &Path) -> ErrorRecord {
+        self.file = Some(path.display().to_string());
+        self
+    }
+
+    /// Locates the error at the `index`th entry, whose text starts at `offset`
+    /// in `contents`.
+    pub fn at_entry(mut self, contents: &str, offset: usize, index: usize) -> ErrorRecord {
+        self.line = Some(contents[..offset].matches('\n').count() + 1);
+        self.entry = Some(index);
+        let headword = ::diff::raw_main_word(&contents[offset..]);
+        self.headword = if headword.is_empty() { None } else { Some(headword.to_string()) };
+        self
+    }
+}
+
+impl Display for ErrorRecord {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if let Some(ref file) = self.file {
+            write!(f, "{}:", file)?;
+            if let Some(line) = self.line {
+                write!(f, "{}:", line)?;
+            }
+            f.write_str(" ")?;
+        }
+        if let Some(ref headword) = self.headword {
+            write!(f, "{}: ", headword)?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+/// Writes `record` to stderr.
+pub fn report(record: &ErrorRecord) {
+    if JSON_ERRORS.load(Ordering::Relaxed) {
+        eprintln!("{}", ::serde_json::to_string(record).unwrap());
     } else {
-        ::std::io::stdout().write_all(output.as_bytes()).unwrap_abort();
+        eprintln!("{}", record);
     }
 }
 
-trait UnwrapAbort {
+/// Reports `record` and exits with `status`.
+pub fn fail(record: ErrorRecord, status: i32) -> ! {
+    report(&record);
+    process::exit(status);
+}
+
+/// Unwrapping of IO results that reports the error on the given file and
+/// exits with `EXIT_IO`.
+pub trait UnwrapOrFail {
     type Out;
 
-    fn unwrap_abort(self) -> Self::Out;
+    fn unwrap_or_fail(self, path: &Path) -> Self::Out;
 }
 
-impl<T, E: fmt::Display> UnwrapAbort for Result<T, E> {
+impl<T, E: Display> UnwrapOrFail for Result<T, E> {
     type Out = T;
 
-    fn unwrap_abort(self) -> T {
-        match self {
-            Ok(v) => v,
-            Err(e) => {
-                eprintln!("{}", e);
-                process::abort();
+    fn unwrap_or_fail(self, path: &Path) -> T {
+        self.unwrap_or_else(|err| fail(ErrorRecord::io(path, err), EXIT_IO))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn patch_entry_blocks() {
+        let text = "<entry main-word=\"A\" source=\"S\">a</entry>\n\n\
+                    <entry main-word=\"B\" source=\"S\">b</entry>\n\n\
+                    <entry main-word=\"C\" source=\"S\">c</entry>\n";
+        let patch = |word: &'static str, replacement: &'static str| patch_entries(text, |_, entry_res| {
+            match entry_res {
+                Ok(ref entry) if entry.main_word == word => Some(replacement.to_string()),
+                _ => None,
             }
+        });
+        assert_eq!(patch("none", ""), text);
+        assert_eq!(patch("B", "<entry main-word=\"B\" source=\"S\">x</entry>"), text.replace(">b<", ">x<"));
+        assert_eq!(patch("A", ""), "<entry main-word=\"B\" source=\"S\">b</entry>\n\n\
+                                    <entry main-word=\"C\" source=\"S\">c</entry>\n");
+        assert_eq!(patch("B", ""), "<entry main-word=\"A\" source=\"S\">a</entry>\n\n\
+                                    <entry main-word=\"C\" source=\"S\">c</entry>\n");
+        assert_eq!(patch("C", ""), "<entry main-word=\"A\" source=\"S\">a</entry>\n\n\
+                                    <entry main-word=\"B\" source=\"S\">b</entry>\n");
+    }
+
+    #[test]
+    fn journal_undo() {
+        let versions = ["<entry main-word=\"A\" source=\"S\">a</entry>\n<entry main-word=\"B\" source=\"S\">b</entry>\n",
+                        "<entry main-word=\"A\" source=\"S\">x</entry>\n<entry main-word=\"B\" source=\"S\">b</entry>\n\
+                         <entry main-word=\"C\" source=\"S\">c</entry>\n",
+                        "<entry main-word=\"A\" source=\"S\">y</entry>\n<entry main-word=\"C\" source=\"S\">c</entry>\n"];
+        let journal = ::std::env::temp_dir().join(format!("gcide-journal-{}.jsonl", process::id()));
+        let _ = fs::remove_file(&journal);
+        for pair in versions.windows(2) {
+            append_journal(&journal, Path::new("CIDE.A"), &diff::diff(pair[0], pair[1], Compare::Bytes)).unwrap();
         }
+        let text = fs::read_to_string(&journal).unwrap();
+        fs::remove_file(&journal).unwrap();
+        let records = JournalRecord::read_all(&text).unwrap();
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[3], JournalRecord {
+            file: "CIDE.A".to_string(),
+            headword: "B".to_string(),
+            before: Some("<entry main-word=\"B\" source=\"S\">b</entry>".to_string()),
+            after: None,
+        });
+
+        let undo = JournalUndo::new(&records);
+        assert_eq!(undo.original("<entry main-word=\"A\" source=\"S\">y</entry>"),
+                   Some(Some("<entry main-word=\"A\" source=\"S\">a</entry>")));
+        assert_eq!(undo.original("<entry main-word=\"C\" source=\"S\">c</entry>"), Some(None));
+        assert_eq!(undo.original("<entry main-word=\"B\" source=\"S\">b</entry>"), None);
+        assert!(JournalRecord::read_all("{}").is_err());
     }
 }
@@ -0,0 +1,153 @@
+//! Programmatic construction of entries.
+
+use entities::Entity;
+use greek::parse_greek;
+use parser::{Entry, EntryItem};
+use tags::TagName;
+
+pub struct EntryBuilder<'a> {
+    main_word: &'a str,
+    source: &'a str,
+    items: ItemBuilder<'a>,
+}
+
+impl<'a> EntryBuilder<'a> {
+    pub fn new(main_word: &'a str, source: &'a str) -> EntryBuilder<'a> {
+        EntryBuilder { main_word, source, items: ItemBuilder::new() }
+    }
+
+    /// Adds top-level items (usually `<p>` blocks) through an `ItemBuilder`.
+    pub fn items<F>(mut self, f: F) -> EntryBuilder<'a>
+    where F: FnOnce(ItemBuilder<'a>) -> ItemBuilder<'a> {
+        self.items = f(self.items);
+        self
+    }
+
+    pub fn tag<F>(self, name: &'a str, f: F) -> EntryBuilder<'a>
+    where F: FnOnce(ItemBuilder<'a>) -> ItemBuilder<'a> {
+        self.items(|b| b.tag(name, f))
+    }
+
+    pub fn build(self) -> Entry<'a> {
+        Entry { main_word: self.main_word, items: self.items.build(), source: self.source, raw: None, id: None }
+    }
+}
+
+/// Builds a list of sibling items. Names are checked against what the parser
+/// would accept, so the result always serializes to parsable markup.
+#[derive(Default)]
+pub struct ItemBuilder<'a> {
+    items: Vec<EntryItem<'a>>,
+}
+
+impl<'a> ItemBuilder<'a> {
+    pub fn new() -> ItemBuilder<'a> {
+        ItemBuilder::default()
+    }
+
+    /// Adds a tag with the items built by `f` as contents.
+    ///
+    /// Panics if `name` is not a valid tag name.
+    pub fn tag<F>(self, name: &'a str, f: F) -> ItemBuilder<'a>
+    where F: FnOnce(ItemBuilder<'a>) -> ItemBuilder<'a> {
+        self.tag_with_source(name, None, f)
+    }
+
+    /// The source attribute is only expected on `p` and `extra` blocks.
+    pub fn tag_with_source<F>(mut self, name: &'a str, source: Option<&'a str>, f: F) -> ItemBuilder<'a>
+    where F: FnOnce(ItemBuilder<'a>) -> ItemBuilder<'a> {
+        assert!(!name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric()),
+                "invalid tag name: {:?}", name);
+        assert!(!source.is_some_and(|s| s.contains('"')), "invalid source: {:?}", source);
+        let items = f(ItemBuilder::new()).build();
+        self.items.push(EntryItem::Tagged { name: TagName::new(name), items, source });
+        self
+    }
+
+    /// Adds plain text; `<` and `>` are turned into the `lt` and `gt` entities.
+    pub fn text(mut self, text: &'a str) -> ItemBuilder<'a> {
+        let mut rest = text;
+        while let Some(idx) = rest.find(['<', '>']) {
+            if idx > 0 {
+                self.items.push(EntryItem::PlainText(&rest[..idx]));
+            }
+            let entity = if rest[idx..].starts_with('<') { Entity::Lt } else { Entity::Gt };
+            self.items.push(EntryItem::Entity(entity));
+            rest = &rest[idx + 1..];
+        }
+        if !rest.is_empty() {
+            self.items.push(EntryItem::PlainText(rest));
+        }
+        self
+    }
+
+    /// Adds an entity such as `amac` (written `<amac/`).
+    ///
+    /// Panics if `name` is not a valid entity name.
+    pub fn entity(mut self, name: &'a str) -> ItemBuilder<'a> {
+        assert!(!name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == ':' || c == '_'),
+                "invalid entity name: {:?}", name);
+        self.items.push(EntryItem::Entity(Entity::new(name)));
+        self
+    }
+
+    pub fn br(mut self) -> ItemBuilder<'a> {
+        self.items.push(EntryItem::EntityBr);
+        self
+    }
+
+    /// Adds Greek given in the GCIDE transliteration used inside `<grk>`.
+    pub fn greek(mut self, transliteration: &str) -> ItemBuilder<'a> {
+        self.items.push(EntryItem::Greek(parse_greek(transliteration)));
+        self
+    }
+
+    /// Panics if `text` contains `-->`.
+    pub fn comment(mut self, text: &'a str) -> ItemBuilder<'a> {
+        assert!(!text.contains("-->"), "comment may not contain -->");
+        self.items.push(EntryItem::Comment(text));
+        self
+    }
+
+    /// Panics if `url` contains a `"` or `text` contains markup characters.
+    pub fn link(mut self, url: &'a str, text: &'a str) -> ItemBuilder<'a> {
+        assert!(!url.contains('"') && !text.contains(['<', '>']), "invalid link: {:?} {:?}", url, text);
+        self.items.push(EntryItem::ExternalLink(url, text));
+        self
+    }
+
+    /// Adds an arbitrary item as is.
+    pub fn item(mut self, item: EntryItem<'a>) -> ItemBuilder<'a> {
+        self.items.push(item);
+        self
+    }
+
+    pub fn build(self) -> Vec<EntryItem<'a>> {
+        self.items
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use exporter::CIDE;
+    use parser::EntryParser;
+
+    #[test]
+    fn round_trip() {
+        let entry = EntryBuilder::new("Abacus", "1913 Webster")
+            .items(|b| b.tag_with_source("p", Some("Century"), |p| p
+                .tag("hw", |b| b.text("Ab\"a*cus"))
+                .text(" ")
+                .tag("ety", |b| b.text("[Gr. ").greek("'a`bax").text("]"))
+                .br()
+                .tag("def", |b| b.text("x < y"))))
+            .build();
+        let text = CIDE(&entry).to_string();
+        assert_eq!(text, "<entry main-word=\"Abacus\" source=\"1913 Webster\"><p source=\"Century\"><hw>Ab\"a*cus</hw> \
+                          <ety>[Gr. <grk>'a`bax</grk>]</ety><br/\n\
+                          <def>x <lt/ y</def></p></entry>");
+        let reparsed = EntryParser::new(&text).next().unwrap().unwrap();
+        assert_eq!(CIDE(&reparsed).to_string(), text);
+    }
+}
@@ -0,0 +1,53 @@
+//! Decoding of source files that are not (entirely) valid UTF-8, keeping track
+//! of what could not be decoded.
+
+use encoding_rs::{DecoderResult, Encoding};
+
+pub struct Decoded {
+    pub text: String,
+    /// Byte offsets in the input of sequences replaced with U+FFFD.
+    pub replaced: Vec<usize>,
+}
+
+/// Decodes `bytes` from `encoding`, without BOM sniffing.
+pub fn decode(bytes: &[u8], encoding: &'static Encoding) -> Decoded {
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut text = String::with_capacity(bytes.len() + bytes.len() / 8 + 16);
+    let mut replaced = Vec::new();
+    let mut read = 0;
+    loop {
+        let (result, count) = decoder.decode_to_string_without_replacement(&bytes[read..], &mut text, true);
+        read += count;
+        match result {
+            DecoderResult::InputEmpty => break,
+            DecoderResult::OutputFull => text.reserve(bytes.len() - read + 16),
+            DecoderResult::Malformed(bad_len, extra) => {
+                replaced.push(read - extra as usize - bad_len as usize);
+                text.push('\u{fffd}');
+            }
+        }
+    }
+    Decoded { text, replaced }
+}
+
+/// The encoding for a WHATWG label such as `utf-8`, `latin1` or `windows-1252`.
+pub fn encoding_for_label(label: &str) -> Option<&'static Encoding> {
+    Encoding::for_label(label.as_bytes())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use encoding_rs::{UTF_8, WINDOWS_1252};
+
+    #[test]
+    fn replaced_positions() {
+        let decoded = decode(b"caf\xe9 <ae/ \xff!", UTF_8);
+        assert_eq!(decoded.text, "caf\u{fffd} <ae/ \u{fffd}!");
+        assert_eq!(decoded.replaced, [3, 10]);
+        let decoded = decode(b"caf\xe9", encoding_for_label("latin1").unwrap());
+        assert_eq!(decoded.text, "caf\u{e9}");
+        assert!(decoded.replaced.is_empty());
+        assert_eq!(encoding_for_label("latin1"), Some(WINDOWS_1252));
+    }
+}
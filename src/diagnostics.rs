@@ -0,0 +1,183 @@
+use std::fmt::{self, Display, Formatter};
+use std::mem;
+use std::sync::Arc;
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use entities::Entity;
+use exporter::unicode_to_entity;
+use greek::{GreekItem, GreekMods};
+use mojibake::find_mojibake;
+use parser::{Entry, EntryItem, ALLOWED_TO_DANGLE};
+use tags::TagName;
+
+/// A non-fatal issue found in an otherwise parsable entry.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseWarning<'a> {
+    UnknownEntity(&'a str),
+    DanglingTagOpen(&'a str),
+    DanglingTagClose(&'a str),
+    /// A Greek letter whose diacritics do not compose into a single character.
+    SuspiciousGreek(char, GreekMods),
+    UnknownGreekChar(char),
+    NestedParagraph,
+    /// A tag the exporter does not know how to render.
+    UnknownTag(&'a str),
+    /// A tag closed by a `PairingRules` rule rather than by a close tag.
+    ImplicitlyClosed(&'a str),
+    /// A tag written with stray spaces or in another case, as found; see
+    /// `EntryParser::lenient_tags`.
+    LooseTag(&'a str),
+    /// A `<` or `>` read as an entity, and the text after it up to the next
+    /// bracket or line break; see `EntryParser::lenient_brackets`.
+    StrayBracket(&'a str),
+    /// A literal character that should be written as the given entity.
+    LiteralCharacter(char, &'static str),
+    /// Doubly encoded UTF-8 and the character it stands for, or U+FFFD.
+    Mojibake(&'a str, Option<char>),
+    /// A tag whose handler panicked, so that it was rendered as usual; see
+    /// `ExportOptions::tag_handler`.
+    HandlerPanicked(&'a str),
+}
+
+/// Callback installed in exporter options to receive warnings.
+pub type WarningHandler = Arc<dyn Fn(&ParseWarning) + Send + Sync>;
+
+impl<'a> Display for ParseWarning<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use self::ParseWarning::*;
+        match *self {
+            UnknownEntity(name) => write!(f, "unknown entity: <{}/", name),
+            DanglingTagOpen(name) => write!(f, "unclosed tag: <{}>", name),
+            DanglingTagClose(name) => write!(f, "unopened tag: </{}>", name),
+            SuspiciousGreek(base, mods) => write!(f, "possibly bad greek letter: {} {:b}", base, mods),
+            UnknownGreekChar(c) => write!(f, "unexpected character in greek: {:?}", c),
+            NestedParagraph => write!(f, "nested <p> tag"),
+            UnknownTag(name) => write!(f, "unknown tag: {}", name),
+            ImplicitlyClosed(name) => write!(f, "implicitly closed tag: <{}>", name),
+            LooseTag(markup) => write!(f, "loosely written tag: {}", markup),
+            StrayBracket(text) => write!(f, "unescaped bracket: {:?}", text),
+            LiteralCharacter(c, entity) => write!(f, "literal character {:?}, use <{}/", c, entity),
+            Mojibake(text, Some(c)) => write!(f, "mojibake {:?}, probably {:?}", text, c),
+            Mojibake(_, None) => write!(f, "replacement character, original text lost"),
+            HandlerPanicked(name) => write!(f, "tag handler for <{}> panicked", name),
+        }
+    }
+}
+
+pub fn check_entry<'a>(entry: &Entry<'a>) -> Vec<ParseWarning<'a>> {
+    check_items(&entry.items)
+}
+
+/// Issues in a sequence of items, such as a fragment from `EntryItem::parse_items`.
+pub fn check_items<'a>(items: &[EntryItem<'a>]) -> Vec<ParseWarning<'a>> {
+    let mut warnings = Vec::new();
+    collect_warnings(items, false, &mut warnings);
+    warnings
+}
+
+fn collect_warnings<'a>(items: &[EntryItem<'a>], in_p: bool, warnings: &mut Vec<ParseWarning<'a>>) {
+    use parser::EntryItem::*;
+    for item in items {
+        match *item {
+            Entity(entity) if !entity.is_known() => warnings.push(ParseWarning::UnknownEntity(entity.as_str())),
+            PlainText(text) => {
+                let garbled = find_mojibake(text);
+                for g in &garbled {
+                    warnings.push(ParseWarning::Mojibake(&text[g.range.clone()], g.repair));
+                }
+                for (idx, c, entity) in entity_suggestions(text) {
+                    if !garbled.iter().any(|g| g.range.contains(&idx)) {
+                        warnings.push(ParseWarning::LiteralCharacter(c, entity));
+                    }
+                }
+            }
+            Greek(ref gitems) => {
+                for gi in gitems {
+                    match *gi {
+                        GreekItem::Unknown(c) => warnings.push(ParseWarning::UnknownGreekChar(c)),
+                        GreekItem::Letter(base, mods) if gi.to_unicode_char().is_none() => {
+                            warnings.push(ParseWarning::SuspiciousGreek(base, mods))
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            Tagged { name, ref items, .. } => {
+                let is_p = name == TagName::P;
+                if is_p && in_p {
+                    warnings.push(ParseWarning::NestedParagraph);
+                }
+                collect_warnings(items, in_p || is_p, warnings);
+            }
+            UnpairedTagOpen(name, _) if !ALLOWED_TO_DANGLE.contains(&name) => {
+                warnings.push(ParseWarning::DanglingTagOpen(name.as_str()))
+            }
+            UnpairedTagClose(name) if !ALLOWED_TO_DANGLE.contains(&name) => {
+                warnings.push(ParseWarning::DanglingTagClose(name.as_str()))
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Byte offsets, characters and suggested entities of the literal characters in
+/// `text` that have an entity.
+pub fn entity_suggestions(text: &str) -> Vec<(usize, char, &'static str)> {
+    text.char_indices().filter_map(|(idx, c)| unicode_to_entity(c).map(|entity| (idx, c, entity))).collect()
+}
+
+/// Replaces literal characters in the text of `items` (outside `<grk>`) with
+/// their entities. Returns whether anything changed.
+pub fn use_entities<'a>(items: &mut Vec<EntryItem<'a>>) -> bool {
+    let mut changed = false;
+    for item in mem::take(items) {
+        match item {
+            EntryItem::PlainText(text) => {
+                let mut copied_upto = 0;
+                for (idx, c, entity) in entity_suggestions(text) {
+                    if copied_upto < idx {
+                        items.push(EntryItem::PlainText(&text[copied_upto..idx]));
+                    }
+                    items.push(EntryItem::Entity(Entity::new(entity)));
+                    copied_upto = idx + c.len_utf8();
+                }
+                changed |= copied_upto > 0;
+                if copied_upto < text.len() {
+                    items.push(EntryItem::PlainText(&text[copied_upto..]));
+                }
+            }
+            EntryItem::Tagged { name, source, items: mut inner } => {
+                changed |= use_entities(&mut inner);
+                items.push(EntryItem::Tagged { name, source, items: inner });
+            }
+            item => items.push(item),
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use exporter::CIDE;
+    use parser::EntryParser;
+
+    #[test]
+    fn literal_entities() {
+        let raw = "<entry main-word=\"Caesar\" source=\"B\">\n<p><hw>C\u{e6}sar</hw> <def>na\u{ef}ve</def> \
+                   <grk>a</grk></p>\n</entry>";
+        let mut entry = EntryParser::new(raw).next().unwrap().unwrap();
+        assert_eq!(check_entry(&entry), [ParseWarning::LiteralCharacter('\u{e6}', "ae"),
+                                         ParseWarning::LiteralCharacter('\u{ef}', "ium")]);
+        assert!(use_entities(&mut entry.items));
+        assert_eq!(CIDE(&entry).to_string(), "<entry main-word=\"Caesar\" source=\"B\">\n<p><hw>C<ae/sar</hw> \
+                                              <def>na<ium/ve</def> <grk>a</grk></p>\n</entry>");
+        assert!(check_entry(&entry).is_empty());
+
+        let raw = "<entry main-word=\"Cafe\" source=\"B\"><p><hw>Caf\u{c3}\u{a9}</hw> \u{fffd}</p></entry>";
+        let entry = EntryParser::new(raw).next().unwrap().unwrap();
+        assert_eq!(check_entry(&entry), [ParseWarning::Mojibake("\u{c3}\u{a9}", Some('\u{e9}')),
+                                         ParseWarning::Mojibake("\u{fffd}", None)]);
+    }
+}
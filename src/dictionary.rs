@@ -0,0 +1,492 @@
+//! A parsed and indexed dictionary that can be shared between threads.
+
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Write};
+#[cfg(feature = "regex")]
+use std::ops::Range;
+use std::path::Path;
+use std::str;
+
+#[cfg(feature = "regex")]
+use regex::Regex;
+
+use entities;
+use greek::{GreekItem, GreekMods};
+use index::{ByteReader, HeadwordIndex, IndexBuilder, IndexError};
+#[cfg(feature = "regex")]
+use model::{self, Sense};
+use parser::{Entry, EntryItem, EntryParser};
+use tags::TagName;
+
+const MAGIC: &[u8; 8] = b"GCIDEDIC";
+const VERSION: u32 = 5;
+
+/// A match of `Dictionary::search_regex`.
+#[cfg(feature = "regex")]
+#[derive(Clone, Debug)]
+pub struct Match<'d> {
+    pub entry: &'d Entry<'d>,
+    /// Position of the sense among `model::senses(entry)`.
+    pub sense_index: usize,
+    /// The matching sense; its `definition` is the text searched.
+    pub sense: Sense,
+    /// Byte range of the match in `sense.definition`.
+    pub range: Range<usize>,
+}
+
+#[cfg(feature = "regex")]
+impl<'d> Match<'d> {
+    /// The matched text.
+    pub fn as_str(&self) -> &str {
+        &self.sense.definition[self.range.clone()]
+    }
+}
+
+/// All entries of a set of CIDE files, parsed once and indexed by headword.
+///
+/// The dictionary owns the file contents, so it is `Send + Sync` and can be
+/// put in an `Arc` and queried from several threads. Entries that fail to
+/// parse are left out.
+pub struct Dictionary {
+    // borrow from `contents`, so they are declared (and dropped) first
+    entries: Vec<Entry<'static>>,
+    index: HeadwordIndex,
+    contents: Vec<String>,
+}
+
+impl Dictionary {
+    /// Loads the `CIDE.*` files in `dir`, in the order of their names.
+    pub fn open<P: AsRef<Path>>(dir: P) -> io::Result<Dictionary> {
+        let mut paths = Vec::new();
+        for dir_entry in fs::read_dir(dir.as_ref())? {
+            let path = dir_entry?.path();
+            if path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with("CIDE.")) {
+                paths.push(path);
+            }
+        }
+        if paths.is_empty() {
+            return Err(io::Error::new(ErrorKind::NotFound,
+                                      format!("no CIDE files in {}", dir.as_ref().display())));
+        }
+        paths.sort();
+        Dictionary::from_files(&paths)
+    }
+
+    pub fn from_files<P: AsRef<Path>>(paths: &[P]) -> io::Result<Dictionary> {
+        let contents = paths.iter()
+            .map(|path| {
+                let path = path.as_ref();
+                fs::read(path)
+                    .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                    .map_err(|err| io::Error::new(err.kind(), format!("{}: {}", path.display(), err)))
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(Dictionary::from_contents(contents))
+    }
+
+    /// Parses and indexes the contents of CIDE files.
+    pub fn from_contents(contents: Vec<String>) -> Dictionary {
+        let mut entries = Vec::new();
+        let mut builder = IndexBuilder::new();
+        for text in &contents {
+            // the heap buffer of a `String` does not move, and `contents` is
+            // never modified, so the entries stay valid as long as `self`
+            let text: &'static str = unsafe { &*(text.as_str() as *const str) };
+            for entry in EntryParser::new(text).with_ids().filter_map(Result::ok) {
+                builder.insert(entry.main_word, entries.len() as u64);
+                entries.push(entry);
+            }
+        }
+        Dictionary { entries, index: builder.finish(), contents }
+    }
+
+    /// Writes the dictionary in a binary form that `from_bytes` reads back
+    /// much faster than the files can be parsed.
+    ///
+    /// The file contents are stored as they are, and the entries as trees of
+    /// byte ranges in them.
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(MAGIC)?;
+        w.write_all(&VERSION.to_le_bytes())?;
+        let mut index_bytes = Vec::new();
+        self.index.write_to(&mut index_bytes)?;
+        w.write_all(&(index_bytes.len() as u64).to_le_bytes())?;
+        w.write_all(&index_bytes)?;
+        w.write_all(&(self.contents.len() as u64).to_le_bytes())?;
+        for text in &self.contents {
+            w.write_all(&(text.len() as u64).to_le_bytes())?;
+            w.write_all(text.as_bytes())?;
+        }
+        w.write_all(&(self.entries.len() as u64).to_le_bytes())?;
+        let mut buf = Vec::new();
+        for entry in &self.entries {
+            let file_no = self.contents.iter()
+                .position(|text| offset_in(text, entry.main_word).is_some())
+                .ok_or_else(|| io::Error::new(ErrorKind::InvalidData, "entry outside the dictionary files"))?;
+            let mut encoder = Encoder { out: &mut buf, text: &self.contents[file_no] };
+            encoder.out.extend_from_slice(&(file_no as u32).to_le_bytes());
+            encoder.entry(entry)?;
+            w.write_all(&buf)?;
+            buf.clear();
+        }
+        Ok(())
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Dictionary, IndexError> {
+        let mut reader = ByteReader(bytes);
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(IndexError::Format("not a dictionary cache"));
+        }
+        if reader.u32()? != VERSION {
+            return Err(IndexError::Format("unsupported dictionary cache version"));
+        }
+        let index_len = reader.u64()? as usize;
+        let index = HeadwordIndex::from_bytes(reader.take(index_len)?)?;
+        let file_count = reader.u64()? as usize;
+        let mut contents = Vec::with_capacity(file_count.min(bytes.len()));
+        for _ in 0..file_count {
+            let len = reader.u64()? as usize;
+            let text = std::str::from_utf8(reader.take(len)?)
+                .map_err(|_| IndexError::Format("invalid UTF-8 in dictionary cache"))?;
+            contents.push(text.to_string());
+        }
+        let entry_count = reader.u64()? as usize;
+        let mut entries = Vec::with_capacity(entry_count.min(bytes.len()));
+        for _ in 0..entry_count {
+            let text = contents.get(reader.u32()? as usize)
+                .ok_or(IndexError::Format("bad file number in dictionary cache"))?;
+            // as in `from_contents`
+            let text: &'static str = unsafe { &*(text.as_str() as *const str) };
+            entries.push(Decoder { reader: &mut reader, text }.entry()?);
+        }
+        Ok(Dictionary { entries, index, contents })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = io::BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Reads a dictionary written by `save`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Dictionary, IndexError> {
+        Dictionary::from_bytes(&fs::read(path)?)
+    }
+
+    /// Entries whose main word matches `word` once normalized.
+    pub fn define(&self, word: &str) -> Vec<&Entry<'_>> {
+        self.entries_at(self.index.get(word))
+    }
+
+    /// Entries at `locations` of `index`.
+    pub fn entries_at(&self, locations: &[u64]) -> Vec<&Entry<'_>> {
+        locations.iter().filter_map(|&loc| self.entries.get(loc as usize)).collect()
+    }
+
+    /// The headword index, whose locations are positions in `entries`.
+    pub fn index(&self) -> &HeadwordIndex {
+        &self.index
+    }
+
+    /// Matches of `re` in the definitions of all entries, in order. The plain
+    /// text of the definitions is searched, with entities resolved and tags
+    /// removed, so that e.g. `café` matches `caf<eacute/`.
+    #[cfg(feature = "regex")]
+    pub fn search_regex<'d>(&'d self, re: &'d Regex) -> impl Iterator<Item = Match<'d>> + 'd {
+        self.entries.iter().flat_map(move |entry| {
+            let mut matches = Vec::new();
+            for (sense_index, sense) in model::senses(entry).into_iter().enumerate() {
+                for found in re.find_iter(&sense.definition) {
+                    matches.push(Match { entry, sense_index, sense: sense.clone(), range: found.range() });
+                }
+            }
+            matches
+        })
+    }
+
+    pub fn entries(&self) -> &[Entry<'_>] {
+        &self.entries
+    }
+
+    /// The contents of the loaded files.
+    pub fn contents(&self) -> &[String] {
+        &self.contents
+    }
+}
+
+/// Byte offset of `part` in `text`, if it is a slice of it.
+fn offset_in(text: &str, part: &str) -> Option<usize> {
+    let start = (part.as_ptr() as usize).checked_sub(text.as_ptr() as usize)?;
+    if start + part.len() <= text.len() { Some(start) } else { None }
+}
+
+struct Encoder<'w> {
+    out: &'w mut Vec<u8>,
+    text: &'w str,
+}
+
+impl<'w> Encoder<'w> {
+    fn str(&mut self, part: &str) -> io::Result<()> {
+        let start = offset_in(self.text, part)
+            .ok_or_else(|| io::Error::new(ErrorKind::InvalidData, "entry text outside its file"))?;
+        self.out.extend_from_slice(&(start as u32).to_le_bytes());
+        self.out.extend_from_slice(&(part.len() as u32).to_le_bytes());
+        Ok(())
+    }
+
+    /// Registered names of tags and entities are written out, since they are
+    /// no longer slices of the file, after their length; other names as text
+    /// of the file, after a 0.
+    fn name(&mut self, name: &str, known: bool) -> io::Result<()> {
+        if known {
+            self.out.push(name.len() as u8);
+            self.out.extend_from_slice(name.as_bytes());
+            Ok(())
+        } else {
+            self.out.push(0);
+            self.str(name)
+        }
+    }
+
+    fn opt_str(&mut self, part: Option<&str>) -> io::Result<()> {
+        match part {
+            Some(part) => {
+                self.out.push(1);
+                self.str(part)
+            }
+            None => {
+                self.out.push(0);
+                Ok(())
+            }
+        }
+    }
+
+    fn entry(&mut self, entry: &Entry) -> io::Result<()> {
+        self.str(entry.main_word)?;
+        self.str(entry.source)?;
+        self.opt_str(entry.raw)?;
+        match entry.id {
+            Some(ref id) => {
+                self.out.push(1);
+                self.out.extend_from_slice(&(id.len() as u32).to_le_bytes());
+                self.out.extend_from_slice(id.as_bytes());
+            }
+            None => self.out.push(0),
+        }
+        self.items(&entry.items)
+    }
+
+    fn items(&mut self, items: &[EntryItem]) -> io::Result<()> {
+        use parser::EntryItem::*;
+        self.out.extend_from_slice(&(items.len() as u32).to_le_bytes());
+        for item in items {
+            match *item {
+                Tagged { name, ref items, source } => {
+                    self.out.push(0);
+                    self.name(name.as_str(), name.is_known())?;
+                    self.opt_str(source)?;
+                    self.items(items)?;
+                }
+                Comment(text) => {
+                    self.out.push(1);
+                    self.str(text)?;
+                }
+                Entity(entity) => {
+                    self.out.push(2);
+                    self.name(entity.as_str(), entity.is_known())?;
+                }
+                EntityBr => self.out.push(3),
+                EntityUnk => self.out.push(4),
+                ExternalLink(url, text) => {
+                    self.out.push(5);
+                    self.str(url)?;
+                    self.str(text)?;
+                }
+                Greek(ref gitems) => {
+                    self.out.push(6);
+                    self.out.extend_from_slice(&(gitems.len() as u32).to_le_bytes());
+                    for gitem in gitems {
+                        let (kind, c, mods) = match *gitem {
+                            GreekItem::Letter(c, mods) => (0, c, mods),
+                            GreekItem::Other(c) => (1, c, GreekMods::empty()),
+                            GreekItem::Unknown(c) => (2, c, GreekMods::empty()),
+                        };
+                        self.out.push(kind);
+                        self.out.extend_from_slice(&(c as u32).to_le_bytes());
+                        self.out.extend_from_slice(&mods.bits().to_le_bytes());
+                    }
+                }
+                PlainText(text) => {
+                    self.out.push(7);
+                    self.str(text)?;
+                }
+                UnpairedTagOpen(name, source) => {
+                    self.out.push(8);
+                    self.name(name.as_str(), name.is_known())?;
+                    self.opt_str(source)?;
+                }
+                UnpairedTagClose(name) => {
+                    self.out.push(9);
+                    self.name(name.as_str(), name.is_known())?;
+                }
+                PageBreak(page) => {
+                    self.out.push(10);
+                    self.out.extend_from_slice(&page.to_le_bytes());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Decoder<'r, 'b: 'r> {
+    reader: &'r mut ByteReader<'b>,
+    text: &'static str,
+}
+
+impl<'r, 'b> Decoder<'r, 'b> {
+    fn str(&mut self) -> Result<&'static str, IndexError> {
+        let start = self.reader.u32()? as usize;
+        let len = self.reader.u32()? as usize;
+        self.text.get(start..start + len).ok_or(IndexError::Format("bad text range in dictionary cache"))
+    }
+
+    /// A name written by `Encoder::name`, interned with `new` or `known`.
+    fn name<T>(&mut self, new: fn(&'static str) -> T, known: fn(&str) -> Option<T>) -> Result<T, IndexError> {
+        match self.reader.u8()? {
+            0 => self.str().map(new),
+            len => str::from_utf8(self.reader.take(len as usize)?).ok()
+                .and_then(known)
+                .ok_or(IndexError::Format("unknown name in dictionary cache")),
+        }
+    }
+
+    fn opt_str(&mut self) -> Result<Option<&'static str>, IndexError> {
+        match self.reader.u8()? {
+            0 => Ok(None),
+            _ => self.str().map(Some),
+        }
+    }
+
+    fn entry(&mut self) -> Result<Entry<'static>, IndexError> {
+        let main_word = self.str()?;
+        let source = self.str()?;
+        let raw = self.opt_str()?;
+        let id = match self.reader.u8()? {
+            0 => None,
+            _ => {
+                let len = self.reader.u32()? as usize;
+                let id = std::str::from_utf8(self.reader.take(len)?)
+                    .map_err(|_| IndexError::Format("invalid UTF-8 in dictionary cache"))?;
+                Some(id.to_string())
+            }
+        };
+        let items = self.items()?;
+        Ok(Entry { main_word, items, source, raw, id })
+    }
+
+    fn items(&mut self) -> Result<Vec<EntryItem<'static>>, IndexError> {
+        use parser::EntryItem::*;
+        let count = self.reader.u32()? as usize;
+        let mut items = Vec::with_capacity(count.min(self.reader.0.len()));
+        for _ in 0..count {
+            items.push(match self.reader.u8()? {
+                0 => {
+                    let name = self.name(TagName::new, TagName::known)?;
+                    let source = self.opt_str()?;
+                    Tagged { name, source, items: self.items()? }
+                }
+                1 => Comment(self.str()?),
+                2 => Entity(self.name(entities::Entity::new, entities::Entity::known)?),
+                3 => EntityBr,
+                4 => EntityUnk,
+                5 => ExternalLink(self.str()?, self.str()?),
+                6 => {
+                    let count = self.reader.u32()? as usize;
+                    let mut gitems = Vec::with_capacity(count.min(self.reader.0.len()));
+                    for _ in 0..count {
+                        let kind = self.reader.u8()?;
+                        let c = char::from_u32(self.reader.u32()?)
+                            .ok_or(IndexError::Format("bad character in dictionary cache"))?;
+                        let mods = GreekMods::from_bits(self.reader.u16()?)
+                            .ok_or(IndexError::Format("bad Greek marks in dictionary cache"))?;
+                        gitems.push(match kind {
+                            0 => GreekItem::Letter(c, mods),
+                            1 => GreekItem::Other(c),
+                            _ => GreekItem::Unknown(c),
+                        });
+                    }
+                    Greek(gitems)
+                }
+                7 => PlainText(self.str()?),
+                8 => UnpairedTagOpen(self.name(TagName::new, TagName::known)?, self.opt_str()?),
+                9 => UnpairedTagClose(self.name(TagName::new, TagName::known)?),
+                10 => PageBreak(self.reader.u32()?),
+                _ => return Err(IndexError::Format("bad item in dictionary cache")),
+            });
+        }
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn shared_lookups() {
+        let contents = vec![
+            "<entry main-word=\"Abacus\" source=\"1913 Webster\"><p><hw>Ab\"a*cus</hw></p></entry>\n\
+             <entry main-word=\"Abase\" source=\"1913 Webster\"><p><hw>A*base\"</hw></p></entry>\n".to_string(),
+            "<entry main-word=\"abacus\" source=\"WordNet 1.5\"><p><hw>abacus</hw></p></entry>\n".to_string(),
+        ];
+        let dict = Arc::new(Dictionary::from_contents(contents));
+        let handle = {
+            let dict = Arc::clone(&dict);
+            thread::spawn(move || dict.define("ABACUS").len())
+        };
+        assert_eq!(handle.join().unwrap(), 2);
+        assert_eq!(dict.define("abase")[0].main_word, "Abase");
+        assert!(dict.define("abbey").is_empty());
+        assert_eq!(dict.entries().len(), 3);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_over_plain_text() {
+        let contents = vec![
+            "<entry main-word=\"Abacus\" source=\"1913 Webster\"><p><pos>n.</pos> <sn>1.</sn> \
+             <def>A <ex>table</ex> for a caf<eacute/.</def></p>\n<p><sn>2.</sn> <def>A table; \
+             a frame.</def></p></entry>\n".to_string(),
+        ];
+        let dict = Dictionary::from_contents(contents);
+        let re = Regex::new(r"\bA table\b").unwrap();
+        let matches: Vec<_> = dict.search_regex(&re).collect();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].entry.main_word, "Abacus");
+        assert_eq!((matches[0].sense_index, matches[0].range.clone()), (0, 0..7));
+        assert_eq!(matches[1].sense.number.as_ref().unwrap(), "2");
+        let re = Regex::new("caf\u{e9}").unwrap();
+        assert_eq!(dict.search_regex(&re).next().unwrap().as_str(), "caf\u{e9}");
+    }
+
+    #[test]
+    fn cache_round_trip() {
+        let contents = vec![
+            "<entry main-word=\"Abacus\" source=\"1913 Webster\">\n<p><hw>Ab\"a*cus</hw> <-- note --> \
+             Gr. <grk>'a`bax</grk><br/\n<note>x <ae/ <frac23/</entry>\n".to_string(),
+        ];
+        let dict = Dictionary::from_contents(contents);
+        let mut bytes = Vec::new();
+        dict.write_to(&mut bytes).unwrap();
+        let loaded = Dictionary::from_bytes(&bytes).unwrap();
+        let (entry, original) = (&loaded.entries()[0], &dict.entries()[0]);
+        assert_eq!((entry.main_word, entry.source, &entry.items), (original.main_word, original.source, &original.items));
+        assert_eq!(entry.id, original.id);
+        assert!(entry.id.is_some());
+        assert_eq!(loaded.define("abacus").len(), 1);
+        assert!(Dictionary::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,234 @@
+//! Compression in the dictzip format: gzip whose data is deflated in chunks
+//! that can be decompressed on their own, with a table of their sizes in the
+//! header, so that dictd can read entries without decompressing the file.
+
+use std::io::{self, ErrorKind};
+
+/// Uncompressed size of the chunks, as used by `dictzip`.
+pub const CHUNK_LEN: usize = 58315;
+
+const WINDOW: usize = 1 << 15;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 128;
+const HASH_BITS: u32 = 15;
+
+/// Compresses `data` into a `.dz` file.
+pub fn compress(data: &[u8]) -> io::Result<Vec<u8>> {
+    let chunks: Vec<&[u8]> = if data.is_empty() { vec![data] } else { data.chunks(CHUNK_LEN).collect() };
+    let extra_len = 6 + 2 * chunks.len();
+    if extra_len + 4 > u16::MAX as usize {
+        return Err(io::Error::new(ErrorKind::InvalidInput, "too much data for a dictzip file"));
+    }
+    let mut deflated = Vec::with_capacity(data.len() / 2);
+    let mut sizes = Vec::with_capacity(chunks.len());
+    for (idx, chunk) in chunks.iter().enumerate() {
+        let start = deflated.len();
+        deflate_chunk(chunk, idx + 1 == chunks.len(), &mut deflated);
+        sizes.push((deflated.len() - start) as u16);
+    }
+
+    let mut out = Vec::with_capacity(deflated.len() + extra_len + 30);
+    // ID1, ID2, CM = deflate, FLG = FEXTRA, MTIME = 0, XFL = 0, OS = Unix
+    out.extend_from_slice(&[0x1f, 0x8b, 8, 4, 0, 0, 0, 0, 0, 3]);
+    out.extend_from_slice(&((extra_len + 4) as u16).to_le_bytes());
+    out.extend_from_slice(b"RA");
+    out.extend_from_slice(&(extra_len as u16).to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&(CHUNK_LEN as u16).to_le_bytes());
+    out.extend_from_slice(&(chunks.len() as u16).to_le_bytes());
+    for size in sizes {
+        out.extend_from_slice(&size.to_le_bytes());
+    }
+    out.extend_from_slice(&deflated);
+    out.extend_from_slice(&crc32(data).to_le_bytes());
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    Ok(out)
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut table = [0u32; 256];
+    for (n, slot) in table.iter_mut().enumerate() {
+        let mut c = n as u32;
+        for _ in 0..8 {
+            c = if c & 1 != 0 { 0xedb8_8320 ^ (c >> 1) } else { c >> 1 };
+        }
+        *slot = c;
+    }
+    !data.iter().fold(!0u32, |crc, &b| table[((crc ^ u32::from(b)) & 0xff) as usize] ^ (crc >> 8))
+}
+
+enum Token {
+    Literal(u8),
+    Match { len: usize, dist: usize },
+}
+
+/// Deflates `chunk` without referring to earlier chunks, ending on a byte
+/// boundary as zlib's full flush does.
+fn deflate_chunk(chunk: &[u8], last: bool, out: &mut Vec<u8>) {
+    let mut bits = BitWriter::default();
+    bits.write(u32::from(last), 1);
+    bits.write(1, 2); // fixed Huffman codes
+    for token in lz77(chunk) {
+        match token {
+            Token::Literal(b) => write_literal(&mut bits, u16::from(b)),
+            Token::Match { len, dist } => {
+                let (code, extra, extra_bits) = length_code(len);
+                write_literal(&mut bits, code);
+                bits.write(extra, extra_bits);
+                let (code, extra, extra_bits) = distance_code(dist);
+                bits.write_code(code, 5);
+                bits.write(extra, extra_bits);
+            }
+        }
+    }
+    write_literal(&mut bits, 256);
+    if !last {
+        // an empty stored block, to get back to a byte boundary
+        bits.write(0, 3);
+        bits.align();
+        bits.out.extend_from_slice(&[0, 0, 0xff, 0xff]);
+    }
+    bits.align();
+
+    // the chunk size must fit in 16 bits; stored blocks always do
+    if bits.out.len() > chunk.len() + 5 {
+        out.push(u8::from(last));
+        out.extend_from_slice(&(chunk.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(!(chunk.len() as u16)).to_le_bytes());
+        out.extend_from_slice(chunk);
+    } else {
+        out.extend_from_slice(&bits.out);
+    }
+}
+
+fn lz77(data: &[u8]) -> Vec<Token> {
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; data.len()];
+    let mut tokens = Vec::with_capacity(data.len() / 2);
+    let mut pos = 0;
+    while pos < data.len() {
+        let mut best = (0, 0);
+        if pos + MIN_MATCH <= data.len() {
+            let max_len = MAX_MATCH.min(data.len() - pos);
+            let mut candidate = head[hash(data, pos)];
+            let mut chain = 0;
+            while candidate != usize::MAX && pos - candidate <= WINDOW && chain < MAX_CHAIN {
+                let len = data[candidate..].iter().zip(&data[pos..pos + max_len]).take_while(|(a, b)| a == b).count();
+                if len > best.0 {
+                    best = (len, pos - candidate);
+                    if len == max_len {
+                        break;
+                    }
+                }
+                candidate = prev[candidate];
+                chain += 1;
+            }
+        }
+        let len = if best.0 >= MIN_MATCH {
+            tokens.push(Token::Match { len: best.0, dist: best.1 });
+            best.0
+        } else {
+            tokens.push(Token::Literal(data[pos]));
+            1
+        };
+        let end = (pos + len).min((data.len() + 1).saturating_sub(MIN_MATCH));
+        for (p, prev_pos) in prev.iter_mut().enumerate().take(end).skip(pos) {
+            let h = hash(data, p);
+            *prev_pos = head[h];
+            head[h] = p;
+        }
+        pos += len;
+    }
+    tokens
+}
+
+fn hash(data: &[u8], pos: usize) -> usize {
+    let key = u32::from(data[pos]) << 16 | u32::from(data[pos + 1]) << 8 | u32::from(data[pos + 2]);
+    (key.wrapping_mul(0x9e37_79b1) >> (32 - HASH_BITS)) as usize
+}
+
+fn write_literal(bits: &mut BitWriter, lit: u16) {
+    let lit = u32::from(lit);
+    match lit {
+        0..=143 => bits.write_code(0x30 + lit, 8),
+        144..=255 => bits.write_code(0x190 + lit - 144, 9),
+        256..=279 => bits.write_code(lit - 256, 7),
+        _ => bits.write_code(0xc0 + lit - 280, 8),
+    }
+}
+
+/// Literal/length code, extra bits and their count for a match length.
+fn length_code(len: usize) -> (u16, u32, u32) {
+    const BASES: [usize; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31,
+                                35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+    const EXTRA: [u32; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2,
+                              3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+    let idx = BASES.iter().rposition(|&base| base <= len).unwrap();
+    (257 + idx as u16, (len - BASES[idx]) as u32, EXTRA[idx])
+}
+
+/// Distance code, extra bits and their count for a match distance.
+fn distance_code(dist: usize) -> (u32, u32, u32) {
+    if dist <= 4 {
+        return (dist as u32 - 1, 0, 0);
+    }
+    let extra_bits = usize::BITS - (dist - 1).leading_zeros() - 2;
+    let base = ((2 | ((dist - 1) >> extra_bits & 1)) << extra_bits) + 1;
+    let code = 2 * extra_bits + 2 + ((dist - 1) >> extra_bits & 1) as u32;
+    (code, (dist - base) as u32, extra_bits)
+}
+
+#[derive(Default)]
+struct BitWriter {
+    out: Vec<u8>,
+    acc: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    /// Writes the low `count` bits of `value`, least significant first.
+    fn write(&mut self, value: u32, count: u32) {
+        self.acc |= value << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.out.push(self.acc as u8);
+            self.acc >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Writes a Huffman code, which is packed most significant bit first.
+    fn write_code(&mut self, code: u32, len: u32) {
+        self.write(code.reverse_bits() >> (32 - len), len);
+    }
+
+    fn align(&mut self) {
+        if self.count > 0 {
+            self.write(0, 8 - self.count);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn chunk_table() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(distance_code(5), (4, 0, 1));
+        assert_eq!(distance_code(32768), (29, 8191, 13));
+        assert_eq!(length_code(258), (285, 0, 0));
+
+        let data: Vec<u8> = (0..CHUNK_LEN + 1000).map(|n| b"abacus abase\n"[n % 13]).collect();
+        let dz = compress(&data).unwrap();
+        assert_eq!(&dz[..4], &[0x1f, 0x8b, 8, 4]);
+        assert_eq!(&dz[12..14], b"RA");
+        let chunk_count = u16::from_le_bytes([dz[20], dz[21]]) as usize;
+        assert_eq!(chunk_count, 2);
+        let sizes: usize = (0..chunk_count).map(|n| u16::from_le_bytes([dz[22 + 2 * n], dz[23 + 2 * n]]) as usize).sum();
+        assert_eq!(dz.len(), 22 + 2 * chunk_count + sizes + 8);
+        assert!(dz.len() < 1000);
+    }
+}
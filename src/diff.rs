@@ -0,0 +1,123 @@
+//! Entry-level comparison of two versions of a CIDE file.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use exporter::{CideOptions, CIDE};
+use headword::normalize_headword;
+use parser::EntryParser;
+
+/// Identifies an entry across versions: its normalized main word and how many
+/// entries with the same main word precede it in the file.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EntryKey {
+    pub headword: String,
+    pub occurrence: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compare {
+    /// Entries differ if their source text differs at all.
+    Bytes,
+    /// Entries differ if their item trees differ; line wrapping and spacing
+    /// between blocks are ignored.
+    Semantic,
+}
+
+#[derive(Debug)]
+pub enum EntryChange<'a> {
+    Added { key: EntryKey, new: &'a str },
+    Removed { key: EntryKey, old: &'a str },
+    Changed { key: EntryKey, old: &'a str, new: &'a str },
+}
+
+/// Raw entry blocks of `contents` with their keys, in file order.
+pub fn keyed_blocks(contents: &str) -> Vec<(EntryKey, Range<usize>)> {
+    let mut seen = HashMap::new();
+    let mut blocks = Vec::new();
+    let mut entry_iter = EntryParser::new(contents);
+    while entry_iter.next().is_some() {
+        let span = entry_iter.last_span().unwrap();
+        let headword = normalize_headword(raw_main_word(&contents[span.clone()]));
+        let count = seen.entry(headword.clone()).or_insert(0);
+        blocks.push((EntryKey { headword, occurrence: *count }, span));
+        *count += 1;
+    }
+    blocks
+}
+
+/// The `main-word` attribute of a raw entry block, even if the block does not parse.
+pub fn raw_main_word(raw: &str) -> &str {
+    let attr = " main-word=\"";
+    raw.find(attr)
+        .map(|idx| &raw[idx + attr.len()..])
+        .and_then(|rest| rest.find('"').map(|end| &rest[..end]))
+        .unwrap_or("")
+}
+
+/// Top-level blocks of an entry in canonical layout with whitespace collapsed,
+/// or `None` if the entry does not parse.
+pub fn semantic_blocks(raw: &str) -> Option<Vec<String>> {
+    let entry = EntryParser::new(raw).next()?.ok()?;
+    let opts = CideOptions { wrap_width: None, ..CideOptions::canonical() };
+    let text = CIDE::with_options(&entry, &opts).to_string();
+    let body = text.split_once('\n').map_or("", |(_, body)| body).trim_end_matches("</entry>");
+    Some(body.split("\n\n").map(|block| block.split_whitespace().collect::<Vec<_>>().join(" ")).collect())
+}
+
+pub fn entries_equal(old: &str, new: &str, mode: Compare) -> bool {
+    match mode {
+        Compare::Bytes => old == new,
+        Compare::Semantic => match (semantic_blocks(old), semantic_blocks(new)) {
+            (Some(old_blocks), Some(new_blocks)) => old_blocks == new_blocks,
+            _ => old == new,
+        },
+    }
+}
+
+/// Changed and added entries in the order of `new`, followed by removed ones.
+pub fn diff<'a>(old: &'a str, new: &'a str, mode: Compare) -> Vec<EntryChange<'a>> {
+    let old_blocks = keyed_blocks(old);
+    let mut old_by_key: HashMap<&EntryKey, &Range<usize>> = old_blocks.iter().map(|(k, r)| (k, r)).collect();
+    let mut changes = Vec::new();
+    for (key, span) in keyed_blocks(new) {
+        let new_raw = &new[span];
+        match old_by_key.remove(&key) {
+            Some(old_span) => {
+                let old_raw = &old[old_span.clone()];
+                if !entries_equal(old_raw, new_raw, mode) {
+                    changes.push(EntryChange::Changed { key, old: old_raw, new: new_raw });
+                }
+            }
+            None => changes.push(EntryChange::Added { key, new: new_raw }),
+        }
+    }
+    for (key, span) in &old_blocks {
+        if old_by_key.contains_key(key) {
+            changes.push(EntryChange::Removed { key: key.clone(), old: &old[span.clone()] });
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn added_removed_changed() {
+        let old = "<entry main-word=\"A\" source=\"\">\n<p><hw>A</hw> <def>one</def></p>\n</entry>\n\
+                   <entry main-word=\"B\" source=\"\">\n<p><hw>B</hw></p>\n</entry>\n\
+                   <entry main-word=\"C\" source=\"\">\n<p><hw>C</hw></p>\n</entry>\n";
+        let new = "<entry main-word=\"A\" source=\"\">\n<p><hw>A</hw>\n<def>one</def></p>\n</entry>\n\
+                   <entry main-word=\"C\" source=\"\">\n<p><hw>C</hw> <def>see</def></p>\n</entry>\n\
+                   <entry main-word=\"D\" source=\"\">\n<p><hw>D</hw></p>\n</entry>\n";
+        let summary = |changes: Vec<EntryChange>| changes.iter().map(|change| match *change {
+            EntryChange::Added { ref key, .. } => format!("+{}", key.headword),
+            EntryChange::Removed { ref key, .. } => format!("-{}", key.headword),
+            EntryChange::Changed { ref key, .. } => format!("~{}", key.headword),
+        }).collect::<Vec<_>>();
+        assert_eq!(summary(diff(old, new, Compare::Bytes)), ["~a", "~c", "+d", "-b"]);
+        assert_eq!(summary(diff(old, new, Compare::Semantic)), ["~c", "+d", "-b"]);
+    }
+}
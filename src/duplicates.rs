@@ -0,0 +1,104 @@
+//! Finding entries that repeat one another, in the same file or across files:
+//! entries with the same headword whose definitions share most of their
+//! wording, compared as sets of word shingles.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use headword::normalize_headword;
+use model::plain_text;
+use parser::Entry;
+
+/// Words per shingle.
+const SHINGLE_LEN: usize = 3;
+
+/// The definitions of an entry as hashes of runs of `SHINGLE_LEN` words,
+/// ignoring case and punctuation. Definitions with fewer words give one
+/// shingle of all of them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Shingles(HashSet<u64>);
+
+impl Shingles {
+    pub fn of_entry(entry: &Entry) -> Shingles {
+        let mut shingles = HashSet::new();
+        for items in entry.find_all("def") {
+            let text = plain_text(items).to_lowercase();
+            let words: Vec<_> = text.split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()).collect();
+            for window in words.windows(SHINGLE_LEN.min(words.len()).max(1)) {
+                let mut hasher = DefaultHasher::new();
+                window.hash(&mut hasher);
+                shingles.insert(hasher.finish());
+            }
+        }
+        Shingles(shingles)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The share of shingles in common, from 0 for none to 1 for the same
+    /// set; 0 if either has none.
+    pub fn resemblance(&self, other: &Shingles) -> f64 {
+        let common = self.0.intersection(&other.0).count();
+        let all = self.0.len() + other.0.len() - common;
+        if common == 0 { 0.0 } else { common as f64 / all as f64 }
+    }
+}
+
+/// Collects entries one at a time and finds, for each, the entries added
+/// before it that it duplicates. `K` identifies an entry in the report, e.g.
+/// by file and line.
+#[derive(Debug)]
+pub struct DuplicateFinder<K> {
+    threshold: f64,
+    by_headword: HashMap<String, Vec<(K, Shingles)>>,
+}
+
+impl<K: Clone> DuplicateFinder<K> {
+    /// A finder reporting entries whose `Shingles::resemblance` is at least
+    /// `threshold`.
+    pub fn new(threshold: f64) -> DuplicateFinder<K> {
+        DuplicateFinder { threshold, by_headword: HashMap::new() }
+    }
+
+    /// Adds the entry at `key` with the main word `main_word`, and returns the
+    /// earlier entries with the same normalized headword that it duplicates,
+    /// with their resemblance. Entries without definitions are not compared.
+    pub fn add(&mut self, key: K, main_word: &str, shingles: Shingles) -> Vec<(K, f64)> {
+        if shingles.is_empty() {
+            return Vec::new();
+        }
+        let threshold = self.threshold;
+        let earlier = self.by_headword.entry(normalize_headword(main_word)).or_default();
+        let found = earlier.iter()
+            .map(|(other, other_shingles)| (other.clone(), shingles.resemblance(other_shingles)))
+            .filter(|&(_, resemblance)| resemblance >= threshold)
+            .collect();
+        earlier.push((key, shingles));
+        found
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::EntryParser;
+
+    #[test]
+    fn find_duplicates() {
+        let text = "<entry main-word=\"Ab\" source=\"A\"><def>The fifth month of the Jewish year.</def></entry>\n\
+                    <entry main-word=\"Ab\" source=\"A\"><def>A prefix meaning from.</def></entry>\n\
+                    <entry main-word=\"AB\" source=\"B\"><def>The fifth month of the Jewish year</def></entry>\n\
+                    <entry main-word=\"Ab\" source=\"B\"><def>The fifth month of the Hebrew year.</def></entry>\n\
+                    <entry main-word=\"Ab\" source=\"B\"><hw>Ab</hw></entry>\n\
+                    <entry main-word=\"Abb\" source=\"B\"><def>The fifth month of the Jewish year.</def></entry>";
+        let mut finder = DuplicateFinder::new(0.3);
+        let found: Vec<_> = EntryParser::new(text).enumerate().map(|(idx, entry)| {
+            let entry = entry.unwrap();
+            finder.add(idx, entry.main_word, Shingles::of_entry(&entry))
+        }).collect();
+        assert_eq!(found, [vec![], vec![], vec![(0, 1.0)], vec![(0, 3.0 / 7.0), (2, 3.0 / 7.0)], vec![], vec![]]);
+    }
+}
@@ -0,0 +1,169 @@
+//! Editing entries in place. Items are addressed by paths of indices: `[2, 0]`
+//! is the first item inside the tag that is the third item of the entry.
+//! Every edit clears `Entry::raw`, and pairs up any unpaired tags it leaves
+//! among the siblings it touched, as the parser would.
+
+use std::fmt::{self, Display, Formatter};
+use std::mem;
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use parser::{self, Entry, EntryItem, PairingRules};
+use tags::TagName;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EditError {
+    /// The path is empty, runs past the end of a list, or goes through an item
+    /// that is not a tag.
+    NoSuchItem,
+    NotATag,
+    NotText,
+    /// An offset outside the text or not on a character boundary.
+    BadOffset,
+}
+
+impl Display for EditError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            EditError::NoSuchItem => "no item at this path",
+            EditError::NotATag => "item is not a tag",
+            EditError::NotText => "item is not plain text",
+            EditError::BadOffset => "offset is not a character boundary inside the text",
+        })
+    }
+}
+
+impl<'a> Entry<'a> {
+    /// Inserts `item` at `path`, moving the items from there on one place
+    /// along. An unpaired open or close tag encloses the siblings after or
+    /// before it up to its match, if any.
+    pub fn insert_item(&mut self, path: &[usize], item: EntryItem<'a>) -> Result<(), EditError> {
+        let (siblings, idx) = self.siblings_mut(path)?;
+        if idx > siblings.len() {
+            return Err(EditError::NoSuchItem);
+        }
+        siblings.insert(idx, item);
+        pair_up(siblings);
+        Ok(())
+    }
+
+    /// Moves the `len` items starting at `path` into a new tag `name`. Unpaired
+    /// tags among them enclose their matches inside the new tag.
+    pub fn wrap_in_tag(&mut self, path: &[usize], len: usize, name: TagName<'a>, source: Option<&'a str>)
+                       -> Result<(), EditError> {
+        let (siblings, idx) = self.siblings_mut(path)?;
+        if idx > siblings.len() || siblings.len() - idx < len {
+            return Err(EditError::NoSuchItem);
+        }
+        let mut items = siblings.splice(idx..idx + len, None).collect();
+        pair_up(&mut items);
+        siblings.insert(idx, EntryItem::Tagged { name, items, source });
+        pair_up(siblings);
+        Ok(())
+    }
+
+    /// Replaces the tag at `path` with its contents.
+    pub fn remove_tag_keep_children(&mut self, path: &[usize]) -> Result<(), EditError> {
+        let (siblings, idx) = self.siblings_mut(path)?;
+        match siblings.get_mut(idx) {
+            Some(&mut EntryItem::Tagged { ref mut items, .. }) => {
+                let items = mem::take(items);
+                siblings.splice(idx..idx + 1, items);
+                pair_up(siblings);
+                Ok(())
+            }
+            Some(_) => Err(EditError::NotATag),
+            None => Err(EditError::NoSuchItem),
+        }
+    }
+
+    /// Splits the plain text at `path` in two at the byte `offset`, so that
+    /// part of it can be wrapped in a tag.
+    pub fn split_text(&mut self, path: &[usize], offset: usize) -> Result<(), EditError> {
+        let (siblings, idx) = self.siblings_mut(path)?;
+        let text = match siblings.get(idx) {
+            Some(&EntryItem::PlainText(text)) => text,
+            Some(_) => return Err(EditError::NotText),
+            None => return Err(EditError::NoSuchItem),
+        };
+        if offset == 0 || offset >= text.len() || !text.is_char_boundary(offset) {
+            return Err(EditError::BadOffset);
+        }
+        siblings[idx] = EntryItem::PlainText(&text[..offset]);
+        siblings.insert(idx + 1, EntryItem::PlainText(&text[offset..]));
+        Ok(())
+    }
+
+    /// The list holding the item at `path`, and its index there, which may be
+    /// one past the end.
+    fn siblings_mut(&mut self, path: &[usize]) -> Result<(&mut Vec<EntryItem<'a>>, usize), EditError> {
+        self.raw = None;
+        let (&idx, parents) = path.split_last().ok_or(EditError::NoSuchItem)?;
+        let mut siblings = &mut self.items;
+        for &parent in parents {
+            siblings = match siblings.get_mut(parent) {
+                Some(&mut EntryItem::Tagged { ref mut items, .. }) => items,
+                _ => return Err(EditError::NoSuchItem),
+            };
+        }
+        Ok((siblings, idx))
+    }
+}
+
+fn pair_up(siblings: &mut Vec<EntryItem>) {
+    let is_unpaired = |item: &EntryItem| matches!(item, EntryItem::UnpairedTagOpen(..) | EntryItem::UnpairedTagClose(_));
+    if siblings.iter().any(is_unpaired) {
+        *siblings = parser::pair_up_items(mem::take(siblings), &PairingRules::default(), &mut Vec::new());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use exporter::CIDE;
+
+    #[test]
+    fn edit_entry() {
+        let text = "<entry main-word=\"A\" source=\"B\"><p><q>To be.</q> Shak.</p><i><b>x</b></i></entry>";
+        let mut entry = Entry::parse(text).unwrap();
+        entry.split_text(&[0, 1], 1).unwrap();
+        entry.wrap_in_tag(&[0, 2], 1, TagName::Au, None).unwrap();
+        entry.remove_tag_keep_children(&[1]).unwrap();
+        entry.insert_item(&[0, 0], EntryItem::UnpairedTagOpen(TagName::Note, None)).unwrap();
+        entry.insert_item(&[0, 2], EntryItem::UnpairedTagClose(TagName::Note)).unwrap();
+        assert_eq!(CIDE(&entry).to_string(), "<entry main-word=\"A\" source=\"B\"><p><note><q>To be.</q>\
+                                              </note> <au>Shak.</au></p><b>x</b></entry>");
+        assert_eq!(entry.items[0], EntryItem::Tagged { name: TagName::P, source: None, items: vec![
+            EntryItem::Tagged { name: TagName::Note, source: None, items: vec![
+                EntryItem::Tagged { name: TagName::Q, source: None, items: vec![EntryItem::PlainText("To be.")] },
+            ] },
+            EntryItem::PlainText(" "),
+            EntryItem::Tagged { name: TagName::Au, source: None, items: vec![EntryItem::PlainText("Shak.")] },
+        ] });
+
+        assert_eq!(entry.split_text(&[0, 1], 1), Err(EditError::BadOffset));
+        assert_eq!(entry.split_text(&[0, 0], 1), Err(EditError::NotText));
+        assert_eq!(entry.remove_tag_keep_children(&[0, 1]), Err(EditError::NotATag));
+        assert_eq!(entry.wrap_in_tag(&[0, 2], 2, TagName::I, None), Err(EditError::NoSuchItem));
+        assert_eq!(entry.insert_item(&[0, 1, 0], EntryItem::PlainText("x")), Err(EditError::NoSuchItem));
+        assert_eq!(entry.insert_item(&[], EntryItem::PlainText("x")), Err(EditError::NoSuchItem));
+    }
+
+    #[test]
+    fn wrap_unpaired() {
+        let mut entry = Entry::parse("<entry main-word=\"A\" source=\"B\"></entry>").unwrap();
+        entry.items = vec![
+            EntryItem::PlainText("a "),
+            EntryItem::UnpairedTagOpen(TagName::Note, None),
+            EntryItem::PlainText("b"),
+            EntryItem::UnpairedTagClose(TagName::Note),
+        ];
+        entry.wrap_in_tag(&[1], 3, TagName::Cs, None).unwrap();
+        assert_eq!(entry.items, [
+            EntryItem::PlainText("a "),
+            EntryItem::Tagged { name: TagName::Cs, source: None, items: vec![
+                EntryItem::Tagged { name: TagName::Note, source: None, items: vec![EntryItem::PlainText("b")] },
+            ] },
+        ]);
+    }
+}
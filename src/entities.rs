@@ -0,0 +1,251 @@
+//! Registry of the GCIDE entities, such as `<amac/`, and the characters they
+//! stand for.
+
+use std::fmt;
+
+macro_rules! entities {
+    ($($variant:ident $name:literal => $unicode:literal,)+) => {
+        /// The name of an entity, as parsed: one of the registry, or `Unknown`
+        /// for the rest. Build it with `Entity::new`, which never makes
+        /// `Unknown` of a registered name.
+        #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Entity<'a> {
+            $($variant,)+
+            Unknown(&'a str),
+        }
+
+        impl<'a> Entity<'a> {
+            pub fn new(name: &'a str) -> Entity<'a> {
+                Entity::known(name).unwrap_or(Entity::Unknown(name))
+            }
+
+            /// The variant for `name`, if it is in the registry.
+            pub fn known(name: &str) -> Option<Entity<'static>> {
+                match name {
+                    $($name => Some(Entity::$variant),)+
+                    _ => None,
+                }
+            }
+
+            pub fn as_str(&self) -> &'a str {
+                match *self {
+                    $(Entity::$variant => $name,)+
+                    Entity::Unknown(name) => name,
+                }
+            }
+
+            /// The text the entity stands for, or U+FFFD for unknown ones.
+            pub fn to_unicode(&self) -> &'static str {
+                match *self {
+                    $(Entity::$variant => $unicode,)+
+                    Entity::Unknown(_) => "\u{fffd}",
+                }
+            }
+        }
+    }
+}
+
+entities! {
+    Lt "lt"                => "<",
+    Gt "gt"                => ">",
+    Ait "ait"              => "a",
+    Eit "eit"              => "e",
+    Iit "iit"              => "i",
+    Oit "oit"              => "o",
+    Uit "uit"              => "u",
+    Ae "ae"                => "\u{00e6}",
+    CapitalAe "AE"         => "\u{00c6}",
+    Oe "oe"                => "\u{0153}",
+    CapitalOe "OE"         => "\u{0152}",
+    Cced "cced"            => "\u{00e7}",
+    Aring "aring"          => "\u{00e5}",
+    Uring "uring"          => "\u{016f}",
+    Aacute "aacute"        => "\u{00e1}",
+    Eacute "eacute"        => "\u{00e9}",
+    Iacute "iacute"        => "\u{00ed}",
+    Oacute "oacute"        => "\u{00f3}",
+    Uacute "uacute"        => "\u{00fa}",
+    CapitalEacute "Eacute" => "\u{00c9}",
+    Acir "acir"            => "\u{00e2}",
+    Ecir "ecir"            => "\u{00ea}",
+    Icir "icir"            => "\u{00ee}",
+    Ocir "ocir"            => "\u{00f4}",
+    Ucir "ucir"            => "\u{00fb}",
+    Agrave "agrave"        => "\u{00e0}",
+    Egrave "egrave"        => "\u{00e8}",
+    Igrave "igrave"        => "\u{00ec}",
+    Ograve "ograve"        => "\u{00f2}",
+    Ugrave "ugrave"        => "\u{00f9}",
+    Aum "aum"              => "\u{00e4}",
+    Eum "eum"              => "\u{00eb}",
+    Ium "ium"              => "\u{00ef}",
+    Oum "oum"              => "\u{00f6}",
+    Uum "uum"              => "\u{00fc}",
+    Atil "atil"            => "\u{00e3}",
+    Etil "etil"            => "\u{1ebd}",
+    Ltil "ltil"            => "l\u{0303}",
+    Mtil "mtil"            => "m\u{0303}",
+    Ntil "ntil"            => "\u{00f1}",
+    Amac "amac"            => "\u{0101}",
+    Emac "emac"            => "\u{0113}",
+    Imac "imac"            => "\u{012b}",
+    Omac "omac"            => "\u{014d}",
+    Umac "umac"            => "\u{016b}",
+    Ymac "ymac"            => "\u{0233}",
+    Aemac "aemac"          => "\u{01e3}",
+    Oomac "oomac"          => "o\u{035e}o",
+    Acr "acr"              => "\u{0103}",
+    Ecr "ecr"              => "\u{0115}",
+    Icr "icr"              => "\u{012d}",
+    Ocr "ocr"              => "\u{014f}",
+    Ucr "ucr"              => "\u{016d}",
+    Ycr "ycr"              => "y\u{0306}",
+    Oocr "oocr"            => "o\u{035d}o",
+    Ocar "ocar"            => "\u{01d2}",
+    Asl "asl"              => "a\u{0304}\u{0307}",
+    Esl "esl"              => "e\u{0304}\u{0307}",
+    Isl "isl"              => "i\u{0304}\u{0307}",
+    Osl "osl"              => "o\u{0304}\u{0307}",
+    Usl "usl"              => "u\u{0304}\u{0307}",
+    Adot "adot"            => "\u{0227}",
+    Ndot "ndot"            => "\u{1e45}",
+    Dsdot "dsdot"          => "\u{1e0d}",
+    Nsdot "nsdot"          => "\u{1e47}",
+    Rsdot "rsdot"          => "\u{1e5b}",
+    Tsdot "tsdot"          => "\u{1e6d}",
+    Usdot "usdot"          => "\u{1ee5}",
+    Add "add"              => "a\u{0324}",
+    Udd "udd"              => "\u{1e73}",
+    Nsm "nsm"              => "\u{1e49}",
+    Hand "hand"            => "\u{261e}",
+    Deg "deg"              => "\u{00b0}",
+    Prime "prime"          => "\u{2032}",
+    Dprime "dprime"        => "\u{2033}",
+    Ldquo "ldquo"          => "\u{201c}",
+    Rdquo "rdquo"          => "\u{201d}",
+    Lsquo "lsquo"          => "\u{2018}",
+    Rsquo "rsquo"          => "\u{2019}",
+    Sect "sect"            => "\u{00a7}",
+    Sharp "sharp"          => "\u{266f}",
+    Flat "flat"            => "\u{266d}",
+    Pound "pound"          => "\u{00a3}",
+    Minus "minus"          => "\u{2212}",
+    Mdash "mdash"          => "\u{2014}",
+    Th "th"                => "t\u{035f}h",
+    Par "par"              => "\u{2016}",
+    Cre "cre"              => "\u{2323}",
+    Edh "edh"              => "\u{00f0}",
+    Thorn "thorn"          => "\u{00fe}",
+    Yogh "yogh"            => "\u{021d}",
+    Divide "divide"        => "\u{00f7}",
+    Times "times"          => "\u{00d7}",
+    Rarr "rarr"            => "\u{2192}",
+    Middot "middot"        => "\u{00b7}",
+    Root "root"            => "\u{221a}",
+    Cuberoot "cuberoot"    => "\u{221b}",
+    Alpha "alpha"          => "\u{03b1}",
+    Beta "beta"            => "\u{03b2}",
+    Gamma "gamma"          => "\u{03b3}",
+    CapitalGamma "GAMMA"   => "\u{0393}",
+    Delta "delta"          => "\u{03b4}",
+    CapitalDelta "DELTA"   => "\u{0394}",
+    Epsilon "epsilon"      => "\u{03b5}",
+    Zeta "zeta"            => "\u{03b6}",
+    Eta "eta"              => "\u{03b7}",
+    Theta "theta"          => "\u{03b8}",
+    CapitalTheta "THETA"   => "\u{0398}",
+    Iota "iota"            => "\u{03b9}",
+    Kappa "kappa"          => "\u{03ba}",
+    Lambda "lambda"        => "\u{03bb}",
+    CapitalLambda "LAMBDA" => "\u{039b}",
+    Mu "mu"                => "\u{03bc}",
+    Nu "nu"                => "\u{03bd}",
+    Xi "xi"                => "\u{03be}",
+    CapitalXi "XI"         => "\u{039e}",
+    Omicron "omicron"      => "\u{03bf}",
+    Pi "pi"                => "\u{03c0}",
+    CapitalPi "PI"         => "\u{03a0}",
+    Rho "rho"              => "\u{03c1}",
+    Sigma "sigma"          => "\u{03c3}",
+    Sigmat "sigmat"        => "\u{03c2}",
+    CapitalSigma "SIGMA"   => "\u{03a3}",
+    Tau "tau"              => "\u{03c4}",
+    Upsilon "upsilon"      => "\u{03c5}",
+    Phi "phi"              => "\u{03c6}",
+    CapitalPhi "PHI"       => "\u{03a6}",
+    Chi "chi"              => "\u{03c7}",
+    Psi "psi"              => "\u{03c8}",
+    CapitalPsi "PSI"       => "\u{03a8}",
+    Omega "omega"          => "\u{03c9}",
+    CapitalOmega "OMEGA"   => "\u{03a9}",
+    Acute "acute"          => "\u{00b4}",
+    Grave "grave"          => "`",
+    Star "star"            => "*",
+    Asterism "asterism"    => "\u{2042}",
+    Cflex "cflex"          => "\u{02c6}",
+    Srtil "srtil"          => "\u{02dc}",
+    Invbre "invbre"        => " \u{0311}",
+    Bacc "bacc"            => "\u{02c8}",
+    Lacc "lacc"            => "\u{02cc}",
+    Sdiv "sdiv"            => "\u{00b7}",
+    Tsup "tsup"            => "\u{1d57}",
+    Esup "esup"            => "\u{1d49}",
+    Isub "isub"            => "\u{1d62}",
+}
+
+impl<'a> Entity<'a> {
+    pub fn is_known(&self) -> bool {
+        !matches!(*self, Entity::Unknown(_))
+    }
+}
+
+impl<'a> fmt::Display for Entity<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Written as the name itself, like `TagName`.
+impl<'a> fmt::Debug for Entity<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<'a> PartialEq<str> for Entity<'a> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<'a, 'b> PartialEq<&'b str> for Entity<'a> {
+    fn eq(&self, other: &&'b str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'a> ::serde::Serialize for Entity<'a> {
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn known_entities() {
+        assert_eq!(Entity::new("amac"), Entity::Amac);
+        assert_eq!(Entity::new("AE").to_unicode(), "\u{00c6}");
+        assert_eq!(Entity::new("ae").to_unicode(), "\u{00e6}");
+        assert_eq!(Entity::new("Eacute"), Entity::CapitalEacute);
+        assert_eq!(Entity::new("eacute").as_str(), "eacute");
+        let unknown = Entity::new("frac23");
+        assert_eq!(unknown, Entity::Unknown("frac23"));
+        assert!(!unknown.is_known() && Entity::Lt.is_known());
+        assert_eq!(unknown.to_unicode(), "\u{fffd}");
+        assert_eq!(format!("<{}/ {:?}", Entity::Mdash, Entity::Mdash), "<mdash/ \"mdash\"");
+    }
+}
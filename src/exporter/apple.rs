@@ -0,0 +1,99 @@
+//! Source files for Apple's Dictionary Development Kit, from which `build_dict.sh`
+//! builds a dictionary for Dictionary.app.
+//!
+//! A project consists of the XML written with `DICTIONARY_HEAD`, the entries,
+//! `FRONT_MATTER` and `DICTIONARY_TAIL`, plus a stylesheet (see
+//! `html::default_stylesheet`), `info_plist` and `MAKEFILE`.
+
+use std::fmt::{self, Display, Formatter};
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use exporter::html::HTML;
+use exporter::ExportOptions;
+use forms;
+use parser::Entry;
+
+/// A `d:entry` element, indexed by the main word, the headword and the word
+/// forms of the entry. The last field is the unique id of the element.
+pub struct AppleEntry<'a>(pub &'a Entry<'a>, pub &'a ExportOptions, pub &'a str);
+
+pub const DICTIONARY_HEAD: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+    <d:dictionary xmlns=\"http://www.w3.org/1999/xhtml\" \
+    xmlns:d=\"http://www.apple.com/DTDs/DictionaryService-1.0.rng\">\n";
+
+pub const DICTIONARY_TAIL: &str = "</d:dictionary>\n";
+
+pub const FRONT_MATTER: &str = "<d:entry id=\"front_back_matter\" d:title=\"Front/Back Matter\">\n\
+    <h1>GNU Collaborative International Dictionary of English</h1>\n\
+    <p>Based on Webster's Revised Unabridged Dictionary (1913), with additions from WordNet and \
+    the GCIDE contributors. Distributed under the GNU General Public License.</p>\n</d:entry>\n";
+
+pub const MAKEFILE: &str = "DICT_NAME = GCIDE\n\
+    DICT_SRC_PATH = GCIDE.xml\n\
+    CSS_PATH = GCIDE.css\n\
+    PLIST_PATH = GCIDE.plist\n\
+    DICT_BUILD_OPTS =\n\
+    DICT_BUILD_TOOL_DIR = \"/Applications/Utilities/Dictionary Development Kit\"\n\
+    DICT_BUILD_TOOL_BIN = \"$(DICT_BUILD_TOOL_DIR)/bin\"\n\
+    DICT_DEV_KIT_OBJ_DIR = ./objects\n\
+    export DICT_DEV_KIT_OBJ_DIR\n\
+    DESTINATION_FOLDER = ~/Library/Dictionaries\n\n\
+    all:\n\
+    \t\"$(DICT_BUILD_TOOL_BIN)/build_dict.sh\" $(DICT_BUILD_OPTS) $(DICT_NAME) $(DICT_SRC_PATH) $(CSS_PATH) $(PLIST_PATH)\n\n\
+    install:\n\
+    \tmkdir -p $(DESTINATION_FOLDER)\n\
+    \tditto --noextattr --norsrc $(DICT_DEV_KIT_OBJ_DIR)/$(DICT_NAME).dictionary \
+    $(DESTINATION_FOLDER)/$(DICT_NAME).dictionary\n\n\
+    clean:\n\
+    \t$(RM) -rf $(DICT_DEV_KIT_OBJ_DIR)\n";
+
+/// The `Info.plist` of the dictionary bundle.
+pub fn info_plist(bundle_id: &str, name: &str) -> String {
+    format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+             <plist version=\"1.0\">\n<dict>\n\
+             \t<key>CFBundleDevelopmentRegion</key>\n\t<string>English</string>\n\
+             \t<key>CFBundleIdentifier</key>\n\t<string>{}</string>\n\
+             \t<key>CFBundleName</key>\n\t<string>{}</string>\n\
+             \t<key>CFBundleShortVersionString</key>\n\t<string>1.0</string>\n\
+             \t<key>DCSDictionaryCopyright</key>\n\t<string>GNU General Public License</string>\n\
+             \t<key>DCSDictionaryManufacturerName</key>\n\t<string>GCIDE</string>\n\
+             \t<key>DCSDictionaryFrontMatterReferenceID</key>\n\t<string>front_back_matter</string>\n\
+             </dict>\n</plist>\n", escape(bundle_id), escape(name))
+}
+
+impl<'a> Display for AppleEntry<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let AppleEntry(entry, opts, id) = *self;
+        writeln!(f, "<d:entry id=\"{}\" d:title=\"{}\">", escape(id), escape(entry.main_word))?;
+        for term in &forms::lookup_terms(entry) {
+            writeln!(f, "<d:index d:value=\"{}\" d:title=\"{}\"/>", escape(term), escape(entry.main_word))?;
+        }
+        writeln!(f, "{}", HTML(entry, opts))?;
+        writeln!(f, "</d:entry>")
+    }
+}
+
+/// Escapes text for XML attributes and contents.
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::EntryParser;
+
+    #[test]
+    fn indexed_entry() {
+        let text = "<entry main-word=\"Abacus\" source=\"1913 Webster\"><hw>Ab\"a*cus</hw>; \
+                    <pl><plw>Abaci</plw></pl>.</entry>";
+        let entry = EntryParser::new(text).next().unwrap().unwrap();
+        let xml = AppleEntry(&entry, &ExportOptions::default(), "abacus").to_string();
+        assert!(xml.starts_with("<d:entry id=\"abacus\" d:title=\"Abacus\">\n\
+                                 <d:index d:value=\"Abacus\" d:title=\"Abacus\"/>\n\
+                                 <d:index d:value=\"Abaci\" d:title=\"Abacus\"/>\n<div class=\"entry\""));
+        assert!(xml.ends_with("</div>\n</d:entry>\n"));
+    }
+}
@@ -0,0 +1,133 @@
+//! A concordance of headwords: for each main word, the other entries whose
+//! definitions use it, for "used in the definition of" links.
+
+use std::collections::BTreeMap;
+
+use fulltext::tokenize;
+use headword::normalize_headword;
+use model;
+use parser::Entry;
+
+/// An entry whose definitions mention a headword.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Use {
+    pub main_word: String,
+    /// Numbers of the senses mentioning it; unnumbered senses are left out.
+    pub senses: Vec<String>,
+}
+
+/// Built in two passes over the files: `add_headword` for every entry, so
+/// that words defined further on are known, then `add_entry`. Only one-word
+/// main words can be matched, since definitions are compared word by word.
+#[derive(Debug, Default)]
+pub struct Concordance {
+    /// Main words by normalized form, as first spelled.
+    headwords: BTreeMap<String, String>,
+    /// Entries mentioning each headword, by its normalized form, in the order
+    /// they were added.
+    uses: BTreeMap<String, Vec<Use>>,
+}
+
+impl Concordance {
+    pub fn new() -> Concordance {
+        Concordance::default()
+    }
+
+    pub fn add_headword(&mut self, main_word: &str) {
+        let key = normalize_headword(main_word);
+        if !key.is_empty() && !key.contains(' ') {
+            self.headwords.entry(key).or_insert_with(|| main_word.to_string());
+        }
+    }
+
+    /// Records the headwords used in the definitions of `entry`, other than
+    /// its own main word.
+    pub fn add_entry(&mut self, entry: &Entry) {
+        let own = normalize_headword(entry.main_word);
+        for sense in model::senses(entry) {
+            let mut words = tokenize(&sense.definition);
+            words.sort_unstable();
+            words.dedup();
+            words.retain(|word| *word != own && self.headwords.contains_key(word));
+            for word in words {
+                let uses = self.uses.entry(word).or_default();
+                if uses.last().is_none_or(|last| last.main_word != entry.main_word) {
+                    uses.push(Use { main_word: entry.main_word.to_string(), senses: Vec::new() });
+                }
+                let last = uses.last_mut().unwrap();
+                if let Some(ref number) = sense.number {
+                    if !last.senses.contains(number) {
+                        last.senses.push(number.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Entries whose definitions use `word` (in any case or spelling that
+    /// normalizes the same).
+    pub fn uses(&self, word: &str) -> &[Use] {
+        self.uses.get(&normalize_headword(word)).map_or(&[], |uses| &uses[..])
+    }
+
+    /// Drops the headwords used by more than `max_entries` entries, which are
+    /// mostly function words such as `the` or `of`.
+    pub fn drop_frequent(&mut self, max_entries: usize) {
+        self.uses.retain(|_, uses| uses.len() <= max_entries);
+    }
+
+    /// Number of headwords used in some definition.
+    pub fn len(&self) -> usize {
+        self.uses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.uses.is_empty()
+    }
+
+    /// One line per headword and entry using it: the headword, the main word
+    /// of the entry and its sense numbers separated by commas, tab-separated.
+    pub fn tsv(&self) -> String {
+        let mut out = String::new();
+        for (key, uses) in &self.uses {
+            for usage in uses {
+                out.push_str(&format!("{}\t{}\t{}\n", self.headwords[key], usage.main_word, usage.senses.join(",")));
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::EntryParser;
+
+    #[test]
+    fn used_in_definitions() {
+        let text = "<entry main-word=\"Abacus\" source=\"1913 Webster\"><p><pos>n.</pos> <sn>1.</sn> \
+                    <def>A table for counting.</def></p>\n<p><sn>2.</sn> <def>A frame with beads; \
+                    an abacus for counting.</def></p></entry>\n\
+                    <entry main-word=\"Count\" source=\"1913 Webster\"><p><pos>v.</pos> \
+                    <def>To number; to tell by the abacus.</def></p></entry>\n\
+                    <entry main-word=\"Table\" source=\"1913 Webster\"><p><pos>n.</pos> \
+                    <def>A flat surface, as of an Abacus.</def></p></entry>\n\
+                    <entry main-word=\"Bead rope\" source=\"1913 Webster\"><p><def>A rope.</def></p></entry>";
+        let entries: Vec<_> = EntryParser::new(text).filter_map(Result::ok).collect();
+        let mut concordance = Concordance::new();
+        for entry in &entries {
+            concordance.add_headword(entry.main_word);
+        }
+        for entry in &entries {
+            concordance.add_entry(entry);
+        }
+        assert_eq!(concordance.len(), 2);
+        assert_eq!(concordance.uses("ABACUS"), [
+            Use { main_word: "Count".to_string(), senses: vec![] },
+            Use { main_word: "Table".to_string(), senses: vec![] },
+        ]);
+        assert_eq!(concordance.tsv(), "Abacus\tCount\t\nAbacus\tTable\t\nTable\tAbacus\t1\n");
+        concordance.drop_frequent(1);
+        assert_eq!(concordance.tsv(), "Table\tAbacus\t1\n");
+    }
+}
@@ -0,0 +1,116 @@
+//! Databases for the dictd server: a `.dict` file with the entries as plain
+//! text and a sorted `.index` of byte ranges in it.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use exporter::{ExportOptions, Plain};
+use forms;
+use parser::Entry;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub struct DictDatabase {
+    body: String,
+    index: Vec<(String, usize, usize)>,
+}
+
+impl DictDatabase {
+    /// A database with the `00-database-*` entries naming it and marking it as UTF-8.
+    pub fn new(short_name: &str, url: &str) -> DictDatabase {
+        let mut db = DictDatabase { body: String::new(), index: Vec::new() };
+        db.add_text(&["00-database-utf8".to_string()], "");
+        db.add_text(&["00-database-short".to_string()], short_name);
+        db.add_text(&["00-database-url".to_string()], url);
+        db
+    }
+
+    /// Sets the text shown by `dict --info`.
+    pub fn add_info(&mut self, info: &str) {
+        self.add_text(&["00-database-info".to_string()], info);
+    }
+
+    /// Adds the plain text of `entry`, indexed by `forms::lookup_terms`.
+    pub fn add_entry(&mut self, entry: &Entry, opts: &ExportOptions) {
+        let text = Plain(entry, opts).to_string();
+        self.add_text(&forms::lookup_terms(entry), text.trim());
+    }
+
+    /// Adds `text` under `headwords`; the first one heads the text.
+    pub fn add_text(&mut self, headwords: &[String], text: &str) {
+        let start = self.body.len();
+        if let Some(first) = headwords.first() {
+            self.body.push_str(first);
+            self.body.push('\n');
+        }
+        for line in text.lines() {
+            if !line.is_empty() {
+                self.body.push_str("   ");
+                self.body.push_str(line);
+            }
+            self.body.push('\n');
+        }
+        let len = self.body.len() - start;
+        for headword in headwords {
+            let headword = headword.split_whitespace().collect::<Vec<_>>().join(" ");
+            self.index.push((headword, start, len));
+        }
+    }
+
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+
+    /// The `.index` file, sorted as dictd expects: ignoring case and
+    /// characters other than letters, digits and spaces.
+    pub fn index(&self) -> String {
+        let mut sorted: Vec<_> = self.index.iter().collect();
+        sorted.sort_by_cached_key(|&(headword, _, _)| sort_key(headword));
+        let mut index = String::new();
+        for &(ref headword, start, len) in sorted {
+            index.push_str(&format!("{}\t{}\t{}\n", headword, base64_number(start), base64_number(len)));
+        }
+        index
+    }
+}
+
+fn sort_key(headword: &str) -> String {
+    headword.chars()
+        .filter(|c| c.is_alphanumeric() || *c == ' ')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// A number in the base-64 notation of dictd indexes.
+pub fn base64_number(mut n: usize) -> String {
+    let mut digits = vec![BASE64[n % 64]];
+    while n >= 64 {
+        n /= 64;
+        digits.push(BASE64[n % 64]);
+    }
+    digits.iter().rev().map(|&b| b as char).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::EntryParser;
+
+    #[test]
+    fn sorted_index() {
+        assert_eq!(base64_number(0), "A");
+        assert_eq!(base64_number(64), "BA");
+        assert_eq!(base64_number(4095), "//");
+
+        let text = "<entry main-word=\"Abacus\" source=\"1913 Webster\"><p><hw>Ab\"a*cus</hw>; \
+                    <pl><plw>Abaci</plw></pl>.</p></entry>";
+        let entry = EntryParser::new(text).next().unwrap().unwrap();
+        let mut db = DictDatabase::new("GCIDE", "https://gcide.gnu.org.ua/");
+        db.add_entry(&entry, &ExportOptions::default());
+        let index = db.index();
+        let lines: Vec<_> = index.lines().collect();
+        assert_eq!(lines, ["00-database-short\tR\tb", "00-database-url\ts\tt", "00-database-utf8\tA\tR",
+                           "Abaci\tBZ\tb", "Abacus\tBZ\tb"]);
+        assert_eq!(&db.body()[89..], "Abacus\n   Ab\"a*cus; Abaci.\n");
+    }
+}
@@ -0,0 +1,115 @@
+//! Rendering of entries as `-man` macros, for reading with `man -l` or `nroff -man`.
+
+use std::fmt::{self, Display, Formatter};
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use exporter::{process_symbols_in_text, ExportOptions, HeadwordStyle};
+use greek;
+use parser::{Entry, EntryItem};
+use tags::TagName;
+
+/// A `-man` section for one entry; output starts with `page_header`.
+pub struct Groff<'a>(pub &'a Entry<'a>, pub &'a ExportOptions);
+
+/// The `.TH` line starting a page.
+pub fn page_header(title: &str) -> String {
+    format!(".TH \"{}\" 7 \"\" \"GCIDE\" \"GNU Collaborative International Dictionary of English\"\n",
+            title.replace('"', "").to_uppercase())
+}
+
+impl<'a> Display for Groff<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let Groff(entry, opts) = *self;
+        let mut body = String::new();
+        for item in &entry.items {
+            write_item(&mut body, item, opts, "R");
+        }
+        writeln!(f, ".SH \"{}\"", entry.main_word.replace('"', ""))?;
+        // leading spaces and blank lines are significant to roff
+        for line in body.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Appends `item` to `out`, where `font` is the font of the enclosing text.
+fn write_item(out: &mut String, item: &EntryItem, opts: &ExportOptions, font: &str) {
+    use parser::EntryItem::*;
+    match *item {
+        Comment(_) | PageBreak(_) | UnpairedTagOpen(..) | UnpairedTagClose(_) => (),
+        Entity(entity) => push_text(out, entity.to_unicode()),
+        EntityBr => out.push_str("\n.br\n"),
+        EntityUnk => out.push('\u{fffd}'),
+        ExternalLink(_, text) => push_text(out, text),
+        Greek(ref gitems) => push_text(out, &greek::render(gitems, opts.greek)),
+        PlainText(text) => push_text(out, &process_symbols_in_text(text)),
+        Tagged { name, ref items, .. } => {
+            if let Some(output) = opts.run_tag_handler(name, items) {
+                return out.push_str(&output);
+            }
+            let inner_font = match name {
+                TagName::P | TagName::Extra => {
+                    out.push_str("\n.PP\n");
+                    for item in items {
+                        write_item(out, item, opts, font);
+                    }
+                    return out.push('\n');
+                }
+                TagName::Hw | TagName::Sn | TagName::Col | TagName::B | TagName::Asp | TagName::Adjf
+                    | TagName::Conjf | TagName::Decf | TagName::Plw | TagName::Singw | TagName::Wf => "B",
+                TagName::Pos | TagName::Pluf | TagName::Singf | TagName::Mark | TagName::Fld | TagName::I
+                    | TagName::It | TagName::Ex | TagName::Qex | TagName::Xex | TagName::Spn
+                    | TagName::Class | TagName::Fam | TagName::Gen | TagName::Ord | TagName::Sig
+                    | TagName::Er | TagName::Cref | TagName::Au | TagName::Qau => "I",
+                _ => font,
+            };
+            if inner_font != font {
+                out.push_str("\\f");
+                out.push_str(inner_font);
+            }
+            if name == TagName::Hw && opts.headwords != HeadwordStyle::Raw {
+                push_text(out, &opts.headwords.apply_to_items(items));
+            } else {
+                for item in items {
+                    write_item(out, item, opts, inner_font);
+                }
+            }
+            if inner_font != font {
+                out.push_str("\\f");
+                out.push_str(font);
+            }
+        }
+    }
+}
+
+/// Appends `text` with backslashes escaped, guarding `.` and `'` at line starts
+/// from being read as requests.
+fn push_text(out: &mut String, text: &str) {
+    for (idx, line) in text.replace('\\', "\\e").split('\n').enumerate() {
+        if idx > 0 {
+            out.push('\n');
+        }
+        let at_line_start = out.is_empty() || out.ends_with('\n');
+        if at_line_start && line.trim_start().starts_with(['.', '\'']) {
+            out.push_str("\\&");
+        }
+        out.push_str(line);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::EntryParser;
+
+    #[test]
+    fn man_section() {
+        let text = "<entry main-word=\"Abacus\" source=\"1913 Webster\">\n<p><hw>Ab\"a*cus</hw>, <pos>n.</pos> \
+                    <def>A table\n.tray \\ frame.</def><br/\n<q>A quote.</q></p>\n\n</entry>";
+        let entry = EntryParser::new(text).next().unwrap().unwrap();
+        assert_eq!(Groff(&entry, &ExportOptions::default()).to_string(),
+                   ".SH \"Abacus\"\n.PP\n\\fBAb\"a*cus\\fR, \\fIn.\\fR A table\n\\&.tray \\e frame.\n.br\nA quote.\n");
+    }
+}
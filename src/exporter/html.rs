@@ -0,0 +1,336 @@
+use std::fmt::{self, Display, Formatter};
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use diagnostics::ParseWarning;
+use entities::Entity;
+use exporter::{process_symbols_in_text, CommentPolicy, ExportOptions, HeadwordStyle, UnknownTagPolicy};
+use greek::{self, GreekStyle};
+use model::plain_text;
+use parser::{Entry, EntryItem};
+use tags::TagName;
+
+#[allow(clippy::upper_case_acronyms)]
+pub struct HTML<'a>(pub &'a Entry<'a>, pub &'a ExportOptions);
+
+impl<'a> Display for HTML<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.0.fmt_html(f, self.1, None)
+    }
+}
+
+trait DisplayHTML {
+    fn fmt_html(&self, f: &mut Formatter, opts: &ExportOptions, ctx_tag: Option<TagName>) -> fmt::Result;
+}
+
+impl<'a> DisplayHTML for Entry<'a> {
+    fn fmt_html(&self, f: &mut Formatter, opts: &ExportOptions, ctx_tag: Option<TagName>) -> fmt::Result {
+        write!(f, "<div class=\"{}\"", opts.css_class("entry"))?;
+        if let Some(ref id) = self.id {
+            write!(f, " id=\"{}\"", id)?;
+        }
+        write!(f, " data-word=\"{}\" data-source=\"{}\">", self.main_word, self.source)?;
+        if let CommentPolicy::Callback(ref handler) = opts.comments {
+            visit_comments(&self.items, &mut |text| handler(self.main_word, text));
+        }
+        self.items.fmt_html(f, opts, ctx_tag)?;
+        write!(f, "</div>")
+    }
+}
+
+impl<'a> DisplayHTML for EntryItem<'a> {
+    fn fmt_html(&self, f: &mut Formatter, opts: &ExportOptions, ctx_tag: Option<TagName>) -> fmt::Result {
+        use parser::EntryItem::*;
+        match *self {
+            Comment(text) => match opts.comments {
+                CommentPolicy::Keep => write!(f, "<!--{}-->", comment_text(text)),
+                _ => Ok(()),
+            },
+            PageBreak(page) => match (&opts.page_href, &opts.comments) {
+                (Some(href), _) => write!(f, "<a class=\"{}\" href=\"{}\">p. {}</a>",
+                                          opts.css_class("page"), href(page), page),
+                (None, CommentPolicy::Keep) => write!(f, "<!-- p. {} -->", page),
+                _ => Ok(()),
+            },
+            Entity(entity) => write!(f, "{}", entity_to_html(entity)),
+            EntityBr => writeln!(f, "<br/>"),
+            EntityUnk => write!(f, "&#xfffd;"),
+            ExternalLink(url, text) => {
+                write!(f, "<a class=\"{}\" href=\"{}\">{}</a>", opts.css_class("extern"), url, text)
+            }
+            Greek(ref gitems) => {
+                write!(f, "<em>")?;
+                match opts.greek {
+                    GreekStyle::Unicode => for gi in gitems {
+                        gi.fmt(f)?;
+                    },
+                    style => write!(f, "{}", greek::render(gitems, style).replace("&", "&amp;"))?,
+                }
+                write!(f, "</em>")
+            }
+            PlainText(text) => if let Some(TagName::Pre) = ctx_tag {
+                write!(f, "{}", text.replace("&", "&amp;"))
+            } else {
+                write!(f, "{}", process_symbols_in_text(text).replace("&", "&amp;"))
+            },
+            Tagged { name, ref items, source } => {
+                if let Some(output) = opts.run_tag_handler(name, items) {
+                    return f.write_str(&output);
+                }
+                match name {
+                    TagName::P => {
+                        match source {
+                            Some(source) => write!(f, "<p data-source=\"{}\">", source)?,
+                            None => write!(f, "<p>")?,
+                        }
+                        items.fmt_html(f, opts, Some(name))?;
+                        write!(f, "</p>")
+                    }
+                    TagName::Hw if opts.headwords != HeadwordStyle::Raw => {
+                        let styled = opts.headwords.apply_to_items(items).replace("&", "&amp;").replace("<", "&lt;");
+                        write!(f, "<strong class=\"{}\">{}</strong>", opts.css_class(name.as_str()), styled)
+                    }
+                    TagName::Hw => {
+                        fmt_tag(f, opts, "strong", Some(name.as_str()), items, Some(name))
+                    }
+                    TagName::Ety | TagName::Ets | TagName::Etsep | TagName::Pr | TagName::Def
+                        | TagName::Altname | TagName::Col | TagName::Cd | TagName::Plain | TagName::Fld
+                        | TagName::Mark | TagName::Sd | TagName::Sn | TagName::Au | TagName::Ecol
+                        | TagName::Stype => {
+                        fmt_tag(f, opts, "span", Some(name.as_str()), items, Some(name))
+                    }
+                    TagName::Pos | TagName::Pluf | TagName::Singf => {
+                        fmt_tag(f, opts, "em", None, items, Some(name))
+                    }
+                    TagName::Asp | TagName::Adjf | TagName::Conjf | TagName::Decf | TagName::Plw
+                        | TagName::Singw | TagName::Wf => {
+                        fmt_tag(f, opts, "strong", Some("altf"), items, Some(name))
+                    }
+                    TagName::Er | TagName::Snr | TagName::Sdr | TagName::Cref => {
+                        let href = match opts.link_href {
+                            Some(ref resolve) if name == TagName::Er || name == TagName::Cref => resolve(&plain_text(items)),
+                            _ => "#".to_string(),
+                        };
+                        write!(f, "<a class=\"{}\" href=\"{}\">", opts.css_class(name.as_str()), href)?;
+                        items.fmt_html(f, opts, Some(name))?;
+                        write!(f, "</a>")
+                    }
+                    TagName::As | TagName::Def2 | TagName::Altsp | TagName::Cs | TagName::Mcol
+                        | TagName::Mhw | TagName::Note | TagName::Syn | TagName::Usage | TagName::Mord
+                        | TagName::Rj | TagName::Specif | TagName::Book | TagName::Org | TagName::City
+                        | TagName::Country | TagName::Geog | TagName::Plu | TagName::Sing | TagName::Amorph
+                        | TagName::Nmorph | TagName::Vmorph | TagName::Wordforms => {
+                        items.fmt_html(f, opts, Some(name))
+                    }
+                    TagName::Oneof => { // TODO handle those without <c> tags
+                        for item in items {
+                            if let Tagged { name: TagName::C, items: ref children, .. } = item {
+                                children.fmt_html(f, opts, ctx_tag)?;
+                            } else {
+                                item.fmt_html(f, opts, Some(TagName::Plain))?;
+                            }
+                        }
+                        Ok(())
+                    }
+                    TagName::Q | TagName::Qau => { // TODO use blockquote
+                        items.fmt_html(f, opts, Some(name))
+                    }
+                    TagName::Class | TagName::Fam | TagName::Gen | TagName::Ord | TagName::Spn | TagName::Ex
+                        | TagName::Qex | TagName::Xex | TagName::It | TagName::Sig => {
+                        fmt_tag(f, opts, "em", None, items, Some(name))
+                    }
+                    _ => {
+                        opts.warn(ParseWarning::UnknownTag(name.as_str()));
+                        match opts.unknown_tags {
+                            UnknownTagPolicy::Placeholder => write!(f, "&#xfffd;<!--{}-->", name),
+                            UnknownTagPolicy::Skip => Ok(()),
+                            UnknownTagPolicy::PassThroughAsSpan => {
+                                fmt_tag(f, opts, "span", Some(name.as_str()), items, Some(name))
+                            }
+                            UnknownTagPolicy::Error => Err(fmt::Error),
+                            UnknownTagPolicy::Callback(ref handler) => f.write_str(&handler(name.as_str(), items)),
+                        }
+                    }
+                }
+            }
+            UnpairedTagOpen(_, _) => Ok(()),
+            UnpairedTagClose(_) => Ok(()),
+        }
+    }
+}
+
+impl<'a> DisplayHTML for Vec<EntryItem<'a>> {
+    fn fmt_html(&self, f: &mut Formatter, opts: &ExportOptions, ctx_tag: Option<TagName>) -> fmt::Result {
+        for item in self {
+            item.fmt_html(f, opts, ctx_tag)?;
+        }
+        Ok(())
+    }
+}
+
+/// `text` with a space between dashes and after a final dash, as XML comments
+/// may not contain `--`.
+fn comment_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c == '-' && escaped.ends_with('-') {
+            escaped.push(' ');
+        }
+        escaped.push(c);
+    }
+    if escaped.ends_with('-') {
+        escaped.push(' ');
+    }
+    escaped
+}
+
+fn visit_comments<F: FnMut(&str)>(items: &[EntryItem], handler: &mut F) {
+    for item in items {
+        match *item {
+            EntryItem::Comment(text) => handler(text),
+            EntryItem::Tagged { ref items, .. } => visit_comments(items, handler),
+            _ => (),
+        }
+    }
+}
+
+fn fmt_tag(f: &mut Formatter,
+           opts: &ExportOptions,
+           tagname: &str,
+           class: Option<&str>,
+           items: &Vec<EntryItem>,
+           ctx_tag: Option<TagName>) -> fmt::Result {
+    if let Some(class) = class {
+        write!(f, "<{} class=\"{}\">", tagname, opts.css_class(class))?;
+    } else {
+        write!(f, "<{}>", tagname)?;
+    }
+    items.fmt_html(f, opts, ctx_tag)?;
+    write!(f, "</{}>", tagname)
+}
+
+/// Rules of the default stylesheet, by built-in class name.
+const DEFAULT_STYLES: &[(&str, &str)] = &[
+    ("entry", "margin: 1em 0; line-height: 1.4"),
+    ("hw", "font-size: 110%"),
+    ("altf", "font-weight: normal; font-style: italic"),
+    ("pr", "color: #555"),
+    ("ety", "color: #555"),
+    ("sn", "font-weight: bold"),
+    ("mark", "font-style: italic"),
+    ("col", "font-weight: bold"),
+    ("au", "font-variant: small-caps"),
+    ("fld", "font-style: italic"),
+    ("er", "color: #036; text-decoration: none"),
+    ("extern", "color: #036"),
+    ("page", "font-size: 80%; color: #888"),
+];
+
+/// The default stylesheet, using the class names of `opts`.
+pub fn default_stylesheet(opts: &ExportOptions) -> String {
+    DEFAULT_STYLES.iter()
+        .map(|&(class, rules)| format!(".{} {{ {} }}\n", opts.css_class(class), rules))
+        .collect()
+}
+
+/// Start of an HTML page up to `<body>`, linking the stylesheets at `css_links`
+/// and, if `embed_default` is set, embedding `default_stylesheet`.
+pub fn page_head(title: &str, css_links: &[String], embed_default: bool, opts: &ExportOptions) -> String {
+    let mut head = String::from("<!DOCTYPE html>\n<html>\n<head>\n\
+                                 <meta http-equiv=\"content-type\" content=\"text/html; charset=utf-8\">\n");
+    head.push_str(&format!("<title>{}</title>\n", title));
+    for href in css_links {
+        head.push_str(&format!("<link rel=\"stylesheet\" href=\"{}\">\n", href));
+    }
+    if embed_default {
+        head.push_str(&format!("<style>\n{}</style>\n", default_stylesheet(opts)));
+    }
+    head.push_str("</head>\n<body>\n");
+    head
+}
+
+/// End of an HTML page started with `page_head`.
+pub const PAGE_TAIL: &str = "\n</body>\n</html>";
+
+pub fn entity_to_html(entity: Entity) -> &'static str {
+    match entity {
+        Entity::Lt  => "&lt;",
+        Entity::Gt  => "&gt;",
+        Entity::Ait => "<i>a</i>",
+        Entity::Eit => "<i>e</i>",
+        Entity::Iit => "<i>i</i>",
+        Entity::Oit => "<i>o</i>",
+        Entity::Uit => "<i>u</i>",
+        _           => entity.to_unicode(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use exporter::CIDE;
+    use parser::EntryParser;
+
+    #[test]
+    fn unknown_tag_policy() {
+        use std::fmt::Write;
+        use std::sync::Arc;
+        let block_str = "<entry main-word=\"Q\" source=\"\"><newtag>x</newtag></entry>";
+        let entry = EntryParser::new(block_str).next().unwrap().unwrap();
+        let render = |unknown_tags| {
+            let opts = ExportOptions { unknown_tags, ..ExportOptions::default() };
+            let mut output = String::new();
+            write!(output, "{}", HTML(&entry, &opts)).map(|_| output)
+        };
+        let wrap = |inner: &str| format!("<div class=\"entry\" data-word=\"Q\" data-source=\"\">{}</div>", inner);
+        assert_eq!(render(UnknownTagPolicy::Placeholder).unwrap(), wrap("&#xfffd;<!--newtag-->"));
+        assert_eq!(render(UnknownTagPolicy::Skip).unwrap(), wrap(""));
+        assert_eq!(render(UnknownTagPolicy::PassThroughAsSpan).unwrap(), wrap("<span class=\"newtag\">x</span>"));
+        assert!(render(UnknownTagPolicy::Error).is_err());
+        let callback = UnknownTagPolicy::Callback(Arc::new(|name, items| format!("<b data-tag=\"{}\">{}</b>", name, items.len())));
+        assert_eq!(render(callback).unwrap(), wrap("<b data-tag=\"newtag\">1</b>"));
+    }
+
+    #[test]
+    fn comment_policy() {
+        use std::sync::{Arc, Mutex};
+        let block_str = "<entry main-word=\"Q\" source=\"\"><p>q<-- TODO: check ---->.<def><--cf. R--></def></p></entry>";
+        let entry = EntryParser::new(block_str).next().unwrap().unwrap();
+        let render = |comments| HTML(&entry, &ExportOptions { comments, ..ExportOptions::default() }).to_string();
+        let wrap = |inner: &str| format!("<div class=\"entry\" data-word=\"Q\" data-source=\"\"><p>{}</p></div>", inner);
+        assert_eq!(render(CommentPolicy::Drop), wrap("q.<span class=\"def\"></span>"));
+        assert_eq!(render(CommentPolicy::Keep),
+                   wrap("q<!-- TODO: check - - -->.<span class=\"def\"><!--cf. R--></span>"));
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reported);
+        let callback = CommentPolicy::Callback(Arc::new(move |word, text| sink.lock().unwrap().push(format!("{}:{}", word, text))));
+        assert_eq!(render(callback), wrap("q.<span class=\"def\"></span>"));
+        assert_eq!(*reported.lock().unwrap(), ["Q: TODO: check --", "Q:cf. R"]);
+    }
+
+    #[test]
+    fn page_breaks() {
+        use std::sync::Arc;
+        let block_str = "<entry main-word=\"Q\" source=\"\"><p>q</p><-- p. 1187 --><p>r<-- p.1188 --><-- p. 01 --></p></entry>";
+        let entry = EntryParser::new(block_str).next().unwrap().unwrap();
+        assert_eq!(entry.items[1], EntryItem::PageBreak(1187));
+        assert_eq!(CIDE(&entry).to_string(), block_str);
+        let opts = ExportOptions {
+            page_href: Some(Arc::new(|page| format!("scans/{:04}.png", page))),
+            ..ExportOptions::default()
+        };
+        assert_eq!(HTML(&entry, &opts).to_string(), "<div class=\"entry\" data-word=\"Q\" data-source=\"\"><p>q</p>\
+                                                     <a class=\"page\" href=\"scans/1187.png\">p. 1187</a><p>r</p></div>");
+    }
+
+    #[test]
+    fn custom_classes() {
+        let block_str = "<entry main-word=\"Q\" source=\"\"><hw>Q</hw> <wf>Qs</wf></entry>";
+        let entry = EntryParser::new(block_str).next().unwrap().unwrap();
+        let opts = ExportOptions::default().class_name("entry", "dict-entry").class_name("hw", "headword");
+        assert_eq!(HTML(&entry, &opts).to_string(), "<div class=\"dict-entry\" data-word=\"Q\" data-source=\"\">\
+                                                     <strong class=\"headword\">Q</strong> \
+                                                     <strong class=\"altf\">Qs</strong></div>");
+        assert!(default_stylesheet(&opts).contains(".headword {"));
+    }
+}
@@ -0,0 +1,164 @@
+//! LaTeX rendering of entries, for typesetting a print edition.
+//!
+//! `PREAMBLE` sets up a two-column page with the first and last main words of
+//! each page as guide words. The output is UTF-8 with Greek and pronunciation
+//! symbols as they are, and is meant for XeLaTeX or LuaLaTeX.
+
+use std::fmt::{self, Display, Formatter};
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use diagnostics::ParseWarning;
+use exporter::{process_symbols_in_text, ExportOptions};
+use greek;
+use parser::{Entry, EntryItem};
+use tags::TagName;
+
+pub struct LaTeX<'a>(pub &'a Entry<'a>, pub &'a ExportOptions);
+
+pub const PREAMBLE: &str = r"\documentclass[10pt,twocolumn]{article}
+\usepackage[margin=2cm]{geometry}
+\usepackage{fontspec}
+\setmainfont{FreeSerif}
+\usepackage{fancyhdr}
+\pagestyle{fancy}
+\fancyhf{}
+\fancyhead[L]{\textbf{\rightmark}}
+\fancyhead[R]{\textbf{\leftmark}}
+\fancyfoot[C]{\thepage}
+\setlength{\parindent}{0pt}
+\newcommand{\gcideentry}[1]{\markboth{#1}{#1}\par\hangindent=1em}
+\newcommand{\gcidehw}[1]{\textbf{#1}}
+\newcommand{\gcidepr}[1]{#1}
+\newcommand{\gcidegreek}[1]{\textit{#1}}
+\begin{document}
+";
+
+pub const POSTAMBLE: &str = "\\end{document}\n";
+
+impl<'a> Display for LaTeX<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let LaTeX(entry, opts) = *self;
+        let body = LaTeXBody(&entry.items, opts).to_string();
+        write!(f, "\\gcideentry{{{}}}{}\n\n", escape(entry.main_word), body.trim_end())
+    }
+}
+
+struct LaTeXBody<'e, 'a: 'e>(&'e Vec<EntryItem<'a>>, &'e ExportOptions);
+
+impl<'e, 'a> Display for LaTeXBody<'e, 'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.0.fmt_latex(f, self.1, None)
+    }
+}
+
+trait DisplayLaTeX {
+    fn fmt_latex(&self, f: &mut Formatter, opts: &ExportOptions, ctx_tag: Option<TagName>) -> fmt::Result;
+}
+
+impl<'a> DisplayLaTeX for EntryItem<'a> {
+    fn fmt_latex(&self, f: &mut Formatter, opts: &ExportOptions, ctx_tag: Option<TagName>) -> fmt::Result {
+        use parser::EntryItem::*;
+        match *self {
+            Comment(_) | PageBreak(_) | UnpairedTagOpen(..) | UnpairedTagClose(_) => Ok(()),
+            Entity(entity) => f.write_str(&escape(entity.to_unicode())),
+            EntityBr => f.write_str("\\newline\n"),
+            EntityUnk => f.write_str("\u{fffd}"),
+            ExternalLink(_, text) => f.write_str(&escape(text)),
+            Greek(ref gitems) => write!(f, "\\gcidegreek{{{}}}", escape(&greek::render(gitems, opts.greek))),
+            PlainText(text) => match ctx_tag {
+                Some(TagName::Hw) | Some(TagName::Pr) => f.write_str(&mark_syllables(&escape(text))),
+                _ => f.write_str(&escape(&process_symbols_in_text(text))),
+            },
+            Tagged { name, ref items, .. } => {
+                if let Some(output) = opts.run_tag_handler(name, items) {
+                    return f.write_str(&output);
+                }
+                let command = match name {
+                    TagName::Hw => {
+                        f.write_str("\\gcidehw{")?;
+                        items.fmt_latex(f, opts, Some(name))?;
+                        return f.write_str("}");
+                    }
+                    TagName::Pr => "gcidepr",
+                    TagName::Sn | TagName::Col | TagName::B | TagName::Asp | TagName::Adjf | TagName::Conjf
+                        | TagName::Decf | TagName::Plw | TagName::Singw | TagName::Wf => "textbf",
+                    TagName::Pos | TagName::Pluf | TagName::Singf | TagName::Mark | TagName::Fld
+                        | TagName::I | TagName::It | TagName::Ex | TagName::Qex | TagName::Xex
+                        | TagName::Spn | TagName::Class | TagName::Fam | TagName::Gen | TagName::Ord
+                        | TagName::Sig | TagName::Er | TagName::Cref => "textit",
+                    TagName::Au | TagName::Qau => "textsc",
+                    TagName::Sub => "textsubscript",
+                    TagName::Sup => "textsuperscript",
+                    TagName::P | TagName::Extra | TagName::Ety | TagName::Ets | TagName::Etsep
+                        | TagName::Def | TagName::Altname | TagName::Cd | TagName::Plain | TagName::Sd
+                        | TagName::Ecol | TagName::Stype | TagName::As | TagName::Def2 | TagName::Altsp
+                        | TagName::Cs | TagName::Mcol | TagName::Mhw | TagName::Note | TagName::Syn
+                        | TagName::Usage | TagName::Mord | TagName::Rj | TagName::Specif | TagName::Book
+                        | TagName::Org | TagName::City | TagName::Country | TagName::Geog | TagName::Plu
+                        | TagName::Sing | TagName::Amorph | TagName::Nmorph | TagName::Vmorph
+                        | TagName::Wordforms | TagName::Oneof | TagName::C | TagName::Q | TagName::Snr
+                        | TagName::Sdr => {
+                        return items.fmt_latex(f, opts, Some(name));
+                    }
+                    _ => {
+                        opts.warn(ParseWarning::UnknownTag(name.as_str()));
+                        return items.fmt_latex(f, opts, Some(name));
+                    }
+                };
+                write!(f, "\\{}{{", command)?;
+                items.fmt_latex(f, opts, Some(name))?;
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+impl<'a> DisplayLaTeX for Vec<EntryItem<'a>> {
+    fn fmt_latex(&self, f: &mut Formatter, opts: &ExportOptions, ctx_tag: Option<TagName>) -> fmt::Result {
+        for item in self {
+            item.fmt_latex(f, opts, ctx_tag)?;
+        }
+        Ok(())
+    }
+}
+
+/// Turns the syllable (`*`) and accent (`"`, `` ` ``) marks of headwords into
+/// the symbols used in print.
+pub fn mark_syllables(text: &str) -> String {
+    text.replace('*', "\u{b7}").replace('"', "\u{2032}").replace('`', "\u{2033}")
+}
+
+/// Escapes LaTeX special characters.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::EntryParser;
+
+    #[test]
+    fn latex_entry() {
+        let text = "<entry main-word=\"Abacus\" source=\"1913 Webster\">\n<p><hw>Ab\"a*cus</hw>, <pos>n.</pos> \
+                    <ety>[L. <ets>abacus</ets>, Gr. <grk>'a`bax</grk>.]</ety> <def>A table & tray, 50%.</def></p>\n</entry>";
+        let entry = EntryParser::new(text).next().unwrap().unwrap();
+        assert_eq!(LaTeX(&entry, &ExportOptions::default()).to_string(),
+                   "\\gcideentry{Abacus}\n\\gcidehw{Ab\u{2032}a\u{b7}cus}, \\textit{n.} [L. abacus, Gr. \
+                    \\gcidegreek{\u{1f04}\u{3b2}\u{3b1}\u{3be}}.] A table \\& tray, 50\\%.\n\n");
+    }
+}
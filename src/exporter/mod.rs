@@ -0,0 +1,841 @@
+#[cfg(not(feature = "std"))]
+use std::collections::BTreeMap as HashMap;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+#[cfg(feature = "std")]
+use std::panic::{self, AssertUnwindSafe};
+use std::str::FromStr;
+use std::sync::Arc;
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use diagnostics::{ParseWarning, WarningHandler};
+use entities::Entity;
+use greek::{self, GreekStyle};
+use headword;
+use parser::{Entry, EntryItem, GreekItem, ALLOWED_TO_DANGLE};
+use tags::TagName;
+
+pub use greek::grktrans_to_unicode;
+
+pub mod apple;
+#[cfg(feature = "fulltext")]
+pub mod concordance;
+pub mod dictd;
+pub mod groff;
+pub mod html;
+pub mod latex;
+pub mod ontolex;
+pub mod site;
+#[cfg(feature = "std")]
+pub mod term;
+#[cfg(feature = "templates")]
+pub mod template;
+pub mod thesaurus;
+pub mod wiki;
+
+pub struct CIDE<'a>(pub &'a Entry<'a>);
+
+/// CIDE rendering of an entry with layout adjustments; see `CIDE::with_options`.
+pub struct CideLayout<'a>(&'a Entry<'a>, &'a CideOptions);
+
+/// Layout of CIDE output. The default reproduces the entry as stored.
+#[derive(Clone, Debug)]
+pub struct CideOptions {
+    /// Re-wrap lines to this many columns; `None` keeps the stored line breaks.
+    pub wrap_width: Option<usize>,
+    /// Break the line after every `<br/`, as the dictionary files do.
+    pub newline_after_br: bool,
+    /// Put the entry head and `</entry>` on lines of their own, separate
+    /// top-level blocks by a blank line and drop other whitespace between them.
+    pub canonical_spacing: bool,
+    pub headwords: HeadwordStyle,
+}
+
+impl Default for CideOptions {
+    fn default() -> CideOptions {
+        CideOptions { wrap_width: None, newline_after_br: true, canonical_spacing: false, headwords: HeadwordStyle::Raw }
+    }
+}
+
+impl CideOptions {
+    /// The layout conventionally used in the GCIDE files.
+    pub fn canonical() -> CideOptions {
+        CideOptions { wrap_width: Some(78), newline_after_br: true, canonical_spacing: true, ..CideOptions::default() }
+    }
+}
+
+/// How the syllable (`*`) and stress (`"`, `` ` ``) marks of headwords are rendered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HeadwordStyle {
+    /// As stored: `Dic"tion*a*ry`.
+    #[default]
+    Raw,
+    /// Without the marks: `Dictionary`.
+    Clean,
+    /// With middle dots between syllables: `Dic\u{b7}tion\u{b7}a\u{b7}ry`.
+    Dotted,
+}
+
+impl FromStr for HeadwordStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<HeadwordStyle, String> {
+        match s {
+            "raw" => Ok(HeadwordStyle::Raw),
+            "clean" => Ok(HeadwordStyle::Clean),
+            "dotted" => Ok(HeadwordStyle::Dotted),
+            _ => Err(format!("unknown headword style: {}", s)),
+        }
+    }
+}
+
+impl HeadwordStyle {
+    /// Renders a headword in CIDE markup, such as the contents of `<hw>`:
+    /// entities as Unicode, tags dropped and the marks restyled. Syllables
+    /// already separated by a space or hyphen get no dot in the dotted style.
+    pub fn apply(self, word: &str) -> String {
+        match self {
+            HeadwordStyle::Raw => headword::markup_to_text(word),
+            style => style.join(&headword::syllables(word)),
+        }
+    }
+
+    /// Like `apply`, but keeps entities, tags and the spaces around the word,
+    /// and writes the dots as the `<middot/` entity.
+    fn apply_to_markup(self, word: &str) -> String {
+        if self == HeadwordStyle::Raw {
+            return word.to_string();
+        }
+        let trimmed = word.trim();
+        let start = word.len() - word.trim_start().len();
+        let separator = if self == HeadwordStyle::Dotted { "<middot/" } else { "" };
+        let styled = headword::join_syllables(&headword::markup_syllables(trimmed), separator);
+        format!("{}{}{}", &word[..start], styled, &word[start + trimmed.len()..])
+    }
+
+    fn join(self, syllables: &[headword::Syllable]) -> String {
+        headword::join_syllables(syllables, if self == HeadwordStyle::Dotted { "\u{b7}" } else { "" })
+    }
+
+    /// The styled text of the items of a `<hw>`.
+    fn apply_to_items(self, items: &[EntryItem]) -> String {
+        let markup: String = items.iter().map(|item| CideItem(item).to_string()).collect();
+        process_symbols_in_text(&self.apply(&markup))
+    }
+}
+
+/// Plain-text rendering of an entry.
+pub struct Plain<'a>(pub &'a Entry<'a>, pub &'a ExportOptions);
+
+/// Rendering options shared by the human-readable exporters.
+#[derive(Clone, Default)]
+pub struct ExportOptions {
+    pub greek: GreekStyle,
+    /// Receives issues found while rendering; they are dropped if unset.
+    pub on_warning: Option<WarningHandler>,
+    /// What the HTML exporter does with tags it does not know.
+    pub unknown_tags: UnknownTagPolicy,
+    /// What the HTML exporter does with `<-- comments -->`.
+    pub comments: CommentPolicy,
+    /// Custom renderers by tag name; see `tag_handler`.
+    pub tag_handlers: HashMap<String, TagHandler>,
+    /// CSS classes to use in HTML output instead of the built-in ones (which
+    /// are mostly the GCIDE tag names, plus `entry`, `altf` and `extern`).
+    pub class_names: HashMap<String, String>,
+    /// Target of the HTML links made for `<er>` and `<cref>`, given the
+    /// referenced word; links point nowhere (`#`) if unset.
+    pub link_href: Option<LinkResolver>,
+    /// Target of the HTML links made for page breaks, given the page number of
+    /// the printed 1913 edition, e.g. a scanned page image; unset, page breaks
+    /// are treated as comments.
+    pub page_href: Option<PageResolver>,
+    /// Rendering of the marks in `<hw>` tags.
+    pub headwords: HeadwordStyle,
+}
+
+/// Renders the contents of a tag into the output buffer.
+pub type TagHandler = Arc<dyn Fn(&[EntryItem], &mut String) + Send + Sync>;
+
+/// Maps a cross-referenced word to a URL.
+pub type LinkResolver = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// Maps a page number of the 1913 edition to a URL.
+pub type PageResolver = Arc<dyn Fn(u32) -> String + Send + Sync>;
+
+/// Renders a tag (given its name and contents) as HTML.
+pub type UnknownTagHandler = Arc<dyn Fn(&str, &[EntryItem]) -> String + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub enum UnknownTagPolicy {
+    /// Write U+FFFD followed by the tag name in a comment, dropping the contents.
+    #[default]
+    Placeholder,
+    /// Drop the tag and its contents.
+    Skip,
+    /// Render the contents in a `<span>` classed with the tag name.
+    PassThroughAsSpan,
+    /// Fail with `fmt::Error`; the caller should `write!` the output and check the result.
+    Error,
+    Callback(UnknownTagHandler),
+}
+
+impl FromStr for UnknownTagPolicy {
+    type Err = String;
+
+    /// Parses the policies that need no callback: `placeholder`, `skip`, `span` or `error`.
+    fn from_str(s: &str) -> Result<UnknownTagPolicy, String> {
+        match s {
+            "placeholder" => Ok(UnknownTagPolicy::Placeholder),
+            "skip" => Ok(UnknownTagPolicy::Skip),
+            "span" => Ok(UnknownTagPolicy::PassThroughAsSpan),
+            "error" => Ok(UnknownTagPolicy::Error),
+            _ => Err(format!("unknown tag policy: {}", s)),
+        }
+    }
+}
+
+/// Receives the main word of an entry and the text of a comment in it.
+pub type CommentHandler = Arc<dyn Fn(&str, &str) + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub enum CommentPolicy {
+    #[default]
+    Drop,
+    /// Write them as HTML comments.
+    Keep,
+    /// Pass them to the handler, in document order, and drop them.
+    Callback(CommentHandler),
+}
+
+impl FromStr for CommentPolicy {
+    type Err = String;
+
+    /// Parses the policies that need no callback: `drop` or `keep`.
+    fn from_str(s: &str) -> Result<CommentPolicy, String> {
+        match s {
+            "drop" => Ok(CommentPolicy::Drop),
+            "keep" => Ok(CommentPolicy::Keep),
+            _ => Err(format!("unknown comment policy: {}", s)),
+        }
+    }
+}
+
+impl fmt::Debug for CommentPolicy {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            CommentPolicy::Drop => f.write_str("Drop"),
+            CommentPolicy::Keep => f.write_str("Keep"),
+            CommentPolicy::Callback(_) => f.write_str("Callback(..)"),
+        }
+    }
+}
+
+impl fmt::Debug for UnknownTagPolicy {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            UnknownTagPolicy::Placeholder => f.write_str("Placeholder"),
+            UnknownTagPolicy::Skip => f.write_str("Skip"),
+            UnknownTagPolicy::PassThroughAsSpan => f.write_str("PassThroughAsSpan"),
+            UnknownTagPolicy::Error => f.write_str("Error"),
+            UnknownTagPolicy::Callback(_) => f.write_str("Callback(..)"),
+        }
+    }
+}
+
+impl ExportOptions {
+    pub fn warn(&self, warning: ParseWarning) {
+        if let Some(ref handler) = self.on_warning {
+            handler(&warning);
+        }
+    }
+
+    /// Renders every `name` tag with `handler` instead of the exporter's own
+    /// rendering. The handler writes into a buffer of its own, which is only
+    /// used if the handler returns normally; if it panics, the tag is rendered
+    /// as usual and `ParseWarning::HandlerPanicked` is reported. The panic hook
+    /// still runs, so the default one prints the panic to stderr (without the
+    /// `std` feature, the panic is not caught).
+    pub fn tag_handler<F>(mut self, name: &str, handler: F) -> ExportOptions
+    where F: Fn(&[EntryItem], &mut String) + Send + Sync + 'static {
+        self.tag_handlers.insert(name.to_string(), Arc::new(handler));
+        self
+    }
+
+    /// Uses `class` for HTML elements that would be classed `name`.
+    pub fn class_name(mut self, name: &str, class: &str) -> ExportOptions {
+        self.class_names.insert(name.to_string(), class.to_string());
+        self
+    }
+
+    /// The CSS class actually used for the built-in class `name`.
+    pub fn css_class<'s>(&'s self, name: &'s str) -> &'s str {
+        self.class_names.get(name).map_or(name, String::as_str)
+    }
+
+    /// Output of the handler registered for `name`, if there is one and it succeeds.
+    fn run_tag_handler(&self, name: TagName, items: &[EntryItem]) -> Option<String> {
+        let handler = self.tag_handlers.get(name.as_str())?;
+        let mut output = String::new();
+        #[cfg(feature = "std")]
+        {
+            if panic::catch_unwind(AssertUnwindSafe(|| handler(items, &mut output))).is_err() {
+                self.warn(ParseWarning::HandlerPanicked(name.as_str()));
+                return None;
+            }
+        }
+        #[cfg(not(feature = "std"))]
+        handler(items, &mut output);
+        Some(output)
+    }
+}
+
+impl fmt::Debug for ExportOptions {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("ExportOptions")
+            .field("greek", &self.greek)
+            .field("on_warning", &self.on_warning.as_ref().map(|_| ".."))
+            .field("unknown_tags", &self.unknown_tags)
+            .field("comments", &self.comments)
+            .field("tag_handlers", &self.tag_handlers.keys().collect::<Vec<_>>())
+            .field("class_names", &self.class_names)
+            .field("link_href", &self.link_href.as_ref().map(|_| ".."))
+            .field("page_href", &self.page_href.as_ref().map(|_| ".."))
+            .field("headwords", &self.headwords)
+            .finish()
+    }
+}
+
+trait DisplayCIDE {
+    fn fmt_cide(&self, f: &mut Formatter) -> fmt::Result;
+}
+
+impl<'a> Display for CIDE<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.0.fmt_cide(f)
+    }
+}
+
+impl<'a> CIDE<'a> {
+    pub fn with_options(entry: &'a Entry<'a>, opts: &'a CideOptions) -> CideLayout<'a> {
+        CideLayout(entry, opts)
+    }
+}
+
+impl<'a> Display for CideLayout<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let CideLayout(entry, opts) = *self;
+        let mut text = if opts.canonical_spacing {
+            let mut blocks = vec![format!("<entry main-word=\"{}\" source=\"{}\">", entry.main_word, entry.source)];
+            let mut last_block = String::new();
+            for item in &entry.items {
+                match *item {
+                    EntryItem::PlainText(text) if text.trim().is_empty() => (),
+                    EntryItem::Tagged { .. } | EntryItem::UnpairedTagOpen(..) if !last_block.is_empty() => {
+                        blocks.push(last_block);
+                        last_block = CideItem(item).to_string();
+                    }
+                    _ => last_block.push_str(&CideItem(item).to_string()),
+                }
+            }
+            if !last_block.is_empty() {
+                blocks.push(last_block);
+            }
+            let head = blocks.remove(0);
+            format!("{}\n{}\n</entry>", head, blocks.join("\n\n"))
+        } else {
+            CIDE(entry).to_string()
+        };
+        if !opts.newline_after_br {
+            text = text.replace("<br/\n", "<br/");
+        }
+        if opts.headwords != HeadwordStyle::Raw {
+            text = restyle_headwords(&text, opts.headwords);
+        }
+        if let Some(width) = opts.wrap_width {
+            text = reflow_cide(&text, width);
+        }
+        f.write_str(&text)
+    }
+}
+
+/// Applies `style` to the contents of the `<hw>` tags in CIDE source.
+fn restyle_headwords(text: &str, style: HeadwordStyle) -> String {
+    let mut restyled = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("<hw>") {
+        let end = rest[start..].find("</hw>").map_or(rest.len(), |idx| start + idx);
+        restyled.push_str(&rest[..start + 4]);
+        restyled.push_str(&style.apply_to_markup(&rest[start + 4..end]));
+        rest = &rest[end..];
+    }
+    restyled.push_str(rest);
+    restyled
+}
+
+struct CideItem<'e, 'a: 'e>(&'e EntryItem<'a>);
+
+impl<'e, 'a> Display for CideItem<'e, 'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.0.fmt_cide(f)
+    }
+}
+
+impl<'a> DisplayCIDE for Entry<'a> {
+    fn fmt_cide(&self, f: &mut Formatter) -> fmt::Result {
+        if let Some(raw) = self.raw {
+            return f.write_str(raw);
+        }
+        write!(f, "<entry main-word=\"{}\" source=\"{}\">", self.main_word, self.source)?;
+        for item in &self.items {
+            item.fmt_cide(f)?;
+        }
+        write!(f, "</entry>")
+    }
+}
+
+impl<'a> DisplayCIDE for EntryItem<'a> {
+    fn fmt_cide(&self, f: &mut Formatter) -> fmt::Result {
+        use parser::EntryItem::*;
+        match *self {
+            Comment(text) => write!(f, "<--{}-->", text),
+            PageBreak(page) => write!(f, "<-- p. {} -->", page),
+            Entity(name) => write!(f, "<{}/", name),
+            EntityBr => writeln!(f, "<br/"),
+            EntityUnk => write!(f, "<?/"),
+            ExternalLink(url, text) => write!(f, "<a href=\"{}\">{}</a>", url, text),
+            Greek(ref gitems) => {
+                write!(f, "<grk>")?;
+                for gi in gitems {
+                    gi.fmt_cide(f)?;
+                }
+                write!(f, "</grk>")
+            }
+            PlainText(text) => write!(f, "{}", text),
+            Tagged { name, ref items, source } => {
+                write_tag_open(f, name, source)?;
+                for item in items {
+                    item.fmt_cide(f)?;
+                }
+                write!(f, "</{}>", name)
+            }
+            UnpairedTagOpen(name, source) => {
+                if !ALLOWED_TO_DANGLE.contains(&name) {
+                    write!(f, "[ERROR->]")?;
+                }
+                write_tag_open(f, name, source)
+            }
+            UnpairedTagClose(name) => {
+                if !ALLOWED_TO_DANGLE.contains(&name) {
+                    write!(f, "[ERROR->]</{}>", name)
+                } else {
+                    write!(f, "</{}>", name)
+                }
+            }
+        }
+    }
+}
+
+impl DisplayCIDE for GreekItem {
+    fn fmt_cide(&self, f: &mut Formatter) -> fmt::Result {
+        self.write_ascii(f)
+    }
+}
+
+fn write_tag_open(f: &mut Formatter, name: TagName, source: Option<&str>) -> fmt::Result {
+    match source {
+        Some(source) => {
+            if name == TagName::P || name == TagName::Extra {
+                write!(f, "<{} source=\"{}\">", name, source)
+            } else {
+                write!(f, "<{} [ERROR->]source=\"{}\">", name, source)
+            }
+        }
+        None => write!(f, "<{}>", name),
+    }
+}
+
+/// Re-wraps CIDE source text to `width` columns without breaking tags apart.
+/// Lines ending in `<br/`, paragraph boundaries, and blank lines are kept.
+pub fn reflow_cide(text: &str, width: usize) -> String {
+    let mut output = String::with_capacity(text.len() + text.len()/width.max(1));
+    let mut block: Vec<&str> = Vec::new();
+    let mut lines = text.split('\n').peekable();
+    while let Some(line) = lines.next() {
+        block.push(line);
+        let next_line = lines.peek().map(|l| l.trim_start());
+        let hard_break = match next_line {
+            None => true,
+            Some(next) => {
+                let line = line.trim_end();
+                line.is_empty() || next.is_empty() || line.ends_with("<br/") || line.ends_with("</p>")
+                    || line.starts_with("<entry ") || next.starts_with("<p>") || next.starts_with("<p ")
+                    || next.starts_with("</entry>")
+            }
+        };
+        if hard_break {
+            wrap_block(&mut output, &block, width);
+            block.clear();
+            if next_line.is_some() {
+                output.push('\n');
+            }
+        }
+    }
+    output
+}
+
+fn wrap_block(output: &mut String, lines: &[&str], width: usize) {
+    let mut col = 0;
+    for line in lines {
+        for word in split_cide_words(line) {
+            let word_len = word.chars().count();
+            if col > 0 && col + 1 + word_len > width {
+                output.push('\n');
+                col = 0;
+            } else if col > 0 {
+                output.push(' ');
+                col += 1;
+            }
+            output.push_str(word);
+            col += word_len;
+        }
+    }
+}
+
+fn split_cide_words(line: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut word_start = None;
+    let mut in_tag = false;
+    for (idx, c) in line.char_indices() {
+        if c.is_whitespace() && !in_tag {
+            if let Some(start) = word_start.take() {
+                words.push(&line[start..idx]);
+            }
+            continue;
+        }
+        if word_start.is_none() {
+            word_start = Some(idx);
+        }
+        if c == '<' && !in_tag {
+            // only tags with attributes (`<name attr="...">`) contain whitespace
+            let rest = &line[idx + 1..];
+            let name_len = rest.find(|c: char| !c.is_alphanumeric()).unwrap_or(rest.len());
+            in_tag = name_len > 0 && rest[name_len..].starts_with(' ');
+        } else if c == '>' {
+            in_tag = false;
+        }
+    }
+    if let Some(start) = word_start {
+        words.push(&line[start..]);
+    }
+    words
+}
+
+impl<'a> Display for Plain<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for item in &self.0.items {
+            item.fmt_plain(f, self.1)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Display for EntryItem<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.fmt_plain(f, &ExportOptions::default())
+    }
+}
+
+trait DisplayPlain {
+    fn fmt_plain(&self, f: &mut Formatter, opts: &ExportOptions) -> fmt::Result;
+}
+
+impl<'a> DisplayPlain for EntryItem<'a> {
+    fn fmt_plain(&self, f: &mut Formatter, opts: &ExportOptions) -> fmt::Result {
+        use parser::EntryItem::*;
+        use std::fmt::Write;
+        match *self {
+            Entity(entity) => f.write_str(entity.to_unicode()),
+            EntityBr => f.write_char('\n'),
+            EntityUnk => f.write_char('\u{fffd}'),
+            ExternalLink(_, text) => f.write_str(text),
+            Greek(ref gitems) => match opts.greek {
+                GreekStyle::Unicode => {
+                    for gi in gitems {
+                        gi.fmt(f)?;
+                    }
+                    Ok(())
+                }
+                style => f.write_str(&greek::render(gitems, style)),
+            },
+            PlainText(text) => f.write_str(&process_symbols_in_text(text)),
+            Tagged { name, ref items, .. } => {
+                if let Some(output) = opts.run_tag_handler(name, items) {
+                    return f.write_str(&output);
+                }
+                if name == TagName::Hw && opts.headwords != HeadwordStyle::Raw {
+                    return f.write_str(&opts.headwords.apply_to_items(items));
+                }
+                for item in items {
+                    item.fmt_plain(f, opts)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+pub fn process_symbols_in_text(text: &str) -> String {
+    text.replace("'", "\u{2019}")
+        .replace("----", "\u{23af}\u{23af}\u{23af}")
+        .replace("--", Entity::Mdash.to_unicode())
+}
+
+pub fn is_known_entity(entity: &str) -> bool {
+    Entity::known(entity).is_some()
+}
+
+pub fn entity_to_unicode(entity: &str) -> &'static str {
+    Entity::new(entity).to_unicode()
+}
+
+/// The entity to write instead of a literal non-ASCII character, if there is one.
+pub fn unicode_to_entity(c: char) -> Option<&'static str> {
+    let entity = match c {
+        '\u{00e6}' => "ae",
+        '\u{00c6}' => "AE",
+        '\u{0153}' => "oe",
+        '\u{0152}' => "OE",
+        '\u{00e7}' => "cced",
+        '\u{00e5}' => "aring",
+        '\u{016f}' => "uring",
+        '\u{00e1}' => "aacute",
+        '\u{00e9}' => "eacute",
+        '\u{00ed}' => "iacute",
+        '\u{00f3}' => "oacute",
+        '\u{00fa}' => "uacute",
+        '\u{00c9}' => "Eacute",
+        '\u{00e2}' => "acir",
+        '\u{00ea}' => "ecir",
+        '\u{00ee}' => "icir",
+        '\u{00f4}' => "ocir",
+        '\u{00fb}' => "ucir",
+        '\u{00e0}' => "agrave",
+        '\u{00e8}' => "egrave",
+        '\u{00ec}' => "igrave",
+        '\u{00f2}' => "ograve",
+        '\u{00f9}' => "ugrave",
+        '\u{00e4}' => "aum",
+        '\u{00eb}' => "eum",
+        '\u{00ef}' => "ium",
+        '\u{00f6}' => "oum",
+        '\u{00fc}' => "uum",
+        '\u{00e3}' => "atil",
+        '\u{1ebd}' => "etil",
+        '\u{00f1}' => "ntil",
+        '\u{0101}' => "amac",
+        '\u{0113}' => "emac",
+        '\u{012b}' => "imac",
+        '\u{014d}' => "omac",
+        '\u{016b}' => "umac",
+        '\u{0233}' => "ymac",
+        '\u{01e3}' => "aemac",
+        '\u{0103}' => "acr",
+        '\u{0115}' => "ecr",
+        '\u{012d}' => "icr",
+        '\u{014f}' => "ocr",
+        '\u{016d}' => "ucr",
+        '\u{01d2}' => "ocar",
+        '\u{0227}' => "adot",
+        '\u{1e45}' => "ndot",
+        '\u{1e0d}' => "dsdot",
+        '\u{1e47}' => "nsdot",
+        '\u{1e5b}' => "rsdot",
+        '\u{1e6d}' => "tsdot",
+        '\u{1ee5}' => "usdot",
+        '\u{1e73}' => "udd",
+        '\u{1e49}' => "nsm",
+        '\u{261e}' => "hand",
+        '\u{00b0}' => "deg",
+        '\u{2032}' => "prime",
+        '\u{2033}' => "dprime",
+        '\u{201c}' => "ldquo",
+        '\u{201d}' => "rdquo",
+        '\u{2018}' => "lsquo",
+        '\u{2019}' => "rsquo",
+        '\u{00a7}' => "sect",
+        '\u{266f}' => "sharp",
+        '\u{266d}' => "flat",
+        '\u{00a3}' => "pound",
+        '\u{2212}' => "minus",
+        '\u{2014}' => "mdash",
+        '\u{2016}' => "par",
+        '\u{2323}' => "cre",
+        '\u{00f0}' => "edh",
+        '\u{00fe}' => "thorn",
+        '\u{021d}' => "yogh",
+        '\u{00f7}' => "divide",
+        '\u{00d7}' => "times",
+        '\u{2192}' => "rarr",
+        '\u{00b7}' => "middot",
+        '\u{221a}' => "root",
+        '\u{221b}' => "cuberoot",
+        '\u{03b1}' => "alpha",
+        '\u{03b2}' => "beta",
+        '\u{03b3}' => "gamma",
+        '\u{0393}' => "GAMMA",
+        '\u{03b4}' => "delta",
+        '\u{0394}' => "DELTA",
+        '\u{03b5}' => "epsilon",
+        '\u{03b6}' => "zeta",
+        '\u{03b7}' => "eta",
+        '\u{03b8}' => "theta",
+        '\u{0398}' => "THETA",
+        '\u{03b9}' => "iota",
+        '\u{03ba}' => "kappa",
+        '\u{03bb}' => "lambda",
+        '\u{039b}' => "LAMBDA",
+        '\u{03bc}' => "mu",
+        '\u{03bd}' => "nu",
+        '\u{03be}' => "xi",
+        '\u{039e}' => "XI",
+        '\u{03bf}' => "omicron",
+        '\u{03c0}' => "pi",
+        '\u{03a0}' => "PI",
+        '\u{03c1}' => "rho",
+        '\u{03c3}' => "sigma",
+        '\u{03c2}' => "sigmat",
+        '\u{03a3}' => "SIGMA",
+        '\u{03c4}' => "tau",
+        '\u{03c5}' => "upsilon",
+        '\u{03c6}' => "phi",
+        '\u{03a6}' => "PHI",
+        '\u{03c7}' => "chi",
+        '\u{03c8}' => "psi",
+        '\u{03a8}' => "PSI",
+        '\u{03c9}' => "omega",
+        '\u{03a9}' => "OMEGA",
+        '\u{00b4}' => "acute",
+        '\u{2042}' => "asterism",
+        '\u{02c6}' => "cflex",
+        '\u{02dc}' => "srtil",
+        '\u{02c8}' => "bacc",
+        '\u{02cc}' => "lacc",
+        '\u{1d57}' => "tsup",
+        '\u{1d49}' => "esup",
+        '\u{1d62}' => "isub",
+        _ => return None,
+    };
+    Some(entity)
+}
+
+#[cfg(test)]
+mod test {
+    use CIDE; use EntryParser;
+
+    fn identity(input: &str) -> String {
+        use std::fmt::Write;
+        let mut entry_iter = EntryParser::new(input);
+        let entry_res = entry_iter.next().expect("no block found!");
+        assert!(entry_iter.remaining().is_empty());
+        let entry = entry_res.expect("bad entry");
+        let mut output = String::new();
+        write!(output, "{}", CIDE(&entry)).unwrap();
+        output
+    }
+
+    #[test]
+    fn simple() {
+        let block_str = "<entry main-word=\"Q\" source=\"1913 Webster\">\n<p><hw>Q</hw> <pr>(k<umac/)</pr>, <def>the seventeenth letter of the English alphabet.</def></p>\n</entry>";
+        let expected = block_str;
+        assert_eq!(expected, identity(block_str));
+    }
+
+    #[test]
+    fn unpaired() {
+        let block_str = "<entry main-word=\"Q\" source=\"\">\n<p><hw>Q</hw> <def>here are two <i>unpaired tags</b>.</def></p>\n</entry>";
+        let expected = "<entry main-word=\"Q\" source=\"\">\n<p><hw>Q</hw> <def>here are two [ERROR->]<i>unpaired tags[ERROR->]</b>.</def></p>\n</entry>";
+        assert_eq!(expected, identity(block_str));
+    }
+
+    #[test]
+    fn odd_greek() {
+        let block_str = "<entry main-word=\"Q\" source=\"\">\n<p><ety>Gr. <grk>*lo`gos, 2</grk></ety></p>\n</entry>";
+        assert_eq!(block_str, identity(block_str));
+    }
+
+    #[test]
+    fn reflow() {
+        use exporter::reflow_cide;
+        let block_str = "<entry main-word=\"Q\" source=\"1913 Webster\">\n<p source=\"1913 Webster\"><hw>Q</hw> <def>the seventeenth letter</def><br/\n<def>of the English alphabet.</def></p>\n</entry>";
+        let expected = "<entry main-word=\"Q\" source=\"1913 Webster\">\n<p source=\"1913 Webster\"><hw>Q</hw>\n<def>the seventeenth\nletter</def><br/\n<def>of the English\nalphabet.</def></p>\n</entry>";
+        assert_eq!(expected, reflow_cide(block_str, 20));
+        assert_eq!(expected, identity(expected));
+    }
+
+    #[test]
+    fn tag_handlers() {
+        use exporter::{ExportOptions, Plain};
+        use std::sync::{Arc, Mutex};
+        let block_str = "<entry main-word=\"Q\" source=\"\"><p><def>A fish (<spn>Esox lucius</spn>).</def> \
+                         <mark>[Obs.]</mark></p></entry>";
+        let entry = EntryParser::new(block_str).next().unwrap().unwrap();
+        let opts = ExportOptions::default()
+            .tag_handler("spn", |items, out| {
+                out.push('_');
+                for item in items {
+                    out.push_str(&item.to_string());
+                }
+                out.push('_');
+            })
+            .tag_handler("mark", |_, _| panic!("broken handler"));
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reported);
+        let opts = ExportOptions {
+            on_warning: Some(Arc::new(move |warning| sink.lock().unwrap().push(warning.to_string()))),
+            ..opts
+        };
+        assert_eq!(Plain(&entry, &opts).to_string(), "A fish (_Esox lucius_). [Obs.]");
+        assert_eq!(*reported.lock().unwrap(), ["tag handler for <mark> panicked"]);
+    }
+
+    #[test]
+    fn canonical_layout() {
+        use exporter::CideOptions;
+        let block_str = "<entry main-word=\"Q\" source=\"\"> <p><hw>Q</hw> <def>the seventeenth letter</def><br/\n\
+                         <def>of the English alphabet.</def></p><p><mark>[Obs.]</mark></p></entry>";
+        let entry = EntryParser::new(block_str).next().unwrap().unwrap();
+        let opts = CideOptions { wrap_width: Some(30), newline_after_br: false, ..CideOptions::canonical() };
+        let expected = "<entry main-word=\"Q\" source=\"\">\n<p><hw>Q</hw> <def>the\nseventeenth\n\
+                        letter</def><br/<def>of the\nEnglish alphabet.</def></p>\n\n<p><mark>[Obs.]</mark></p>\n</entry>";
+        assert_eq!(expected, CIDE::with_options(&entry, &opts).to_string());
+        assert_eq!(block_str, CIDE::with_options(&entry, &CideOptions::default()).to_string());
+    }
+
+    #[test]
+    fn headword_styles() {
+        use exporter::{CideOptions, ExportOptions, HeadwordStyle, Plain};
+        use exporter::html::HTML;
+        let block_str = "<entry main-word=\"Dictionary\" source=\"\"><hw>Dic\"tion*a*ry`</hw> <def>a *book*</def></entry>";
+        let entry = EntryParser::new(block_str).next().unwrap().unwrap();
+        let plain = |headwords| Plain(&entry, &ExportOptions { headwords, ..ExportOptions::default() }).to_string();
+        assert_eq!(plain(HeadwordStyle::Raw), "Dic\"tion*a*ry` a *book*");
+        assert_eq!(plain(HeadwordStyle::Clean), "Dictionary a *book*");
+        assert_eq!(plain(HeadwordStyle::Dotted), "Dic\u{b7}tion\u{b7}a\u{b7}ry a *book*");
+        let opts = CideOptions { headwords: HeadwordStyle::Clean, ..CideOptions::default() };
+        assert_eq!(CIDE::with_options(&entry, &opts).to_string(),
+                   "<entry main-word=\"Dictionary\" source=\"\"><hw>Dictionary</hw> <def>a *book*</def></entry>");
+
+        let block_str = "<entry main-word=\"Caesar\" source=\"\"><hw>C<ae/*sar</hw>, <hw>Ph<ae/\"ton</hw></entry>";
+        let entry = EntryParser::new(block_str).next().unwrap().unwrap();
+        let dotted = ExportOptions { headwords: HeadwordStyle::Dotted, ..ExportOptions::default() };
+        assert_eq!(Plain(&entry, &dotted).to_string(), "C\u{e6}\u{b7}sar, Ph\u{e6}\u{b7}ton");
+        assert!(HTML(&entry, &dotted).to_string().contains("<strong class=\"hw\">C\u{e6}\u{b7}sar</strong>"));
+        let opts = CideOptions { headwords: HeadwordStyle::Dotted, ..CideOptions::default() };
+        assert_eq!(CIDE::with_options(&entry, &opts).to_string(),
+                   "<entry main-word=\"Caesar\" source=\"\"><hw>C<ae/<middot/sar</hw>, <hw>Ph<ae/<middot/ton</hw></entry>");
+        assert_eq!(HeadwordStyle::Clean.apply("Ab\u{b7}a\u{2032}cus"), "Abacus");
+    }
+}
@@ -0,0 +1,148 @@
+//! OntoLex-Lemon rendering of entries as Turtle, for linked-data use.
+//!
+//! Each part of speech of an entry becomes an `ontolex:LexicalEntry` with a
+//! canonical form, other forms and senses. IRIs are relative to the base given
+//! to `header`.
+
+use std::fmt::{self, Display, Formatter};
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use exporter::site::slug;
+use exporter::wiki::pos_heading;
+use exporter::HeadwordStyle;
+use forms::{self, FormRelation};
+use model::{self, Sense};
+use parser::Entry;
+
+pub struct OntoLex<'a>(pub &'a Entry<'a>);
+
+/// `@base` and `@prefix` lines starting a Turtle document.
+pub fn header(base: &str) -> String {
+    format!("@base <{}> .\n\
+             @prefix ontolex: <http://www.w3.org/ns/lemon/ontolex#> .\n\
+             @prefix lexinfo: <http://www.lexinfo.net/ontology/3.0/lexinfo#> .\n\
+             @prefix skos: <http://www.w3.org/2004/02/skos/core#> .\n\
+             @prefix dct: <http://purl.org/dc/terms/> .\n\n", base)
+}
+
+impl<'a> Display for OntoLex<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let entry = self.0;
+        let model = model::entry_model(entry);
+        let headword = HeadwordStyle::Clean.apply(&model.headword);
+        let word_slug = slug(entry.main_word);
+        let mut groups: Vec<(&str, Vec<&Sense>)> = Vec::new();
+        for sense in &model.senses {
+            let heading = pos_heading(sense.pos.as_ref().map_or("", String::as_str));
+            match groups.iter_mut().find(|&&mut (h, _)| h == heading) {
+                Some(&mut (_, ref mut senses)) => senses.push(sense),
+                None => groups.push((heading, vec![sense])),
+            }
+        }
+        if groups.is_empty() {
+            groups.push(("Definitions", Vec::new()));
+        }
+        let other_forms = forms::word_forms(entry);
+        for (group_no, &(heading, ref senses)) in groups.iter().enumerate() {
+            let iri = format!("{}-{}", word_slug, heading.to_lowercase());
+            writeln!(f, "<{}> a ontolex:LexicalEntry ;", iri)?;
+            if let Some(pos) = lexinfo_pos(heading) {
+                writeln!(f, "    lexinfo:partOfSpeech lexinfo:{} ;", pos)?;
+            }
+            writeln!(f, "    ontolex:canonicalForm <{}-form> ;", iri)?;
+            // forms are not tied to a part of speech in the source
+            if group_no == 0 {
+                for idx in 0..other_forms.len() {
+                    writeln!(f, "    ontolex:otherForm <{}-form-{}> ;", iri, idx + 1)?;
+                }
+            }
+            for idx in 0..senses.len() {
+                writeln!(f, "    ontolex:sense <{}-sense-{}> ;", iri, idx + 1)?;
+            }
+            if let Some(ref id) = entry.id {
+                writeln!(f, "    dct:identifier {} ;", literal(id, None))?;
+            }
+            writeln!(f, "    dct:source {} .", literal(&model.source, None))?;
+            writeln!(f, "<{}-form> a ontolex:Form ;\n    ontolex:writtenRep {} .", iri, literal(&headword, Some("en")))?;
+            if group_no == 0 {
+                for (idx, &(ref form, relation)) in other_forms.iter().enumerate() {
+                    write!(f, "<{}-form-{}> a ontolex:Form ;\n    ontolex:writtenRep {}", iri, idx + 1,
+                           literal(form, Some("en")))?;
+                    match relation {
+                        FormRelation::Plural => writeln!(f, " ;\n    lexinfo:number lexinfo:plural .")?,
+                        FormRelation::Singular => writeln!(f, " ;\n    lexinfo:number lexinfo:singular .")?,
+                        _ => writeln!(f, " .")?,
+                    }
+                }
+            }
+            for (idx, sense) in senses.iter().enumerate() {
+                write!(f, "<{}-sense-{}> a ontolex:LexicalSense ;\n    skos:definition {}", iri, idx + 1,
+                       literal(&sense.definition, Some("en")))?;
+                for quote in &sense.quotes {
+                    write!(f, " ;\n    lexinfo:example {}", literal(&quote.text, Some("en")))?;
+                }
+                writeln!(f, " .")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn lexinfo_pos(heading: &str) -> Option<&'static str> {
+    match heading {
+        "Noun" => Some("noun"),
+        "Verb" => Some("verb"),
+        "Adjective" => Some("adjective"),
+        "Adverb" => Some("adverb"),
+        "Preposition" => Some("preposition"),
+        "Conjunction" => Some("conjunction"),
+        "Interjection" => Some("interjection"),
+        "Pronoun" => Some("pronoun"),
+        _ => None,
+    }
+}
+
+/// A Turtle string literal, with a language tag if given.
+fn literal(text: &str, lang: Option<&str>) -> String {
+    let mut quoted = String::with_capacity(text.len() + 8);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    if let Some(lang) = lang {
+        quoted.push('@');
+        quoted.push_str(lang);
+    }
+    quoted
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::EntryParser;
+
+    #[test]
+    fn lexical_entry() {
+        let text = "<entry main-word=\"Abacus\" source=\"1913 Webster\">\n<p><hw>Ab\"a*cus</hw>, <pos>n.</pos>; \
+                    <pl><plw>Abaci</plw></pl>. <def>A \"calculating\" frame.</def></p>\n</entry>";
+        let entry = EntryParser::new(text).next().unwrap().unwrap();
+        assert_eq!(OntoLex(&entry).to_string(),
+                   "<abacus-noun> a ontolex:LexicalEntry ;\n    lexinfo:partOfSpeech lexinfo:noun ;\n    \
+                    ontolex:canonicalForm <abacus-noun-form> ;\n    ontolex:otherForm <abacus-noun-form-1> ;\n    \
+                    ontolex:sense <abacus-noun-sense-1> ;\n    dct:source \"1913 Webster\" .\n\
+                    <abacus-noun-form> a ontolex:Form ;\n    ontolex:writtenRep \"Abacus\"@en .\n\
+                    <abacus-noun-form-1> a ontolex:Form ;\n    ontolex:writtenRep \"Abaci\"@en ;\n    \
+                    lexinfo:number lexinfo:plural .\n\
+                    <abacus-noun-sense-1> a ontolex:LexicalSense ;\n    \
+                    skos:definition \"A \\\"calculating\\\" frame.\"@en .\n\n");
+    }
+}
@@ -0,0 +1,149 @@
+//! Splitting HTML output into linked pages, as needed for static dictionary sites.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use exporter::html::PAGE_TAIL;
+use headword::normalize_headword;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SplitMode {
+    /// One page per initial letter, with an anchor per headword.
+    Letter,
+    /// One page per headword, holding all its homographs.
+    Entry,
+}
+
+impl FromStr for SplitMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<SplitMode, String> {
+        match s {
+            "letter" => Ok(SplitMode::Letter),
+            "entry" => Ok(SplitMode::Entry),
+            _ => Err(format!("unknown split mode: {}", s)),
+        }
+    }
+}
+
+/// Anchor for a headword: its normalized form with characters other than
+/// letters and digits replaced by `-`.
+pub fn slug(word: &str) -> String {
+    let slug: String = normalize_headword(word).chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect();
+    if slug.is_empty() { "-".to_string() } else { slug }
+}
+
+/// File name of the page holding `word`.
+pub fn page_name(word: &str, mode: SplitMode) -> String {
+    let slug = slug(word);
+    match mode {
+        SplitMode::Letter => match slug.chars().find(|c| c.is_alphanumeric()) {
+            Some(c) if c.is_ascii_alphabetic() => format!("{}.html", c),
+            _ => "misc.html".to_string(),
+        },
+        SplitMode::Entry => format!("{}.html", slug),
+    }
+}
+
+/// Link to `word` from any page of the site.
+pub fn href(word: &str, mode: SplitMode) -> String {
+    match mode {
+        SplitMode::Letter => format!("{}#{}", page_name(word, mode), slug(word)),
+        SplitMode::Entry => page_name(word, mode),
+    }
+}
+
+/// Collects rendered entries into pages.
+pub struct Site {
+    mode: SplitMode,
+    pages: BTreeMap<String, Page>,
+}
+
+#[derive(Default)]
+struct Page {
+    title: String,
+    body: String,
+    /// Main words on the page, without duplicates.
+    words: Vec<String>,
+}
+
+impl Site {
+    pub fn new(mode: SplitMode) -> Site {
+        Site { mode, pages: BTreeMap::new() }
+    }
+
+    /// Adds the HTML of an entry to the page of its main word.
+    pub fn add(&mut self, main_word: &str, html: &str) {
+        let name = page_name(main_word, self.mode);
+        let page = self.pages.entry(name.clone()).or_default();
+        if page.title.is_empty() {
+            page.title = match self.mode {
+                SplitMode::Letter => name.trim_end_matches(".html").to_uppercase(),
+                SplitMode::Entry => main_word.to_string(),
+            };
+        }
+        if !page.words.iter().any(|word| slug(word) == slug(main_word)) {
+            page.words.push(main_word.to_string());
+            if self.mode == SplitMode::Letter {
+                page.body.push_str(&format!("\n<a id=\"{}\"></a>", slug(main_word)));
+            }
+        }
+        page.body.push('\n');
+        page.body.push_str(html);
+        page.body.push('\n');
+    }
+
+    /// File names and contents of all pages, followed by `index.html`. `head`
+    /// gives the start of a page with the given title (see `html::page_head`).
+    pub fn pages<F>(&self, head: F) -> Vec<(String, String)>
+    where F: Fn(&str) -> String {
+        let mut files: Vec<_> = self.pages.iter()
+            .map(|(name, page)| (name.clone(), format!("{}{}{}", head(&page.title), page.body, PAGE_TAIL)))
+            .collect();
+        files.push(("index.html".to_string(), self.index(&head)));
+        files
+    }
+
+    fn index<F>(&self, head: &F) -> String
+    where F: Fn(&str) -> String {
+        let mut index = head("Index");
+        index.push_str("<ul class=\"index\">\n");
+        for (name, page) in &self.pages {
+            match self.mode {
+                SplitMode::Letter => index.push_str(&format!("<li><a href=\"{}\">{}</a></li>\n", name, page.title)),
+                SplitMode::Entry => for word in &page.words {
+                    index.push_str(&format!("<li><a href=\"{}\">{}</a></li>\n", name, word));
+                },
+            }
+        }
+        index.push_str("</ul>");
+        index.push_str(PAGE_TAIL);
+        index
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn split_pages() {
+        assert_eq!(href("Ab\"a*cus", SplitMode::Letter), "a.html#abacus");
+        assert_eq!(href("Black bird", SplitMode::Entry), "black-bird.html");
+        assert_eq!(page_name("1st", SplitMode::Letter), "misc.html");
+
+        let mut site = Site::new(SplitMode::Letter);
+        site.add("Abacus", "<div>1</div>");
+        site.add("Abacus", "<div>2</div>");
+        site.add("Bee", "<div>3</div>");
+        let pages = site.pages(|title| format!("<h1>{}</h1>", title));
+        let names: Vec<_> = pages.iter().map(|(name, _)| &name[..]).collect();
+        assert_eq!(names, ["a.html", "b.html", "index.html"]);
+        assert_eq!(pages[0].1, format!("<h1>A</h1>\n<a id=\"abacus\"></a>\n<div>1</div>\n\n<div>2</div>\n{}", PAGE_TAIL));
+        assert!(pages[2].1.contains("<li><a href=\"b.html\">B</a></li>"));
+    }
+}
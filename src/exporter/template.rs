@@ -0,0 +1,63 @@
+//! Rendering of entries through user-supplied Handlebars templates.
+//!
+//! A template is rendered once per entry with the entry's `model::EntryModel`
+//! as data, e.g. `{{headword}}: {{#each senses}}{{number}} {{definition}} {{/each}}`.
+
+use handlebars::{no_escape, Handlebars, RenderError, TemplateError};
+
+use model::{entry_model, EntryModel};
+use parser::Entry;
+
+const TEMPLATE_NAME: &str = "entry";
+
+pub struct TemplateExporter {
+    registry: Handlebars<'static>,
+}
+
+impl TemplateExporter {
+    /// Compiles `template`. Values are inserted as they are; see `escape_html`.
+    pub fn new(template: &str) -> Result<TemplateExporter, Box<TemplateError>> {
+        let mut registry = Handlebars::new();
+        registry.register_escape_fn(no_escape);
+        registry.register_template_string(TEMPLATE_NAME, template).map_err(Box::new)?;
+        Ok(TemplateExporter { registry })
+    }
+
+    /// Escapes values inserted with `{{...}}` for HTML (`{{{...}}}` never escapes).
+    pub fn escape_html(mut self, escape: bool) -> TemplateExporter {
+        if escape {
+            self.registry.unregister_escape_fn();
+        } else {
+            self.registry.register_escape_fn(no_escape);
+        }
+        self
+    }
+
+    pub fn render(&self, entry: &Entry) -> Result<String, RenderError> {
+        self.render_model(&entry_model(entry))
+    }
+
+    /// Renders a model built (and perhaps annotated) by the caller.
+    pub fn render_model(&self, model: &EntryModel) -> Result<String, RenderError> {
+        self.registry.render(TEMPLATE_NAME, model)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::EntryParser;
+
+    #[test]
+    fn render_senses() {
+        let text = "<entry main-word=\"Abacus\" source=\"1913 Webster\">\n<p><hw>Ab\"a*cus</hw>, <pos>n.</pos> \
+                    <sn>1.</sn> <def>A table & tray.</def> <sn>2.</sn> <def>A frame.</def></p>\n</entry>";
+        let entry = EntryParser::new(text).next().unwrap().unwrap();
+        let template = "{{main_word}} ({{pos}}){{#each senses}} {{number}}) {{definition}}{{/each}}";
+        let exporter = TemplateExporter::new(template).unwrap();
+        assert_eq!(exporter.render(&entry).unwrap(), "Abacus (n.) 1) A table & tray. 2) A frame.");
+        let exporter = exporter.escape_html(true);
+        assert_eq!(exporter.render(&entry).unwrap(), "Abacus (n.) 1) A table &amp; tray. 2) A frame.");
+        assert!(TemplateExporter::new("{{#each senses}}").is_err());
+    }
+}
@@ -0,0 +1,201 @@
+//! Rendering of entries for terminals, with ANSI styles and wrapped lines.
+
+use std::env;
+use std::fmt::{self, Display, Formatter};
+
+use unicode_normalization::char::is_combining_mark;
+
+use exporter::{process_symbols_in_text, ExportOptions, HeadwordStyle};
+use greek;
+use parser::{Entry, EntryItem};
+use tags::TagName;
+
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const ITALIC: &str = "\x1b[3m";
+const NORMAL_INTENSITY: &str = "\x1b[22m";
+const NOT_ITALIC: &str = "\x1b[23m";
+
+/// Styled rendering of an entry, wrapped to `terminal_width()`.
+pub struct Term<'a>(pub &'a Entry<'a>);
+
+/// Styled rendering with explicit options; see `Term::with_options`.
+pub struct TermLayout<'a>(&'a Entry<'a>, &'a ExportOptions, usize);
+
+impl<'a> Term<'a> {
+    pub fn with_options(entry: &'a Entry<'a>, opts: &'a ExportOptions, width: usize) -> TermLayout<'a> {
+        TermLayout(entry, opts, width)
+    }
+}
+
+/// Width given by `$COLUMNS`, or 80.
+pub fn terminal_width() -> usize {
+    env::var("COLUMNS").ok().and_then(|cols| cols.parse().ok()).filter(|&cols| cols > 0).unwrap_or(80)
+}
+
+impl<'a> Display for Term<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        TermLayout(self.0, &ExportOptions::default(), terminal_width()).fmt(f)
+    }
+}
+
+impl<'a> Display for TermLayout<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let TermLayout(entry, opts, width) = *self;
+        let mut lines = vec![String::new()];
+        for item in &entry.items {
+            match *item {
+                EntryItem::PlainText(text) if text.trim().is_empty() => (),
+                EntryItem::Tagged { name: TagName::P, ref items, source } => {
+                    new_line(&mut lines);
+                    for item in items {
+                        write_item(&mut lines, item, opts, &mut Vec::new());
+                    }
+                    if let Some(source) = source.filter(|&source| source != entry.source) {
+                        write_styled(&mut lines, DIM, NORMAL_INTENSITY, &format!("[{}]", source));
+                    }
+                    new_line(&mut lines);
+                }
+                ref item => write_item(&mut lines, item, opts, &mut Vec::new()),
+            }
+        }
+        write_styled(&mut lines, DIM, NORMAL_INTENSITY, &format!("[{}]", entry.source));
+        let mut first = true;
+        for line in lines.iter().filter(|line| !line.trim().is_empty()) {
+            if !first {
+                f.write_str("\n")?;
+            }
+            first = false;
+            f.write_str(&wrap(line.trim(), width))?;
+        }
+        Ok(())
+    }
+}
+
+fn new_line(lines: &mut Vec<String>) {
+    if !lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.push(String::new());
+    }
+}
+
+fn write_styled(lines: &mut [String], on: &str, off: &str, text: &str) {
+    let line = lines.last_mut().unwrap();
+    line.push(' ');
+    line.push_str(on);
+    line.push_str(text);
+    line.push_str(off);
+}
+
+/// Writes `item` at the end of `lines`, inside tags whose styles were turned
+/// on with `styles`, which it turns back on after its own are turned off.
+fn write_item(lines: &mut Vec<String>, item: &EntryItem, opts: &ExportOptions, styles: &mut Vec<&'static str>) {
+    use parser::EntryItem::*;
+    match *item {
+        Comment(_) | PageBreak(_) | UnpairedTagOpen(..) | UnpairedTagClose(_) => (),
+        Entity(entity) => lines.last_mut().unwrap().push_str(entity.to_unicode()),
+        EntityBr => lines.push(String::new()),
+        EntityUnk => lines.last_mut().unwrap().push('\u{fffd}'),
+        ExternalLink(_, text) => lines.last_mut().unwrap().push_str(text),
+        Greek(ref gitems) => lines.last_mut().unwrap().push_str(&greek::render(gitems, opts.greek)),
+        PlainText(text) => {
+            let text = process_symbols_in_text(text).replace('\n', " ");
+            lines.last_mut().unwrap().push_str(&text);
+        }
+        Tagged { name, ref items, .. } => {
+            if let Some(output) = opts.run_tag_handler(name, items) {
+                return lines.last_mut().unwrap().push_str(&output);
+            }
+            let (on, off) = match name {
+                TagName::Hw | TagName::Sn | TagName::Col | TagName::B | TagName::Asp | TagName::Adjf
+                    | TagName::Conjf | TagName::Decf | TagName::Plw | TagName::Singw | TagName::Wf => {
+                    (BOLD, NORMAL_INTENSITY)
+                }
+                TagName::Spn | TagName::Class | TagName::Fam | TagName::Gen | TagName::Ord | TagName::Pos
+                    | TagName::I | TagName::It | TagName::Ex | TagName::Qex | TagName::Xex | TagName::Fld
+                    | TagName::Mark => (ITALIC, NOT_ITALIC),
+                TagName::Au | TagName::Qau => (DIM, NORMAL_INTENSITY),
+                _ => ("", ""),
+            };
+            lines.last_mut().unwrap().push_str(on);
+            if name == TagName::Hw && opts.headwords != HeadwordStyle::Raw {
+                lines.last_mut().unwrap().push_str(&opts.headwords.apply_to_items(items));
+            } else {
+                styles.push(on);
+                for item in items {
+                    write_item(lines, item, opts, styles);
+                }
+                styles.pop();
+            }
+            let line = lines.last_mut().unwrap();
+            line.push_str(off);
+            if !off.is_empty() {
+                for &style in styles.iter() {
+                    line.push_str(style);
+                }
+            }
+        }
+    }
+}
+
+/// Number of columns taken by `text`, ignoring escape sequences and combining marks.
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for c in text.chars() {
+        if in_escape {
+            in_escape = c != 'm';
+        } else if c == '\x1b' {
+            in_escape = true;
+        } else if !is_combining_mark(c) {
+            width += 1;
+        }
+    }
+    width
+}
+
+fn wrap(line: &str, width: usize) -> String {
+    let mut wrapped = String::with_capacity(line.len());
+    let mut col = 0;
+    for word in line.split_whitespace() {
+        let word_width = visible_width(word);
+        if col > 0 && col + 1 + word_width > width {
+            wrapped.push('\n');
+            col = 0;
+        } else if col > 0 {
+            wrapped.push(' ');
+            col += 1;
+        }
+        wrapped.push_str(word);
+        col += word_width;
+    }
+    wrapped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::EntryParser;
+
+    #[test]
+    fn styled_and_wrapped() {
+        let text = "<entry main-word=\"Pike\" source=\"1913 Webster\">\n<p><hw>Pike</hw>, <pos>n.</pos> \
+                    <def>A fish (<spn>Esox lucius</spn>).</def></p>\n\n<p source=\"PJC\"><def>A spear.</def></p>\n</entry>";
+        let entry = EntryParser::new(text).next().unwrap().unwrap();
+        let expected = "\x1b[1mPike\x1b[22m, \x1b[3mn.\x1b[23m A\nfish (\x1b[3mEsox\nlucius\x1b[23m).\n\
+                        A spear.\n\x1b[2m[PJC]\x1b[22m\n\x1b[2m[1913\nWebster]\x1b[22m";
+        assert_eq!(Term::with_options(&entry, &ExportOptions::default(), 12).to_string(), expected);
+
+        let text = "<entry main-word=\"Pike\" source=\"A\"><hw>Pike\"let</hw></entry>";
+        let entry = EntryParser::new(text).next().unwrap().unwrap();
+        let dotted = ExportOptions { headwords: HeadwordStyle::Dotted, ..ExportOptions::default() };
+        assert_eq!(Term::with_options(&entry, &dotted, 80).to_string(),
+                   "\x1b[1mPike\u{b7}let\x1b[22m \x1b[2m[A]\x1b[22m");
+
+        let text = "<entry main-word=\"Pike\" source=\"A\"><p><ex>a <spn>b</spn> c</ex> <b>d <b>e</b> f</b></p>\
+                    </entry>";
+        let entry = EntryParser::new(text).next().unwrap().unwrap();
+        assert_eq!(Term::with_options(&entry, &ExportOptions::default(), 80).to_string(),
+                   "\x1b[3ma \x1b[3mb\x1b[23m\x1b[3m c\x1b[23m \x1b[1md \x1b[1me\x1b[22m\x1b[1m f\x1b[22m\n\
+                    \x1b[2m[A]\x1b[22m");
+    }
+}
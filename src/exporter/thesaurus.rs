@@ -0,0 +1,132 @@
+//! Thesaurus files built from the `<syn>` lists and the antonyms of senses:
+//! the comma-separated lines of the Moby thesaurus (synonyms only), and the
+//! `.dat` and `.idx` files of MyThes, the thesaurus format of OpenOffice and
+//! LibreOffice.
+
+use std::collections::BTreeMap;
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use exporter::wiki::pos_heading;
+use model::{self, SynonymList};
+use parser::Entry;
+
+#[derive(Debug, Default)]
+pub struct Thesaurus {
+    /// Lists by lowercased main word, in file order.
+    words: BTreeMap<String, WordLists>,
+}
+
+#[derive(Debug, Default)]
+struct WordLists {
+    synonyms: Vec<SynonymList>,
+    /// Antonyms with the part of speech of their sense.
+    antonyms: Vec<(Option<String>, String)>,
+}
+
+impl Thesaurus {
+    pub fn new() -> Thesaurus {
+        Thesaurus::default()
+    }
+
+    /// Adds the synonym lists and antonyms of `entry` under its main word.
+    pub fn add_entry(&mut self, entry: &Entry) {
+        let synonyms = model::synonyms(entry);
+        let antonyms: Vec<_> = model::senses(entry).into_iter()
+            .flat_map(|sense| {
+                let pos = sense.pos;
+                sense.antonyms.into_iter().map(move |antonym| (pos.clone(), antonym))
+            })
+            .collect();
+        if !synonyms.is_empty() || !antonyms.is_empty() {
+            let lists = self.words.entry(entry.main_word.to_lowercase()).or_default();
+            lists.synonyms.extend(synonyms);
+            lists.antonyms.extend(antonyms);
+        }
+    }
+
+    /// Number of words with synonyms or antonyms.
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// One line per word: the word, then all its synonyms, separated by commas.
+    pub fn moby(&self) -> String {
+        let mut out = String::new();
+        for (word, lists) in self.words.iter().filter(|&(_, lists)| !lists.synonyms.is_empty()) {
+            out.push_str(word);
+            let mut seen: Vec<&str> = Vec::new();
+            for synonym in lists.synonyms.iter().flat_map(|list| &list.words) {
+                if !seen.contains(&&synonym[..]) {
+                    seen.push(synonym);
+                    out.push(',');
+                    out.push_str(synonym);
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// The MyThes `.dat` file, with a `word|count` line per word followed by
+    /// a `(part of speech)|synonym|...` line per list and one with the
+    /// antonyms of each part of speech, as `word (antonym)`; and its `.idx`
+    /// of `word|byte offset` lines.
+    pub fn mythes(&self) -> (String, String) {
+        let mut dat = String::from("UTF-8\n");
+        let mut idx = format!("UTF-8\n{}\n", self.words.len());
+        for (word, lists) in &self.words {
+            let mut lines: Vec<(Option<&String>, Vec<String>)> = lists.synonyms.iter()
+                .map(|list| (list.pos.as_ref(), list.words.clone()))
+                .collect();
+            let mut antonym_lines: Vec<(Option<&String>, Vec<String>)> = Vec::new();
+            for (pos, antonym) in &lists.antonyms {
+                let antonym = format!("{} (antonym)", antonym);
+                match antonym_lines.iter_mut().find(|line| line.0 == pos.as_ref()) {
+                    Some(line) => if !line.1.contains(&antonym) {
+                        line.1.push(antonym);
+                    },
+                    None => antonym_lines.push((pos.as_ref(), vec![antonym])),
+                }
+            }
+            lines.extend(antonym_lines);
+            idx.push_str(&format!("{}|{}\n", word, dat.len()));
+            dat.push_str(&format!("{}|{}\n", word, lines.len()));
+            for (pos, words) in lines {
+                let pos = pos.map_or("-".to_string(), |pos| pos_heading(pos).to_lowercase());
+                dat.push_str(&format!("({})|{}\n", pos, words.join("|")));
+            }
+        }
+        (dat, idx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::EntryParser;
+
+    #[test]
+    fn moby_and_mythes() {
+        let text = "<entry main-word=\"Abase\" source=\"1913 Webster\"><p><pos>v. t.</pos> \
+                    <syn><b>Syn. --</b> To degrade; lower; sink.</syn></p></entry>\n\
+                    <entry main-word=\"Abate\" source=\"1913 Webster\"><p><syn>Syn. -- lessen; lower</syn> \
+                    <syn>Syn. -- diminish, lessen.</syn></p></entry>\n\
+                    <entry main-word=\"Active\" source=\"1913 Webster\"><p><pos>a.</pos> <def>Acting; \
+                    -- opposed to <ex>passive</ex>.</def></p></entry>";
+        let mut thesaurus = Thesaurus::new();
+        for entry in EntryParser::new(text).filter_map(Result::ok) {
+            thesaurus.add_entry(&entry);
+        }
+        assert_eq!(thesaurus.len(), 3);
+        assert_eq!(thesaurus.moby(), "abase,To degrade,lower,sink\nabate,lessen,lower,diminish\n");
+        let (dat, idx) = thesaurus.mythes();
+        assert_eq!(dat, "UTF-8\nabase|1\n(verb)|To degrade|lower|sink\nabate|2\n(-)|lessen|lower\n(-)|diminish|lessen\n\
+                         active|1\n(adjective)|passive (antonym)\n");
+        assert_eq!(idx, "UTF-8\n3\nabase|6\nabate|43\nactive|88\n");
+    }
+}
@@ -0,0 +1,139 @@
+//! Wikitext rendering of entries following Wiktionary conventions, for comparing
+//! with or importing into Wiktionary.
+
+use std::fmt::{self, Display, Formatter};
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use exporter::HeadwordStyle;
+use model::{self, Sense, UsageLabel};
+use parser::{Entry, EntryItem};
+use tags::TagName;
+
+/// An `==English==` section for one entry, with a subsection per part of speech.
+pub struct Wikitext<'a>(pub &'a Entry<'a>);
+
+impl<'a> Display for Wikitext<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let entry = self.0;
+        let model = model::entry_model(entry);
+        let headword = HeadwordStyle::Clean.apply(&model.headword);
+        writeln!(f, "==English==")?;
+        if let Some(ref id) = entry.id {
+            writeln!(f, "{{{{anchor|{}}}}}", id)?;
+        }
+        if let Some(ety) = find_ety(&entry.items) {
+            write!(f, "\n===Etymology===\n{}\n", escape(ety.trim_matches(['[', ']', ' '])))?;
+        }
+        let mut last_heading = None;
+        for sense in &model.senses {
+            let heading = pos_heading(sense.pos.as_ref().map_or("", String::as_str));
+            if last_heading != Some(heading) {
+                write!(f, "\n==={}===\n'''{}'''\n\n", heading, escape(&headword))?;
+                last_heading = Some(heading);
+            }
+            write_sense(f, sense)?;
+        }
+        if !model.collocations.is_empty() {
+            writeln!(f, "\n====Derived terms====")?;
+            for collocation in &model.collocations {
+                writeln!(f, "* [[{}]]", collocation.phrase.replace(['[', ']', '|'], ""))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_sense(f: &mut Formatter, sense: &Sense) -> fmt::Result {
+    f.write_str("# ")?;
+    if !sense.labels.is_empty() {
+        let labels: Vec<_> = sense.labels.iter().map(label_name).collect();
+        write!(f, "{{{{lb|en|{}}}}} ", labels.join("|").replace('}', ""))?;
+    }
+    writeln!(f, "{}", escape(&sense.definition))?;
+    for quote in &sense.quotes {
+        match quote.author {
+            Some(ref author) => writeln!(f, "#* ''{}'' \u{2014} {}", escape(&quote.text), escape(author))?,
+            None => writeln!(f, "#* ''{}''", escape(&quote.text))?,
+        }
+    }
+    Ok(())
+}
+
+fn find_ety(items: &[EntryItem]) -> Option<String> {
+    items.iter().filter_map(|item| match *item {
+        EntryItem::Tagged { name: TagName::Ety, ref items, .. } => Some(model::plain_text(items)),
+        EntryItem::Tagged { ref items, .. } => find_ety(items),
+        _ => None,
+    }).next()
+}
+
+/// The Wiktionary heading for a Webster's part of speech.
+pub fn pos_heading(pos: &str) -> &'static str {
+    let pos = pos.trim();
+    let starts = |prefixes: &[&str]| prefixes.iter().any(|p| pos.starts_with(p));
+    if starts(&["n.", "n "]) || pos == "n" {
+        "Noun"
+    } else if starts(&["v."]) {
+        "Verb"
+    } else if starts(&["a.", "adj."]) {
+        "Adjective"
+    } else if starts(&["adv."]) {
+        "Adverb"
+    } else if starts(&["prep."]) {
+        "Preposition"
+    } else if starts(&["conj."]) {
+        "Conjunction"
+    } else if starts(&["interj."]) {
+        "Interjection"
+    } else if starts(&["pron."]) {
+        "Pronoun"
+    } else if starts(&["p. p.", "p. pr.", "imp."]) {
+        "Participle"
+    } else {
+        "Definitions"
+    }
+}
+
+fn label_name(label: &UsageLabel) -> String {
+    match *label {
+        UsageLabel::Obsolete => "obsolete".into(),
+        UsageLabel::Archaic => "archaic".into(),
+        UsageLabel::Rare => "rare".into(),
+        UsageLabel::Colloquial => "colloquial".into(),
+        UsageLabel::Slang => "slang".into(),
+        UsageLabel::Poetic => "poetic".into(),
+        UsageLabel::Provincial => "dialectal".into(),
+        UsageLabel::Vulgar => "vulgar".into(),
+        UsageLabel::Humorous => "humorous".into(),
+        UsageLabel::Regional(ref region) | UsageLabel::Other(ref region) => region.trim_end_matches('.').into(),
+    }
+}
+
+/// Keeps text containing wiki markup from being interpreted.
+pub fn escape(text: &str) -> String {
+    if ["''", "[[", "]]", "{{", "}}", "~~~", "<"].iter().any(|markup| text.contains(markup)) {
+        format!("<nowiki>{}</nowiki>", text.replace("</nowiki>", "&lt;/nowiki>"))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::EntryParser;
+
+    #[test]
+    fn wiktionary_sections() {
+        let text = "<entry main-word=\"Abacus\" source=\"1913 Webster\">\n<p><hw>Ab\"a*cus</hw>, <pos>n.</pos> \
+                    <ety>[L. abacus.]</ety> <sn>1.</sn> <def>A table.</def> <mark>[Obs.]</mark></p>\n\n\
+                    <p><sn>2.</sn> <def>A calculating frame.</def><br/\n<q>An abacus quote.</q> <qau>Shak.</qau></p>\n\n\
+                    <p><hw>Ab\"a*cus</hw>, <pos>v. t.</pos> <def>To reckon.</def></p>\n</entry>";
+        let entry = EntryParser::new(text).next().unwrap().unwrap();
+        assert_eq!(Wikitext(&entry).to_string(), "==English==\n\n===Etymology===\nL. abacus.\n\n\
+                                                  ===Noun===\n'''Abacus'''\n\n# {{lb|en|obsolete}} A table.\n\
+                                                  # A calculating frame.\n#* ''An abacus quote.'' \u{2014} Shak.\n\n\
+                                                  ===Verb===\n'''Abacus'''\n\n# To reckon.\n");
+    }
+}
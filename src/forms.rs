@@ -0,0 +1,111 @@
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use exporter::HeadwordStyle;
+use model;
+use parser::{Entry, EntryItem};
+use tags::TagName;
+
+/// How an inflected or variant form relates to the entry's headword.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FormRelation {
+    Plural,
+    Singular,
+    Conjugated,
+    Adjectival,
+    Declined,
+    WordForm,
+    AltSpelling,
+}
+
+impl FormRelation {
+    pub fn from_tag(name: &str) -> Option<FormRelation> {
+        match name {
+            "plw" | "pluf" => Some(FormRelation::Plural),
+            "singw" | "singf" => Some(FormRelation::Singular),
+            "conjf" => Some(FormRelation::Conjugated),
+            "adjf" => Some(FormRelation::Adjectival),
+            "decf" => Some(FormRelation::Declined),
+            "wf" => Some(FormRelation::WordForm),
+            "asp" | "altsp" => Some(FormRelation::AltSpelling),
+            _ => None,
+        }
+    }
+}
+
+/// All word forms in an entry (inflections, alternative spellings, ...) as
+/// plain text with syllable and stress marks removed.
+pub fn word_forms(entry: &Entry) -> Vec<(String, FormRelation)> {
+    let mut forms = Vec::new();
+    collect_forms(&entry.items, &mut forms);
+    forms
+}
+
+/// Words to look the entry up by: its main word, its headword and its word
+/// forms, without duplicates.
+pub fn lookup_terms(entry: &Entry) -> Vec<String> {
+    let mut terms = vec![entry.main_word.to_string()];
+    let headword = HeadwordStyle::Clean.apply(&model::entry_model(entry).headword);
+    let forms = word_forms(entry).into_iter().map(|(form, _)| form);
+    for term in Some(headword).into_iter().chain(forms) {
+        if !term.is_empty() && !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    terms
+}
+
+fn collect_forms(items: &[EntryItem], forms: &mut Vec<(String, FormRelation)>) {
+    for item in items {
+        if let EntryItem::Tagged { name, ref items, .. } = *item {
+            match FormRelation::from_tag(name.as_str()) {
+                // `altsp` usually wraps the individual `asp` spellings
+                Some(_) if name == TagName::Altsp && contains_tag(items, TagName::Asp) => collect_forms(items, forms),
+                Some(relation) => {
+                    let form = form_text(items);
+                    if !form.is_empty() {
+                        forms.push((form, relation));
+                    }
+                }
+                None => collect_forms(items, forms),
+            }
+        }
+    }
+}
+
+fn contains_tag(items: &[EntryItem], tag_name: TagName) -> bool {
+    items.iter().any(|item| match *item {
+        EntryItem::Tagged { name, ref items, .. } => name == tag_name || contains_tag(items, tag_name),
+        _ => false,
+    })
+}
+
+fn form_text(items: &[EntryItem]) -> String {
+    let mut text = String::new();
+    for item in items {
+        text.push_str(&item.to_string());
+    }
+    let cleaned: String = text.chars().filter(|&c| c != '*' && c != '"' && c != '`').collect();
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::EntryParser;
+
+    #[test]
+    fn forms_and_terms() {
+        let text = "<entry main-word=\"Abacus\" source=\"1913 Webster\">\n\
+                    <p><hw>Ab\"a*cus</hw>, <pos>n.</pos>; pl. E. <pluf>Ab\"a*ci</pluf> \
+                    <altsp>also <asp>Ab\"a*cuss</asp> or <asp>Abacus</asp></altsp> <altsp>Ab\"a*kus</altsp></p>\n</entry>";
+        let entry = EntryParser::new(text).next().unwrap().unwrap();
+        assert_eq!(word_forms(&entry), [
+            ("Abaci".to_string(), FormRelation::Plural),
+            ("Abacuss".to_string(), FormRelation::AltSpelling),
+            ("Abacus".to_string(), FormRelation::AltSpelling),
+            ("Abakus".to_string(), FormRelation::AltSpelling),
+        ]);
+        assert_eq!(lookup_terms(&entry), ["Abacus", "Abaci", "Abacuss", "Abakus"]);
+    }
+}
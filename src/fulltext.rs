@@ -0,0 +1,111 @@
+//! Inverted index over the definition and quotation text of entries.
+
+use std::collections::BTreeMap;
+
+use headword::normalize_headword;
+use index::location;
+use model;
+use parser::{Entry, EntryParser};
+
+/// Maps every word used in definitions or quotations to the locations of the
+/// entries using it. Locations are as in `index::HeadwordIndex`.
+#[derive(Default)]
+pub struct FullTextIndex {
+    terms: BTreeMap<String, Vec<u64>>,
+}
+
+/// Lowercased words of `text` with diacritics removed.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(normalize_headword)
+        .collect()
+}
+
+impl FullTextIndex {
+    pub fn new() -> FullTextIndex {
+        FullTextIndex::default()
+    }
+
+    pub fn add_entry(&mut self, entry: &Entry, loc: u64) {
+        for sense in model::senses(entry) {
+            let quotes = sense.quotes.iter().map(|quote| &quote.text[..]);
+            for text in Some(&sense.definition[..]).into_iter().chain(quotes) {
+                for term in tokenize(text) {
+                    let postings = self.terms.entry(term).or_default();
+                    if postings.last() != Some(&loc) {
+                        postings.push(loc);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn add_file(&mut self, file_no: u32, contents: &str) {
+        let mut entry_iter = EntryParser::new(contents);
+        while let Some(entry_res) = entry_iter.next() {
+            if let Ok(entry) = entry_res {
+                let span = entry_iter.last_span().unwrap();
+                self.add_entry(&entry, location(file_no, span.start));
+            }
+        }
+    }
+
+    /// Locations of entries containing all words of `query`, in index order.
+    /// A word ending in `*` matches every word starting with it.
+    pub fn search(&self, query: &str) -> Vec<u64> {
+        let mut result: Option<Vec<u64>> = None;
+        for word in query.split_whitespace() {
+            let (word, is_prefix) = match word.strip_suffix('*') {
+                Some(prefix) => (prefix, true),
+                None => (word, false),
+            };
+            for term in tokenize(word) {
+                let mut matches: Vec<u64> = if is_prefix {
+                    self.terms.range(term.clone()..)
+                        .take_while(|(key, _)| key.starts_with(&term))
+                        .flat_map(|(_, locs)| locs.iter().cloned())
+                        .collect()
+                } else {
+                    self.terms.get(&term).cloned().unwrap_or_default()
+                };
+                matches.sort_unstable();
+                matches.dedup();
+                result = Some(match result {
+                    Some(prev) => prev.into_iter().filter(|loc| matches.binary_search(loc).is_ok()).collect(),
+                    None => matches,
+                });
+            }
+        }
+        result.unwrap_or_default()
+    }
+
+    /// Number of distinct words indexed.
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn search_definitions() {
+        let contents = "<entry main-word=\"Azure\" source=\"\">\n<p><hw>Az\"ure</hw> <def>The color blue, \
+                        in <fld>heraldry</fld>.</def></p>\n</entry>\n\n\
+                        <entry main-word=\"Or\" source=\"\">\n<p><hw>Or</hw> <def>Gold, in Heraldry.</def><br/\n\
+                        <q>Fields of or.</q> <qau>Anon.</qau></p>\n</entry>\n";
+        let mut index = FullTextIndex::new();
+        index.add_file(0, contents);
+        let second = location(0, contents.find("<entry main-word=\"Or\"").unwrap());
+        assert_eq!(index.search("heraldry"), [0, second]);
+        assert_eq!(index.search("HERALDRY gold"), [second]);
+        assert_eq!(index.search("field*"), [second]);
+        assert!(index.search("silver").is_empty());
+    }
+}
@@ -0,0 +1,427 @@
+use std::fmt::{self, Display, Formatter, Write};
+use std::str::FromStr;
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use nom::types::CompleteStr;
+use unicode_normalization::char::compose as unic_compose;
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum GreekItem {
+    Letter(char, GreekMods),
+    Other(char), // typically, hyphen or space
+    Unknown(char), // outside the transcription scheme; kept verbatim
+}
+
+bitflags! {
+    pub struct GreekMods: u16 {
+        const SLENIS     = 1 << 0; // '
+        const SASPER     = 1 << 1; // "
+        const ACUTE      = 1 << 2; // `
+        const GRAVE      = 1 << 3; // ~
+        const CIRCUMFLEX = 1 << 4; // ^
+        const IOTASUB    = 1 << 5; // ,
+        const DIAERESIS  = 1 << 6; // :
+        const TERMINAL   = 1 << 15; // to distinguish between normal and terminal sigma
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for GreekMods {
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u16(self.bits())
+    }
+}
+
+/// Parses Greek transcribed in the GCIDE ASCII scheme (the contents of `<grk>`).
+/// Characters outside the scheme are kept as `GreekItem::Unknown`.
+pub fn parse_greek(text: &str) -> Vec<GreekItem> {
+    let mut items = Vec::with_capacity(text.len());
+    let mut rest = CompleteStr(text);
+    while !rest.is_empty() {
+        match grk_item(rest) {
+            Ok((remaining, item)) => {
+                items.push(item);
+                rest = remaining;
+            }
+            Err(_) => {
+                let c = rest.chars().next().unwrap();
+                items.push(GreekItem::Unknown(c));
+                rest = CompleteStr(&rest[c.len_utf8()..]);
+            }
+        }
+    }
+    mark_terminal_sigmas(&mut items);
+    items
+}
+
+pub fn to_unicode(items: &[GreekItem]) -> String {
+    let mut output = String::with_capacity(items.len() * 2);
+    for gi in items {
+        write!(output, "{}", gi).unwrap();
+    }
+    output
+}
+
+pub fn to_ascii(items: &[GreekItem]) -> String {
+    let mut output = String::with_capacity(items.len() * 2);
+    for gi in items {
+        gi.write_ascii(&mut output).unwrap();
+    }
+    output
+}
+
+/// Output scheme for Greek text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GreekStyle {
+    #[default]
+    Unicode,
+    /// The GCIDE ASCII transcription, as found in the source files.
+    Ascii,
+    /// TLG Beta Code, with lowercase letters and `*` marking capitals.
+    BetaCode,
+    /// ALA-LC romanization (accents dropped, breathings as `h`).
+    AlaLc,
+}
+
+impl FromStr for GreekStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<GreekStyle, String> {
+        match s {
+            "unicode" => Ok(GreekStyle::Unicode),
+            "ascii" => Ok(GreekStyle::Ascii),
+            "betacode" => Ok(GreekStyle::BetaCode),
+            "ala-lc" => Ok(GreekStyle::AlaLc),
+            _ => Err(format!("unknown greek style: {} (expected unicode, ascii, betacode or ala-lc)", s)),
+        }
+    }
+}
+
+pub fn render(items: &[GreekItem], style: GreekStyle) -> String {
+    match style {
+        GreekStyle::Unicode => to_unicode(items),
+        GreekStyle::Ascii => to_ascii(items),
+        GreekStyle::BetaCode => to_betacode(items),
+        GreekStyle::AlaLc => to_romanized(items),
+    }
+}
+
+pub fn to_betacode(items: &[GreekItem]) -> String {
+    let mut output = String::with_capacity(items.len() * 2);
+    for gi in items {
+        match *gi {
+            GreekItem::Letter(base, mods) => {
+                let letter = grktrans_to_betacode(base.to_ascii_lowercase());
+                let mut marks = String::new();
+                if mods.contains(GreekMods::SLENIS) {
+                    marks.push(')');
+                } else if mods.contains(GreekMods::SASPER) {
+                    marks.push('(');
+                }
+                if mods.contains(GreekMods::DIAERESIS) {
+                    marks.push('+');
+                }
+                if mods.contains(GreekMods::ACUTE) {
+                    marks.push('/');
+                } else if mods.contains(GreekMods::GRAVE) {
+                    marks.push('\\');
+                } else if mods.contains(GreekMods::CIRCUMFLEX) {
+                    marks.push('=');
+                }
+                if base.is_uppercase() {
+                    output.push('*');
+                    output.push_str(&marks);
+                    output.push(letter);
+                } else {
+                    output.push(letter);
+                    output.push_str(&marks);
+                }
+                if mods.contains(GreekMods::IOTASUB) {
+                    output.push('|');
+                }
+            }
+            GreekItem::Other(c) | GreekItem::Unknown(c) => output.push(c),
+        }
+    }
+    output
+}
+
+pub fn to_romanized(items: &[GreekItem]) -> String {
+    let mut output = String::with_capacity(items.len() * 2);
+    let mut prev: Option<char> = None;
+    let mut prev_start = 0;
+    for (idx, gi) in items.iter().enumerate() {
+        match *gi {
+            GreekItem::Letter(base, mods) => {
+                let lower = base.to_ascii_lowercase();
+                let next = match items.get(idx + 1) {
+                    Some(&GreekItem::Letter(next, _)) => Some(next.to_ascii_lowercase()),
+                    _ => None,
+                };
+                let mut roman = String::new();
+                if mods.contains(GreekMods::SASPER) && lower != 'r' {
+                    let in_diphthong = "iy".contains(lower) && prev.is_some_and(|p| "aehoy".contains(p));
+                    if in_diphthong {
+                        // breathing written on the second vowel goes before the diphthong
+                        let first = output[prev_start..].chars().next().unwrap();
+                        if first.is_uppercase() {
+                            let lowered: String = first.to_lowercase().collect();
+                            output.replace_range(prev_start..prev_start + first.len_utf8(), &lowered);
+                            output.insert(prev_start, 'H');
+                        } else {
+                            output.insert(prev_start, 'h');
+                        }
+                    } else {
+                        roman.push('h');
+                    }
+                }
+                roman.push_str(match lower {
+                    'g' if next.is_some_and(|n| "gkxc".contains(n)) => "n",
+                    'y' if prev.is_some_and(|p| "aeho".contains(p)) => "u",
+                    'y' if next == Some('i') => "u",
+                    'r' if mods.contains(GreekMods::SASPER) => "rh",
+                    _ => grktrans_to_romanized(lower),
+                });
+                if mods.contains(GreekMods::DIAERESIS) {
+                    roman = roman.replace('i', "\u{00ef}").replace('y', "\u{00ff}");
+                }
+                if mods.contains(GreekMods::IOTASUB) {
+                    roman.push('i');
+                }
+                let start = output.len();
+                if base.is_uppercase() {
+                    let mut chars = roman.chars();
+                    if let Some(first) = chars.next() {
+                        output.extend(first.to_uppercase());
+                        output.push_str(chars.as_str());
+                    }
+                } else {
+                    output.push_str(&roman);
+                }
+                prev = Some(lower);
+                prev_start = start;
+            }
+            GreekItem::Other(c) | GreekItem::Unknown(c) => {
+                output.push(c);
+                prev = None;
+            }
+        }
+    }
+    output
+}
+
+impl GreekItem {
+    /// The precomposed Unicode character for this item, if there is one.
+    pub fn to_unicode_char(&self) -> Option<char> {
+        match *self {
+            GreekItem::Letter(base, mods) => {
+                let mut letter = Some(grktrans_to_unicode(base, mods.contains(GreekMods::TERMINAL)));
+                let compose = |l_opt: Option<char>, m| l_opt.and_then(|l| unic_compose(l, m));
+                if mods.contains(GreekMods::SLENIS) {
+                    letter = compose(letter, '\u{0313}');
+                } else if mods.contains(GreekMods::SASPER) {
+                    letter = compose(letter, '\u{0314}');
+                }
+                if mods.contains(GreekMods::DIAERESIS) {
+                    letter = compose(letter, '\u{0308}');
+                }
+                if mods.contains(GreekMods::ACUTE) {
+                    letter = compose(letter, '\u{0301}');
+                } else if mods.contains(GreekMods::GRAVE) {
+                    letter = compose(letter, '\u{0300}');
+                } else if mods.contains(GreekMods::CIRCUMFLEX) {
+                    letter = compose(letter, '\u{0342}');
+                }
+                if mods.contains(GreekMods::IOTASUB) {
+                    letter = compose(letter, '\u{0345}');
+                }
+                letter
+            }
+            GreekItem::Other(c) | GreekItem::Unknown(c) => Some(c),
+        }
+    }
+
+    pub fn write_ascii<W: Write>(&self, w: &mut W) -> fmt::Result {
+        match *self {
+            GreekItem::Letter(base, mods) => {
+                if mods.contains(GreekMods::SLENIS) {
+                    w.write_char('\'')?;
+                } else if mods.contains(GreekMods::SASPER) {
+                    w.write_char('"')?;
+                }
+                w.write_char(base)?;
+                if mods.contains(GreekMods::DIAERESIS) {
+                    w.write_char(':')?;
+                }
+                if mods.contains(GreekMods::ACUTE) {
+                    w.write_char('`')?;
+                } else if mods.contains(GreekMods::GRAVE) {
+                    w.write_char('~')?;
+                } else if mods.contains(GreekMods::CIRCUMFLEX) {
+                    w.write_char('^')?;
+                }
+                if mods.contains(GreekMods::IOTASUB) {
+                    w.write_char(',')?;
+                }
+                Ok(())
+            }
+            GreekItem::Other(c) | GreekItem::Unknown(c) => w.write_char(c),
+        }
+    }
+}
+
+impl Display for GreekItem {
+    // letters that fail to compose are reported by `diagnostics::check_entry`
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_char(self.to_unicode_char().unwrap_or('\u{fffd}'))
+    }
+}
+
+pub(crate) fn mark_terminal_sigmas(items: &mut [GreekItem]) {
+    use self::GreekItem::Letter;
+    let mut iter = items.iter_mut().peekable();
+    while let Some(gi) = iter.next() {
+        if let Letter('s', ref mut mods) = *gi {
+            match iter.peek() {
+                Some(&&mut Letter(..)) => (),
+                _ => *mods |= GreekMods::TERMINAL,
+            }
+        }
+    }
+}
+
+named!(pub(crate) grk_item<CompleteStr, GreekItem>,
+       alt!(grk_letter | grk_other | grk_unknown));
+
+named!(grk_letter<CompleteStr, GreekItem>,
+       do_parse!(
+           pre: opt!(one_of!("'\"")) >>
+           base: grk_letter_base >>
+           post: many0!(one_of!("`~^:")) >>
+           // only vowels taking an iota subscript; a comma elsewhere is punctuation
+           iota: cond!("ahwAHW".contains(base), opt!(char!(','))) >>
+           // the subscript is found before the accents too
+           post_iota: many0!(one_of!("`~^:")) >>
+           ({
+               let mut mods = GreekMods::empty();
+               match pre {
+                   Some('\'') => mods |= GreekMods::SLENIS,
+                   Some('"') => mods |= GreekMods::SASPER,
+                   Some(_) => unreachable!(),
+                   None => (),
+               }
+               for m in post.into_iter().chain(post_iota) {
+                   match m {
+                       '`' => mods |= GreekMods::ACUTE,
+                       '~' => mods |= GreekMods::GRAVE,
+                       '^' => mods |= GreekMods::CIRCUMFLEX,
+                       ':' => mods |= GreekMods::DIAERESIS,
+                       _ => unreachable!(),
+                   }
+               }
+               if let Some(Some(_)) = iota {
+                   mods |= GreekMods::IOTASUB;
+               }
+               GreekItem::Letter(base, mods)
+           })));
+
+named!(grk_other<CompleteStr, GreekItem>,
+       map!(one_of!(" -"), GreekItem::Other));
+
+named!(grk_unknown<CompleteStr, GreekItem>,
+       map!(none_of!("<"), GreekItem::Unknown));
+
+named!(grk_letter_base<CompleteStr, char>, one_of!("abcdefghijklmnopqrstvwxyzABCDEFGHIJKLMNOPQRSTWXYZ"));
+
+/// Transcribed Greek in ASCII (per GCIDE spec) to Unicode Greek character.
+pub fn grktrans_to_unicode(trans: char, is_terminal: bool) -> char {
+    match trans {
+        'a' => '\u{03b1}', 'b' => '\u{03b2}',
+        'g' => '\u{03b3}', 'd' => '\u{03b4}',
+        'e' => '\u{03b5}', 'z' => '\u{03b6}',
+        'h' => '\u{03b7}', 'q' => '\u{03b8}',
+        'i' => '\u{03b9}', 'k' => '\u{03ba}',
+        'l' => '\u{03bb}', 'm' => '\u{03bc}',
+        'n' => '\u{03bd}', 'x' => '\u{03be}',
+        'o' => '\u{03bf}', 'p' => '\u{03c0}',
+        'r' => '\u{03c1}', 's' => if is_terminal { '\u{03c2}' } else { '\u{03c3}' },
+        't' => '\u{03c4}', 'y' => '\u{03c5}',
+        'f' => '\u{03c6}', 'c' => '\u{03c7}',
+        'j' => '\u{03c8}', 'w' => '\u{03c9}',
+        'v' => '\u{03dd}',
+        'A' => '\u{0391}', 'B' => '\u{0392}',
+        'G' => '\u{0393}', 'D' => '\u{0394}',
+        'E' => '\u{0395}', 'Z' => '\u{0396}',
+        'H' => '\u{0397}', 'Q' => '\u{0398}',
+        'I' => '\u{0399}', 'K' => '\u{039a}',
+        'L' => '\u{039b}', 'M' => '\u{039c}',
+        'N' => '\u{039d}', 'X' => '\u{039e}',
+        'O' => '\u{039f}', 'P' => '\u{03a0}',
+        'R' => '\u{03a1}', 'S' => '\u{03a3}',
+        'T' => '\u{03a4}', 'Y' => '\u{03a5}',
+        'F' => '\u{03a6}', 'C' => '\u{03a7}',
+        'J' => '\u{03a8}', 'W' => '\u{03a9}',
+        _   => '\u{fffd}',
+    }
+}
+
+fn grktrans_to_betacode(trans: char) -> char {
+    match trans {
+        'x' => 'c', 'c' => 'x',
+        'j' => 'y', 'y' => 'u',
+        other => other,
+    }
+}
+
+fn grktrans_to_romanized(trans: char) -> &'static str {
+    match trans {
+        'a' => "a", 'b' => "b", 'g' => "g", 'd' => "d",
+        'e' => "e", 'z' => "z", 'h' => "\u{0113}", 'q' => "th",
+        'i' => "i", 'k' => "k", 'l' => "l", 'm' => "m",
+        'n' => "n", 'x' => "x", 'o' => "o", 'p' => "p",
+        'r' => "r", 's' => "s", 't' => "t", 'y' => "y",
+        'f' => "ph", 'c' => "ch", 'j' => "ps", 'w' => "\u{014d}",
+        'v' => "w",
+        _   => "\u{fffd}",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let trans = "\"h`lios lo`gos 'a`bax";
+        let items = parse_greek(trans);
+        assert_eq!(trans, to_ascii(&items));
+        assert_eq!("\u{1f25}λιος λόγος \u{1f04}βαξ", to_unicode(&items));
+    }
+
+    #[test]
+    fn transliterations() {
+        let items = parse_greek("\"h`lios 'A`ggelos a\"y`ra");
+        assert_eq!("h(/lios *)/aggelos au(/ra", to_betacode(&items));
+        assert_eq!("h\u{0113}lios Angelos haura", to_romanized(&items));
+    }
+
+    #[test]
+    fn unknown_chars() {
+        let items = parse_greek("a1");
+        assert_eq!(vec![GreekItem::Letter('a', GreekMods::empty()), GreekItem::Unknown('1')], items);
+        let items = parse_greek("*lo`gos, 2");
+        assert_eq!("*λόγος, 2", to_unicode(&items));
+        assert_eq!("*lo`gos, 2", to_ascii(&items));
+    }
+
+    #[test]
+    fn iota_subscript() {
+        let accent_first = parse_greek("tw^, lo`gw,");
+        assert_eq!(accent_first, parse_greek("tw,^ lo`gw,"));
+        assert_eq!(accent_first[1], GreekItem::Letter('w', GreekMods::CIRCUMFLEX | GreekMods::IOTASUB));
+        assert_eq!("τ\u{1ff7} λόγ\u{1ff3}", to_unicode(&accent_first));
+        assert_eq!("tw^, lo`gw,", to_ascii(&accent_first));
+    }
+}
@@ -0,0 +1,196 @@
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+use exporter::entity_to_unicode;
+use pronunciation::Stress;
+
+/// Normalizes a headword (raw CIDE markup allowed) for lookups: entities are
+/// reduced to base letters, markup and syllable/stress marks are removed, and
+/// the result is case-folded with whitespace collapsed.
+pub fn normalize_headword(word: &str) -> String {
+    let plain = markup_to_text(word);
+    let mut normalized = String::with_capacity(plain.len());
+    for c in plain.nfd() {
+        match c {
+            '*' | '"' | '`' | '\u{b7}' | '\u{2032}' | '\u{2033}' | '\u{fffd}' => (),
+            c if is_combining_mark(c) => (),
+            '\u{2019}' => normalized.push('\''), // as rendered by the plain-text exporter
+            c if c.is_whitespace() => {
+                if !normalized.is_empty() && !normalized.ends_with(' ') {
+                    normalized.push(' ');
+                }
+            }
+            c => match fold_ligature(c) {
+                Some(s) => normalized.push_str(s),
+                None => normalized.extend(c.to_lowercase()),
+            },
+        }
+    }
+    let trimmed_len = normalized.trim_end().len();
+    normalized.truncate(trimmed_len);
+    normalized
+}
+
+/// Syllable of a headword, as marked by `*` (a break), `"` (a break after a
+/// syllable with primary stress) or `` ` `` (secondary stress).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Syllable {
+    pub text: String,
+    pub stress: Option<Stress>,
+    /// Spaces or hyphens between this syllable and the next.
+    pub after: String,
+}
+
+/// Splits a headword (raw CIDE markup allowed) into syllables.
+pub fn syllables(word: &str) -> Vec<Syllable> {
+    split_syllables(&markup_to_text(word), false)
+}
+
+/// Like `syllables`, but keeps entities and tags as written in the text of the
+/// syllables, for rewriting headwords in CIDE markup.
+pub(crate) fn markup_syllables(word: &str) -> Vec<Syllable> {
+    split_syllables(word, true)
+}
+
+/// Splits `word` into syllables, taking tags and entities as letters if
+/// `keep_markup` is set.
+fn split_syllables(word: &str, keep_markup: bool) -> Vec<Syllable> {
+    let new_syllable = || Syllable { text: String::new(), stress: None, after: String::new() };
+    let mut syllables = vec![new_syllable()];
+    let mut rest = word.trim();
+    while let Some(c) = rest.chars().next() {
+        let len = if keep_markup && c == '<' { markup_len(rest) } else { c.len_utf8() };
+        let (unit, next) = rest.split_at(len);
+        rest = next;
+        let stress = match c {
+            '"' | '\u{2032}' => Some(Stress::Primary),
+            '`' | '\u{2033}' => Some(Stress::Secondary),
+            _ => None,
+        };
+        let last = syllables.last_mut().unwrap();
+        if c == '*' || c == '\u{b7}' || stress.is_some() {
+            last.stress = last.stress.or(stress);
+            if !last.text.is_empty() {
+                syllables.push(new_syllable());
+            }
+        } else if c.is_whitespace() || c == '-' {
+            last.after.push(c);
+        } else if !last.after.is_empty() {
+            syllables.push(Syllable { text: unit.to_string(), ..new_syllable() });
+        } else {
+            last.text.push_str(unit);
+        }
+    }
+    if syllables.last().is_some_and(|syllable| syllable.text.is_empty()) && syllables.len() > 1 {
+        syllables.pop();
+    }
+    syllables
+}
+
+/// The headword as it should be displayed: markup rendered, syllable and stress
+/// marks removed, e.g. `Dictionary` for `Dic"tion*a*ry`.
+pub fn display_form(word: &str) -> String {
+    join_syllables(&syllables(word), "")
+}
+
+/// The text of `syllables` with `separator` between those not already
+/// separated by a space or hyphen.
+pub fn join_syllables(syllables: &[Syllable], separator: &str) -> String {
+    let mut joined = String::new();
+    for (idx, syllable) in syllables.iter().enumerate() {
+        joined.push_str(&syllable.text);
+        if syllable.after.is_empty() && idx + 1 < syllables.len() {
+            joined.push_str(separator);
+        }
+        joined.push_str(&syllable.after);
+    }
+    joined
+}
+
+/// Renders entities and drops tags.
+pub(crate) fn markup_to_text(word: &str) -> String {
+    let mut plain = String::with_capacity(word.len());
+    let mut rest = word;
+    while let Some(idx) = rest.find('<') {
+        plain.push_str(&rest[..idx]);
+        rest = &rest[idx..];
+        let markup_len = markup_len(rest);
+        let markup = &rest[..markup_len];
+        if !markup.starts_with("</") && markup.ends_with('/') {
+            plain.push_str(entity_to_unicode(&markup[1..markup_len - 1]));
+        }
+        rest = &rest[markup_len..];
+    }
+    plain.push_str(rest);
+    plain
+}
+
+/// Length of the tag or entity at the start of `text`.
+fn markup_len(text: &str) -> usize {
+    let markup_end = if text.starts_with("</") { text.find('>') } else { text.find(['/', '>']) };
+    markup_end.map(|i| i + 1).unwrap_or(text.len())
+}
+
+/// Sort key following dictionary order: letters and digits first, ignoring
+/// spaces, hyphens and apostrophes, with the normalized form as tie-breaker.
+pub fn collation_key(word: &str) -> String {
+    let normalized = normalize_headword(word);
+    let mut key: String = normalized.chars().filter(|c| c.is_alphanumeric()).collect();
+    key.push('\0');
+    key.push_str(&normalized);
+    key
+}
+
+/// The part of a `collation_key` before its tie-breaker: the letters and
+/// digits of the word.
+pub fn key_letters(key: &str) -> &str {
+    key.split('\0').next().unwrap_or("")
+}
+
+fn fold_ligature(c: char) -> Option<&'static str> {
+    match c {
+        '\u{e6}' | '\u{c6}' => Some("ae"),
+        '\u{153}' | '\u{152}' => Some("oe"),
+        '\u{1e3}' | '\u{1e2}' => Some("ae"),
+        '\u{df}' => Some("ss"),
+        '\u{fe}' | '\u{de}' => Some("th"),
+        '\u{f0}' | '\u{d0}' => Some("dh"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn normalize() {
+        assert_eq!("dictionary", normalize_headword("Dic\"tion*a*ry"));
+        assert_eq!("aeon", normalize_headword("<AE/on"));
+        assert_eq!("cafe au lait", normalize_headword("Caf<eacute/  au lait "));
+        assert_eq!("o'clock", normalize_headword("<b>O'clock</b>"));
+    }
+
+    #[test]
+    fn syllabify() {
+        let stressed = |text: &str, stress| Syllable { text: text.into(), stress, after: String::new() };
+        assert_eq!(syllables("Dic\"tion*a*ry"), [stressed("Dic", Some(Stress::Primary)), stressed("tion", None),
+                                                 stressed("a", None), stressed("ry", None)]);
+        assert_eq!(display_form("Ab\"a*cus"), "Abacus");
+        assert_eq!(display_form("Caf<eacute/ au lait"), "Caf\u{e9} au lait");
+        let pinned = syllables("Bar\"ley-corn`");
+        assert_eq!(pinned[1], Syllable { text: "ley".into(), stress: None, after: "-".into() });
+        assert_eq!(pinned[2], stressed("corn", Some(Stress::Secondary)));
+        assert_eq!(join_syllables(&markup_syllables("C<ae/*sar-like"), "\u{b7}"), "C<ae/\u{b7}sar-like");
+    }
+
+    #[test]
+    fn collation() {
+        let mut words = vec!["A-bas", "Abaca", "Ab\"a*cus", "a", "A 1"];
+        words.sort_by_key(|w| collation_key(w));
+        assert_eq!(vec!["a", "A 1", "Abaca", "Ab\"a*cus", "A-bas"], words);
+    }
+}
@@ -0,0 +1,69 @@
+//! Entry identifiers that stay the same across GCIDE releases as long as the
+//! headword, part of speech and order of homographs do.
+
+use std::collections::BTreeMap;
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use headword::normalize_headword;
+use model;
+use parser::Entry;
+
+/// Identifier of the `ordinal`th (from 1) entry for `headword` and `pos`:
+/// `e` and the 64-bit FNV-1a hash of their normalized forms in hex, which is
+/// usable as an XML or HTML id.
+pub fn entry_id(headword: &str, pos: Option<&str>, ordinal: usize) -> String {
+    format!("e{:016x}", fnv1a(id_key(headword, pos, ordinal).as_bytes()))
+}
+
+fn id_key(headword: &str, pos: Option<&str>, ordinal: usize) -> String {
+    let pos: String = pos.unwrap_or("").chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect();
+    format!("{}\u{1f}{}\u{1f}{}", normalize_headword(headword), pos, ordinal)
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// Assigns ids to entries in file order, counting the ordinals of homographs.
+#[derive(Debug, Default)]
+pub struct EntryIds {
+    seen: BTreeMap<String, usize>,
+}
+
+impl EntryIds {
+    pub fn new() -> EntryIds {
+        EntryIds::default()
+    }
+
+    /// The id of the next entry, keyed by its main word and first `<pos>`.
+    pub fn next_id(&mut self, entry: &Entry) -> String {
+        let pos = model::first_pos(entry);
+        let key = id_key(entry.main_word, pos.as_deref(), 0);
+        let ordinal = self.seen.entry(key).or_insert(0);
+        *ordinal += 1;
+        entry_id(entry.main_word, pos.as_deref(), *ordinal)
+    }
+
+    pub fn assign(&mut self, entry: &mut Entry) {
+        entry.id = Some(self.next_id(entry));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::EntryParser;
+
+    #[test]
+    fn homograph_ordinals() {
+        assert_eq!(entry_id("Ab\"a*cus", Some("n."), 1), entry_id("abacus", Some("N"), 1));
+        assert_eq!(entry_id("abacus", Some("n."), 1), "e7591f8017c10147b");
+
+        let text = "<entry main-word=\"Bear\" source=\"1913 Webster\"><p><hw>Bear</hw>, <pos>n.</pos></p></entry>\n\
+                    <entry main-word=\"Bear\" source=\"1913 Webster\"><p><hw>Bear</hw>, <pos>v. t.</pos></p></entry>\n\
+                    <entry main-word=\"Bear\" source=\"1913 Webster\"><p><hw>Bear</hw>, <pos>n.</pos></p></entry>\n";
+        let ids: Vec<_> = EntryParser::new(text).with_ids().map(|entry| entry.unwrap().id.unwrap()).collect();
+        assert_eq!(ids, [entry_id("bear", Some("n."), 1), entry_id("bear", Some("v.t."), 1), entry_id("bear", Some("n."), 2)]);
+    }
+}
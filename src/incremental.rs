@@ -0,0 +1,110 @@
+use std::ops::Range;
+
+use parser::{Entry, EntryParser, ParserError};
+
+/// Entry block layout of a CIDE document, kept in sync across edits so that
+/// only the entries touched by an edit need to be parsed again.
+#[derive(Clone, Debug, Default)]
+pub struct Document {
+    spans: Vec<Range<usize>>,
+}
+
+/// A replacement of `range` (byte offsets in the text before the edit) by
+/// `new_len` bytes of new text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub new_len: usize,
+}
+
+pub type ParseResult<'a> = Result<Entry<'a>, ParserError<'a>>;
+
+impl Document {
+    pub fn parse(contents: &str) -> (Document, Vec<ParseResult<'_>>) {
+        let (spans, results) = scan(contents, 0);
+        (Document { spans }, results)
+    }
+
+    /// Byte ranges of the entry blocks, in document order.
+    pub fn spans(&self) -> &[Range<usize>] {
+        &self.spans
+    }
+
+    /// Updates the layout for an edit already applied to `new_contents`, and
+    /// returns the re-parsed entries along with their indices in `spans()`.
+    /// All other entries keep their previous parse results (shifted by the
+    /// change in length, if they come after the edit).
+    ///
+    /// An entry left without `</entry>` by the edit is reported as an error
+    /// without swallowing the entries that follow it.
+    pub fn reparse<'a>(&mut self, new_contents: &'a str, edit: &TextEdit) -> Vec<(usize, ParseResult<'a>)> {
+        let removed = edit.range.end - edit.range.start;
+        let first = self.spans.iter().position(|s| s.end >= edit.range.start).unwrap_or(self.spans.len());
+        let last = first + self.spans[first..].iter().take_while(|s| s.start <= edit.range.end).count();
+
+        let region_start = if first > 0 { self.spans[first - 1].end } else { 0 };
+        let region_end = match self.spans.get(last) {
+            Some(next) => next.start + edit.new_len - removed,
+            None => new_contents.len(),
+        };
+        let (new_spans, results) = scan(&new_contents[region_start..region_end], region_start);
+
+        for span in &mut self.spans[last..] {
+            span.start = span.start + edit.new_len - removed;
+            span.end = span.end + edit.new_len - removed;
+        }
+        let count = new_spans.len();
+        self.spans.splice(first..last, new_spans);
+        (first..first + count).zip(results).collect()
+    }
+}
+
+fn scan(contents: &str, offset: usize) -> (Vec<Range<usize>>, Vec<ParseResult<'_>>) {
+    let mut spans = Vec::new();
+    let mut results = Vec::new();
+    let mut entry_iter = EntryParser::new(contents);
+    while let Some(entry_res) = entry_iter.next() {
+        let span = entry_iter.last_span().unwrap();
+        spans.push(span.start + offset..span.end + offset);
+        results.push(entry_res);
+    }
+    (spans, results)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ENTRY_A: &str = "<entry main-word=\"A\" source=\"\"><p><hw>A</hw></p></entry>";
+    const ENTRY_B: &str = "<entry main-word=\"B\" source=\"\"><p><hw>B</hw></p></entry>";
+
+    #[test]
+    fn edit_inside_entry() {
+        let old = format!("{}\n\n{}\n\n{}\n", ENTRY_A, ENTRY_B, ENTRY_A);
+        let (mut doc, results) = Document::parse(&old);
+        assert_eq!(3, results.len());
+
+        let edit_at = old.find("<hw>B").unwrap() + 4;
+        let new = format!("{}Bee{}", &old[..edit_at], &old[edit_at + 1..]);
+        let reparsed = doc.reparse(&new, &TextEdit { range: edit_at..edit_at + 1, new_len: 3 });
+        assert_eq!(1, reparsed.len());
+        assert_eq!(1, reparsed[0].0);
+        assert_eq!("B", reparsed[0].1.as_ref().unwrap().main_word);
+
+        let (fresh, _) = Document::parse(&new);
+        assert_eq!(fresh.spans(), doc.spans());
+    }
+
+    #[test]
+    fn insert_new_entry() {
+        let old = format!("{}\n\n{}\n", ENTRY_A, ENTRY_A);
+        let (mut doc, _) = Document::parse(&old);
+        let insert_at = ENTRY_A.len() + 1;
+        let inserted = format!("\n{}\n", ENTRY_B);
+        let new = format!("{}{}{}", &old[..insert_at], inserted, &old[insert_at..]);
+        let reparsed = doc.reparse(&new, &TextEdit { range: insert_at..insert_at, new_len: inserted.len() });
+        assert!(reparsed.iter().any(|(_, res)| res.as_ref().unwrap().main_word == "B"));
+        let (fresh, _) = Document::parse(&new);
+        assert_eq!(fresh.spans(), doc.spans());
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use fst::{self, IntoStreamer, Map, MapBuilder, Streamer};
+use fst::automaton::{Automaton, Levenshtein, Str};
+
+use headword::normalize_headword;
+use parser::EntryParser;
+
+const MAGIC: &[u8; 8] = b"GCIDEIDX";
+const VERSION: u32 = 1;
+
+/// Edits allowed between a word and the headwords suggested for it.
+const MAX_SUGGESTION_DISTANCE: u32 = 2;
+
+/// Finite-state index from normalized headwords to entry locations.
+///
+/// Locations are opaque `u64`s chosen by the caller; `IndexBuilder::add_file`
+/// uses `location(file_no, byte_offset)`.
+pub struct HeadwordIndex {
+    map: Map<Vec<u8>>,
+    postings: Vec<Vec<u64>>,
+}
+
+#[derive(Default)]
+pub struct IndexBuilder {
+    words: BTreeMap<String, Vec<u64>>,
+}
+
+#[derive(Debug)]
+pub enum IndexError {
+    Io(io::Error),
+    Fst(fst::Error),
+    Format(&'static str),
+}
+
+pub fn location(file_no: u32, offset: usize) -> u64 {
+    (u64::from(file_no) << 40) | offset as u64
+}
+
+/// Splits a location made by `location` into file number and byte offset.
+pub fn split_location(loc: u64) -> (u32, usize) {
+    ((loc >> 40) as u32, (loc & ((1 << 40) - 1)) as usize)
+}
+
+impl IndexBuilder {
+    pub fn new() -> IndexBuilder {
+        IndexBuilder::default()
+    }
+
+    pub fn insert(&mut self, headword: &str, loc: u64) {
+        let key = normalize_headword(headword);
+        if !key.is_empty() {
+            self.words.entry(key).or_default().push(loc);
+        }
+    }
+
+    /// Indexes the main word of every parsable entry in a CIDE file.
+    pub fn add_file(&mut self, file_no: u32, contents: &str) {
+        let mut entry_iter = EntryParser::new(contents);
+        while let Some(entry_res) = entry_iter.next() {
+            if let Ok(entry) = entry_res {
+                let span = entry_iter.last_span().unwrap();
+                self.insert(entry.main_word, location(file_no, span.start));
+            }
+        }
+    }
+
+    pub fn finish(self) -> HeadwordIndex {
+        let mut builder = MapBuilder::memory();
+        let mut postings = Vec::with_capacity(self.words.len());
+        for (key, locs) in self.words {
+            builder.insert(key, postings.len() as u64).unwrap(); // keys are sorted and unique
+            postings.push(locs);
+        }
+        let map = Map::new(builder.into_inner().unwrap()).unwrap();
+        HeadwordIndex { map, postings }
+    }
+}
+
+impl HeadwordIndex {
+    pub fn get(&self, word: &str) -> &[u64] {
+        match self.map.get(normalize_headword(word)) {
+            Some(idx) => &self.postings[idx as usize],
+            None => &[],
+        }
+    }
+
+    pub fn prefix(&self, prefix: &str) -> Vec<(String, &[u64])> {
+        let prefix = normalize_headword(prefix);
+        self.collect(self.map.search(Str::new(&prefix).starts_with()).into_stream())
+    }
+
+    /// Headwords within `distance` edits of `word`.
+    pub fn fuzzy(&self, word: &str, distance: u32) -> Result<Vec<(String, &[u64])>, IndexError> {
+        let word = normalize_headword(word);
+        let automaton = Levenshtein::new(&word, distance)
+            .map_err(|_| IndexError::Format("query too large for fuzzy search"))?;
+        Ok(self.collect(self.map.search(automaton).into_stream()))
+    }
+
+    /// Up to `limit` headwords close to `word`, for when it is not found: those
+    /// within `MAX_SUGGESTION_DISTANCE` edits, nearest first, then in index
+    /// order. `word` itself is left out.
+    pub fn suggest(&self, word: &str, limit: usize) -> Vec<String> {
+        let normalized = normalize_headword(word);
+        // large distances make the automaton too big for long words
+        let candidates = (1..=MAX_SUGGESTION_DISTANCE).rev()
+            .filter_map(|distance| self.fuzzy(&normalized, distance).ok())
+            .next()
+            .unwrap_or_default();
+        let mut ranked: Vec<(usize, String)> = candidates.into_iter()
+            .filter(|(key, _)| *key != normalized)
+            .map(|(key, _)| (edit_distance(&key, &normalized), key))
+            .collect();
+        ranked.sort();
+        ranked.into_iter().take(limit).map(|(_, key)| key).collect()
+    }
+
+    /// Headwords in the half-open range `[from, to)` of normalized forms.
+    pub fn range(&self, from: &str, to: &str) -> Vec<(String, &[u64])> {
+        let from = normalize_headword(from);
+        let to = normalize_headword(to);
+        self.collect(self.map.range().ge(from).lt(to).into_stream())
+    }
+
+    pub fn len(&self) -> usize {
+        self.postings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.postings.is_empty()
+    }
+
+    fn collect<S>(&self, mut stream: S) -> Vec<(String, &[u64])>
+    where S: for<'k> Streamer<'k, Item = (&'k [u8], u64)> {
+        let mut results = Vec::new();
+        while let Some((key, idx)) = stream.next() {
+            results.push((String::from_utf8_lossy(key).into_owned(), &self.postings[idx as usize][..]));
+        }
+        results
+    }
+
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let fst_bytes = self.map.as_fst().as_bytes();
+        w.write_all(MAGIC)?;
+        w.write_all(&VERSION.to_le_bytes())?;
+        w.write_all(&(fst_bytes.len() as u64).to_le_bytes())?;
+        w.write_all(fst_bytes)?;
+        w.write_all(&(self.postings.len() as u64).to_le_bytes())?;
+        for locs in &self.postings {
+            w.write_all(&(locs.len() as u32).to_le_bytes())?;
+            for loc in locs {
+                w.write_all(&loc.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<HeadwordIndex, IndexError> {
+        let mut reader = ByteReader(bytes);
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(IndexError::Format("not a headword index"));
+        }
+        if reader.u32()? != VERSION {
+            return Err(IndexError::Format("unsupported index version"));
+        }
+        let fst_len = reader.u64()? as usize;
+        let map = Map::new(reader.take(fst_len)?.to_vec()).map_err(IndexError::Fst)?;
+        let count = reader.u64()? as usize;
+        if count != map.len() {
+            return Err(IndexError::Format("postings do not match headwords"));
+        }
+        let mut stream = map.stream();
+        while let Some((_, idx)) = stream.next() {
+            if idx >= count as u64 {
+                return Err(IndexError::Format("postings do not match headwords"));
+            }
+        }
+        let mut postings = Vec::with_capacity(count.min(bytes.len()));
+        for _ in 0..count {
+            let len = reader.u32()? as usize;
+            let mut locs = Vec::with_capacity(len.min(bytes.len()));
+            for _ in 0..len {
+                locs.push(reader.u64()?);
+            }
+            postings.push(locs);
+        }
+        Ok(HeadwordIndex { map, postings })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = io::BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<HeadwordIndex, IndexError> {
+        let mut bytes = Vec::new();
+        File::open(path)?.read_to_end(&mut bytes)?;
+        HeadwordIndex::from_bytes(&bytes)
+    }
+}
+
+/// Levenshtein distance between `a` and `b`, in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + (ca != cb) as usize;
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+pub(crate) struct ByteReader<'a>(pub &'a [u8]);
+
+impl<'a> ByteReader<'a> {
+    pub fn take(&mut self, len: usize) -> Result<&'a [u8], IndexError> {
+        if self.0.len() < len {
+            return Err(IndexError::Format("truncated index"));
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, IndexError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, IndexError> {
+        let mut buf = [0; 2];
+        buf.copy_from_slice(self.take(2)?);
+        Ok(u16::from_le_bytes(buf))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, IndexError> {
+        let mut buf = [0; 4];
+        buf.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, IndexError> {
+        let mut buf = [0; 8];
+        buf.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(buf))
+    }
+}
+
+impl From<io::Error> for IndexError {
+    fn from(err: io::Error) -> IndexError {
+        IndexError::Io(err)
+    }
+}
+
+impl Display for IndexError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            IndexError::Io(ref err) => write!(f, "{}", err),
+            IndexError::Fst(ref err) => write!(f, "{}", err),
+            IndexError::Format(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn queries() {
+        let mut builder = IndexBuilder::new();
+        builder.insert("Ab\"a*cus", 1);
+        builder.insert("Abase", 2);
+        builder.insert("Abbot", 3);
+        builder.insert("abase", 4);
+        let index = builder.finish();
+        let mut bytes = Vec::new();
+        index.write_to(&mut bytes).unwrap();
+        let index = HeadwordIndex::from_bytes(&bytes).unwrap();
+        assert_eq!(&[2, 4], index.get("ABASE"));
+        assert_eq!(vec!["abacus", "abase"], index.prefix("aba").into_iter().map(|r| r.0).collect::<Vec<_>>());
+        assert_eq!(vec!["abbot"], index.fuzzy("abot", 1).unwrap().into_iter().map(|r| r.0).collect::<Vec<_>>());
+        assert_eq!(vec!["abase", "abacus"], index.suggest("Abas", 5));
+        assert_eq!(vec!["abbot"], index.suggest("abot", 1));
+        assert_eq!(vec!["abacus"], index.suggest("abacuss", 1));
+        assert!(index.suggest("abase", 5).is_empty());
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(vec!["abase", "abbot"], index.range("abase", "abc").into_iter().map(|r| r.0).collect::<Vec<_>>());
+
+        let count_at = bytes.len() - 3 * 4 - 4 * 8 - 8;
+        bytes[count_at..count_at + 8].copy_from_slice(&0u64.to_le_bytes());
+        assert!(matches!(HeadwordIndex::from_bytes(&bytes[..count_at + 8]), Err(IndexError::Format(_))));
+    }
+}
@@ -1,20 +1,138 @@
+//! With the default `std` feature off, the parser, the diagnostics and most
+//! exporters build with `no_std` and `alloc`. This needs a nightly compiler,
+//! since nom 4 only supports `alloc` there.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
+
 #[macro_use]
 extern crate nom;
 
 #[cfg(feature = "binaries")]
-#[macro_use]
 extern crate structopt;
 
+#[cfg(feature = "binaries")]
+extern crate serde_json;
+
+#[cfg(all(feature = "binaries", unix))]
+extern crate libc;
+
 #[macro_use]
 extern crate bitflags;
 
 extern crate unicode_normalization;
 
+extern crate memchr;
+
+#[cfg(feature = "fst")]
+extern crate fst;
+
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+
+#[cfg(feature = "templates")]
+extern crate handlebars;
+
+#[cfg(feature = "mmap")]
+extern crate memmap2;
+
+#[cfg(feature = "encoding_rs")]
+extern crate encoding_rs;
+
+#[cfg(feature = "regex")]
+extern crate regex;
+
+#[cfg(feature = "toml")]
+extern crate toml;
+
+/// Stands in for `std` without the `std` feature, so that modules can keep
+/// importing from `std`.
+#[cfg(not(feature = "std"))]
+mod std {
+    pub use core::{fmt, iter, mem, ops, slice, str};
+    pub use alloc::{borrow, collections, sync, vec};
+
+    pub mod prelude {
+        pub mod v1 {
+            pub use alloc::borrow::ToOwned;
+            pub use alloc::boxed::Box;
+            pub use alloc::string::{String, ToString};
+            pub use alloc::vec::Vec;
+        }
+    }
+}
+
 #[cfg(feature = "binaries")]
 pub mod binutils;
 
 pub mod parser;
+pub mod arena;
+#[cfg(feature = "std")]
+pub mod authors;
+#[cfg(feature = "std")]
+pub mod builder;
+#[cfg(all(feature = "std", feature = "encoding_rs"))]
+pub mod decode;
 pub mod exporter;
+pub mod diagnostics;
+#[cfg(feature = "std")]
+pub mod diff;
+#[cfg(feature = "std")]
+pub mod duplicates;
+pub mod edit;
+pub mod entities;
+pub mod forms;
+pub mod greek;
+pub mod headword;
+pub mod ids;
+#[cfg(feature = "std")]
+pub mod manifest;
+#[cfg(feature = "std")]
+pub mod incremental;
+#[cfg(feature = "std")]
+pub mod merge;
+pub mod model;
+pub mod mojibake;
+#[cfg(feature = "std")]
+pub mod normalize;
+#[cfg(feature = "std")]
+pub mod parallel;
+#[cfg(feature = "std")]
+pub mod pipeline;
+pub mod phonetic;
+pub mod pronunciation;
+pub mod query;
+#[cfg(feature = "rules")]
+pub mod rules;
+pub mod scan;
+#[cfg(feature = "std")]
+pub mod sort;
+#[cfg(feature = "std")]
+pub mod sources;
+pub mod tags;
+#[cfg(feature = "std")]
+pub mod taxa;
+pub mod todos;
+#[cfg(feature = "std")]
+pub mod visit;
+pub mod web1913;
+#[cfg(feature = "std")]
+pub mod wordnet;
+#[cfg(all(feature = "std", feature = "fst"))]
+pub mod dictionary;
+#[cfg(feature = "std")]
+pub mod dictzip;
+#[cfg(all(feature = "std", feature = "fst"))]
+pub mod index;
+#[cfg(feature = "fulltext")]
+pub mod fulltext;
 
 pub use parser::{Entry, EntryParser};
+pub use diagnostics::ParseWarning;
 pub use exporter::CIDE;
+#[cfg(all(feature = "std", feature = "fst"))]
+pub use dictionary::Dictionary;
@@ -0,0 +1,152 @@
+//! Manifests describing exported files, so that packagers can check and
+//! reproduce builds.
+
+use std::collections::BTreeMap;
+
+use parser::EntryParser;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Manifest {
+    /// Name and version of the program that made the outputs.
+    pub generator: String,
+    pub source: SourceInfo,
+    pub outputs: Vec<FileInfo>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct SourceInfo {
+    pub file: FileInfo,
+    /// GCIDE version, as stated in the comments before the first entry.
+    pub version: Option<String>,
+    pub entries: usize,
+    /// Entries by the upper-cased first letter of their main word.
+    pub entries_per_letter: BTreeMap<String, usize>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct FileInfo {
+    pub name: String,
+    pub bytes: usize,
+    pub sha256: String,
+}
+
+impl FileInfo {
+    pub fn new(name: &str, contents: &[u8]) -> FileInfo {
+        FileInfo { name: name.to_string(), bytes: contents.len(), sha256: hex(&sha256(contents)) }
+    }
+}
+
+impl SourceInfo {
+    pub fn new(name: &str, contents: &str) -> SourceInfo {
+        let mut entries = 0;
+        let mut entries_per_letter = BTreeMap::new();
+        for entry in EntryParser::new(contents).filter_map(Result::ok) {
+            entries += 1;
+            let letter = entry.main_word.chars().next().map_or(String::new(), |c| c.to_uppercase().collect());
+            *entries_per_letter.entry(letter).or_insert(0) += 1;
+        }
+        SourceInfo { file: FileInfo::new(name, contents.as_bytes()), version: source_version(contents), entries, entries_per_letter }
+    }
+}
+
+/// The version number following "version" in the text before the first entry.
+pub fn source_version(contents: &str) -> Option<String> {
+    let preface = &contents[..contents.find("<entry").unwrap_or(contents.len())];
+    let mut words = preface.split_whitespace();
+    while let Some(word) = words.next() {
+        if word.eq_ignore_ascii_case("version") {
+            let version = words.next()?.trim_end_matches([',', '.', ';', ')']);
+            if version.starts_with(|c: char| c.is_ascii_digit()) {
+                return Some(version.to_string());
+            }
+        }
+    }
+    None
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut state: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+    ];
+    let mut padded = data.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in padded.chunks(64) {
+        let mut w = [0u32; 64];
+        for (t, word) in block.chunks(4).enumerate() {
+            w[t] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for t in 16..64 {
+            let s0 = w[t - 15].rotate_right(7) ^ w[t - 15].rotate_right(18) ^ (w[t - 15] >> 3);
+            let s1 = w[t - 2].rotate_right(17) ^ w[t - 2].rotate_right(19) ^ (w[t - 2] >> 10);
+            w[t] = w[t - 16].wrapping_add(s0).wrapping_add(w[t - 7]).wrapping_add(s1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for t in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[t]).wrapping_add(w[t]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (s, v) in state.iter_mut().zip(&[a, b, c, d, e, f, g, h]) {
+            *s = s.wrapping_add(*v);
+        }
+    }
+    let mut digest = [0; 32];
+    for (chunk, s) in digest.chunks_mut(4).zip(&state) {
+        chunk.copy_from_slice(&s.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn digests_and_counts() {
+        assert_eq!(hex(&sha256(b"abc")), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(hex(&sha256(&[b'a'; 64])), "ffe054fe7ae0cb6dc65c3af9b61d5209f439851db43d0ba5997337df154668eb");
+
+        let contents = "<-- GCIDE version 0.53, Copyright (C) 2018 Free Software Foundation -->\n\n\
+                        <entry main-word=\"Abacus\" source=\"1913 Webster\"><p>a</p></entry>\n\
+                        <entry main-word=\"abase\" source=\"1913 Webster\"><p>b</p></entry>\n\
+                        <entry main-word=\"Babe\" source=\"1913 Webster\"><p>c version 1</p></entry>\n";
+        let source = SourceInfo::new("CIDE.A", contents);
+        assert_eq!(source.version.as_deref(), Some("0.53"));
+        assert_eq!(source.entries, 3);
+        assert_eq!(source.entries_per_letter.into_iter().collect::<Vec<_>>(), [("A".to_string(), 2), ("B".to_string(), 1)]);
+    }
+}
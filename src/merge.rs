@@ -0,0 +1,127 @@
+//! Three-way merges of CIDE files at entry granularity.
+//!
+//! Entries are matched by `diff::EntryKey` and compared semantically, so edits
+//! that only re-wrap lines never conflict. Text between entries is taken from
+//! our side.
+
+use std::collections::HashMap;
+
+use diff::{entries_equal, keyed_blocks, Compare, EntryKey};
+
+pub struct MergeResult {
+    pub text: String,
+    /// Entries that were changed differently on both sides; the merged text
+    /// contains both versions between conflict markers.
+    pub conflicts: Vec<EntryKey>,
+}
+
+enum Resolution<'a> {
+    Take(&'a str),
+    Drop,
+    Conflict(Option<&'a str>, Option<&'a str>),
+}
+
+fn resolve<'a>(base: Option<&'a str>, ours: Option<&'a str>, theirs: Option<&'a str>) -> Resolution<'a> {
+    let same = |a: Option<&str>, b: Option<&str>| match (a, b) {
+        (Some(a), Some(b)) => entries_equal(a, b, Compare::Semantic),
+        (None, None) => true,
+        _ => false,
+    };
+    let pick = if same(ours, theirs) || same(base, theirs) {
+        ours
+    } else if same(base, ours) {
+        theirs
+    } else {
+        return Resolution::Conflict(ours, theirs);
+    };
+    pick.map_or(Resolution::Drop, Resolution::Take)
+}
+
+fn block<'a>(contents: &'a str, blocks: &HashMap<EntryKey, (usize, usize)>, key: &EntryKey) -> Option<&'a str> {
+    blocks.get(key).map(|&(start, end)| &contents[start..end])
+}
+
+pub fn merge3(base: &str, ours: &str, theirs: &str) -> MergeResult {
+    let lookup = |contents: &str| -> HashMap<EntryKey, (usize, usize)> {
+        keyed_blocks(contents).into_iter().map(|(key, span)| (key, (span.start, span.end))).collect()
+    };
+    let base_blocks = lookup(base);
+    let our_blocks = keyed_blocks(ours);
+    let our_keys: HashMap<&EntryKey, ()> = our_blocks.iter().map(|(key, _)| (key, ())).collect();
+
+    // entries missing on our side are placed after their predecessor in `theirs`
+    let their_blocks = keyed_blocks(theirs);
+    let their_map = lookup(theirs);
+    let mut inserts: HashMap<Option<&EntryKey>, Vec<&EntryKey>> = HashMap::new();
+    let mut anchor = None;
+    for (key, _) in &their_blocks {
+        if our_keys.contains_key(key) {
+            anchor = Some(key);
+        } else {
+            inserts.entry(anchor).or_default().push(key);
+        }
+    }
+
+    let mut merged = MergeResult { text: String::with_capacity(ours.len()), conflicts: Vec::new() };
+    let emit = |merged: &mut MergeResult, key: &EntryKey, ours_raw: Option<&str>| -> bool {
+        let theirs_raw = block(theirs, &their_map, key);
+        match resolve(block(base, &base_blocks, key), ours_raw, theirs_raw) {
+            Resolution::Take(raw) => merged.text.push_str(raw),
+            Resolution::Drop => return false,
+            Resolution::Conflict(o, t) => {
+                merged.conflicts.push(key.clone());
+                merged.text.push_str(&format!("<<<<<<< ours\n{}\n=======\n{}\n>>>>>>> theirs",
+                                              o.unwrap_or(""), t.unwrap_or("")));
+            }
+        }
+        true
+    };
+    let mut copied_upto = our_blocks.first().map_or(ours.len(), |(_, span)| span.start);
+    merged.text.push_str(&ours[..copied_upto]);
+    for key in inserts.get(&None).into_iter().flatten() {
+        if emit(&mut merged, key, None) {
+            merged.text.push_str("\n\n");
+        }
+    }
+    for (key, span) in &our_blocks {
+        merged.text.push_str(&ours[copied_upto..span.start]);
+        copied_upto = span.end;
+        if !emit(&mut merged, key, Some(&ours[span.clone()])) {
+            // swallow the separator that followed the dropped entry
+            let rest = &ours[copied_upto..];
+            copied_upto += rest.len() - rest.trim_start().len();
+        }
+        for key in inserts.get(&Some(key)).into_iter().flatten() {
+            let len = merged.text.len();
+            merged.text.push_str("\n\n");
+            if !emit(&mut merged, key, None) {
+                merged.text.truncate(len);
+            }
+        }
+    }
+    merged.text.push_str(&ours[copied_upto..]);
+    merged
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(word: &str, def: &str) -> String {
+        format!("<entry main-word=\"{0}\" source=\"\">\n<p><hw>{0}</hw> <def>{1}</def></p>\n</entry>", word, def)
+    }
+
+    #[test]
+    fn merge_entries() {
+        let base = [entry("A", "a"), entry("B", "b"), entry("C", "c")].join("\n\n");
+        // ours re-wraps A, edits B and deletes C
+        let ours = [entry("A", "a").replace("</hw> ", "</hw>\n"), entry("B", "b1")].join("\n\n");
+        // theirs edits A and B and adds D
+        let theirs = [entry("A", "a2"), entry("B", "b2"), entry("C", "c"), entry("D", "d")].join("\n\n");
+        let merged = merge3(&base, &ours, &theirs);
+        assert_eq!(merged.conflicts, [EntryKey { headword: "b".into(), occurrence: 0 }]);
+        let expected = format!("{}\n\n<<<<<<< ours\n{}\n=======\n{}\n>>>>>>> theirs\n\n{}",
+                               entry("A", "a2"), entry("B", "b1"), entry("B", "b2"), entry("D", "d"));
+        assert_eq!(merged.text, expected);
+    }
+}
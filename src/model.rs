@@ -0,0 +1,708 @@
+//! A flattened, semantic view of entries: senses with their definitions and quotations.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use std::fmt::{self, Display, Formatter};
+use std::slice;
+
+use parser::{Entry, EntryItem};
+use tags::TagName;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Sense {
+    /// Contents of `<sn>` without the trailing period, e.g. `2` or `b`.
+    pub number: Option<String>,
+    /// The closest `<pos>` before the sense.
+    pub pos: Option<String>,
+    pub definition: String,
+    /// Source of the enclosing paragraph, or else of the entry.
+    pub source: String,
+    pub quotes: Vec<Quote>,
+    /// Usage marks of the sense, including those given for the whole entry.
+    pub labels: Vec<UsageLabel>,
+    /// Words the sense is said to be the opposite of; see `antonyms_in`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub antonyms: Vec<String>,
+    /// WordNet synset of the sense, set by `wordnet::WordNet::annotate`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub synset: Option<String>,
+}
+
+impl Sense {
+    /// Whether the sense is marked obsolete or archaic.
+    pub fn is_dated(&self) -> bool {
+        self.labels.iter().any(|label| matches!(*label, UsageLabel::Obsolete | UsageLabel::Archaic))
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Quote {
+    pub text: String,
+    pub author: Option<String>,
+}
+
+/// A phrase defined within an entry (`<col>` followed by its `<cd>`).
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Collocation {
+    pub phrase: String,
+    pub definition: String,
+}
+
+/// Phrases introducing the opposite of a sense, in lowercase.
+const ANTONYM_CUES: &[&str] = &["opposed to ", "opposite of ", "opposite to ", "contrary of ", "the reverse of ",
+                                "in contradistinction to ", "distinguished from "];
+
+/// Phrases after an antonym cue longer than this are not taken as antonyms.
+const MAX_ANTONYM_WORDS: usize = 3;
+
+/// Parts of a synonym list longer than this are taken to be discussion.
+const MAX_SYNONYM_WORDS: usize = 4;
+
+/// The words listed in a `<syn>` block (`<b>Syn. --</b> To degrade; lower; sink.`).
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct SynonymList {
+    /// The closest `<pos>` before the block.
+    pub pos: Option<String>,
+    pub words: Vec<String>,
+}
+
+/// The semantic model of a whole entry, as given to templates.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct EntryModel {
+    /// The `id` of the entry, if it was assigned one.
+    pub id: Option<String>,
+    pub main_word: String,
+    pub source: String,
+    /// Contents of the first `<hw>`, or else the main word.
+    pub headword: String,
+    /// The first `<pos>` of the entry.
+    pub pos: Option<String>,
+    pub senses: Vec<Sense>,
+    pub collocations: Vec<Collocation>,
+    pub synonyms: Vec<SynonymList>,
+}
+
+/// Normalized usage marks, as given in `<mark>` (`[Obs.]`, `[R.]`, `[Colloq. U. S.]`, ...)
+/// and `<usage>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum UsageLabel {
+    Obsolete,
+    Archaic,
+    Rare,
+    Colloquial,
+    Slang,
+    Poetic,
+    Provincial,
+    Vulgar,
+    Humorous,
+    /// A region where the usage is current, e.g. `Scot.` or `U. S.`.
+    Regional(String),
+    Other(String),
+}
+
+impl UsageLabel {
+    /// Labels in the bracketed parts of `text`, or in all of it if there are no brackets.
+    pub fn find_all(text: &str) -> Vec<UsageLabel> {
+        let mut labels = Vec::new();
+        let mut rest = text;
+        let mut found_brackets = false;
+        while let Some(start) = rest.find('[') {
+            let end = rest[start..].find(']').map_or(rest.len(), |i| start + i);
+            labels.extend(UsageLabel::parse_list(&rest[start + 1..end]));
+            rest = &rest[(end + 1).min(rest.len())..];
+            found_brackets = true;
+        }
+        if !found_brackets {
+            labels.extend(UsageLabel::parse_list(text));
+        }
+        labels
+    }
+
+    fn parse_list(text: &str) -> Vec<UsageLabel> {
+        text.split([',', ';', '&'])
+            .flat_map(|part| part.split(" or "))
+            .flat_map(|part| part.split(" and "))
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .map(UsageLabel::parse)
+            .collect()
+    }
+
+    pub fn parse(mark: &str) -> UsageLabel {
+        let lower = mark.trim().to_lowercase();
+        let starts = |prefixes: &[&str]| prefixes.iter().any(|p| lower.starts_with(p));
+        if starts(&["obs"]) {
+            UsageLabel::Obsolete
+        } else if starts(&["archaic", "arch."]) {
+            UsageLabel::Archaic
+        } else if lower == "r." || starts(&["rare"]) {
+            UsageLabel::Rare
+        } else if starts(&["colloq"]) {
+            UsageLabel::Colloquial
+        } else if starts(&["slang", "cant"]) {
+            UsageLabel::Slang
+        } else if starts(&["poetic", "poet."]) {
+            UsageLabel::Poetic
+        } else if starts(&["prov", "dial"]) {
+            UsageLabel::Provincial
+        } else if starts(&["vulgar", "low"]) {
+            UsageLabel::Vulgar
+        } else if starts(&["humorous", "jocose", "jocular"]) {
+            UsageLabel::Humorous
+        } else if starts(&["scot", "u. s", "eng.", "southern", "western", "local", "north", "ir.", "irish", "canada"]) {
+            UsageLabel::Regional(mark.trim().to_string())
+        } else {
+            UsageLabel::Other(mark.trim().to_string())
+        }
+    }
+}
+
+pub fn entry_model(entry: &Entry) -> EntryModel {
+    let first_tag = |tag| find_tag(&entry.items, tag).map(plain_text);
+    EntryModel {
+        id: entry.id.clone(),
+        main_word: entry.main_word.to_string(),
+        source: entry.source.to_string(),
+        headword: first_tag("hw").unwrap_or_else(|| entry.main_word.to_string()),
+        pos: first_pos(entry),
+        senses: senses(entry),
+        collocations: collocations(entry),
+        synonyms: synonyms(entry),
+    }
+}
+
+/// Contents of the first `<pos>` of the entry.
+pub fn first_pos(entry: &Entry) -> Option<String> {
+    find_tag(&entry.items, "pos").map(plain_text)
+}
+
+impl<'a> Entry<'a> {
+    /// Contents of every `<hw>`, including those grouped in an `<mhw>`.
+    pub fn headwords(&self) -> Vec<String> {
+        let mut found = Vec::new();
+        walk_tags(&self.items, &mut |name, _, items| if name == TagName::Hw {
+            found.push(plain_text(items));
+        });
+        found
+    }
+
+    /// Contents of every `<pos>`, without repeats; see also `first_pos`.
+    pub fn pos(&self) -> Vec<String> {
+        let mut found = Vec::new();
+        walk_tags(&self.items, &mut |name, _, items| if name == TagName::Pos {
+            let pos = plain_text(items);
+            if !found.contains(&pos) {
+                found.push(pos);
+            }
+        });
+        found
+    }
+
+    /// Contents of the first `<def>`.
+    pub fn first_def_text(&self) -> Option<String> {
+        find_tag(&self.items, "def").map(plain_text)
+    }
+
+    /// The source of the entry followed by those given on its tags (mostly
+    /// `<p source="...">`, paired or not), without repeats.
+    pub fn sources(&self) -> Vec<&'a str> {
+        fn collect_sources<'a>(items: &[EntryItem<'a>], found: &mut Vec<&'a str>) {
+            for item in items {
+                match *item {
+                    EntryItem::Tagged { ref items, source, .. } => {
+                        if let Some(source) = source {
+                            if !found.contains(&source) {
+                                found.push(source);
+                            }
+                        }
+                        collect_sources(items, found);
+                    }
+                    EntryItem::UnpairedTagOpen(_, Some(source)) if !found.contains(&source) => found.push(source),
+                    _ => (),
+                }
+            }
+        }
+        let mut found = vec![self.source];
+        collect_sources(&self.items, &mut found);
+        found
+    }
+
+    /// Whether a `name` tag appears anywhere in the entry.
+    pub fn has_tag(&self, name: TagName) -> bool {
+        fn has_tag(items: &[EntryItem], tag: TagName) -> bool {
+            items.iter().any(|item| match *item {
+                EntryItem::Tagged { name, ref items, .. } => name == tag || has_tag(items, tag),
+                _ => false,
+            })
+        }
+        has_tag(&self.items, name)
+    }
+
+    /// Contents of the tags matching `path`, in document order: tag names
+    /// separated by `/`, each step but the first a direct child of the one
+    /// before. `def` finds every `<def>`, and `p/def` those directly inside a
+    /// `<p>`. Paths of more than 63 steps find nothing.
+    pub fn find_all<'e>(&'e self, path: &'e str) -> FindAll<'e, 'a> {
+        let path: Vec<_> = path.split('/').collect();
+        let stack = if path.len() < 64 { vec![(self.items.iter(), 0)] } else { Vec::new() };
+        FindAll { path, stack }
+    }
+}
+
+/// Iterator returned by `Entry::find_all`.
+pub struct FindAll<'e, 'a> {
+    path: Vec<&'e str>,
+    /// Siblings left to visit, and the lengths of the path prefixes matched by
+    /// their parent and its ancestors as a bit set.
+    stack: Vec<(slice::Iter<'e, EntryItem<'a>>, u64)>,
+}
+
+impl<'e, 'a> Iterator for FindAll<'e, 'a> {
+    type Item = &'e [EntryItem<'a>];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (siblings, matched) = self.stack.last_mut()?;
+            match siblings.next() {
+                Some(&EntryItem::Tagged { name, ref items, .. }) => {
+                    // the path may start anywhere, as if the empty prefix matched
+                    let parent = *matched | 1;
+                    let path = &self.path;
+                    let matched = (0..path.len()).filter(|&k| parent & 1 << k != 0 && name == path[k])
+                        .fold(0, |matched, k| matched | 1 << (k + 1));
+                    self.stack.push((items.iter(), matched));
+                    if matched & 1 << self.path.len() != 0 {
+                        return Some(items);
+                    }
+                }
+                Some(_) => (),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Calls `f` with the name, source and contents of every tag in `items`, each
+/// before those inside it.
+fn walk_tags<'e, 'a>(items: &'e [EntryItem<'a>],
+                     f: &mut dyn FnMut(TagName<'a>, Option<&'a str>, &'e [EntryItem<'a>])) {
+    for item in items {
+        if let EntryItem::Tagged { name, ref items, source } = *item {
+            f(name, source, items);
+            walk_tags(items, f);
+        }
+    }
+}
+
+fn find_tag<'e, 'a>(items: &'e [EntryItem<'a>], tag: &str) -> Option<&'e [EntryItem<'a>]> {
+    items.iter().filter_map(|item| match *item {
+        EntryItem::Tagged { name, ref items, .. } if name == tag => Some(&items[..]),
+        EntryItem::Tagged { ref items, .. } => find_tag(items, tag),
+        _ => None,
+    }).next()
+}
+
+/// Senses of an entry in order. Every `<def>` belongs to some sense: a new one
+/// starts at each `<sn>`, and at a `<def>` in a new paragraph.
+pub fn senses(entry: &Entry) -> Vec<Sense> {
+    let mut builder = SenseBuilder {
+        senses: Vec::new(),
+        current: Sense::default(),
+        pos: None,
+        entry_labels: Vec::new(),
+        after_def: String::new(),
+    };
+    for item in &entry.items {
+        match *item {
+            EntryItem::Tagged { name, ref items, source } if name == TagName::P || name == TagName::Extra => {
+                builder.start_paragraph(source.unwrap_or(entry.source));
+                for item in items {
+                    builder.add(item);
+                }
+            }
+            ref item => builder.add(item),
+        }
+    }
+    builder.finish()
+}
+
+struct SenseBuilder {
+    senses: Vec<Sense>,
+    current: Sense,
+    pos: Option<String>,
+    /// Marks given before the first sense apply to all of them.
+    entry_labels: Vec<UsageLabel>,
+    /// Text following the definitions of the current sense, such as
+    /// `; -- opposed to <ex>passive</ex>`.
+    after_def: String,
+}
+
+impl SenseBuilder {
+    fn start_paragraph(&mut self, source: &str) {
+        if !self.current.definition.is_empty() {
+            self.flush();
+        }
+        self.current.source = source.to_string();
+    }
+
+    fn flush(&mut self) {
+        let source = self.current.source.clone();
+        let mut sense = ::std::mem::replace(&mut self.current, Sense { source, ..Sense::default() });
+        let after_def = ::std::mem::take(&mut self.after_def);
+        if !sense.definition.is_empty() || !sense.quotes.is_empty() {
+            sense.labels.splice(0..0, self.entry_labels.iter().cloned());
+            sense.antonyms = antonyms_in(&format!("{}; {}", sense.definition, after_def));
+            self.senses.push(sense);
+        }
+    }
+
+    fn add(&mut self, item: &EntryItem) {
+        let (name, items) = match *item {
+            EntryItem::Tagged { name, ref items, .. } => (name, items),
+            EntryItem::PlainText(text) if !self.current.definition.is_empty() => {
+                return self.after_def.push_str(text);
+            }
+            _ => return,
+        };
+        match name {
+            TagName::Pos => self.pos = Some(plain_text(items)),
+            TagName::Sn => {
+                if !self.current.definition.is_empty() || self.current.number.is_some() {
+                    self.flush();
+                }
+                self.current.number = Some(plain_text(items).trim_end_matches('.').to_string());
+            }
+            TagName::Def => {
+                if self.current.pos.is_none() {
+                    self.current.pos = self.pos.clone();
+                }
+                if !self.current.definition.is_empty() {
+                    self.current.definition.push_str("; ");
+                }
+                self.current.definition.push_str(&plain_text(items));
+            }
+            TagName::Mark | TagName::Usage => {
+                let labels = UsageLabel::find_all(&plain_text(items));
+                if self.senses.is_empty() && self.current.definition.is_empty() && self.current.number.is_none() {
+                    self.entry_labels.extend(labels);
+                } else {
+                    self.current.labels.extend(labels);
+                }
+            }
+            TagName::Ex | TagName::Er | TagName::It if !self.current.definition.is_empty() => {
+                self.after_def.push_str(&plain_text(items))
+            }
+            TagName::Q => self.current.quotes.push(Quote { text: plain_text(items), author: None }),
+            TagName::Qau | TagName::Au => if let Some(quote) = self.current.quotes.last_mut() {
+                if quote.author.is_none() {
+                    quote.author = Some(plain_text(items));
+                }
+            },
+            _ => (),
+        }
+    }
+
+    fn finish(mut self) -> Vec<Sense> {
+        self.flush();
+        self.senses
+    }
+}
+
+/// An `<sn>` out of sequence; see `check_sense_numbers`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SenseNumberError {
+    /// Contents of an `<sn>` that repeats the one before.
+    Repeated(String),
+    /// Contents of an `<sn>`, and the number expected in its place.
+    Skipped(String, String),
+}
+
+impl Display for SenseNumberError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            SenseNumberError::Repeated(ref found) => write!(f, "sense number {} repeats the one before", found),
+            SenseNumberError::Skipped(ref found, ref expected) => {
+                write!(f, "sense number {} where {} was expected", found, expected)
+            }
+        }
+    }
+}
+
+/// Gaps and repeats in the `<sn>` numbering of `entry`, such as `1.`, `2.`,
+/// `3.` with sub-senses `(a)`, `(b)`, which start over at each number. A new
+/// series may start at `1.`, and an entry without a headword, continuing the
+/// one before, at any number. `<sn>`s that are neither a number nor a letter
+/// are passed over.
+pub fn check_sense_numbers(entry: &Entry) -> Vec<SenseNumberError> {
+    let mut errors = Vec::new();
+    let mut number = if entry.has_tag(TagName::Hw) { Some(0) } else { None };
+    let mut letter = None;
+    for items in entry.find_all("sn") {
+        let text = plain_text(items);
+        let (new_number, new_letter) = match parse_sense_number(&text) {
+            Some(parsed) => parsed,
+            None => continue,
+        };
+        let mut error = None;
+        if let Some(new_number) = new_number {
+            match number {
+                Some(last) if new_number == last => error = Some(SenseNumberError::Repeated(text.clone())),
+                Some(last) if new_number != last + 1 && new_number != 1 => {
+                    error = Some(SenseNumberError::Skipped(text.clone(), format!("{}.", last + 1)));
+                }
+                _ => (),
+            }
+            number = Some(new_number);
+            letter = None;
+        }
+        if let Some(new_letter) = new_letter {
+            let expected = letter.map_or(b'a', |last| last + 1);
+            if error.is_none() && Some(new_letter) == letter {
+                error = Some(SenseNumberError::Repeated(text.clone()));
+            } else if error.is_none() && new_letter != expected {
+                error = Some(SenseNumberError::Skipped(text.clone(), format!("({})", expected as char)));
+            }
+            letter = Some(new_letter);
+        }
+        errors.extend(error);
+    }
+    errors
+}
+
+/// The number and letter of sense numbers such as `2.`, `(b)`, `b` or `2. (a)`.
+fn parse_sense_number(text: &str) -> Option<(Option<u32>, Option<u8>)> {
+    let text = text.trim();
+    let digits = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let number = text[..digits].parse().ok();
+    let rest = text[digits..].trim_start_matches('.').trim();
+    let rest = rest.strip_prefix('(').and_then(|rest| rest.strip_suffix(')'))
+        .unwrap_or_else(|| rest.trim_end_matches('.'));
+    let letter = match *rest.as_bytes() {
+        [] => None,
+        [letter @ b'a'..=b'z'] => Some(letter),
+        _ => return None,
+    };
+    if number.is_none() && letter.is_none() { None } else { Some((number, letter)) }
+}
+
+/// Collocations of an entry in order. Consecutive `<col>`s (`<col>A</col>, or
+/// <col>B</col>`) share the definition that follows them.
+pub fn collocations(entry: &Entry) -> Vec<Collocation> {
+    let mut found = Vec::new();
+    let mut phrases = Vec::new();
+    collect_collocations(&entry.items, &mut phrases, &mut found);
+    found.extend(phrases.into_iter().map(|phrase| Collocation { phrase, definition: String::new() }));
+    found
+}
+
+fn collect_collocations(items: &[EntryItem], phrases: &mut Vec<String>, found: &mut Vec<Collocation>) {
+    for item in items {
+        if let EntryItem::Tagged { name, ref items, .. } = *item {
+            match name {
+                TagName::Col => phrases.push(plain_text(items).trim_end_matches([',', ';']).to_string()),
+                TagName::Cd => {
+                    let definition = plain_text(items);
+                    found.extend(phrases.drain(..).map(|phrase| Collocation { phrase, definition: definition.clone() }));
+                }
+                _ => collect_collocations(items, phrases, found),
+            }
+        }
+    }
+}
+
+/// The synonym lists of an entry in order. Sense numbers and the longer
+/// phrases of synonym discussions are left out.
+pub fn synonyms(entry: &Entry) -> Vec<SynonymList> {
+    let mut found = Vec::new();
+    collect_synonyms(&entry.items, &mut None, &mut found);
+    found
+}
+
+fn collect_synonyms(items: &[EntryItem], pos: &mut Option<String>, found: &mut Vec<SynonymList>) {
+    for item in items {
+        match *item {
+            EntryItem::Tagged { name: TagName::Pos, ref items, .. } => *pos = Some(plain_text(items)),
+            EntryItem::Tagged { name: TagName::Syn, ref items, .. } => {
+                let words = synonym_words(&plain_text(items));
+                if !words.is_empty() {
+                    found.push(SynonymList { pos: pos.clone(), words });
+                }
+            }
+            EntryItem::Tagged { ref items, .. } => collect_synonyms(items, pos, found),
+            _ => (),
+        }
+    }
+}
+
+/// The words of a synonym list such as `Syn. -- 1. To degrade; lower. 2. To sink.`
+fn synonym_words(text: &str) -> Vec<String> {
+    let text = text.trim_start();
+    let text = text.strip_prefix("Syn.").map_or(text, |rest| rest.trim_start().trim_start_matches(['-', '\u{2014}']));
+    let mut words: Vec<String> = Vec::new();
+    for part in text.split([';', ',', '.']).map(str::trim) {
+        let is_list_item = !part.is_empty() && part.split_whitespace().count() <= MAX_SYNONYM_WORDS
+            && !part.contains("--") && !part.contains('\u{2014}') && part.chars().any(char::is_alphabetic);
+        if is_list_item && !words.iter().any(|word| word == part) {
+            words.push(part.to_string());
+        }
+    }
+    words
+}
+
+/// Words in `text` following phrases such as `opposed to` or `the opposite of`,
+/// up to the next punctuation; `A and B` gives both.
+pub fn antonyms_in(text: &str) -> Vec<String> {
+    let lower = text.to_ascii_lowercase();
+    let mut found: Vec<String> = Vec::new();
+    for cue in ANTONYM_CUES {
+        for (idx, _) in lower.match_indices(cue) {
+            let rest = &text[idx + cue.len()..];
+            let end = rest.find([',', ';', '.', ':', '(', ')', '[', '"']).unwrap_or(rest.len());
+            for word in rest[..end].split(" and ").flat_map(|part| part.split(" or ")) {
+                let word = word.trim();
+                let word = ["the ", "a ", "an "].iter().fold(word, |w, article| w.strip_prefix(article).unwrap_or(w));
+                let is_word = !word.is_empty() && word.split_whitespace().count() <= MAX_ANTONYM_WORDS
+                    && word.chars().all(|c| c.is_alphabetic() || c == ' ' || c == '-' || c == '\'');
+                if is_word && !found.iter().any(|w| w == word) {
+                    found.push(word.to_string());
+                }
+            }
+        }
+    }
+    found
+}
+
+/// Rendered text of `items` with whitespace collapsed.
+pub fn plain_text(items: &[EntryItem]) -> String {
+    let text: String = items.iter().map(|item| item.to_string()).collect();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::EntryParser;
+
+    #[test]
+    fn abacus_senses() {
+        let text = "<entry main-word=\"Abacus\" source=\"1913 Webster\">\n\
+                    <p><hw>Ab\"a*cus</hw>, <pos>n.</pos> <mark>[R.]</mark> <sn>1.</sn> <def>A table strewn with sand.</def> \
+                    <mark>[Obs. or Prov. Eng.]</mark></p>\n\n\
+                    <p source=\"PJC\"><sn>2.</sn> <def>A calculating frame.</def><br/\n\
+                    <q>An abacus quote.</q> <qau>Shak.</qau></p>\n\n\
+                    <p><def>Unnumbered sense.</def></p>\n</entry>";
+        let entry = EntryParser::new(text).next().unwrap().unwrap();
+        let senses = senses(&entry);
+        assert_eq!(senses.len(), 3);
+        assert_eq!(senses[0].number.as_ref().unwrap(), "1");
+        assert_eq!(senses[0].pos.as_ref().unwrap(), "n.");
+        assert_eq!(senses[0].labels, [UsageLabel::Rare, UsageLabel::Obsolete, UsageLabel::Provincial]);
+        assert!(senses[0].is_dated() && !senses[1].is_dated());
+        assert_eq!(senses[1].source, "PJC");
+        assert_eq!(senses[1].quotes, [Quote { text: "An abacus quote.".into(), author: Some("Shak.".into()) }]);
+        assert_eq!((senses[2].number.as_ref(), &senses[2].definition[..], &senses[2].source[..]),
+                   (None, "Unnumbered sense.", "1913 Webster"));
+    }
+
+    #[test]
+    fn entry_accessors() {
+        let text = "<entry main-word=\"Abacus\" source=\"1913 Webster\">\n\
+                    <p><mhw>{<hw>Ab\"a*cus</hw>}, or <hw>Ab\"a*cus*es</hw></mhw>, <pos>n.</pos> \
+                    <sn>1.</sn> <def>A table strewn with <i>sand</i>.</def></p>\n\
+                    <p source=\"PJC\"><pos>n.</pos> <def>A calculating frame.</def></p>\n\
+                    <p source=\"WordNet 1.5\"><pos>v.</pos> <def>To count.</def></p>\n</entry>";
+        let entry = EntryParser::new(text).next().unwrap().unwrap();
+        assert_eq!(entry.headwords(), ["Ab\"a*cus", "Ab\"a*cus*es"]);
+        assert_eq!(entry.pos(), ["n.", "v."]);
+        assert_eq!(entry.first_def_text().unwrap(), "A table strewn with sand.");
+        assert_eq!(entry.sources(), ["1913 Webster", "PJC", "WordNet 1.5"]);
+        assert!(entry.has_tag(TagName::I) && !entry.has_tag(TagName::Q));
+    }
+
+    #[test]
+    fn find_tags_by_path() {
+        let text = "<entry main-word=\"A\" source=\"B\"><p><def>one</def> <sd>(a)</sd> <def>two</def></p>\n\
+                    <def>three</def><note><p><def>four <def>five</def></def></p></note></entry>";
+        let entry = EntryParser::new(text).next().unwrap().unwrap();
+        let found = |path| entry.find_all(path).map(plain_text).collect::<Vec<_>>();
+        assert_eq!(found("def"), ["one", "two", "three", "four five", "five"]);
+        assert_eq!(found("p/def"), ["one", "two", "four five"]);
+        assert_eq!(found("note/p/def"), ["four five"]);
+        assert_eq!(found("def/def"), ["five"]);
+        assert!(found("p/sd/def").is_empty() && found("").is_empty());
+        assert!(found(&["p"; 64].join("/")).is_empty());
+    }
+
+    #[test]
+    fn sense_numbering() {
+        let check = |text: &str| check_sense_numbers(&EntryParser::new(text).next().unwrap().unwrap());
+        let text = "<entry main-word=\"Bar\" source=\"A\"><p><hw>Bar</hw>, <pos>n.</pos> <sn>1.</sn> <def>a</def> \
+                    <sn>2.</sn> <sn>(a)</sn> <def>b</def> <sn>(b)</sn> <def>c</def> <sn>3. (a)</sn> <def>d</def> \
+                    <sn>(c)</sn> <def>e</def> <sn>(c)</sn> <def>f</def> <sn>5.</sn> <def>g</def> <sn>5.</sn> \
+                    <def>h</def></p><p><hw>Bar</hw>, <pos>v. t.</pos> <sn>1.</sn> <def>i</def> <sn>b</sn> \
+                    <sn>Fig.</sn> <def>j</def></p></entry>";
+        assert_eq!(check(text), [
+            SenseNumberError::Skipped("(c)".into(), "(b)".into()),
+            SenseNumberError::Repeated("(c)".into()),
+            SenseNumberError::Skipped("5.".into(), "4.".into()),
+            SenseNumberError::Repeated("5.".into()),
+            SenseNumberError::Skipped("b".into(), "(a)".into()),
+        ]);
+        assert_eq!(SenseNumberError::Skipped("5.".into(), "4.".into()).to_string(),
+                   "sense number 5. where 4. was expected");
+        assert!(check("<entry main-word=\"Bar\" source=\"A\"><p><sn>6.</sn> <def>k</def></p></entry>").is_empty());
+        assert_eq!(check("<entry main-word=\"Bar\" source=\"A\"><p><hw>Bar</hw> <sn>2.</sn></p></entry>"),
+                   [SenseNumberError::Skipped("2.".into(), "1.".into())]);
+    }
+
+    #[test]
+    fn antonym_cues() {
+        let text = "<entry main-word=\"Active\" source=\"1913 Webster\">\n<p><hw>Ac\"tive</hw>, <pos>a.</pos> \
+                    <sn>1.</sn> <def>Having the power of acting; -- opposed to <ex>passive</ex>.</def></p>\n\
+                    <p><sn>2.</sn> <def>Quick in action</def>; -- opposed to <ex>dull</ex>, <ex>sluggish</ex>.</p>\n\
+                    <p><sn>3.</sn> <def>Busy; the opposite of the idle and the lazy.</def></p></entry>";
+        let entry = EntryParser::new(text).next().unwrap().unwrap();
+        let antonyms: Vec<_> = senses(&entry).into_iter().map(|sense| sense.antonyms).collect();
+        assert_eq!(antonyms, [vec!["passive"], vec!["dull"], vec!["idle", "lazy"]]);
+        assert!(antonyms_in("opposed to the doctrine that all men are equal").is_empty());
+    }
+
+    #[test]
+    fn synonym_lists() {
+        let text = "<entry main-word=\"Abase\" source=\"1913 Webster\">\n<p><hw>A*base\"</hw>, <pos>v. t.</pos> \
+                    <def>To lower.</def></p>\n<p><syn><b>Syn. --</b> 1. To degrade; lower; sink; depress. \
+                    2. To humble; humiliate, disgrace.</syn></p>\n<p><syn><b>Syn. --</b> <sd>--</sd> To abase is to \
+                    bring down in spirit or condition; lower.</syn></p></entry>";
+        let entry = EntryParser::new(text).next().unwrap().unwrap();
+        let lists = synonyms(&entry);
+        assert_eq!(lists.len(), 2);
+        assert_eq!(lists[0].pos.as_ref().unwrap(), "v. t.");
+        assert_eq!(lists[0].words, ["To degrade", "lower", "sink", "depress", "To humble", "humiliate", "disgrace"]);
+        assert_eq!(lists[1].words, ["lower"]);
+    }
+
+    #[test]
+    fn abacus_collocations() {
+        let text = "<entry main-word=\"Abacus\" source=\"1913 Webster\">\n<p><hw>Ab\"a*cus</hw> \
+                    <def>A table.</def></p>\n\n<p><cs><col><b>Abacus harmonicus</b></col>, <cd>The structure of \
+                    the keys.</cd> <col><b>Abacus major</b></col>, or <col><b>Abacus maior</b></col>, <cd>A trough \
+                    used in mines.</cd></cs></p>\n</entry>";
+        let entry = EntryParser::new(text).next().unwrap().unwrap();
+        let pairs: Vec<_> = collocations(&entry).into_iter().map(|c| (c.phrase, c.definition)).collect();
+        assert_eq!(pairs, [
+            ("Abacus harmonicus".to_string(), "The structure of the keys.".to_string()),
+            ("Abacus major".to_string(), "A trough used in mines.".to_string()),
+            ("Abacus maior".to_string(), "A trough used in mines.".to_string()),
+        ]);
+        assert_eq!(senses(&entry).len(), 1);
+    }
+}
@@ -0,0 +1,107 @@
+//! Text that went through a UTF-8 to Latin-1 (or Windows-1252) round trip too
+//! many, such as `Ã©` for `é`, and U+FFFD left by failed decoding.
+
+use std::ops::Range;
+use std::str;
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+/// Characters of Windows-1252 bytes 0x80 to 0x9F; the unassigned ones are
+/// the C1 controls, as in Latin-1.
+const CP1252_HIGH: [char; 32] = [
+    '\u{20ac}', '\u{81}', '\u{201a}', '\u{192}', '\u{201e}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2c6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8d}', '\u{17d}', '\u{8f}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201c}', '\u{201d}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2dc}', '\u{2122}', '\u{161}', '\u{203a}', '\u{153}', '\u{9d}', '\u{17e}', '\u{178}',
+];
+
+/// A garbled character found by `find_mojibake`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Garbled {
+    pub range: Range<usize>,
+    /// The intended character, or `None` for U+FFFD, which cannot be repaired.
+    pub repair: Option<char>,
+}
+
+/// The byte a character stands for when UTF-8 was misread as Latin-1 or
+/// Windows-1252.
+fn misread_byte(c: char) -> Option<u8> {
+    match c as u32 {
+        0x80..=0xff => Some(c as u8),
+        _ => CP1252_HIGH.iter().position(|&h| h == c).map(|idx| 0x80 + idx as u8),
+    }
+}
+
+/// Mis-decoded UTF-8 sequences and replacement characters in `text`.
+pub fn find_mojibake(text: &str) -> Vec<Garbled> {
+    let mut found = Vec::new();
+    let mut chars = text.char_indices();
+    while let Some((start, c)) = chars.next() {
+        if c == '\u{fffd}' {
+            found.push(Garbled { range: start..start + c.len_utf8(), repair: None });
+            continue;
+        }
+        let lead = match misread_byte(c) {
+            Some(b @ 0xc2..=0xf4) => b,
+            _ => continue,
+        };
+        let len = if lead < 0xe0 { 2 } else if lead < 0xf0 { 3 } else { 4 };
+        let mut bytes = [lead, 0, 0, 0];
+        let mut rest = chars.clone();
+        let mut end = start + c.len_utf8();
+        let complete = (1..len).all(|n| match rest.next() {
+            Some((idx, c)) => match misread_byte(c) {
+                Some(b @ 0x80..=0xbf) => {
+                    bytes[n] = b;
+                    end = idx + c.len_utf8();
+                    true
+                }
+                _ => false,
+            },
+            None => false,
+        });
+        if let (true, Ok(decoded)) = (complete, str::from_utf8(&bytes[..len])) {
+            found.push(Garbled { range: start..end, repair: decoded.chars().next() });
+            chars = rest;
+        }
+    }
+    found
+}
+
+/// `text` with its mis-decoded sequences repaired, or `None` if there are
+/// none. Replacement characters are left as they are.
+pub fn repair_mojibake(text: &str) -> Option<String> {
+    let mut repaired = String::with_capacity(text.len());
+    let mut copied_upto = 0;
+    for garbled in find_mojibake(text) {
+        if let Some(c) = garbled.repair {
+            repaired.push_str(&text[copied_upto..garbled.range.start]);
+            repaired.push(c);
+            copied_upto = garbled.range.end;
+        }
+    }
+    if copied_upto == 0 {
+        return None;
+    }
+    repaired.push_str(&text[copied_upto..]);
+    Some(repaired)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn double_encoding() {
+        let text = "caf\u{c3}\u{a9} \u{e2}\u{20ac}\u{201d} na\u{ef}ve \u{c3}\u{a6}on \u{fffd} \u{c2}\u{80} \u{c3}";
+        assert_eq!(find_mojibake(text), [
+            Garbled { range: 3..7, repair: Some('\u{e9}') },
+            Garbled { range: 8..16, repair: Some('\u{2014}') },
+            Garbled { range: 24..28, repair: Some('\u{e6}') },
+            Garbled { range: 31..34, repair: None },
+            Garbled { range: 35..39, repair: Some('\u{80}') },
+        ]);
+        assert_eq!(repair_mojibake(text).unwrap(), "caf\u{e9} \u{2014} na\u{ef}ve \u{e6}on \u{fffd} \u{80} \u{c3}");
+        assert_eq!(repair_mojibake("na\u{ef}ve"), None);
+    }
+}
@@ -0,0 +1,172 @@
+//! Rewriting the dash and apostrophe conventions of the source text itself,
+//! rather than on output as `process_symbols_in_text` does, and the spacing
+//! between entries.
+
+use std::mem;
+use std::str::FromStr;
+
+use entities::Entity;
+use parser::{Block, EntryItem, EntryParser};
+use tags::TagName;
+
+/// How a symbol is written in the source.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolStyle {
+    /// Leave it as it is.
+    Keep,
+    /// `--` for dashes and `'` for apostrophes.
+    Ascii,
+    /// `<mdash/` for dashes and `<rsquo/` for apostrophes.
+    Entity,
+}
+
+impl FromStr for SymbolStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<SymbolStyle, String> {
+        match s {
+            "keep" => Ok(SymbolStyle::Keep),
+            "ascii" => Ok(SymbolStyle::Ascii),
+            "entity" => Ok(SymbolStyle::Entity),
+            _ => Err(format!("unknown symbol style: {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct NormalizeRules {
+    pub dashes: SymbolStyle,
+    pub apostrophes: SymbolStyle,
+}
+
+impl Default for NormalizeRules {
+    fn default() -> NormalizeRules {
+        NormalizeRules { dashes: SymbolStyle::Ascii, apostrophes: SymbolStyle::Ascii }
+    }
+}
+
+/// Tags whose text uses `'` and `-` as marks rather than punctuation.
+const VERBATIM_TAGS: &[TagName] = &[TagName::Hw, TagName::Pr, TagName::Mhw];
+
+/// Rewrites dashes and apostrophes in `items` according to `rules`. `----`
+/// (a long dash) and text in headwords and pronunciations are left alone.
+/// Returns whether anything changed.
+pub fn normalize_symbols<'a>(items: &mut Vec<EntryItem<'a>>, rules: &NormalizeRules) -> bool {
+    let mut changed = false;
+    for item in mem::take(items) {
+        match item {
+            EntryItem::PlainText(text) => changed |= split_symbols(text, rules, items),
+            EntryItem::Entity(entity) => {
+                let style = match entity {
+                    Entity::Mdash => rules.dashes,
+                    Entity::Rsquo => rules.apostrophes,
+                    _ => SymbolStyle::Keep,
+                };
+                if style == SymbolStyle::Ascii {
+                    items.push(EntryItem::PlainText(if entity == Entity::Mdash { "--" } else { "'" }));
+                    changed = true;
+                } else {
+                    items.push(item);
+                }
+            }
+            EntryItem::Tagged { name, source, items: mut inner } => {
+                if !VERBATIM_TAGS.contains(&name) {
+                    changed |= normalize_symbols(&mut inner, rules);
+                }
+                items.push(EntryItem::Tagged { name, source, items: inner });
+            }
+            item => items.push(item),
+        }
+    }
+    changed
+}
+
+/// Pushes `text` to `items`, with the symbols to be written as entities split out.
+fn split_symbols<'a>(text: &'a str, rules: &NormalizeRules, items: &mut Vec<EntryItem<'a>>) -> bool {
+    let mut copied_upto = 0;
+    let mut idx = 0;
+    while idx < text.len() {
+        let rest = &text[idx..];
+        let (len, entity) = if rest.starts_with("----") {
+            (4, None)
+        } else if rest.starts_with("--") && rules.dashes == SymbolStyle::Entity {
+            (2, Some(Entity::Mdash))
+        } else if rest.starts_with('\'') && rules.apostrophes == SymbolStyle::Entity {
+            (1, Some(Entity::Rsquo))
+        } else {
+            (rest.chars().next().unwrap().len_utf8(), None)
+        };
+        if let Some(entity) = entity {
+            if copied_upto < idx {
+                items.push(EntryItem::PlainText(&text[copied_upto..idx]));
+            }
+            items.push(EntryItem::Entity(entity));
+            copied_upto = idx + len;
+        }
+        idx += len;
+    }
+    if copied_upto < text.len() {
+        items.push(EntryItem::PlainText(&text[copied_upto..]));
+    }
+    copied_upto > 0
+}
+
+/// `contents` with `separator` between every two consecutive entry blocks,
+/// in place of the whitespace that was there. Other text outside entries,
+/// such as comments, is kept as it is.
+pub fn normalize_spacing(contents: &str, separator: &str) -> String {
+    let mut normalized = String::with_capacity(contents.len());
+    let mut gap = None;
+    let mut after_entry = false;
+    let mut block_iter = EntryParser::new(contents).with_gaps();
+    while let Some(block) = block_iter.next() {
+        match block {
+            Block::Gap { text, .. } => gap = Some(text),
+            Block::Entry(_) => {
+                match gap.take() {
+                    Some(text) if !after_entry || !text.trim().is_empty() => normalized.push_str(text),
+                    _ if after_entry => normalized.push_str(separator),
+                    _ => (),
+                }
+                normalized.push_str(&contents[block_iter.parser().last_span().unwrap()]);
+                after_entry = true;
+            }
+        }
+    }
+    normalized.push_str(gap.unwrap_or(""));
+    normalized
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use exporter::CIDE;
+    use parser::EntryParser;
+
+    #[test]
+    fn dashes_and_apostrophes() {
+        let raw = "<entry main-word=\"O'er\" source=\"B\">\n<p><hw>O'er</hw> <def>over -- o'er<mdash/ ----</def></p>\n</entry>";
+        let render = |rules| {
+            let mut entry = EntryParser::new(raw).next().unwrap().unwrap();
+            normalize_symbols(&mut entry.items, &rules);
+            CIDE(&entry).to_string()
+        };
+        let entities = NormalizeRules { dashes: SymbolStyle::Entity, apostrophes: SymbolStyle::Entity };
+        assert_eq!(render(entities), "<entry main-word=\"O'er\" source=\"B\">\n<p><hw>O'er</hw> \
+                                      <def>over <mdash/ o<rsquo/er<mdash/ ----</def></p>\n</entry>");
+        assert_eq!(render(NormalizeRules::default()), "<entry main-word=\"O'er\" source=\"B\">\n<p><hw>O'er</hw> \
+                                                       <def>over -- o'er-- ----</def></p>\n</entry>");
+        let keep = NormalizeRules { dashes: SymbolStyle::Keep, apostrophes: SymbolStyle::Keep };
+        assert_eq!(render(keep), raw);
+    }
+
+    #[test]
+    fn spacing_between_entries() {
+        let entry = |word| format!("<entry main-word=\"{}\" source=\"B\">{}</entry>", word, word);
+        let contents = format!("<-- preface -->\n\n{}\n \n\n{}{}\n<-- note -->\n{}\n",
+                               entry("A"), entry("B"), entry("C"), entry("D"));
+        assert_eq!(normalize_spacing(&contents, "\n\n"),
+                   format!("<-- preface -->\n\n{}\n\n{}\n\n{}\n<-- note -->\n{}\n",
+                           entry("A"), entry("B"), entry("C"), entry("D")));
+    }
+}
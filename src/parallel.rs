@@ -0,0 +1,102 @@
+//! Mapping over entries on several threads without changing the order of the
+//! output.
+
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+use std::sync::mpsc;
+use std::thread;
+
+/// Items being mapped or waiting to be passed on, per thread.
+const WINDOW_PER_JOB: usize = 16;
+
+/// The number of threads to use for `jobs`: itself, or one per CPU if 0.
+pub fn thread_count(jobs: usize) -> usize {
+    match jobs {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    }
+}
+
+/// Calls `f` on each of `items` using `thread_count(jobs)` threads, and `sink`
+/// on the results in the order of `items`.
+///
+/// Items are numbered as they are taken from `items` (on the calling thread,
+/// so the iterator need not be `Send`), and results that come early wait for
+/// those before them; taking items pauses while `WINDOW_PER_JOB` per thread
+/// are in flight. A panic in `f` is raised again on the calling thread.
+pub fn ordered_map<I, R, F, S>(items: I, jobs: usize, f: F, mut sink: S)
+where I: IntoIterator, I::Item: Send, R: Send, F: Fn(I::Item) -> R + Sync, S: FnMut(R) {
+    let jobs = thread_count(jobs);
+    if jobs == 1 {
+        return items.into_iter().for_each(|item| sink(f(item)));
+    }
+    let window = jobs * WINDOW_PER_JOB;
+    let (work_tx, work_rx) = mpsc::sync_channel::<(usize, I::Item)>(window);
+    let (result_tx, result_rx) = mpsc::sync_channel::<(usize, thread::Result<R>)>(window);
+    let work_rx = Mutex::new(work_rx);
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            let (f, work_rx, result_tx) = (&f, &work_rx, result_tx.clone());
+            scope.spawn(move || loop {
+                let job = work_rx.lock().unwrap().recv();
+                let (idx, item) = match job {
+                    Ok(job) => job,
+                    Err(_) => break,
+                };
+                let result = panic::catch_unwind(AssertUnwindSafe(|| f(item)));
+                if result_tx.send((idx, result)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(result_tx);
+
+        let mut pending = BTreeMap::new();
+        let mut next = 0;
+        let mut receive = |pending: &mut BTreeMap<usize, R>, next: &mut usize| {
+            let (idx, result) = result_rx.recv().expect("worker threads stopped early");
+            pending.insert(idx, result.unwrap_or_else(|err| panic::resume_unwind(err)));
+            while let Some(result) = pending.remove(next) {
+                sink(result);
+                *next += 1;
+            }
+        };
+        let mut taken = 0;
+        for item in items {
+            while taken - next >= window {
+                receive(&mut pending, &mut next);
+            }
+            work_tx.send((taken, item)).unwrap();
+            taken += 1;
+        }
+        drop(work_tx);
+        while next < taken {
+            receive(&mut pending, &mut next);
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn keeps_order() {
+        let mut out = Vec::new();
+        ordered_map(0..200u64, 4, |n| {
+            // later items tend to finish first
+            thread::sleep(Duration::from_micros((200 - n) * 20));
+            n * 2
+        }, |n| out.push(n));
+        assert_eq!(out, (0..200).map(|n| n * 2).collect::<Vec<_>>());
+
+        let mut out = Vec::new();
+        ordered_map(vec!["a", "b"], 1, str::to_uppercase, |s| out.push(s));
+        assert_eq!(out, ["A", "B"]);
+
+        let caught = panic::catch_unwind(|| ordered_map(0..10, 2, |n| assert!(n != 5), |_| ()));
+        assert!(caught.is_err());
+    }
+}
@@ -1,46 +1,57 @@
+use std::borrow::Cow;
 use std::fmt::{self, Display, Formatter};
+use std::ops::Range;
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
 
 use nom::types::CompleteStr;
 use nom::{alphanumeric1, self};
 
+use arena::{self, ArenaEntry};
+use diagnostics::{self, ParseWarning};
+use entities::Entity;
+use exporter::CIDE;
+use greek::{self, grk_item};
+use tags::TagName;
+use ids::EntryIds;
+use scan::{EntryBoundaries, ENTRY_CLOSE};
+pub use greek::{GreekItem, GreekMods};
+
+/// Tags that legitimately span several paragraphs and so may appear unpaired.
+pub const ALLOWED_TO_DANGLE: &[TagName] = &[TagName::Collapse, TagName::Cs, TagName::Note, TagName::Usage];
+
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Entry<'a> {
     pub main_word: &'a str,
     pub items: Vec<EntryItem<'a>>,
     pub source: &'a str,
+    /// The source of the entry, kept by `EntryParser::verified` when `CIDE`
+    /// would not reproduce it byte for byte. `CIDE` writes it in place of the
+    /// items, so clear it after editing them.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub raw: Option<&'a str>,
+    /// Stable identifier assigned by `EntryParser::with_ids`; see `ids::entry_id`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub id: Option<String>,
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum EntryItem<'a> {
-    Tagged { name: &'a str, items: Vec<EntryItem<'a>>, source: Option<&'a str> },
+    Tagged { name: TagName<'a>, items: Vec<EntryItem<'a>>, source: Option<&'a str> },
     Comment(&'a str),
-    Entity(&'a str),
+    /// A `<-- p. 123 -->` comment marking where a page of the printed 1913
+    /// edition starts.
+    PageBreak(u32),
+    Entity(Entity<'a>),
     EntityBr,
     EntityUnk,
     ExternalLink(&'a str, &'a str),
     Greek(Vec<GreekItem>),
     PlainText(&'a str),
-    UnpairedTagOpen(&'a str, Option<&'a str>),
-    UnpairedTagClose(&'a str),
-}
-
-#[derive(Debug, PartialEq)]
-pub enum GreekItem {
-    Letter(char, GreekMods),
-    Other(char), // typically, hyphen or space
-}
-
-bitflags! {
-    pub struct GreekMods: u16 {
-        const SLENIS     = 1 << 0; // '
-        const SASPER     = 1 << 1; // "
-        const ACUTE      = 1 << 2; // `
-        const GRAVE      = 1 << 3; // ~
-        const CIRCUMFLEX = 1 << 4; // ^
-        const IOTASUB    = 1 << 5; // ,
-        const DIAERESIS  = 1 << 6; // :
-        const TERMINAL   = 1 << 15; // to distinguish between normal and terminal sigma
-    }
+    UnpairedTagOpen(TagName<'a>, Option<&'a str>),
+    UnpairedTagClose(TagName<'a>),
 }
 
 named!(parse_items<CompleteStr, Vec<EntryItem>>, many0!(entry_item));
@@ -48,6 +59,39 @@ named!(parse_items<CompleteStr, Vec<EntryItem>>, many0!(entry_item));
 named!(entry_item<CompleteStr, EntryItem>,
        alt!(plain_text | grk_tag | open_tag | close_tag | entity | comment | ext_link));
 
+/// `parse_items` with the recovery of `EntryParser::lenient_tags` if `tags`,
+/// and of `EntryParser::lenient_brackets` if `brackets`, adding a warning for
+/// each item recovered.
+fn lenient_items<'a>(mut input: CompleteStr<'a>, tags: bool, brackets: bool,
+                     warnings: &mut Vec<ParseWarning<'a>>) -> (CompleteStr<'a>, Vec<EntryItem<'a>>) {
+    let mut items = Vec::new();
+    while !input.is_empty() {
+        if tags {
+            if let Ok((rest, (item, markup))) = loose_tag(input) {
+                warnings.push(ParseWarning::LooseTag(markup));
+                items.push(item);
+                input = rest;
+                continue;
+            }
+        }
+        if let Ok((rest, item)) = entry_item(input) {
+            items.push(item);
+            input = rest;
+            continue;
+        }
+        let entity = match input.as_bytes()[0] {
+            b'<' if brackets => Entity::Lt,
+            b'>' if brackets => Entity::Gt,
+            _ => break,
+        };
+        let context = input[1..].find(['<', '>', '\n']).map_or(input.len(), |len| 1 + len);
+        warnings.push(ParseWarning::StrayBracket(&input.0[..context]));
+        items.push(EntryItem::Entity(entity));
+        input = CompleteStr(&input.0[1..]);
+    }
+    (input, items)
+}
+
 named!(plain_text<CompleteStr, EntryItem>,
        map!(is_not!("<>"), |s| EntryItem::PlainText(s.0)));
 
@@ -61,72 +105,79 @@ named!(grk_tag<CompleteStr, EntryItem>,
            tag!("</grk>") >>
            ({
                let mut items = items;
-               { // mark terminal sigmas
-                   use parser::GreekItem::{Letter, Other};
-                   let mut iter = items.iter_mut().peekable();
-                   while let Some(gi) = iter.next() {
-                       if let Letter('s', ref mut mods) = *gi {
-                           if let Other(_) = iter.peek().unwrap_or(& &mut Other(' ')) {
-                               *mods |= GreekMods::TERMINAL;
-                           }
-                       }
-                   }
-               }
+               greek::mark_terminal_sigmas(&mut items);
                EntryItem::Greek(items)
            })));
 
-named!(grk_item<CompleteStr, GreekItem>,
-       alt!(grk_letter | grk_other));
-
-named!(grk_letter<CompleteStr, GreekItem>,
-       do_parse!(
-           pre: opt!(one_of!("'\"")) >>
-           base: grk_letter_base >>
-           post: many0!(one_of!("`~^,:")) >>
-           ({
-               let mut mods = GreekMods::empty();
-               match pre {
-                   Some('\'') => mods |= GreekMods::SLENIS,
-                   Some('"') => mods |= GreekMods::SASPER,
-                   Some(_) => unreachable!(),
-                   None => (),
-               }
-               for m in post {
-                   match m {
-                       '`' => mods |= GreekMods::ACUTE,
-                       '~' => mods |= GreekMods::GRAVE,
-                       '^' => mods |= GreekMods::CIRCUMFLEX,
-                       ',' => mods |= GreekMods::IOTASUB,
-                       ':' => mods |= GreekMods::DIAERESIS,
-                       _ => unreachable!(),
-                   }
-               }
-               GreekItem::Letter(base, mods)
-           })));
-
-named!(grk_other<CompleteStr, GreekItem>,
-       map!(one_of!(" -"), |c| GreekItem::Other(c)));
-
-named!(grk_letter_base<CompleteStr, char>, one_of!("abcdefghijklmnopqrstvwxyzABCDEFGHIJKLMNOPQRSTWXYZ"));
-
 named!(open_tag<CompleteStr, EntryItem>,
        do_parse!(
            tag!("<") >>
            name: alphanumeric1 >>
            source: opt!(source_attr) >>
            tag!(">") >>
-           ( EntryItem::UnpairedTagOpen(name.0, source.map(|s| s.0)) )));
+           ( EntryItem::UnpairedTagOpen(TagName::new(name.0), source.map(|s| s.0)) )));
 
 named!(close_tag<CompleteStr, EntryItem>,
-       map!(delimited!(tag!("</"), alphanumeric1, tag!(">")), |s| EntryItem::UnpairedTagClose(s.0)));
+       map!(delimited!(tag!("</"), alphanumeric1, tag!(">")), |s| EntryItem::UnpairedTagClose(TagName::new(s.0))));
+
+/// A registered tag written with stray spaces or in another case, such as
+/// `< hw>` or `</Def >`, and the markup as written. Fails on tags written as
+/// `open_tag` and `close_tag` expect them, and on unregistered names.
+fn loose_tag<'a>(input: CompleteStr<'a>) -> nom::IResult<CompleteStr<'a>, (EntryItem<'a>, &'a str)> {
+    let fail = || Err(nom::Err::Error(error_position!(input, nom::ErrorKind::Custom(0))));
+    let text = input.0;
+    let rest = match text.strip_prefix('<') {
+        Some(rest) => rest.trim_start_matches(' '),
+        None => return fail(),
+    };
+    let (close, rest) = match rest.strip_prefix('/') {
+        Some(rest) => (true, rest.trim_start_matches(' ')),
+        None => (false, rest),
+    };
+    let name_len = rest.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(rest.len());
+    let (name, mut rest) = rest.split_at(name_len);
+    let tag = match TagName::known(&name.to_ascii_lowercase()) {
+        Some(tag) => tag,
+        None => return fail(),
+    };
+    let mut source = None;
+    if !close {
+        if let Some((value, after)) = rest.strip_prefix(" source=\"").and_then(|attr| attr.split_once('"')) {
+            source = Some(value);
+            rest = after;
+        }
+    }
+    let body = match rest.trim_start_matches(' ').strip_prefix('>') {
+        Some(body) => body,
+        None => return fail(),
+    };
+    let markup = &text[..text.len() - body.len()];
+    let strict_len = 2 + close as usize + name.len() + source.map_or(0, |source| " source=\"\"".len() + source.len());
+    if markup.len() == strict_len && name == tag.as_str() {
+        return fail();
+    }
+    let item = if close { EntryItem::UnpairedTagClose(tag) } else { EntryItem::UnpairedTagOpen(tag, source) };
+    Ok((CompleteStr(body), (item, markup)))
+}
 
 named!(entity<CompleteStr, EntryItem>,
        alt!(map!(tag!("<?/"), |_| EntryItem::EntityUnk) |
             map!(tuple!(tag!("<br/"), opt!(char!('\n'))), |_| EntryItem::EntityBr) |
-            map!(delimited!(tag!("<"), take_while1!(is_entity_char), tag!("/")), |s| EntryItem::Entity(s.0))));
+            map!(delimited!(tag!("<"), take_while1!(is_entity_char), tag!("/")),
+                 |s| EntryItem::Entity(Entity::new(s.0)))));
 
 named!(comment<CompleteStr, EntryItem>,
-       map!(delimited!(tag!("<--"), take_until!("-->"), tag!("-->")), |s| EntryItem::Comment(s.0)));
+       map!(delimited!(tag!("<--"), take_until!("-->"), tag!("-->")), |s| comment_or_page_break(s.0)));
+
+/// A `PageBreak` if `text` is exactly as `CIDE` would write it, else a `Comment`.
+fn comment_or_page_break(text: &str) -> EntryItem<'_> {
+    match text.strip_prefix(" p. ").and_then(|rest| rest.strip_suffix(' ')) {
+        Some(page) if !page.starts_with('0') && page.bytes().all(|b| b.is_ascii_digit()) => {
+            page.parse().map_or(EntryItem::Comment(text), EntryItem::PageBreak)
+        }
+        _ => EntryItem::Comment(text),
+    }
+}
 
 named!(ext_link<CompleteStr, EntryItem>,
        do_parse!(
@@ -137,17 +188,180 @@ named!(ext_link<CompleteStr, EntryItem>,
            tag!("</a>") >>
            ( EntryItem::ExternalLink(url.0, text.0) )));
 
+impl<'a> Entry<'a> {
+    /// Parses the first entry block in `text`.
+    pub fn parse(text: &'a str) -> Result<Entry<'a>, ParserError<'a>> {
+        EntryParser::new(text).next().unwrap_or(Err(ParserError {
+            leading: "",
+            trailing: text,
+            fragment: false,
+            attribute: None,
+        }))
+    }
+}
+
+impl<'a> EntryItem<'a> {
+    /// Parses a sequence of items as found inside an entry (a fragment such as
+    /// `<def>...</def>`), pairing up tags. Errors are reported relative to the
+    /// fragment; see `diagnostics::check_items` for non-fatal issues.
+    pub fn parse_items(text: &'a str) -> Result<Vec<EntryItem<'a>>, ParserError<'a>> {
+        match parse_items(CompleteStr(text)) {
+            Ok((unparsed, items)) => if unparsed.is_empty() {
+                Ok(pair_up_items(items, &PairingRules::default(), &mut Vec::new()))
+            } else {
+                let lead_len = text.len() - unparsed.len();
+                Err(ParserError::split(text, lead_len, lead_len, true))
+            },
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
 fn is_entity_char(c: char) -> bool {
     c.is_digit(36) || c == ':' || c == '_'
 }
 
 pub struct EntryParser<'a> {
+    input: &'a str,
     contents: &'a str,
+    last_span: Option<Range<usize>>,
+    warnings: Vec<ParseWarning<'a>>,
+    rules: PairingRules,
+    keep_raw: bool,
+    lenient_heads: bool,
+    lenient_tags: bool,
+    lenient_brackets: bool,
+    ids: Option<EntryIds>,
+    entries: usize,
+    on_progress: Option<Box<dyn FnMut(Progress) + 'a>>,
+    bounds: EntryBoundaries,
+}
+
+/// How far an `EntryParser` has got through its input.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Progress {
+    /// Entry blocks returned so far, parsable or not.
+    pub entries: usize,
+    pub bytes: usize,
+    pub total_bytes: usize,
+}
+
+/// How opening and closing tags are paired up. By default every close tag
+/// pairs with the nearest open tag of the same name, and tags that cannot be
+/// paired are kept as `UnpairedTagOpen`/`UnpairedTagClose`. Tags closed by a
+/// rule render with a close tag that is not in the source.
+#[derive(Clone, Debug, Default)]
+pub struct PairingRules {
+    /// `(tag, opener)` pairs: an open `tag` is closed where an `opener` tag opens.
+    pub auto_close: Vec<(String, String)>,
+    /// Close tags still open inside a tag when it closes (except those allowed to dangle).
+    pub close_at_parent_end: bool,
 }
 
 impl<'a> EntryParser<'a> {
     pub fn new(contents: &'a str) -> EntryParser<'a> {
-        EntryParser { contents }
+        EntryParser {
+            input: contents,
+            contents,
+            last_span: None,
+            warnings: Vec::new(),
+            rules: PairingRules::default(),
+            keep_raw: false,
+            lenient_heads: false,
+            lenient_tags: false,
+            lenient_brackets: false,
+            ids: None,
+            entries: 0,
+            on_progress: None,
+            bounds: EntryBoundaries::new(),
+        }
+    }
+
+    /// Calls `callback` after each entry block is parsed.
+    pub fn on_progress<F>(mut self, callback: F) -> EntryParser<'a>
+    where F: FnMut(Progress) + 'a {
+        self.on_progress = Some(Box::new(callback));
+        self
+    }
+
+    pub fn progress(&self) -> Progress {
+        Progress {
+            entries: self.entries,
+            bytes: self.input.len() - self.contents.len(),
+            total_bytes: self.input.len(),
+        }
+    }
+
+    /// Keeps the source of entries that `CIDE` would not reproduce exactly (see
+    /// `Entry::raw`), so that writing them back is guaranteed lossless.
+    pub fn verified(mut self) -> EntryParser<'a> {
+        self.keep_raw = true;
+        self
+    }
+
+    /// Accepts entry heads as written by older CIDE releases (before 0.51) and
+    /// files derived from them: attributes in any order, quoted with `"` or
+    /// `'`, other attributes ignored, and `source` optional (empty if missing).
+    /// `CIDE` writes such heads in the usual form.
+    pub fn lenient_heads(mut self) -> EntryParser<'a> {
+        self.lenient_heads = true;
+        self
+    }
+
+    /// Accepts registered tags written with stray spaces or in another case
+    /// (`< hw>`, `</def >`, `<Def>`) instead of failing on them or leaving
+    /// them unpaired, with a `ParseWarning::LooseTag` for each. `CIDE` writes
+    /// them in the usual form.
+    pub fn lenient_tags(mut self) -> EntryParser<'a> {
+        self.lenient_tags = true;
+        self
+    }
+
+    /// Reads a `<` or `>` that starts no tag, entity or comment, as in
+    /// `a < b`, as `<lt/` or `<gt/` instead of failing on the entry, with a
+    /// `ParseWarning::StrayBracket` for each. Some broken markup, such as
+    /// `<hw` missing its `>`, is read as text this way too.
+    pub fn lenient_brackets(mut self) -> EntryParser<'a> {
+        self.lenient_brackets = true;
+        self
+    }
+
+    /// Gives every entry an `id`. Homographs are numbered in the order they
+    /// come, so the parser must start at the beginning of the file.
+    pub fn with_ids(mut self) -> EntryParser<'a> {
+        self.ids = Some(EntryIds::new());
+        self
+    }
+
+    pub fn with_rules(mut self, rules: PairingRules) -> EntryParser<'a> {
+        self.rules = rules;
+        self
+    }
+
+    /// Yields the source of each entry block along with its parse result.
+    pub fn with_raw(self) -> WithRaw<'a> {
+        WithRaw { parser: self }
+    }
+
+    /// Yields entries whose items all live in one `Vec`; see `arena::ArenaEntry`.
+    pub fn with_arena(self) -> WithArena<'a> {
+        WithArena { parser: self }
+    }
+
+    /// Also yields the text outside entry blocks, such as the preface and any
+    /// stray markup between `</entry>` and the next `<entry `.
+    pub fn with_gaps(self) -> WithGaps<'a> {
+        WithGaps { parser: self, pending: None, copied_upto: 0 }
+    }
+
+    /// Non-fatal issues found in the entry last returned by `next`.
+    pub fn warnings(&self) -> &[ParseWarning<'a>] {
+        &self.warnings
+    }
+
+    /// Byte range (in the original input) of the block last returned by `next`.
+    pub fn last_span(&self) -> Option<Range<usize>> {
+        self.last_span.clone()
     }
 
     pub fn get_preface(&self) -> Option<&'a str> {
@@ -168,7 +382,15 @@ struct EntryHead<'a> {
     source: &'a str,
 }
 
-named!(entry_head<&str, EntryHead>,
+/// An entry block as parsed, before its tags are paired up.
+struct Tokens<'a> {
+    head: EntryHead<'a>,
+    items: Vec<EntryItem<'a>>,
+    /// The whole block, from `<entry ` to `</entry>`.
+    raw: &'a str,
+}
+
+named!(entry_head<&str, EntryHead<'_>>,
        do_parse!(
            tag!("<entry") >>
            main_word: delimited!(tag!(" main-word=\""), take_till!(|c| c == '"'), tag!("\"")) >>
@@ -176,53 +398,236 @@ named!(entry_head<&str, EntryHead>,
            tag!(">") >>
            ( EntryHead { main_word, source } )));
 
+/// `entry_head` for `EntryParser::lenient_heads`; the error is the text that
+/// could not be parsed.
+fn lenient_entry_head(input: &str) -> Result<(&str, EntryHead<'_>), &str> {
+    let mut rest = input.strip_prefix("<entry").ok_or(input)?;
+    let (mut main_word, mut source) = (None, None);
+    loop {
+        let trimmed = rest.trim_start();
+        if let Some(body) = trimmed.strip_prefix('>') {
+            return match main_word {
+                Some(main_word) => Ok((body, EntryHead { main_word, source: source.unwrap_or("") })),
+                None => Err(input),
+            };
+        }
+        if trimmed.len() == rest.len() {
+            return Err(rest);
+        }
+        let eq = trimmed.find('=').ok_or(trimmed)?;
+        let (name, value) = (trimmed[..eq].trim_end(), trimmed[eq + 1..].trim_start());
+        let quote = value.chars().next().filter(|&c| c == '"' || c == '\'').ok_or(trimmed)?;
+        let len = value[1..].find(quote).ok_or(trimmed)?;
+        match name {
+            "main-word" => main_word = Some(&value[1..1 + len]),
+            "source" => source = Some(&value[1..1 + len]),
+            _ => (),
+        }
+        rest = &value[len + 2..];
+    }
+}
+
 impl<'a> Iterator for EntryParser<'a> {
     type Item = Result<Entry<'a>, ParserError<'a>>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.contents.find("<entry ").map(|start_idx| {
+        self.warnings.clear();
+        let entry_res = self.next_block()?;
+        if let Ok(ref entry) = entry_res {
+            self.warnings.extend(diagnostics::check_entry(entry));
+        }
+        self.count_block();
+        Some(entry_res)
+    }
+}
+
+/// Iterator returned by `EntryParser::with_arena`. Entries are neither
+/// verified nor given ids, and their warnings are only those of parsing and
+/// pairing tags.
+pub struct WithArena<'a> {
+    parser: EntryParser<'a>,
+}
+
+impl<'a> WithArena<'a> {
+    /// The underlying parser, for `warnings` and `last_span`.
+    pub fn parser(&self) -> &EntryParser<'a> {
+        &self.parser
+    }
+}
+
+impl<'a> Iterator for WithArena<'a> {
+    type Item = Result<ArenaEntry<'a>, ParserError<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let parser = &mut self.parser;
+        parser.warnings.clear();
+        let entry_res = parser.next_tokens()?.map(|Tokens { head, items, .. }| ArenaEntry {
+            main_word: head.main_word,
+            source: head.source,
+            nodes: arena::pair_up_nodes(items, &parser.rules, &mut parser.warnings),
+        });
+        parser.count_block();
+        Some(entry_res)
+    }
+}
+
+/// Iterator returned by `EntryParser::with_raw`.
+pub struct WithRaw<'a> {
+    parser: EntryParser<'a>,
+}
+
+impl<'a> WithRaw<'a> {
+    /// The underlying parser, for `warnings` and `last_span`.
+    pub fn parser(&self) -> &EntryParser<'a> {
+        &self.parser
+    }
+}
+
+impl<'a> Iterator for WithRaw<'a> {
+    type Item = (&'a str, Result<Entry<'a>, ParserError<'a>>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry_res = self.parser.next()?;
+        let span = self.parser.last_span().unwrap();
+        Some((&self.parser.input[span], entry_res))
+    }
+}
+
+/// An item of `WithGaps`.
+#[derive(Debug)]
+pub enum Block<'a> {
+    /// Non-empty text outside entry blocks, starting at byte `offset` of the input.
+    Gap { offset: usize, text: &'a str },
+    Entry(Result<Entry<'a>, ParserError<'a>>),
+}
+
+/// Iterator returned by `EntryParser::with_gaps`. Its blocks, in order, make
+/// up the whole input.
+pub struct WithGaps<'a> {
+    parser: EntryParser<'a>,
+    pending: Option<Result<Entry<'a>, ParserError<'a>>>,
+    copied_upto: usize,
+}
+
+impl<'a> WithGaps<'a> {
+    /// The underlying parser, for `warnings` and `last_span` of the last entry.
+    pub fn parser(&self) -> &EntryParser<'a> {
+        &self.parser
+    }
+}
+
+impl<'a> Iterator for WithGaps<'a> {
+    type Item = Block<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(entry_res) = self.pending.take() {
+            return Some(Block::Entry(entry_res));
+        }
+        let offset = self.copied_upto;
+        let input = self.parser.input;
+        match self.parser.next() {
+            Some(entry_res) => {
+                let span = self.parser.last_span().unwrap();
+                self.copied_upto = span.end;
+                if span.start == offset {
+                    return Some(Block::Entry(entry_res));
+                }
+                self.pending = Some(entry_res);
+                Some(Block::Gap { offset, text: &input[offset..span.start] })
+            }
+            None if offset < input.len() => {
+                self.copied_upto = input.len();
+                Some(Block::Gap { offset, text: &input[offset..] })
+            }
+            None => None,
+        }
+    }
+}
+
+impl<'a> EntryParser<'a> {
+    fn count_block(&mut self) {
+        self.entries += 1;
+        let progress = self.progress();
+        if let Some(ref mut callback) = self.on_progress {
+            callback(progress);
+        }
+    }
+
+    fn next_block(&mut self) -> Option<Result<Entry<'a>, ParserError<'a>>> {
+        let tokens_res = self.next_tokens()?;
+        Some(tokens_res.map(|Tokens { head, items, raw }| {
+            let mut entry = Entry {
+                main_word: head.main_word,
+                items: pair_up_items(items, &self.rules, &mut self.warnings),
+                source: head.source,
+                raw: None,
+                id: None,
+            };
+            if self.keep_raw && CIDE(&entry).to_string() != raw {
+                entry.raw = Some(raw);
+            }
+            if let Some(ref mut ids) = self.ids {
+                ids.assign(&mut entry);
+            }
+            entry
+        }))
+    }
+
+    fn next_tokens(&mut self) -> Option<Result<Tokens<'a>, ParserError<'a>>> {
+        self.bounds.find_open(self.contents).map(|start_idx| {
             let remaining = &self.contents[start_idx..];
-            let end_idx = match remaining.find("</entry>") {
+            let span_start = self.input.len() - remaining.len();
+            let end_idx = match self.bounds.find_close(remaining) {
                 Some(i) => i,
                 None => {
                     self.contents = ""; // further parsing not possible
+                    self.last_span = Some(span_start..self.input.len());
                     return Err(ParserError {
                         leading: "",
-                        trailing: "",
+                        trailing: remaining,
+                        fragment: false,
+                        attribute: None,
                     });
                 }
             };
-            let close_len = "</entry>".len();
+            let close_len = ENTRY_CLOSE.len();
             self.contents = &remaining[end_idx + close_len..];
-            match entry_head(&remaining[..end_idx]) {
-                Ok((entry_str, EntryHead { main_word, source })) => {
-                    match parse_items(CompleteStr(entry_str)) {
+            self.last_span = Some(span_start..span_start + end_idx + close_len);
+            let head = if self.lenient_heads {
+                lenient_entry_head(&remaining[..end_idx])
+            } else {
+                match entry_head(&remaining[..end_idx]) {
+                    Ok(parsed) => Ok(parsed),
+                    Err(nom::Err::Error(nom::simple_errors::Context::Code(context, _))) => Err(context),
+                    Err(_) => unreachable!(),
+                }
+            };
+            match head {
+                Ok((entry_str, head)) => {
+                    let mut recovered = Vec::new();
+                    let parsed = if self.lenient_tags || self.lenient_brackets {
+                        let input = CompleteStr(entry_str);
+                        Ok(lenient_items(input, self.lenient_tags, self.lenient_brackets, &mut recovered))
+                    } else {
+                        parse_items(CompleteStr(entry_str))
+                    };
+                    match parsed {
                         Ok((unparsed, items)) => {
                             if unparsed.len() > 0 {
                                 let lead_len = end_idx - unparsed.len();
-                                Err(ParserError {
-                                    leading: &remaining[..lead_len],
-                                    trailing: &remaining[lead_len..end_idx + close_len],
-                                })
+                                Err(ParserError::split(&remaining[..end_idx + close_len], lead_len, lead_len, false))
                             } else {
-                                Ok(Entry {
-                                    main_word: main_word,
-                                    items: pair_up_items(items),
-                                    source: source,
-                                })
+                                self.warnings.extend(recovered);
+                                Ok(Tokens { head, items, raw: &remaining[..end_idx + close_len] })
                             }
                         }
                         Err(_) => unreachable!(),
                     }
                 }
-                Err(nom::Err::Error(nom::simple_errors::Context::Code(context, _))) => {
+                Err(context) => {
                     let lead_len = end_idx - context.len();
-                    Err(ParserError {
-                        leading: &remaining[..lead_len],
-                        trailing: &remaining[lead_len..end_idx + close_len],
-                    })
+                    Err(ParserError::split(&remaining[..end_idx + close_len], 0, lead_len, false))
                 }
-                Err(_) => unreachable!(),
             }
         })
     }
@@ -232,6 +637,54 @@ impl<'a> Iterator for EntryParser<'a> {
 pub struct ParserError<'a> {
     pub leading: &'a str,
     pub trailing: &'a str,
+    /// Whether the input was a sequence of items rather than whole entries.
+    fragment: bool,
+    attribute: Option<AttributeError<'a>>,
+}
+
+/// A malformed attribute in an entry head or tag.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AttributeError<'a> {
+    /// An attribute given a second time.
+    Duplicate(&'a str),
+    /// An attribute whose value has no closing quote.
+    Unterminated(&'a str),
+}
+
+impl<'a> Display for AttributeError<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            AttributeError::Duplicate(name) => write!(f, "duplicate `{}` attribute", name),
+            AttributeError::Unterminated(name) => write!(f, "value of `{}` attribute is missing its closing quote", name),
+        }
+    }
+}
+
+/// The first malformed attribute of the entry head or tag at the start of
+/// `markup`, and its offset.
+fn find_attribute_error(markup: &str) -> Option<(usize, AttributeError<'_>)> {
+    let mut offset = 1 + markup.strip_prefix('<')?.find(|c: char| !c.is_ascii_alphanumeric())?;
+    let mut seen = Vec::new();
+    loop {
+        let start = offset + 1;
+        let attr = markup[offset..].strip_prefix(' ')?;
+        let name_len = attr.find("=\"")?;
+        let name = &attr[..name_len];
+        if name.is_empty() || !name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-') {
+            return None;
+        }
+        let value = &attr[name_len + 2..];
+        // a quote followed by more text is where the next value starts
+        let value_len = match value.find(['"', '<', '>', '\n']) {
+            Some(len) if value[len..].starts_with('"') && value[len + 1..].starts_with([' ', '>']) => len,
+            _ => return Some((start, AttributeError::Unterminated(name))),
+        };
+        if seen.contains(&name) {
+            return Some((start, AttributeError::Duplicate(name)));
+        }
+        seen.push(name);
+        offset = start + name_len + 2 + value_len + 1;
+    }
 }
 
 impl<'a> Display for ParserError<'a> {
@@ -240,26 +693,153 @@ impl<'a> Display for ParserError<'a> {
     }
 }
 
-fn pair_up_items<'a>(items: Vec<EntryItem<'a>>) -> Vec<EntryItem<'a>> {
+impl<'a> ParserError<'a> {
+    /// An error splitting `text` at `lead_len`, or at the first malformed
+    /// attribute of the entry head or tag at `tag_start` if it has one.
+    fn split(text: &'a str, tag_start: usize, lead_len: usize, fragment: bool) -> ParserError<'a> {
+        let found = find_attribute_error(&text[tag_start..]);
+        let lead_len = found.map_or(lead_len, |(offset, _)| tag_start + offset);
+        ParserError {
+            leading: &text[..lead_len],
+            trailing: &text[lead_len..],
+            fragment,
+            attribute: found.map(|(_, err)| err),
+        }
+    }
+
+    /// Line and column (both 1-based, counted in chars) of the failing position,
+    /// relative to the start of the entry.
+    pub fn position(&self) -> (usize, usize) {
+        let line_start = self.leading.rfind('\n').map_or(0, |i| i + 1);
+        let line = self.leading.matches('\n').count() + 1;
+        (line, self.leading[line_start..].chars().count() + 1)
+    }
+
+    /// The items parsed before the failing position, with tags paired up as far
+    /// as possible. Empty if the entry head itself is broken.
+    pub fn partial_items(&self) -> Vec<EntryItem<'a>> {
+        let body = if self.fragment {
+            self.leading
+        } else {
+            match entry_head(self.leading) {
+                Ok((body, _)) => body,
+                Err(_) => return Vec::new(),
+            }
+        };
+        match parse_items(CompleteStr(body)) {
+            Ok((_, items)) => pair_up_items(items, &PairingRules::default(), &mut Vec::new()),
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// The attribute at the failing position, if it is given twice or its
+    /// value is not closed.
+    pub fn attribute_error(&self) -> Option<AttributeError<'a>> {
+        self.attribute
+    }
+
+    /// A short guess at what the parser expected at the failing position.
+    pub fn hint(&self) -> Cow<'static, str> {
+        if let Some(err) = self.attribute {
+            return Cow::Owned(err.to_string());
+        }
+        let t = self.trailing;
+        let at_start = self.leading.is_empty() && !self.fragment;
+        Cow::Borrowed(if at_start && !t.contains("<entry ") {
+            "no <entry> block found"
+        } else if at_start && !t.contains("</entry>") {
+            "entry is never closed with </entry>"
+        } else if !self.fragment && !self.leading.contains('>') {
+            "malformed entry head; expected <entry main-word=\"...\" source=\"...\">"
+        } else if t.starts_with('>') {
+            "stray `>`; write it as <gt/"
+        } else if t.starts_with("<grk>") {
+            "invalid Greek transliteration or missing </grk>"
+        } else if t.starts_with("<a ") {
+            "malformed link; expected <a href=\"...\">text</a>"
+        } else if t.starts_with("<--") {
+            "comment is never closed with -->"
+        } else if t.starts_with('<') {
+            "expected a tag, an entity or a comment; a bare `<` should be written as <lt/"
+        } else {
+            "unexpected input"
+        })
+    }
+
+    /// Renders the failing line with a little context and a caret under the
+    /// failing position, instead of dumping the whole entry.
+    pub fn annotated(&self) -> Annotated<'a> {
+        Annotated { error: *self, first_line: 1 }
+    }
+}
+
+/// Display adapter returned by `ParserError::annotated`.
+pub struct Annotated<'a> {
+    error: ParserError<'a>,
+    first_line: usize,
+}
+
+impl<'a> Annotated<'a> {
+    const CONTEXT_LINES: usize = 2;
+
+    /// Number lines as if the entry started at `line` of its file.
+    pub fn first_line(mut self, line: usize) -> Annotated<'a> {
+        self.first_line = line;
+        self
+    }
+}
+
+impl<'a> Display for Annotated<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let err = &self.error;
+        let (line, column) = err.position();
+        let line_no = line + self.first_line - 1;
+        let gutter = line_no.to_string().len();
+        writeln!(f, "error: {}", err.hint())?;
+        writeln!(f, "{:w$}--> line {}, column {}", "", line_no, column, w = gutter)?;
+        writeln!(f, "{:w$} |", "", w = gutter)?;
+        let leading_lines: Vec<&str> = err.leading.split('\n').collect();
+        let context_start = leading_lines.len().saturating_sub(Self::CONTEXT_LINES + 1);
+        for (idx, text) in leading_lines[context_start..leading_lines.len() - 1].iter().enumerate() {
+            writeln!(f, "{:>w$} | {}", line_no - (leading_lines.len() - 1 - context_start) + idx, text, w = gutter)?;
+        }
+        let failing_rest = err.trailing.split('\n').next().unwrap_or("");
+        writeln!(f, "{:>w$} | {}{}", line_no, leading_lines[leading_lines.len() - 1], failing_rest, w = gutter)?;
+        write!(f, "{:w$} | {:c$}^", "", "", w = gutter, c = column - 1)
+    }
+}
+
+pub(crate) fn pair_up_items<'a>(items: Vec<EntryItem<'a>>,
+                                rules: &PairingRules,
+                                warnings: &mut Vec<ParseWarning<'a>>) -> Vec<EntryItem<'a>> {
     use self::EntryItem::*;
 
     let mut stack = Vec::with_capacity(items.len()*2/3 + 1);
     for item in items {
         match item {
+            UnpairedTagOpen(name, _) => {
+                for (tag, _) in rules.auto_close.iter().filter(|(_, opener)| name == opener.as_str()) {
+                    if let Some((open_idx, _)) = find_open_tag(&stack, TagName::new(tag)) {
+                        close_open_tag(&mut stack, open_idx);
+                        warnings.push(ParseWarning::ImplicitlyClosed(tag_name(&stack[open_idx])));
+                    }
+                }
+                stack.push(item);
+            }
             UnpairedTagClose(name) => {
-                let is_tag_open_name = |item: &EntryItem<'a>| {
-                    match *item {
-                        UnpairedTagOpen(n, src) if n == name => Some(src),
-                        _ => None,
+                if let Some((open_idx, _)) = find_open_tag(&stack, name) {
+                    if rules.close_at_parent_end {
+                        while let Some((inner_idx, inner)) = linear_search_rev_by(&stack[open_idx + 1..], |item| {
+                            match *item {
+                                UnpairedTagOpen(n, _) if !ALLOWED_TO_DANGLE.contains(&n) => Some(n.as_str()),
+                                _ => None,
+                            }
+                        }) {
+                            close_open_tag(&mut stack, open_idx + 1 + inner_idx);
+                            warnings.push(ParseWarning::ImplicitlyClosed(inner));
+                        }
                     }
-                };
-                if let Some((open_idx, source)) = linear_search_rev_by(&stack, is_tag_open_name) {
-                    let tagged = Tagged {
-                        name: name,
-                        items: stack.drain(open_idx+1..).collect(),
-                        source: source,
-                    };
-                    stack[open_idx] = tagged;
+                    close_open_tag(&mut stack, open_idx);
                 } else {
                     stack.push(item);
                 }
@@ -270,12 +850,207 @@ fn pair_up_items<'a>(items: Vec<EntryItem<'a>>) -> Vec<EntryItem<'a>> {
     stack
 }
 
-fn linear_search_rev_by<T, U, F>(haystack: &Vec<T>, filter_map: F) -> Option<(usize, U)>
+/// Index of the innermost unpaired open tag `name` in `stack`.
+fn find_open_tag<'a>(stack: &[EntryItem<'a>], name: TagName) -> Option<(usize, Option<&'a str>)> {
+    linear_search_rev_by(stack, |item| match *item {
+        EntryItem::UnpairedTagOpen(n, src) if n == name => Some(src),
+        _ => None,
+    })
+}
+
+/// Turns the open tag at `open_idx` into a tag holding everything after it.
+fn close_open_tag(stack: &mut Vec<EntryItem>, open_idx: usize) {
+    if let EntryItem::UnpairedTagOpen(name, source) = stack[open_idx] {
+        let items = stack.drain(open_idx + 1..).collect();
+        stack[open_idx] = EntryItem::Tagged { name, items, source };
+    }
+}
+
+fn tag_name<'a>(item: &EntryItem<'a>) -> &'a str {
+    match *item {
+        EntryItem::Tagged { name, .. } | EntryItem::UnpairedTagOpen(name, _) | EntryItem::UnpairedTagClose(name) => {
+            name.as_str()
+        }
+        _ => "",
+    }
+}
+
+fn linear_search_rev_by<T, U, F>(haystack: &[T], filter_map: F) -> Option<(usize, U)>
 where T: PartialEq, F: Fn(&T) -> Option<U> {
     for (idx, item) in haystack.iter().enumerate().rev() {
         if let Some(out) = filter_map(item) {
             return Some((idx, out));
         }
     }
-    return None;
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn annotated_error() {
+        let text = "<entry main-word=\"A\" source=\"B\">\n<p><hw>A</hw>\n<def>x > y</def></p>\n</entry>\n";
+        let err = EntryParser::new(text).next().unwrap().unwrap_err();
+        assert_eq!(err.position(), (3, 8));
+        assert_eq!(err.hint(), "stray `>`; write it as <gt/");
+        assert_eq!(err.annotated().first_line(10).to_string(), "\
+error: stray `>`; write it as <gt/
+  --> line 12, column 8
+   |
+10 | <entry main-word=\"A\" source=\"B\">
+11 | <p><hw>A</hw>
+12 | <def>x > y</def></p>
+   |        ^");
+    }
+
+    #[test]
+    fn attribute_errors() {
+        fn errors(text: &str) -> (Option<AttributeError<'_>>, (usize, usize), String) {
+            let err = Entry::parse(text).unwrap_err();
+            (err.attribute_error(), err.position(), err.hint().into_owned())
+        }
+        assert_eq!(errors("<entry main-word=\"A\" source=\"B\" source=\"C\"><hw>A</hw></entry>"),
+                   (Some(AttributeError::Duplicate("source")), (1, 33), "duplicate `source` attribute".to_string()));
+        assert_eq!(errors("<entry main-word=\"A source=\"B\"><hw>A</hw></entry>").0,
+                   Some(AttributeError::Unterminated("main-word")));
+        assert_eq!(errors("<entry main-word=\"A\" source=\"B\">\n<hw source=\"C>A</hw></entry>"),
+                   (Some(AttributeError::Unterminated("source")), (2, 5),
+                    "value of `source` attribute is missing its closing quote".to_string()));
+        assert_eq!(errors("<entry main-word=\"A\" source=\"B\">a > b</entry>").0, None);
+    }
+
+    #[test]
+    fn parse_snippets() {
+        let entry = Entry::parse("<entry main-word=\"A\" source=\"B\"><hw>A</hw></entry>").unwrap();
+        assert_eq!((entry.main_word, entry.items.len()), ("A", 1));
+        assert_eq!(Entry::parse("<hw>A</hw>").unwrap_err().hint(), "no <entry> block found");
+
+        let items = EntryItem::parse_items("<def>a <i>b</i></def><br/").unwrap();
+        assert_eq!(items, [
+            EntryItem::Tagged { name: TagName::Def, source: None, items: vec![
+                EntryItem::PlainText("a "),
+                EntryItem::Tagged { name: TagName::I, source: None, items: vec![EntryItem::PlainText("b")] },
+            ] },
+            EntryItem::EntityBr,
+        ]);
+        let err = EntryItem::parse_items("<def>a > b</def>").unwrap_err();
+        assert_eq!((err.leading, err.trailing), ("<def>a ", "> b</def>"));
+        assert_eq!(EntryItem::parse_items("x < y").unwrap_err().hint(),
+                   "expected a tag, an entity or a comment; a bare `<` should be written as <lt/");
+    }
+
+    #[test]
+    fn pairing_rules() {
+        let text = "<entry main-word=\"A\" source=\"B\"><note>a<note>b</note>c</note>\
+                    <p>one<p>two</p><def>x<i>y</def></entry>";
+        let render = |entry: &Entry| entry.items.iter().map(|item| format!("{:?}", item)).collect::<Vec<_>>().join(" ");
+        let mut parser = EntryParser::new(text);
+        let plain = parser.next().unwrap().unwrap();
+        assert!(render(&plain).starts_with("Tagged { name: \"note\", items: [PlainText(\"a\"), Tagged { name: \"note\""));
+        assert_eq!(parser.warnings(), [ParseWarning::DanglingTagOpen("p"), ParseWarning::DanglingTagOpen("i")]);
+
+        let rules = PairingRules { auto_close: vec![("p".into(), "p".into())], close_at_parent_end: true };
+        let mut parser = EntryParser::new(text).with_rules(rules);
+        let entry = parser.next().unwrap().unwrap();
+        assert_eq!(parser.warnings(), [ParseWarning::ImplicitlyClosed("p"), ParseWarning::ImplicitlyClosed("i")]);
+        assert_eq!(entry.items[1], EntryItem::Tagged { name: TagName::P, source: None, items: vec![EntryItem::PlainText("one")] });
+    }
+
+    #[test]
+    fn verified_round_trip() {
+        use exporter::CIDE;
+        let text = "<entry main-word=\"A\" source=\"B\"><p>one<br/two</p></entry>\n\
+                    <entry main-word=\"C\" source=\"B\"><p>three</p></entry>";
+        let lossy: Vec<_> = EntryParser::new(text).map(|entry| CIDE(&entry.unwrap()).to_string()).collect();
+        assert_eq!(lossy[0], "<entry main-word=\"A\" source=\"B\"><p>one<br/\ntwo</p></entry>");
+        let entries: Vec<_> = EntryParser::new(text).verified().map(Result::unwrap).collect();
+        assert_eq!(entries[0].raw, Some(&text[..57]));
+        assert_eq!(entries[1].raw, None);
+        let written: Vec<_> = entries.iter().map(|entry| CIDE(entry).to_string()).collect();
+        assert_eq!(written.join("\n"), text);
+    }
+
+    #[test]
+    fn salvage_items() {
+        let text = "<entry main-word=\"A\" source=\"B\">\n<p><hw>A</hw> <def>x > y</def></p>\n</entry>";
+        let err = Entry::parse(text).unwrap_err();
+        let items = err.partial_items();
+        assert_eq!(items[1], EntryItem::UnpairedTagOpen(TagName::P, None));
+        assert_eq!(items[2], EntryItem::Tagged { name: TagName::Hw, source: None, items: vec![EntryItem::PlainText("A")] });
+        assert!(Entry::parse("<entry main-word=\"A\">x > y</entry>").unwrap_err().partial_items().is_empty());
+    }
+
+    #[test]
+    fn raw_slices() {
+        let text = "<entry main-word=\"A\" source=\"B\">a</entry>\n<entry main-word=\"C\" source=\"B\">x > y</entry>\n";
+        let blocks: Vec<_> = EntryParser::new(text).with_raw().map(|(raw, res)| (raw, res.is_ok())).collect();
+        assert_eq!(blocks, [("<entry main-word=\"A\" source=\"B\">a</entry>", true),
+                            ("<entry main-word=\"C\" source=\"B\">x > y</entry>", false)]);
+    }
+
+    #[test]
+    fn lenient_entry_heads() {
+        let text = "<entry source='1913 Webster' key=\"x\"  main-word=\"A\">a</entry>\n\
+                    <entry main-word=\"B\">b</entry>\n<entry source=\"C\">c</entry>";
+        fn parse(parser: EntryParser<'_>) -> Vec<Result<(&str, &str), &str>> {
+            parser.map(|res| res.map(|entry| (entry.main_word, entry.source)).map_err(|err| err.leading)).collect()
+        }
+        assert!(parse(EntryParser::new(text)).iter().all(Result::is_err));
+        assert_eq!(parse(EntryParser::new(text).lenient_heads()),
+                   [Ok(("A", "1913 Webster")), Ok(("B", "")), Err("")]);
+    }
+
+    #[test]
+    fn lenient_tags() {
+        use exporter::CIDE;
+        let text = "<entry main-word=\"A\" source=\"B\"><p>< hw>A</hw> <Def>one</def ></p></entry>";
+        assert!(EntryParser::new(text).next().unwrap().is_err());
+        let mut parser = EntryParser::new(text).lenient_tags();
+        let entry = parser.next().unwrap().unwrap();
+        assert_eq!(CIDE(&entry).to_string(),
+                   "<entry main-word=\"A\" source=\"B\"><p><hw>A</hw> <def>one</def></p></entry>");
+        assert_eq!(parser.warnings(), [ParseWarning::LooseTag("< hw>"), ParseWarning::LooseTag("<Def>"),
+                                       ParseWarning::LooseTag("</def >")]);
+        let text = "<entry main-word=\"A\" source=\"B\"><p><Foo>a</Foo></p></entry>";
+        let mut parser = EntryParser::new(text).lenient_tags();
+        parser.next();
+        assert!(!parser.warnings().iter().any(|warning| matches!(warning, ParseWarning::LooseTag(_))));
+    }
+
+    #[test]
+    fn lenient_brackets() {
+        use exporter::CIDE;
+        let text = "<entry main-word=\"A\" source=\"B\"><p><def>if a < b and b > c,\nthen a <lt/ c</def></p></entry>";
+        assert!(EntryParser::new(text).next().unwrap().is_err());
+        let mut parser = EntryParser::new(text).lenient_brackets();
+        let entry = parser.next().unwrap().unwrap();
+        assert_eq!(CIDE(&entry).to_string(), "<entry main-word=\"A\" source=\"B\"><p><def>if a <lt/ b and b <gt/ c,\n\
+                                              then a <lt/ c</def></p></entry>");
+        assert_eq!(parser.warnings(), [ParseWarning::StrayBracket("< b and b "), ParseWarning::StrayBracket("> c,")]);
+    }
+
+    #[test]
+    fn gaps_between_entries() {
+        let text = "<-- preface -->\n<entry main-word=\"A\" source=\"B\">a</entry><entry main-word=\"C\" source=\"B\">c</entry>\n\
+                    <p>stray</p>\n";
+        let blocks: Vec<_> = EntryParser::new(text).with_gaps().map(|block| match block {
+            Block::Gap { offset, text } => (offset, text),
+            Block::Entry(res) => (0, res.unwrap().main_word),
+        }).collect();
+        assert_eq!(blocks, [(0, "<-- preface -->\n"), (0, "A"), (0, "C"), (98, "\n<p>stray</p>\n")]);
+        assert_eq!(EntryParser::new("").with_gaps().count(), 0);
+    }
+
+    #[test]
+    fn progress_callback() {
+        use std::cell::RefCell;
+        let text = "<entry main-word=\"A\" source=\"B\">a</entry>\n<entry main-word=\"C\" source=\"B\">c</entry>\n";
+        let seen = RefCell::new(Vec::new());
+        let parser = EntryParser::new(text).on_progress(|progress| seen.borrow_mut().push(progress));
+        assert_eq!(parser.count(), 2);
+        assert_eq!(seen.into_inner(), [Progress { entries: 1, bytes: 41, total_bytes: 84 },
+                                       Progress { entries: 2, bytes: 83, total_bytes: 84 }]);
+    }
 }
@@ -0,0 +1,528 @@
+//! Phonetic keys of headwords (Lawrence Philips' Double Metaphone), so that
+//! words can be looked up by how they sound, e.g. `nee-mon-ik` for `Mnemonic`.
+
+use std::collections::BTreeMap;
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use headword::normalize_headword;
+
+/// Length of the keys, as is usual for Double Metaphone.
+const KEY_LEN: usize = 4;
+
+/// Beginnings whose first letter is not pronounced. `MN` is not in the
+/// original algorithm, but is common in the Greek borrowings of the
+/// dictionary (mnemonic, mnemosyne).
+const SILENT_STARTS: &[&str] = &["GN", "KN", "PN", "WR", "PS", "MN"];
+
+const L_R_N_M_B_H_F_V_W: &[&str] = &["L", "R", "N", "M", "B", "H", "F", "V", "W"];
+const G_BEFORE_SOFT: &[&str] = &["ES", "EP", "EB", "EL", "EY", "IB", "IL", "IN", "IE", "EI", "ER"];
+const L_T_K_S_N_M_B_Z: &[&str] = &["L", "T", "K", "S", "N", "M", "B", "Z"];
+
+/// Maps the phonetic keys of headwords to the locations of their entries.
+/// Locations are opaque, as in `index::HeadwordIndex`.
+#[derive(Debug, Default)]
+pub struct PhoneticIndex {
+    keys: BTreeMap<String, Vec<u64>>,
+}
+
+impl PhoneticIndex {
+    pub fn new() -> PhoneticIndex {
+        PhoneticIndex::default()
+    }
+
+    /// Indexes `headword` (raw CIDE markup allowed) under both its keys.
+    pub fn insert(&mut self, headword: &str, loc: u64) {
+        let (primary, alternate) = double_metaphone(headword);
+        for key in Some(primary.clone()).into_iter().chain(Some(alternate).filter(|key| *key != primary)) {
+            if !key.is_empty() {
+                self.keys.entry(key).or_default().push(loc);
+            }
+        }
+    }
+
+    /// Locations of the headwords sharing a key with `word`, in index order.
+    pub fn get(&self, word: &str) -> Vec<u64> {
+        let (primary, alternate) = double_metaphone(word);
+        let mut locs: Vec<u64> = [primary, alternate].iter()
+            .filter_map(|key| self.keys.get(key))
+            .flat_map(|locs| locs.iter().cloned())
+            .collect();
+        locs.sort_unstable();
+        locs.dedup();
+        locs
+    }
+
+    /// Number of distinct keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+/// The primary and alternate Double Metaphone keys of `word`. Only its
+/// letters count, once normalized as by `normalize_headword`; the alternate
+/// key is the primary one where the word has a single likely pronunciation.
+pub fn double_metaphone(word: &str) -> (String, String) {
+    let letters: Vec<char> = normalize_headword(word).chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let mut encoder = Encoder { word: letters, primary: String::new(), alternate: String::new() };
+    encoder.encode();
+    (encoder.primary, encoder.alternate)
+}
+
+struct Encoder {
+    word: Vec<char>,
+    primary: String,
+    alternate: String,
+}
+
+impl Encoder {
+    fn encode(&mut self) {
+        let slavo_germanic = self.word.iter().any(|&c| c == 'W' || c == 'K')
+            || self.find("CZ") || self.find("WITZ");
+        let mut idx = if SILENT_STARTS.iter().any(|start| self.at(0, 2, &[start])) { 1 } else { 0 };
+        let last = self.word.len() as isize - 1;
+        while idx <= last && (self.primary.len() < KEY_LEN || self.alternate.len() < KEY_LEN) {
+            let next = self.char_at(idx + 1);
+            idx = match self.char_at(idx) {
+                'A' | 'E' | 'I' | 'O' | 'U' | 'Y' => {
+                    if idx == 0 {
+                        self.add("A");
+                    }
+                    idx + 1
+                }
+                'B' => self.add_skipping("P", idx, 'B'),
+                'C' => self.c(idx),
+                'D' => self.d(idx),
+                'F' => self.add_skipping("F", idx, 'F'),
+                'G' => self.g(idx, slavo_germanic),
+                'H' => {
+                    if (idx == 0 || is_vowel(self.char_at(idx - 1))) && is_vowel(next) {
+                        self.add("H");
+                        idx + 2
+                    } else {
+                        idx + 1
+                    }
+                }
+                'J' => self.j(idx, slavo_germanic),
+                'K' => self.add_skipping("K", idx, 'K'),
+                'L' => {
+                    if next == 'L' {
+                        if self.spanish_ll(idx) {
+                            self.add_both("L", "");
+                        } else {
+                            self.add("L");
+                        }
+                        idx + 2
+                    } else {
+                        self.add("L");
+                        idx + 1
+                    }
+                }
+                'M' => {
+                    self.add("M");
+                    let silent_b = self.at(idx - 1, 3, &["UMB"]) && (idx + 1 == last || self.at(idx + 2, 2, &["ER"]));
+                    if next == 'M' || silent_b { idx + 2 } else { idx + 1 }
+                }
+                'N' => self.add_skipping("N", idx, 'N'),
+                'P' => {
+                    if next == 'H' {
+                        self.add("F");
+                        idx + 2
+                    } else {
+                        self.add("P");
+                        if next == 'P' || next == 'B' { idx + 2 } else { idx + 1 }
+                    }
+                }
+                'Q' => self.add_skipping("K", idx, 'Q'),
+                'R' => {
+                    if idx == last && !slavo_germanic && self.at(idx - 2, 2, &["IE"]) && !self.at(idx - 4, 2, &["ME", "MA"]) {
+                        self.add_both("", "R");
+                    } else {
+                        self.add("R");
+                    }
+                    if next == 'R' { idx + 2 } else { idx + 1 }
+                }
+                'S' => self.s(idx, slavo_germanic),
+                'T' => self.t(idx),
+                'V' => self.add_skipping("F", idx, 'V'),
+                'W' => self.w(idx),
+                'X' => {
+                    if idx == 0 {
+                        self.add("S");
+                        idx + 1
+                    } else {
+                        let silent = idx == last && (self.at(idx - 3, 3, &["IAU", "EAU"]) || self.at(idx - 2, 2, &["AU", "OU"]));
+                        if !silent {
+                            self.add("KS");
+                        }
+                        if next == 'C' || next == 'X' { idx + 2 } else { idx + 1 }
+                    }
+                }
+                'Z' => {
+                    if next == 'H' {
+                        self.add("J");
+                        idx + 2
+                    } else {
+                        if self.at(idx + 1, 2, &["ZO", "ZI", "ZA"]) || (slavo_germanic && idx > 0 && self.char_at(idx - 1) != 'T') {
+                            self.add_both("S", "TS");
+                        } else {
+                            self.add("S");
+                        }
+                        if next == 'Z' { idx + 2 } else { idx + 1 }
+                    }
+                }
+                _ => idx + 1,
+            };
+        }
+    }
+
+    fn c(&mut self, idx: isize) -> isize {
+        if self.germanic_ch(idx) {
+            self.add("K");
+            idx + 2
+        } else if idx == 0 && self.at(idx, 6, &["CAESAR"]) {
+            self.add("S");
+            idx + 2
+        } else if self.at(idx, 2, &["CH"]) {
+            self.ch(idx)
+        } else if self.at(idx, 2, &["CZ"]) && !self.at(idx - 2, 4, &["WICZ"]) {
+            self.add_both("S", "X");
+            idx + 2
+        } else if self.at(idx + 1, 3, &["CIA"]) {
+            self.add("X");
+            idx + 3
+        } else if self.at(idx, 2, &["CC"]) && !(idx == 1 && self.char_at(0) == 'M') {
+            if self.at(idx + 2, 1, &["I", "E", "H"]) && !self.at(idx + 2, 2, &["HU"]) {
+                if (idx == 1 && self.char_at(0) == 'A') || self.at(idx - 1, 5, &["UCCEE", "UCCES"]) {
+                    self.add("KS");
+                } else {
+                    self.add("X");
+                }
+                idx + 3
+            } else {
+                self.add("K");
+                idx + 2
+            }
+        } else if self.at(idx, 2, &["CK", "CG", "CQ"]) {
+            self.add("K");
+            idx + 2
+        } else if self.at(idx, 2, &["CI", "CE", "CY"]) {
+            if self.at(idx, 3, &["CIO", "CIE", "CIA"]) {
+                self.add_both("S", "X");
+            } else {
+                self.add("S");
+            }
+            idx + 2
+        } else {
+            self.add("K");
+            if self.at(idx + 1, 1, &["C", "K", "Q"]) && !self.at(idx + 1, 2, &["CE", "CI"]) { idx + 2 } else { idx + 1 }
+        }
+    }
+
+    /// `CH` sounding `K` as in German (e.g. `bacharach`).
+    fn germanic_ch(&self, idx: isize) -> bool {
+        if self.at(idx, 4, &["CHIA"]) {
+            true
+        } else if idx <= 1 || is_vowel(self.char_at(idx - 2)) || !self.at(idx - 1, 3, &["ACH"]) {
+            false
+        } else {
+            let c = self.char_at(idx + 2);
+            (c != 'I' && c != 'E') || self.at(idx - 2, 6, &["BACHER", "MACHER"])
+        }
+    }
+
+    fn ch(&mut self, idx: isize) -> isize {
+        let greek_start = idx == 0
+            && (self.at(idx + 1, 5, &["HARAC", "HARIS"]) || self.at(idx + 1, 3, &["HOR", "HYM", "HIA", "HEM"]))
+            && !self.at(0, 5, &["CHORE"]);
+        let hard = self.at(0, 3, &["SCH"])
+            || self.at(idx - 2, 6, &["ORCHES", "ARCHIT", "ORCHID"])
+            || self.at(idx + 2, 1, &["T", "S"])
+            || ((self.at(idx - 1, 1, &["A", "O", "U", "E"]) || idx == 0)
+                && (self.at(idx + 2, 1, L_R_N_M_B_H_F_V_W) || idx + 1 == self.word.len() as isize - 1));
+        if idx > 0 && self.at(idx, 4, &["CHAE"]) {
+            self.add_both("K", "X");
+        } else if greek_start || hard {
+            self.add("K");
+        } else if idx == 0 {
+            self.add("X");
+        } else if self.at(0, 2, &["MC"]) {
+            self.add("K");
+        } else {
+            self.add_both("X", "K");
+        }
+        idx + 2
+    }
+
+    fn d(&mut self, idx: isize) -> isize {
+        if self.at(idx, 2, &["DG"]) {
+            if self.at(idx + 2, 1, &["I", "E", "Y"]) {
+                self.add("J");
+                idx + 3
+            } else {
+                self.add("TK");
+                idx + 2
+            }
+        } else if self.at(idx, 2, &["DT", "DD"]) {
+            self.add("T");
+            idx + 2
+        } else {
+            self.add("T");
+            idx + 1
+        }
+    }
+
+    fn g(&mut self, idx: isize, slavo_germanic: bool) -> isize {
+        let next = self.char_at(idx + 1);
+        if next == 'H' {
+            self.gh(idx)
+        } else if next == 'N' {
+            if idx == 1 && is_vowel(self.char_at(0)) && !slavo_germanic {
+                self.add_both("KN", "N");
+            } else if !self.at(idx + 2, 2, &["EY"]) && !slavo_germanic {
+                self.add_both("N", "KN");
+            } else {
+                self.add("KN");
+            }
+            idx + 2
+        } else if self.at(idx + 1, 2, &["LI"]) && !slavo_germanic {
+            self.add_both("KL", "L");
+            idx + 2
+        } else if (idx == 0 && (next == 'Y' || self.at(idx + 1, 2, G_BEFORE_SOFT)))
+            || ((self.at(idx + 1, 2, &["ER"]) || next == 'Y')
+                && !self.at(0, 6, &["DANGER", "RANGER", "MANGER"])
+                && !self.at(idx - 1, 1, &["E", "I"])
+                && !self.at(idx - 1, 3, &["RGY", "OGY"])) {
+            self.add_both("K", "J");
+            idx + 2
+        } else if self.at(idx + 1, 1, &["E", "I", "Y"]) || self.at(idx - 1, 4, &["AGGI", "OGGI"]) {
+            if self.at(0, 3, &["SCH"]) || self.at(idx + 1, 2, &["ET"]) {
+                self.add("K");
+            } else if self.at(idx + 1, 3, &["IER"]) {
+                self.add("J");
+            } else {
+                self.add_both("J", "K");
+            }
+            idx + 2
+        } else {
+            self.add("K");
+            if next == 'G' { idx + 2 } else { idx + 1 }
+        }
+    }
+
+    fn gh(&mut self, idx: isize) -> isize {
+        if idx > 0 && !is_vowel(self.char_at(idx - 1)) {
+            self.add("K");
+        } else if idx == 0 {
+            self.add(if self.char_at(idx + 2) == 'I' { "J" } else { "K" });
+        } else if (idx > 1 && self.at(idx - 2, 1, &["B", "H", "D"]))
+            || (idx > 2 && self.at(idx - 3, 1, &["B", "H", "D"]))
+            || (idx > 3 && self.at(idx - 4, 1, &["B", "H"])) {
+            // silent, as in `bough` or `height`
+        } else if idx > 2 && self.char_at(idx - 1) == 'U' && self.at(idx - 3, 1, &["C", "G", "L", "R", "T"]) {
+            self.add("F");
+        } else if self.char_at(idx - 1) != 'I' {
+            self.add("K");
+        }
+        idx + 2
+    }
+
+    fn j(&mut self, idx: isize, slavo_germanic: bool) -> isize {
+        let next = self.char_at(idx + 1);
+        if self.at(idx, 4, &["JOSE"]) {
+            if self.word.len() == 4 {
+                self.add("H");
+            } else {
+                self.add_both("J", "H");
+            }
+            return idx + 1;
+        }
+        if idx == 0 {
+            self.add_both("J", "A");
+        } else if is_vowel(self.char_at(idx - 1)) && !slavo_germanic && (next == 'A' || next == 'O') {
+            self.add_both("J", "H");
+        } else if idx == self.word.len() as isize - 1 {
+            self.add_both("J", "");
+        } else if !self.at(idx + 1, 1, L_T_K_S_N_M_B_Z) && !self.at(idx - 1, 1, &["S", "K", "L"]) {
+            self.add("J");
+        }
+        if next == 'J' { idx + 2 } else { idx + 1 }
+    }
+
+    fn s(&mut self, idx: isize, slavo_germanic: bool) -> isize {
+        let next = self.char_at(idx + 1);
+        if self.at(idx - 1, 3, &["ISL", "YSL"]) {
+            idx + 1
+        } else if idx == 0 && self.at(idx, 5, &["SUGAR"]) {
+            self.add_both("X", "S");
+            idx + 1
+        } else if self.at(idx, 2, &["SH"]) {
+            if self.at(idx + 1, 4, &["HEIM", "HOEK", "HOLM", "HOLZ"]) {
+                self.add("S");
+            } else {
+                self.add("X");
+            }
+            idx + 2
+        } else if self.at(idx, 3, &["SIO", "SIA"]) {
+            if slavo_germanic {
+                self.add("S");
+            } else {
+                self.add_both("S", "X");
+            }
+            idx + 3
+        } else if (idx == 0 && self.at(idx + 1, 1, &["M", "N", "L", "W"])) || next == 'Z' {
+            self.add_both("S", "X");
+            if next == 'Z' { idx + 2 } else { idx + 1 }
+        } else if self.at(idx, 2, &["SC"]) {
+            if self.char_at(idx + 2) == 'H' {
+                if self.at(idx + 3, 2, &["ER", "EN"]) {
+                    self.add_both("X", "SK");
+                } else if self.at(idx + 3, 2, &["OO", "UY", "ED", "EM"]) {
+                    self.add("SK");
+                } else if idx == 0 && !is_vowel(self.char_at(3)) && self.char_at(3) != 'W' {
+                    self.add_both("X", "S");
+                } else {
+                    self.add("X");
+                }
+            } else if self.at(idx + 2, 1, &["I", "E", "Y"]) {
+                self.add("S");
+            } else {
+                self.add("SK");
+            }
+            idx + 3
+        } else {
+            if idx == self.word.len() as isize - 1 && self.at(idx - 2, 2, &["AI", "OI"]) {
+                self.add_both("", "S");
+            } else {
+                self.add("S");
+            }
+            if next == 'S' || next == 'Z' { idx + 2 } else { idx + 1 }
+        }
+    }
+
+    fn t(&mut self, idx: isize) -> isize {
+        if self.at(idx, 4, &["TION"]) || self.at(idx, 3, &["TIA", "TCH"]) {
+            self.add("X");
+            idx + 3
+        } else if self.at(idx, 2, &["TH"]) || self.at(idx, 3, &["TTH"]) {
+            if self.at(idx + 2, 2, &["OM", "AM"]) || self.at(0, 3, &["SCH"]) {
+                self.add("T");
+            } else {
+                self.add_both("0", "T");
+            }
+            idx + 2
+        } else {
+            self.add("T");
+            if self.at(idx + 1, 1, &["T", "D"]) { idx + 2 } else { idx + 1 }
+        }
+    }
+
+    fn w(&mut self, idx: isize) -> isize {
+        let next = self.char_at(idx + 1);
+        if self.at(idx, 2, &["WR"]) {
+            self.add("R");
+            idx + 2
+        } else if idx == 0 && (is_vowel(next) || next == 'H') {
+            if is_vowel(next) {
+                self.add_both("A", "F");
+            } else {
+                self.add("A");
+            }
+            idx + 1
+        } else if (idx == self.word.len() as isize - 1 && is_vowel(self.char_at(idx - 1)))
+            || self.at(idx - 1, 5, &["EWSKI", "EWSKY", "OWSKI", "OWSKY"])
+            || self.at(0, 3, &["SCH"]) {
+            self.add_both("", "F");
+            idx + 1
+        } else if self.at(idx, 4, &["WICZ", "WITZ"]) {
+            self.add_both("TS", "FX");
+            idx + 4
+        } else {
+            idx + 1
+        }
+    }
+
+    /// `LL` sounding as in Spanish (e.g. `cabrillo`, `gallegos`).
+    fn spanish_ll(&self, idx: isize) -> bool {
+        let len = self.word.len() as isize;
+        (idx == len - 3 && self.at(idx - 1, 4, &["ILLO", "ILLA", "ALLE"]))
+            || ((self.at(len - 2, 2, &["AS", "OS"]) || self.at(len - 1, 1, &["A", "O"])) && self.at(idx - 1, 4, &["ALLE"]))
+    }
+
+    fn char_at(&self, idx: isize) -> char {
+        if idx < 0 { '\0' } else { self.word.get(idx as usize).cloned().unwrap_or('\0') }
+    }
+
+    /// Whether the `len` letters at `start` are one of `options`.
+    fn at(&self, start: isize, len: usize, options: &[&str]) -> bool {
+        if start < 0 || start as usize + len > self.word.len() {
+            return false;
+        }
+        let part = &self.word[start as usize..start as usize + len];
+        options.iter().any(|option| option.chars().eq(part.iter().cloned()))
+    }
+
+    fn find(&self, part: &str) -> bool {
+        let part: Vec<char> = part.chars().collect();
+        self.word.windows(part.len()).any(|window| window == &part[..])
+    }
+
+    fn add(&mut self, code: &str) {
+        self.add_both(code, code);
+    }
+
+    fn add_both(&mut self, primary: &str, alternate: &str) {
+        for (key, code) in [(&mut self.primary, primary), (&mut self.alternate, alternate)] {
+            let room = KEY_LEN.saturating_sub(key.len());
+            key.extend(code.chars().take(room));
+        }
+    }
+
+    /// Adds `code` for the letter at `idx`, skipping a following `doubled`.
+    fn add_skipping(&mut self, code: &str, idx: isize, doubled: char) -> isize {
+        self.add(code);
+        if self.char_at(idx + 1) == doubled { idx + 2 } else { idx + 1 }
+    }
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'A' | 'E' | 'I' | 'O' | 'U' | 'Y')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sounds_alike() {
+        let keys = |word| {
+            let (primary, alternate) = double_metaphone(word);
+            format!("{}/{}", primary, alternate)
+        };
+        assert_eq!(keys("Mne*mon\"ic"), "NMNK/NMNK");
+        assert_eq!(keys("nee-mon-ik"), "NMNK/NMNK");
+        assert_eq!(keys("Thumb"), "0M/TM");
+        assert_eq!(keys("Knight"), "NT/NT");
+        assert_eq!(keys("Schmidt"), "XMT/SMT");
+        assert_eq!(keys("Caesar"), "SSR/SSR");
+        assert_eq!(keys("Ph\u{e6}ton"), "FTN/FTN");
+        assert_eq!(keys("Chorus"), "KRS/KRS");
+        assert_eq!(keys("Jose"), "HS/HS");
+        assert_eq!(keys(""), "/");
+
+        let mut index = PhoneticIndex::new();
+        index.insert("Mne*mon\"ic", 1);
+        index.insert("Numeric", 2);
+        index.insert("Nominy", 3);
+        assert_eq!(index.get("nee-mon-ik"), [1]);
+        assert_eq!(index.get("noo-mer-ik"), [2]);
+        assert!(index.get("abacus").is_empty());
+    }
+}
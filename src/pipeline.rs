@@ -0,0 +1,140 @@
+//! Composable rewrites of the item tree, applied before exporting an entry.
+
+use parser::{Entry, EntryItem};
+
+/// A rewrite of single items. Children of a tag are rewritten before the tag
+/// itself is handed to the pass.
+pub trait Pass {
+    /// Pushes whatever should replace `item` (possibly nothing) to `out`.
+    fn rewrite<'a>(&self, item: EntryItem<'a>, out: &mut Vec<EntryItem<'a>>);
+}
+
+/// Drops all comments.
+pub struct RemoveComments;
+
+impl Pass for RemoveComments {
+    fn rewrite<'a>(&self, item: EntryItem<'a>, out: &mut Vec<EntryItem<'a>>) {
+        if let EntryItem::Comment(_) = item {
+            return;
+        }
+        out.push(item);
+    }
+}
+
+/// Replaces known entities with their Unicode text; unknown ones are kept.
+pub struct ResolveEntities;
+
+impl Pass for ResolveEntities {
+    fn rewrite<'a>(&self, item: EntryItem<'a>, out: &mut Vec<EntryItem<'a>>) {
+        out.push(match item {
+            EntryItem::Entity(entity) if entity.is_known() => EntryItem::PlainText(entity.to_unicode()),
+            item => item,
+        });
+    }
+}
+
+/// Drops the named tags together with their contents.
+pub struct DropTags<'t>(pub &'t [&'t str]);
+
+impl<'t> Pass for DropTags<'t> {
+    fn rewrite<'a>(&self, item: EntryItem<'a>, out: &mut Vec<EntryItem<'a>>) {
+        let name = match item {
+            EntryItem::Tagged { name, .. } |
+            EntryItem::UnpairedTagOpen(name, _) |
+            EntryItem::UnpairedTagClose(name) => Some(name),
+            _ => None,
+        };
+        if !name.is_some_and(|name| self.0.contains(&name.as_str())) {
+            out.push(item);
+        }
+    }
+}
+
+/// Adapter for closures mapping an item to its replacement (or `None` to drop it).
+pub struct MapItems<F>(pub F);
+
+impl<F> Pass for MapItems<F>
+where F: for<'a> Fn(EntryItem<'a>) -> Option<EntryItem<'a>> {
+    fn rewrite<'a>(&self, item: EntryItem<'a>, out: &mut Vec<EntryItem<'a>>) {
+        out.extend((self.0)(item));
+    }
+}
+
+/// An ordered list of passes; each pass walks the whole tree before the next.
+#[derive(Default)]
+pub struct Pipeline<'p> {
+    passes: Vec<Box<dyn Pass + 'p>>,
+}
+
+impl<'p> Pipeline<'p> {
+    pub fn new() -> Pipeline<'p> {
+        Pipeline::default()
+    }
+
+    pub fn then<P: Pass + 'p>(mut self, pass: P) -> Pipeline<'p> {
+        self.passes.push(Box::new(pass));
+        self
+    }
+
+    pub fn map<F>(self, f: F) -> Pipeline<'p>
+    where F: for<'a> Fn(EntryItem<'a>) -> Option<EntryItem<'a>> + 'p {
+        self.then(MapItems(f))
+    }
+
+    /// Runs the passes over `entry`, clearing `Entry::raw`.
+    pub fn apply(&self, entry: &mut Entry) {
+        entry.raw = None;
+        for pass in &self.passes {
+            let items = ::std::mem::take(&mut entry.items);
+            entry.items = rewrite_items(&**pass, items);
+        }
+    }
+}
+
+fn rewrite_items<'a>(pass: &dyn Pass, items: Vec<EntryItem<'a>>) -> Vec<EntryItem<'a>> {
+    let mut out = Vec::with_capacity(items.len());
+    for item in items {
+        let item = match item {
+            EntryItem::Tagged { name, items, source } =>
+                EntryItem::Tagged { name, items: rewrite_items(pass, items), source },
+            item => item,
+        };
+        pass.rewrite(item, &mut out);
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::EntryParser;
+    use exporter::CIDE;
+    use tags::TagName;
+
+    #[test]
+    fn compose_passes() {
+        let text = "<entry main-word=\"A\" source=\"B\"><p><hw>A</hw> <--x--><def>f<oe/tus \
+                    <mark>[Obs.]</mark></def> <pos>n.</pos></p></entry>";
+        let mut entry = EntryParser::new(text).next().unwrap().unwrap();
+        Pipeline::new()
+            .then(RemoveComments)
+            .then(ResolveEntities)
+            .then(DropTags(&["mark"]))
+            .map(|item| match item {
+                EntryItem::Tagged { name: TagName::Pos, .. } => None,
+                item => Some(item),
+            })
+            .apply(&mut entry);
+        assert_eq!(CIDE(&entry).to_string(),
+                   "<entry main-word=\"A\" source=\"B\"><p><hw>A</hw> <def>f\u{153}tus </def> </p></entry>");
+    }
+
+    #[test]
+    fn edit_verified_entry() {
+        let text = "<entry main-word=\"A\" source=\"B\"><p>one<br/two <--x--></p></entry>";
+        let mut entry = EntryParser::new(text).verified().next().unwrap().unwrap();
+        assert!(entry.raw.is_some());
+        Pipeline::new().then(RemoveComments).apply(&mut entry);
+        assert_eq!(CIDE(&entry).to_string(), "<entry main-word=\"A\" source=\"B\"><p>one<br/\ntwo </p></entry>");
+    }
+}
@@ -0,0 +1,191 @@
+//! Tokenizing the contents of `<pr>` tags, as a basis for IPA conversion and
+//! for aligning pronunciations with audio.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use parser::EntryItem;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stress {
+    /// `"` or `<bacc/`.
+    Primary,
+    /// `` ` `` or `<lacc/`.
+    Secondary,
+}
+
+/// The diacritic of a vowel entity, which Webster's uses to mark its quality.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VowelQuality {
+    /// Macron (`<amac/`): long.
+    Long,
+    /// Breve (`<acr/`): short.
+    Short,
+    /// Macron with a dot (`<asl/`): half-long.
+    HalfLong,
+    /// Dot above (`<adot/`): obscure.
+    Obscure,
+    /// Dot below or diaeresis below (`<usdot/`, `<udd/`).
+    Below,
+    Circumflex,
+    Acute,
+    Grave,
+    Diaeresis,
+    Tilde,
+    Caron,
+    Ring,
+    /// Italic (`<ait/`): silent or slurred.
+    Italic,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PronToken<'a> {
+    /// A run of plain letters.
+    Letters(&'a str),
+    /// A vowel written as an entity, e.g. `<amac/` is `a` with `Long`.
+    Vowel(&'a str, VowelQuality),
+    Stress(Stress),
+    /// `*` or `<sdiv/`.
+    SyllableBreak,
+    /// Parentheses, spaces, commas and other punctuation.
+    Punct(&'a str),
+    /// Any other entity, by name.
+    Entity(&'a str),
+}
+
+const VOWELS: &[&str] = &["a", "e", "i", "o", "u", "y", "ae", "oe", "oo"];
+
+const QUALITIES: &[(&str, VowelQuality)] = &[
+    ("mac", VowelQuality::Long),
+    ("cr", VowelQuality::Short),
+    ("sl", VowelQuality::HalfLong),
+    ("dot", VowelQuality::Obscure),
+    ("sdot", VowelQuality::Below),
+    ("dd", VowelQuality::Below),
+    ("cir", VowelQuality::Circumflex),
+    ("acute", VowelQuality::Acute),
+    ("grave", VowelQuality::Grave),
+    ("um", VowelQuality::Diaeresis),
+    ("til", VowelQuality::Tilde),
+    ("car", VowelQuality::Caron),
+    ("ring", VowelQuality::Ring),
+    ("it", VowelQuality::Italic),
+];
+
+/// Splits the contents of a `<pr>` tag into tokens. Tags inside it (such as
+/// `<it>`) are looked through.
+pub fn tokenize<'a>(items: &[EntryItem<'a>]) -> Vec<PronToken<'a>> {
+    let mut tokens = Vec::new();
+    push_tokens(items, &mut tokens);
+    tokens
+}
+
+/// Groups tokens into syllables, breaking at `SyllableBreak` and `Punct`, and
+/// after stress marks that follow a syllable (as `"` does in Webster's; stress
+/// marks before a syllable, as `<bacc/` is used, start one).
+pub fn syllables<'t, 'a>(tokens: &'t [PronToken<'a>]) -> Vec<&'t [PronToken<'a>]> {
+    let mut syllables = Vec::new();
+    let mut start = 0;
+    let mut has_sound = false;
+    for (idx, token) in tokens.iter().enumerate() {
+        match *token {
+            PronToken::SyllableBreak | PronToken::Punct(_) => {
+                if has_sound {
+                    syllables.push(&tokens[start..idx]);
+                }
+                start = idx + 1;
+                has_sound = false;
+            }
+            PronToken::Stress(_) if has_sound => {
+                syllables.push(&tokens[start..idx + 1]);
+                start = idx + 1;
+                has_sound = false;
+            }
+            PronToken::Stress(_) => (),
+            _ => has_sound = true,
+        }
+    }
+    if has_sound {
+        syllables.push(&tokens[start..]);
+    }
+    syllables
+}
+
+fn push_tokens<'a>(items: &[EntryItem<'a>], tokens: &mut Vec<PronToken<'a>>) {
+    for item in items {
+        match *item {
+            EntryItem::PlainText(text) => push_text(text, tokens),
+            EntryItem::Entity(entity) => tokens.push(entity_token(entity.as_str())),
+            EntryItem::Tagged { ref items, .. } => push_tokens(items, tokens),
+            _ => (),
+        }
+    }
+}
+
+fn entity_token<'a>(name: &'a str) -> PronToken<'a> {
+    match name {
+        "bacc" => return PronToken::Stress(Stress::Primary),
+        "lacc" => return PronToken::Stress(Stress::Secondary),
+        "sdiv" => return PronToken::SyllableBreak,
+        _ => (),
+    }
+    for &(suffix, quality) in QUALITIES {
+        if let Some(vowel) = name.strip_suffix(suffix) {
+            if VOWELS.contains(&vowel) {
+                return PronToken::Vowel(vowel, quality);
+            }
+        }
+    }
+    PronToken::Entity(name)
+}
+
+fn push_text<'a>(text: &'a str, tokens: &mut Vec<PronToken<'a>>) {
+    let mut run_start = 0;
+    let mut run_is_letters = None;
+    for (idx, c) in text.char_indices() {
+        let token = match c {
+            '*' => Some(PronToken::SyllableBreak),
+            '"' => Some(PronToken::Stress(Stress::Primary)),
+            '`' => Some(PronToken::Stress(Stress::Secondary)),
+            _ => None,
+        };
+        let is_letter = c.is_alphabetic();
+        if token.is_some() || run_is_letters != Some(is_letter) {
+            push_run(&text[run_start..idx], run_is_letters, tokens);
+            run_start = idx;
+            run_is_letters = Some(is_letter);
+        }
+        if let Some(token) = token {
+            tokens.push(token);
+            run_start = idx + c.len_utf8();
+            run_is_letters = None;
+        }
+    }
+    push_run(&text[run_start..], run_is_letters, tokens);
+}
+
+fn push_run<'a>(run: &'a str, is_letters: Option<bool>, tokens: &mut Vec<PronToken<'a>>) {
+    match is_letters {
+        _ if run.is_empty() => (),
+        Some(true) => tokens.push(PronToken::Letters(run)),
+        _ => tokens.push(PronToken::Punct(run)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::PronToken::*;
+
+    #[test]
+    fn abacus() {
+        let items = EntryItem::parse_items("(<acr/b\"<adot/*k<ucr/s)").unwrap();
+        let tokens = tokenize(&items);
+        assert_eq!(tokens, [Punct("("), Vowel("a", VowelQuality::Short), Letters("b"), Stress(super::Stress::Primary),
+                            Vowel("a", VowelQuality::Obscure), SyllableBreak, Letters("k"),
+                            Vowel("u", VowelQuality::Short), Letters("s"), Punct(")")]);
+        assert_eq!(syllables(&tokens).len(), 3);
+        assert_eq!(tokenize(&EntryItem::parse_items("<ndot/<bacc/").unwrap()),
+                   [Entity("ndot"), Stress(super::Stress::Primary)]);
+    }
+}
@@ -0,0 +1,194 @@
+//! Selectors over the item tree, in a small subset of CSS: tag names (or `*`
+//! for any tag) joined by `>` for a direct child or by spaces for any
+//! descendant, each optionally followed by `[source="..."]`. `entry` stands for
+//! the entry itself and its head, as in `entry[source="1913 Webster"] > p > def`.
+
+use std::fmt::{self, Display, Formatter};
+use std::slice;
+use std::str::FromStr;
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use parser::{Entry, EntryItem};
+
+/// A compiled selector; see the module documentation for the syntax.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Query {
+    steps: Vec<Step>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Step {
+    /// `None` for `*`, or for a step with only an attribute.
+    name: Option<String>,
+    source: Option<String>,
+    /// Whether this step must be a direct child of the one before.
+    child: bool,
+}
+
+impl Step {
+    fn matches(&self, name: &str, source: Option<&str>) -> bool {
+        self.name.as_ref().is_none_or(|own| own == name)
+            && self.source.as_ref().is_none_or(|own| Some(&own[..]) == source)
+    }
+}
+
+impl Query {
+    /// Compiles `text`; steps are limited to 63.
+    pub fn parse(text: &str) -> Result<Query, String> {
+        let mut steps = Vec::new();
+        let mut rest = text.trim_start();
+        let mut child = false;
+        while !rest.is_empty() {
+            let name_len = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '*')).unwrap_or(rest.len());
+            let (name, mut after) = rest.split_at(name_len);
+            let mut source = None;
+            while let Some(attr) = after.strip_prefix('[') {
+                let value = attr.strip_prefix("source=\"").and_then(|value| value.split_once("\"]"));
+                match value {
+                    Some((value, next)) => {
+                        source = Some(value.to_string());
+                        after = next;
+                    }
+                    None => return Err(format!("expected [source=\"...\"] at: {}", after)),
+                }
+            }
+            if name.is_empty() && source.is_none() || name.contains('*') && name != "*" {
+                return Err(format!("expected a tag name, * or [source=\"...\"] at: {}", rest));
+            }
+            let name = if name == "*" || name.is_empty() { None } else { Some(name.to_string()) };
+            steps.push(Step { name, source, child });
+
+            let trimmed = after.trim_start();
+            child = trimmed.starts_with('>');
+            rest = trimmed.strip_prefix('>').unwrap_or(trimmed).trim_start();
+            if trimmed.len() == after.len() && !child && !rest.is_empty() {
+                return Err(format!("expected a space or > at: {}", rest));
+            }
+            if child && rest.is_empty() {
+                return Err("expected a step after >".to_string());
+            }
+        }
+        if steps.is_empty() {
+            return Err("empty query".to_string());
+        }
+        if steps.len() > 63 {
+            return Err("too many steps".to_string());
+        }
+        Ok(Query { steps })
+    }
+
+    /// Contents of the tags matching the query, in document order: the items
+    /// of the whole entry for `entry`, and the `EntryItem::Greek` itself for
+    /// `grk`.
+    pub fn find_all<'e, 'a>(&self, entry: &'e Entry<'a>) -> Vec<&'e [EntryItem<'a>]> {
+        let mut found = Vec::new();
+        let at = self.advance("entry", Some(entry.source), 0, 1);
+        if self.is_match(at) {
+            found.push(&entry.items[..]);
+        }
+        self.find_in(&entry.items, at, 1 | at, &mut found);
+        found
+    }
+
+    /// Whether anything in `entry` matches the query.
+    pub fn matches(&self, entry: &Entry) -> bool {
+        !self.find_all(entry).is_empty()
+    }
+
+    /// `at` and `above` are bit sets of the numbers of steps matched, ending
+    /// at the parent of `items` and at it or any of its ancestors.
+    fn find_in<'e, 'a>(&self, items: &'e [EntryItem<'a>], at: u64, above: u64, found: &mut Vec<&'e [EntryItem<'a>]>) {
+        for item in items {
+            match *item {
+                EntryItem::Tagged { name, ref items, source } => {
+                    let matched = self.advance(name.as_str(), source, at, above);
+                    if self.is_match(matched) {
+                        found.push(items);
+                    }
+                    self.find_in(items, matched, above | matched, found);
+                }
+                EntryItem::Greek(_) if self.is_match(self.advance("grk", None, at, above)) => {
+                    found.push(slice::from_ref(item));
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// The steps matched ending at a node `name`, given those matched by its
+    /// parent and ancestors.
+    fn advance(&self, name: &str, source: Option<&str>, at: u64, above: u64) -> u64 {
+        let mut matched = 0;
+        for (k, step) in self.steps.iter().enumerate() {
+            let before = if step.child { at } else { above };
+            if before & 1 << k != 0 && step.matches(name, source) {
+                matched |= 1 << (k + 1);
+            }
+        }
+        matched
+    }
+
+    fn is_match(&self, matched: u64) -> bool {
+        matched & 1 << self.steps.len() != 0
+    }
+}
+
+impl FromStr for Query {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Query, String> {
+        Query::parse(s)
+    }
+}
+
+impl Display for Query {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for (k, step) in self.steps.iter().enumerate() {
+            if k > 0 {
+                f.write_str(if step.child { " > " } else { " " })?;
+            }
+            match (&step.name, &step.source) {
+                (Some(name), _) => f.write_str(name)?,
+                (None, None) => f.write_str("*")?,
+                (None, Some(_)) => (),
+            }
+            if let Some(ref source) = step.source {
+                write!(f, "[source=\"{}\"]", source)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use model::plain_text;
+    use parser::EntryParser;
+
+    #[test]
+    fn select_items() {
+        let text = "<entry main-word=\"A\" source=\"1913 Webster\"><p><def>one <i>x</i></def> <grk>a</grk></p>\n\
+                    <p source=\"PJC\"><def>two</def> <note><def>three</def></note></p></entry>";
+        let entry = EntryParser::new(text).next().unwrap().unwrap();
+        let found = |query: &str| {
+            let query = Query::parse(query).unwrap();
+            query.find_all(&entry).into_iter().map(plain_text).collect::<Vec<_>>()
+        };
+        assert_eq!(found("def"), ["one x", "two", "three"]);
+        assert_eq!(found("entry > p > def"), ["one x", "two"]);
+        assert_eq!(found("p def"), ["one x", "two", "three"]);
+        assert_eq!(found("p[source=\"PJC\"] > *"), ["two", "three"]);
+        assert_eq!(found("entry[source=\"1913 Webster\"] grk").len(), 1);
+        assert!(found("entry[source=\"PJC\"]").is_empty());
+        assert_eq!(found("[source=\"PJC\"]"), ["two three"]);
+        assert!(Query::parse("def > i").unwrap().matches(&entry));
+
+        for bad in &["", "p >", "p[au=\"x\"]", "p>>def", "d*f"] {
+            assert!(Query::parse(bad).is_err(), "{}", bad);
+        }
+        assert_eq!(Query::parse("entry>p  [source=\"PJC\"] *").unwrap().to_string(),
+                   "entry > p [source=\"PJC\"] *");
+    }
+}
@@ -0,0 +1,220 @@
+//! Batch rewrite rules for mechanical cleanups, loaded from TOML:
+//!
+//! ```toml
+//! [[rule]]
+//! name = "author after quote"
+//! match = '</q> (Shak\.)'
+//! replace = '</q> <au>$1</au>'
+//! source = "1913 Webster"
+//! from = "A"
+//! to = "B"
+//! ```
+//!
+//! `match` is a regular expression over the source of an entry block, and
+//! `replace` may refer to its groups as `$1` or `${name}`. The other keys are
+//! optional: `source` limits the rule to entries crediting that source, and
+//! `from` and `to` to main words in that range of dictionary order (`to = "B"`
+//! takes in every word starting with B).
+
+use std::borrow::Cow;
+use std::fmt::{self, Display, Formatter};
+
+use regex::Regex;
+
+use headword::{collation_key, key_letters};
+use parser::{Entry, EntryParser};
+
+#[derive(Deserialize)]
+struct RuleFile {
+    #[serde(default)]
+    rule: Vec<RuleSpec>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleSpec {
+    name: Option<String>,
+    #[serde(rename = "match")]
+    pattern: String,
+    replace: String,
+    source: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Rule {
+    pub name: String,
+    pattern: Regex,
+    replace: String,
+    source: Option<String>,
+    /// Bounds of the range as `collation_key` letters.
+    from: Option<String>,
+    to: Option<String>,
+}
+
+impl Rule {
+    fn applies_to(&self, entry: &Entry) -> bool {
+        let key = collation_key(entry.main_word);
+        let letters = key_letters(&key);
+        self.source.as_ref().is_none_or(|source| entry.sources().contains(&&source[..]))
+            && self.from.as_ref().is_none_or(|from| letters >= &from[..])
+            && self.to.as_ref().is_none_or(|to| letters <= &to[..] || letters.starts_with(&to[..]))
+    }
+}
+
+/// Rules in the order they are applied, each to the result of the one before.
+#[derive(Debug)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+/// How often each rule of a `RuleSet` was applied, in the same order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Report {
+    /// Name, number of replacements and number of entries left unchanged
+    /// because the result would not parse or would have more warnings, per
+    /// rule.
+    pub counts: Vec<(String, usize, usize)>,
+}
+
+impl RuleSet {
+    pub fn parse(text: &str) -> Result<RuleSet, String> {
+        let file: RuleFile = toml::from_str(text).map_err(|err| err.to_string())?;
+        let mut rules = Vec::with_capacity(file.rule.len());
+        for (idx, spec) in file.rule.into_iter().enumerate() {
+            let name = spec.name.unwrap_or_else(|| format!("rule {}", idx + 1));
+            let pattern = Regex::new(&spec.pattern).map_err(|err| format!("{}: {}", name, err))?;
+            let bound = |word: Option<String>| word.map(|word| key_letters(&collation_key(&word)).to_string());
+            rules.push(Rule {
+                name,
+                pattern,
+                replace: spec.replace,
+                source: spec.source,
+                from: bound(spec.from),
+                to: bound(spec.to),
+            });
+        }
+        Ok(RuleSet { rules })
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// A report with every count at zero.
+    pub fn new_report(&self) -> Report {
+        Report { counts: self.rules.iter().map(|rule| (rule.name.clone(), 0, 0)).collect() }
+    }
+
+    /// Applies the rules to the entry block `raw`, parsed as `entry`, and
+    /// returns the new block if any rule changed it. A rule whose result is not
+    /// a single parsable entry, or has more parse warnings, is skipped for this
+    /// entry.
+    pub fn apply(&self, raw: &str, entry: &Entry, report: &mut Report) -> Option<String> {
+        let mut text = Cow::Borrowed(raw);
+        let mut warnings = None;
+        for (rule, counts) in self.rules.iter().zip(&mut report.counts) {
+            if !rule.applies_to(entry) {
+                continue;
+            }
+            let found = rule.pattern.find_iter(&text).count();
+            if found == 0 {
+                continue;
+            }
+            let replaced = rule.pattern.replace_all(&text, &rule.replace[..]).into_owned();
+            let before = *warnings.get_or_insert_with(|| count_warnings(&text).unwrap_or(usize::MAX));
+            match count_warnings(&replaced) {
+                Some(after) if after <= before => {
+                    counts.1 += found;
+                    text = Cow::Owned(replaced);
+                    warnings = Some(after);
+                }
+                _ => counts.2 += 1,
+            }
+        }
+        match text {
+            Cow::Owned(text) if text != raw => Some(text),
+            _ => None,
+        }
+    }
+}
+
+/// The number of parse warnings of `text` if it is a single parsable entry.
+fn count_warnings(text: &str) -> Option<usize> {
+    let mut parser = EntryParser::new(text);
+    let warnings = match parser.next() {
+        Some(Ok(_)) => parser.warnings().len(),
+        _ => return None,
+    };
+    if parser.next().is_none() && parser.remaining().is_empty() { Some(warnings) } else { None }
+}
+
+impl Report {
+    /// Adds the counts of `other`, a report of the same rules.
+    pub fn add(&mut self, other: &Report) {
+        for (counts, other) in self.counts.iter_mut().zip(&other.counts) {
+            counts.1 += other.1;
+            counts.2 += other.2;
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.iter().map(|counts| counts.1).sum()
+    }
+}
+
+impl Display for Report {
+    /// One line per rule: replacements, then entries where the result did not
+    /// parse if any, then the name.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for (name, applied, rejected) in &self.counts {
+            match *rejected {
+                0 => writeln!(f, "{:8} {}", applied, name)?,
+                _ => writeln!(f, "{:8} {} ({} entries left as they were: broken result)", applied, name, rejected)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn apply_rules() {
+        let rules = RuleSet::parse(r#"
+            [[rule]]
+            name = "author"
+            match = '</q> (Shak\.)'
+            replace = '</q> <au>$1</au>'
+            source = "1913 Webster"
+            to = "B"
+
+            [[rule]]
+            match = '<i>'
+            replace = '<b>'
+        "#).unwrap();
+        let text = "<entry main-word=\"Abide\" source=\"1913 Webster\"><q>Abide.</q> Shak.</entry>\n\
+                    <entry main-word=\"Cease\" source=\"1913 Webster\"><q>Cease.</q> Shak.</entry>\n\
+                    <entry main-word=\"Be\" source=\"PJC\"><q>Be.</q> Shak. <i>x</i></entry>";
+        let mut report = rules.new_report();
+        let mut parser = EntryParser::new(text);
+        let mut changed = Vec::new();
+        while let Some(entry) = parser.next() {
+            let raw = &text[parser.last_span().unwrap()];
+            changed.push(rules.apply(raw, &entry.unwrap(), &mut report));
+        }
+        assert_eq!(changed, [
+            Some("<entry main-word=\"Abide\" source=\"1913 Webster\"><q>Abide.</q> <au>Shak.</au></entry>".to_string()),
+            None,
+            None,
+        ]);
+        assert_eq!(report.counts, [("author".to_string(), 1, 0), ("rule 2".to_string(), 0, 1)]);
+        assert_eq!(report.to_string(), "       1 author\n       0 rule 2 (1 entries left as they were: broken result)\n");
+
+        assert!(RuleSet::parse("[[rule]]\nmatch = '('\nreplace = ''").unwrap_err().starts_with("rule 1: "));
+        assert!(RuleSet::parse("[[rule]]\nmatch = 'a'\nreplace = ''\nsources = 'x'").is_err());
+    }
+}
@@ -0,0 +1,55 @@
+//! Vectorized searches (via `memchr`) for the entry boundaries that the
+//! parser skips to. Runs of plain text within entries are short, and showed no
+//! clear gain from it; see `benches/scan.rs`.
+
+use memchr::memmem::Finder;
+
+pub const ENTRY_OPEN: &str = "<entry ";
+pub const ENTRY_CLOSE: &str = "</entry>";
+
+/// Searchers for `ENTRY_OPEN` and `ENTRY_CLOSE`, built once per parser.
+#[derive(Clone, Debug)]
+pub struct EntryBoundaries {
+    open: Finder<'static>,
+    close: Finder<'static>,
+}
+
+impl EntryBoundaries {
+    pub fn new() -> EntryBoundaries {
+        EntryBoundaries {
+            open: Finder::new(ENTRY_OPEN.as_bytes()),
+            close: Finder::new(ENTRY_CLOSE.as_bytes()),
+        }
+    }
+
+    /// Byte offset of the first `<entry ` in `text`.
+    pub fn find_open(&self, text: &str) -> Option<usize> {
+        self.open.find(text.as_bytes())
+    }
+
+    /// Byte offset of the first `</entry>` in `text`.
+    pub fn find_close(&self, text: &str) -> Option<usize> {
+        self.close.find(text.as_bytes())
+    }
+}
+
+impl Default for EntryBoundaries {
+    fn default() -> EntryBoundaries {
+        EntryBoundaries::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn finds_boundaries() {
+        let text = "<-- preface --> <entries> <entry main-word=\"\u{e6}\" source=\"S\"><p>a > b</p></entry>";
+        let bounds = EntryBoundaries::new();
+        assert_eq!(bounds.find_open(text), text.find(ENTRY_OPEN));
+        assert_eq!(bounds.find_close(text), text.find(ENTRY_CLOSE));
+        assert_eq!(bounds.find_open("<entry-"), None);
+        assert_eq!(bounds.find_close("</entr"), None);
+    }
+}
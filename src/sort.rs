@@ -0,0 +1,183 @@
+//! Reordering entries, joining entries that GCIDE splits into several blocks,
+//! splitting entries by part of speech, and checking the order of letter
+//! files.
+
+use headword::{collation_key, key_letters};
+use parser::{Entry, EntryItem};
+use tags::TagName;
+
+/// Sorts entries in dictionary order of their main words, keeping the file
+/// order of entries with the same key.
+pub fn sort_entries(entries: &mut [Entry]) {
+    entries.sort_by_cached_key(|entry| collation_key(entry.main_word));
+}
+
+/// Appends the items of `other` to `entry`, separated by a blank line. Blocks
+/// of `other` that do not name a source get the source of `other`, if it
+/// differs from that of `entry`.
+pub fn merge_entry<'a>(entry: &mut Entry<'a>, other: Entry<'a>) {
+    let is_space = |item: &EntryItem| matches!(*item, EntryItem::PlainText(text) if text.trim().is_empty());
+    if entry.items.last().is_some_and(is_space) {
+        entry.items.pop();
+    }
+    entry.raw = None;
+    entry.items.push(EntryItem::PlainText("\n\n"));
+    let other_source = other.source;
+    for mut item in other.items.into_iter().skip_while(is_space) {
+        if other_source != entry.source {
+            if let EntryItem::Tagged { name: TagName::P, ref mut source, .. } = item {
+                source.get_or_insert(other_source);
+            }
+        }
+        entry.items.push(item);
+    }
+}
+
+/// Merges each run of consecutive entries with the same main word into one.
+pub fn merge_duplicates(entries: Vec<Entry>) -> Vec<Entry> {
+    let mut merged: Vec<Entry> = Vec::with_capacity(entries.len());
+    for entry in entries {
+        match merged.last_mut() {
+            Some(last) if last.main_word == entry.main_word => merge_entry(last, entry),
+            _ => merged.push(entry),
+        }
+    }
+    merged
+}
+
+/// Splits an entry into one entry per part of speech: a new entry starts at each
+/// top-level block whose first `<pos>` differs from the one before. Blocks
+/// without a `<pos>` stay with the preceding block.
+pub fn split_by_pos(entry: Entry) -> Vec<Entry> {
+    let Entry { main_word, source, items, .. } = entry;
+    let mut parts = vec![Entry { main_word, source, items: Vec::new(), raw: None, id: None }];
+    let mut current_pos = None;
+    for item in items {
+        let pos = match item {
+            EntryItem::Tagged { ref items, .. } => first_pos(items),
+            _ => None,
+        };
+        if let Some(pos) = pos {
+            if current_pos.is_some_and(|current| current != pos) {
+                parts.push(Entry { main_word, source, items: vec![EntryItem::PlainText("\n")], raw: None, id: None });
+            }
+            current_pos = Some(pos);
+        }
+        parts.last_mut().unwrap().items.push(item);
+    }
+    let last = parts.len() - 1;
+    for part in &mut parts[..last] {
+        while part.items.last().is_some_and(|item| matches!(*item, EntryItem::PlainText(t) if t.trim().is_empty())) {
+            part.items.pop();
+        }
+        part.items.push(EntryItem::PlainText("\n"));
+    }
+    parts
+}
+
+fn first_pos<'a>(items: &[EntryItem<'a>]) -> Option<&'a str> {
+    items.iter().filter_map(|item| match *item {
+        EntryItem::Tagged { name: TagName::Pos, ref items, .. } => match items[..] {
+            [EntryItem::PlainText(pos)] => Some(pos.trim()),
+            _ => None,
+        },
+        EntryItem::Tagged { ref items, .. } => first_pos(items),
+        _ => None,
+    }).next()
+}
+
+/// An entry out of place in a letter file; see `check_letter_file`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Misplaced<'a> {
+    /// The main word does not begin with the letter of the file.
+    WrongLetter(&'a str),
+    /// The main word sorts before the one given second, the last in place
+    /// before it.
+    OutOfOrder(&'a str, &'a str),
+}
+
+/// The letter of a GCIDE file named like `CIDE.A`.
+pub fn file_letter(file_name: &str) -> Option<char> {
+    let mut rest = file_name.strip_prefix("CIDE.")?.chars();
+    match (rest.next(), rest.next()) {
+        (Some(letter), None) if letter.is_ascii_alphabetic() => Some(letter.to_ascii_lowercase()),
+        _ => None,
+    }
+}
+
+/// Checks main words, in file order, against a file of entries for `letter`:
+/// each should begin with it, unless it begins with a digit or symbol, and
+/// none should sort before the one preceding it. Words reported as misplaced
+/// are left out of the comparison with the next. Returns the index of each
+/// misplaced word with the problem.
+pub fn check_letter_file<'a, I>(letter: Option<char>, main_words: I) -> Vec<(usize, Misplaced<'a>)>
+where I: IntoIterator<Item = &'a str> {
+    let mut misplaced = Vec::new();
+    let mut previous: Option<(&str, String)> = None;
+    for (idx, word) in main_words.into_iter().enumerate() {
+        let key = collation_key(word);
+        let letters = key_letters(&key);
+        let first = letters.chars().next();
+        if let (Some(letter), Some(first)) = (letter, first) {
+            if first.is_alphabetic() && first != letter {
+                misplaced.push((idx, Misplaced::WrongLetter(word)));
+                continue;
+            }
+        }
+        if let Some((before, ref before_letters)) = previous {
+            if letters < &before_letters[..] {
+                misplaced.push((idx, Misplaced::OutOfOrder(word, before)));
+                continue;
+            }
+        }
+        previous = Some((word, letters.to_string()));
+    }
+    misplaced
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use exporter::CIDE;
+    use parser::EntryParser;
+
+    #[test]
+    fn sort_and_merge() {
+        let text = "<entry main-word=\"Be\" source=\"A\">\n<p><hw>Be</hw></p>\n</entry>\n\
+                    <entry main-word=\"ab\" source=\"A\">\n<p><hw>ab</hw></p>\n</entry>\n\
+                    <entry main-word=\"Be\" source=\"B\">\n<p><def>two</def></p>\n</entry>\n";
+        let mut entries: Vec<_> = EntryParser::new(text).map(Result::unwrap).collect();
+        sort_entries(&mut entries);
+        let entries = merge_duplicates(entries);
+        let rendered: Vec<_> = entries.iter().map(|entry| CIDE(entry).to_string()).collect();
+        assert_eq!(rendered, [
+            "<entry main-word=\"ab\" source=\"A\">\n<p><hw>ab</hw></p>\n</entry>",
+            "<entry main-word=\"Be\" source=\"A\">\n<p><hw>Be</hw></p>\n\n<p source=\"B\"><def>two</def></p>\n</entry>",
+        ]);
+    }
+
+    #[test]
+    fn letter_file() {
+        assert_eq!(file_letter("CIDE.B"), Some('b'));
+        assert_eq!(file_letter("CIDE.idx"), None);
+        let words = ["A", "Ab\"a*cus", "Abaca", "Bee", "Abase", "Abed", "<AE/on", "1000"];
+        assert_eq!(check_letter_file(Some('a'), words.iter().cloned()), [
+            (2, Misplaced::OutOfOrder("Abaca", "Ab\"a*cus")),
+            (3, Misplaced::WrongLetter("Bee")),
+            (7, Misplaced::OutOfOrder("1000", "<AE/on")),
+        ]);
+    }
+
+    #[test]
+    fn split_pos() {
+        let text = "<entry main-word=\"Fast\" source=\"A\">\n<p><hw>Fast</hw>, <pos>a.</pos> <def>firm</def></p>\n\n\
+                    <p><def>quick</def></p>\n\n<p><hw>Fast</hw>, <pos>v. i.</pos> <def>to abstain</def></p>\n</entry>";
+        let entry = EntryParser::new(text).next().unwrap().unwrap();
+        let rendered: Vec<_> = split_by_pos(entry).iter().map(|entry| CIDE(entry).to_string()).collect();
+        assert_eq!(rendered, [
+            "<entry main-word=\"Fast\" source=\"A\">\n<p><hw>Fast</hw>, <pos>a.</pos> <def>firm</def></p>\n\n\
+             <p><def>quick</def></p>\n</entry>",
+            "<entry main-word=\"Fast\" source=\"A\">\n<p><hw>Fast</hw>, <pos>v. i.</pos> <def>to abstain</def></p>\n</entry>",
+        ]);
+    }
+}
@@ -0,0 +1,82 @@
+//! Provenance of entries and paragraphs (`source="1913 Webster"`, ...).
+
+use parser::{Entry, EntryItem};
+
+/// All sources credited in an entry: its own, then those of its blocks, without
+/// duplicates; the same as `Entry::sources`.
+pub fn sources<'a>(entry: &Entry<'a>) -> Vec<&'a str> {
+    entry.sources()
+}
+
+/// Selects content by source. Combined sources such as `1913 Webster + PJC` are
+/// allowed if any part is included and no part is excluded.
+#[derive(Clone, Debug, Default)]
+pub struct SourceFilter {
+    /// If not empty, only these sources are kept.
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl SourceFilter {
+    pub fn allows(&self, source: &str) -> bool {
+        let mut parts = source.split('+').map(str::trim);
+        let included = self.include.is_empty() || parts.clone().any(|part| self.include.iter().any(|s| s == part));
+        included && !parts.any(|part| self.exclude.iter().any(|s| s == part))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+}
+
+/// Removes the blocks of `entry` whose source (their own, or else the entry's)
+/// is not allowed, clearing `Entry::raw`. Returns whether any block is left.
+pub fn retain_sources(entry: &mut Entry, filter: &SourceFilter) -> bool {
+    if filter.is_empty() {
+        return true;
+    }
+    entry.raw = None;
+    let entry_allowed = filter.allows(entry.source);
+    retain_blocks(&mut entry.items, entry_allowed, filter);
+    entry.items.iter().any(|item| match *item {
+        EntryItem::PlainText(text) => !text.trim().is_empty(),
+        EntryItem::Comment(_) | EntryItem::PageBreak(_) => false,
+        _ => true,
+    })
+}
+
+fn retain_blocks(items: &mut Vec<EntryItem>, parent_allowed: bool, filter: &SourceFilter) {
+    items.retain(|item| match *item {
+        EntryItem::Tagged { source: Some(source), .. } => filter.allows(source),
+        _ => parent_allowed,
+    });
+    for item in items.iter_mut() {
+        if let EntryItem::Tagged { ref mut items, .. } = *item {
+            retain_blocks(items, true, filter);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use exporter::CIDE;
+    use parser::EntryParser;
+
+    #[test]
+    fn filter_by_source() {
+        let text = "<entry main-word=\"A\" source=\"1913 Webster\">\n<p><hw>A</hw> <def>one</def></p>\n\n\
+                    <p source=\"WordNet 1.5\"><def>two</def></p>\n</entry>";
+        let mut entry = EntryParser::new(text).next().unwrap().unwrap();
+        assert_eq!(sources(&entry), ["1913 Webster", "WordNet 1.5"]);
+
+        let webster = SourceFilter { include: vec!["1913 Webster".into()], exclude: vec![] };
+        assert!(webster.allows("1913 Webster + PJC"));
+        assert!(retain_sources(&mut entry, &webster));
+        assert_eq!(CIDE(&entry).to_string(), "<entry main-word=\"A\" source=\"1913 Webster\">\n\
+                                              <p><hw>A</hw> <def>one</def></p>\n\n\n</entry>");
+
+        let wordnet = SourceFilter { include: vec![], exclude: vec!["1913 Webster".into()] };
+        assert!(!retain_sources(&mut entry, &wordnet));
+    }
+}
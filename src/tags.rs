@@ -0,0 +1,297 @@
+//! Registry of the GCIDE tags and the rules for nesting them.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use std::fmt;
+
+use parser::{Entry, EntryItem};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TagKind {
+    /// Paragraph-level blocks.
+    Block,
+    /// Tags that may span several blocks, and so appear unpaired.
+    Span,
+    /// Parts of the entry head: headwords, pronunciations, parts of speech, forms.
+    Head,
+    /// Definitions, quotations and other contents of senses.
+    Sense,
+    /// Names, references and other phrase-level markup.
+    Phrase,
+    /// Typographic markup.
+    Style,
+}
+
+#[derive(Debug)]
+pub struct TagSpec {
+    pub name: &'static str,
+    pub kind: TagKind,
+    /// Kinds of tags this tag may not appear in, at any depth.
+    pub not_inside: &'static [TagKind],
+    /// Whether the tag may appear inside another tag of the same name.
+    pub self_nesting: bool,
+    /// Tags this tag may hold although their `not_inside` forbids it, as a
+    /// plural holds its own headword and etymology.
+    pub children: &'static [&'static str],
+}
+
+use self::TagKind::*;
+
+const NOT_IN_CONTENT: &[TagKind] = &[Block, Head, Sense, Phrase, Style];
+
+const MORPH_PARTS: &[&str] = &["pos", "conjf", "adjf", "decf", "pluf", "singf", "plw", "singw", "wf", "pr", "ety"];
+
+macro_rules! tags {
+    ($($kind:ident $not_inside:expr, $self_nesting:expr, $children:expr => $($name:expr),+;)*) => {
+        &[$($(TagSpec {
+            name: $name,
+            kind: $kind,
+            not_inside: $not_inside,
+            self_nesting: $self_nesting,
+            children: $children,
+        },)+)*]
+    }
+}
+
+pub const TAGS: &[TagSpec] = tags! {
+    Span &[Head, Sense, Phrase, Style], true, &[] => "collapse", "cs", "note", "usage";
+    Head &[Head, Sense], false, &[] => "hw";
+    Head &[Head], false, &[] => "pr", "pos", "asp", "adjf", "conjf", "decf", "plw", "singw", "wf", "pluf", "singf",
+        "mord", "ety";
+    Head &[Head], false, &["hw", "pr", "asp", "altsp"] => "mhw";
+    Head &[Head], false, &["plw", "pr", "pos", "ety"] => "plu";
+    Head &[Head], false, &["singw", "pr", "pos", "ety"] => "sing";
+    Head &[Head], false, &["asp", "pr", "pos", "ety"] => "altsp";
+    Head &[Head], false, MORPH_PARTS => "wordforms", "amorph", "nmorph", "vmorph";
+    Head &[], true, &[] => "ets", "etsep";
+    Sense &[Head], false, &[] => "sn";
+    Sense &[Head], false, &[] => "def", "def2", "sd", "cd", "q", "syn", "as", "specif", "mcol", "col";
+    Sense &[Head], true, &[] => "mark", "fld";
+    Phrase &[], false, &[] => "er", "snr", "sdr", "cref", "au", "qau", "ecol", "stype", "altname", "book", "org", "city",
+        "country", "geog", "spn", "class", "fam", "gen", "ord", "ex", "qex", "xex", "sig", "oneof", "c", "plain",
+        "rj";
+    Style &[], true, &[] => "i", "it", "b", "sub", "sup", "ant", "pre";
+};
+
+/// The paragraph tags, which may only appear at the top level or inside `Span` tags.
+pub const BLOCK_TAGS: &[TagSpec] = &[
+    TagSpec { name: "p", kind: Block, not_inside: NOT_IN_CONTENT, self_nesting: false, children: &[] },
+    TagSpec { name: "extra", kind: Block, not_inside: NOT_IN_CONTENT, self_nesting: false, children: &[] },
+];
+
+pub fn lookup(name: &str) -> Option<&'static TagSpec> {
+    BLOCK_TAGS.iter().chain(TAGS).find(|spec| spec.name == name)
+}
+
+pub fn is_known_tag(name: &str) -> bool {
+    lookup(name).is_some()
+}
+
+macro_rules! tag_names {
+    ($($variant:ident $name:literal,)+) => {
+        /// The name of a tag, as parsed: one of the registry, or `Other` for
+        /// the rest. Build it with `TagName::new`, which never makes `Other`
+        /// of a registered name, so that comparing names compares variants.
+        #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum TagName<'a> {
+            $($variant,)+
+            Other(&'a str),
+        }
+
+        impl<'a> TagName<'a> {
+            pub fn new(name: &'a str) -> TagName<'a> {
+                TagName::known(name).unwrap_or(TagName::Other(name))
+            }
+
+            /// The variant for `name`, if it is in the registry.
+            pub fn known(name: &str) -> Option<TagName<'static>> {
+                match name {
+                    $($name => Some(TagName::$variant),)+
+                    _ => None,
+                }
+            }
+
+            pub fn as_str(&self) -> &'a str {
+                match *self {
+                    $(TagName::$variant => $name,)+
+                    TagName::Other(name) => name,
+                }
+            }
+        }
+    }
+}
+
+tag_names! {
+    P "p", Extra "extra",
+    Collapse "collapse", Cs "cs", Note "note", Usage "usage",
+    Hw "hw", Pr "pr", Pos "pos", Asp "asp", Adjf "adjf", Conjf "conjf", Decf "decf", Plw "plw", Singw "singw",
+    Wf "wf", Pluf "pluf", Singf "singf", Plu "plu", Sing "sing", Wordforms "wordforms", Amorph "amorph",
+    Nmorph "nmorph", Vmorph "vmorph", Altsp "altsp", Mhw "mhw", Mord "mord", Ety "ety", Ets "ets", Etsep "etsep",
+    Sn "sn", Def "def", Def2 "def2", Sd "sd", Cd "cd", Q "q", Syn "syn", As "as", Specif "specif", Mcol "mcol",
+    Col "col", Mark "mark", Fld "fld",
+    Er "er", Snr "snr", Sdr "sdr", Cref "cref", Au "au", Qau "qau", Ecol "ecol", Stype "stype", Altname "altname",
+    Book "book", Org "org", City "city", Country "country", Geog "geog", Spn "spn", Class "class", Fam "fam",
+    Gen "gen", Ord "ord", Ex "ex", Qex "qex", Xex "xex", Sig "sig", Oneof "oneof", C "c", Plain "plain", Rj "rj",
+    I "i", It "it", B "b", Sub "sub", Sup "sup", Ant "ant", Pre "pre",
+}
+
+impl<'a> TagName<'a> {
+    pub fn is_known(&self) -> bool {
+        !matches!(*self, TagName::Other(_))
+    }
+
+    /// The registry entry of this tag, if any.
+    pub fn spec(&self) -> Option<&'static TagSpec> {
+        match *self {
+            TagName::Other(_) => None,
+            name => lookup(name.as_str()),
+        }
+    }
+}
+
+impl<'a> fmt::Display for TagName<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Written as the name itself, so that dumps read as the source does.
+impl<'a> fmt::Debug for TagName<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<'a> PartialEq<str> for TagName<'a> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<'a, 'b> PartialEq<&'b str> for TagName<'a> {
+    fn eq(&self, other: &&'b str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'a> ::serde::Serialize for TagName<'a> {
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// A tag that appears inside a tag it may not be in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NestingViolation<'a> {
+    pub tag: &'a str,
+    /// The enclosing tag that makes the nesting illegal.
+    pub ancestor: &'a str,
+    /// How many `tag` tags come before this one in the entry.
+    pub occurrence: usize,
+}
+
+impl<'a> NestingViolation<'a> {
+    /// Byte offset of the offending open tag in `raw`, the source of the entry.
+    pub fn offset_in(&self, raw: &str) -> Option<usize> {
+        let open = format!("<{}", self.tag);
+        raw.match_indices(&open)
+            .filter(|&(idx, _)| raw[idx + open.len()..].starts_with(['>', ' ']))
+            .nth(self.occurrence)
+            .map(|(idx, _)| idx)
+    }
+}
+
+/// Illegal nestings of known tags in `entry`, in document order.
+pub fn validate_nesting<'a>(entry: &Entry<'a>) -> Vec<NestingViolation<'a>> {
+    let mut violations = Vec::new();
+    let mut counts = Vec::new();
+    check_nesting(&entry.items, &mut Vec::new(), &mut counts, &mut violations);
+    violations
+}
+
+fn check_nesting<'a>(items: &[EntryItem<'a>],
+                     ancestors: &mut Vec<TagName<'a>>,
+                     counts: &mut Vec<(TagName<'a>, usize)>,
+                     violations: &mut Vec<NestingViolation<'a>>) {
+    for item in items {
+        if let EntryItem::Tagged { name, ref items, .. } = *item {
+            let occurrence = match counts.iter_mut().find(|&&mut (n, _)| n == name) {
+                Some(&mut (_, ref mut count)) => {
+                    *count += 1;
+                    *count - 1
+                }
+                None => {
+                    counts.push((name, 1));
+                    0
+                }
+            };
+            if let Some(spec) = name.spec() {
+                let illegal = ancestors.iter().rev().find(|&&ancestor| {
+                    (ancestor == name && !spec.self_nesting)
+                        || ancestor.spec().is_some_and(|parent| {
+                            spec.not_inside.contains(&parent.kind) && !parent.children.contains(&spec.name)
+                        })
+                });
+                if let Some(&ancestor) = illegal {
+                    violations.push(NestingViolation { tag: name.as_str(), ancestor: ancestor.as_str(), occurrence });
+                }
+            }
+            ancestors.push(name);
+            check_nesting(items, ancestors, counts, violations);
+            ancestors.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::EntryParser;
+
+    #[test]
+    fn tag_names() {
+        for spec in BLOCK_TAGS.iter().chain(TAGS) {
+            let name = TagName::new(spec.name);
+            assert!(name != TagName::Other(spec.name), "{} is not interned", spec.name);
+            assert_eq!(name.as_str(), spec.name);
+            assert_eq!(name.spec().map(|spec| spec.name), Some(spec.name));
+        }
+        assert_eq!(TagName::new("ent"), TagName::Other("ent"));
+        assert_eq!(TagName::known("ent"), None);
+        assert_eq!(TagName::new("ent").spec().map(|spec| spec.kind), None);
+        assert!(TagName::Def == "def" && TagName::Other("ent") == "ent");
+        assert_eq!(format!("{} {:?}", TagName::Hw, TagName::Hw), "hw \"hw\"");
+    }
+
+    #[test]
+    fn illegal_nesting() {
+        let raw = "<entry main-word=\"A\" source=\"B\">\n<p><hw>A</hw> <def>one <hw>A</hw> <i><i>x</i></i></def></p>\n\
+                   <note><p><def>two</def></p></note></entry>";
+        let entry = EntryParser::new(raw).next().unwrap().unwrap();
+        let violations = validate_nesting(&entry);
+        assert_eq!(violations, [NestingViolation { tag: "hw", ancestor: "def", occurrence: 1 }]);
+        assert_eq!(violations[0].offset_in(raw), Some(56));
+        assert_eq!(&raw[56..60], "<hw>");
+
+        let raw = "<entry main-word=\"A\" source=\"B\"><p>a<p>b</p></p></entry>";
+        let nested = EntryParser::new(raw).next().unwrap().unwrap();
+        assert_eq!(validate_nesting(&nested), [NestingViolation { tag: "p", ancestor: "p", occurrence: 1 }]);
+    }
+
+    #[test]
+    fn head_containers() {
+        let raw = "<entry main-word=\"Abacus\" source=\"1913 Webster\">\n\
+                   <p><mhw>{<hw>Ab\"a*cus</hw>, <hw>Ab\"a*cist</hw>}</mhw> <pos>n.</pos>; \
+                   <plu><ety>L.</ety> <plw>Abaci</plw></plu> <wordforms>; <pos>pl.</pos> <wf>Abacuses</wf></wordforms> \
+                   <vmorph>[<pos>imp. & p. p.</pos> <conjf>Abacused</conjf>]</vmorph> \
+                   <altsp>or <asp>Abaque</asp></altsp></p>\n\
+                   <p><plu><plw>Abaci <mhw>x</mhw></plw></plu> <wordforms><hw>Abacus</hw></wordforms></p></entry>";
+        let entry = EntryParser::new(raw).next().unwrap().unwrap();
+        assert_eq!(validate_nesting(&entry), [
+            NestingViolation { tag: "mhw", ancestor: "plw", occurrence: 1 },
+            NestingViolation { tag: "hw", ancestor: "wordforms", occurrence: 2 },
+        ]);
+    }
+}
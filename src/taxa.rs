@@ -0,0 +1,116 @@
+//! Natural-history names (`<spn>`, `<gen>`, `<fam>`, ...) mentioned in entries.
+
+use model::plain_text;
+use parser::{Entry, EntryItem};
+use tags::TagName;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum Rank {
+    Kingdom,
+    Phylum,
+    Class,
+    Subclass,
+    Order,
+    Suborder,
+    Family,
+    Subfamily,
+    Tribe,
+    Genus,
+    Species,
+}
+
+impl Rank {
+    pub fn from_tag(name: &str) -> Option<Rank> {
+        Some(match name {
+            "kingdom" => Rank::Kingdom,
+            "phylum" => Rank::Phylum,
+            "class" => Rank::Class,
+            "subclass" => Rank::Subclass,
+            "ord" => Rank::Order,
+            "subord" | "suborder" => Rank::Suborder,
+            "fam" => Rank::Family,
+            "subfam" => Rank::Subfamily,
+            "tribe" => Rank::Tribe,
+            "gen" => Rank::Genus,
+            "spn" => Rank::Species,
+            _ => return None,
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            Rank::Kingdom => "kingdom",
+            Rank::Phylum => "phylum",
+            Rank::Class => "class",
+            Rank::Subclass => "subclass",
+            Rank::Order => "order",
+            Rank::Suborder => "suborder",
+            Rank::Family => "family",
+            Rank::Subfamily => "subfamily",
+            Rank::Tribe => "tribe",
+            Rank::Genus => "genus",
+            Rank::Species => "species",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct TaxonMention {
+    pub rank: Rank,
+    pub name: String,
+    /// Number of the sense the name appears in, if any.
+    pub sense: Option<String>,
+}
+
+/// Taxon names in an entry in order of appearance; repeated mentions within
+/// a sense are reported once.
+pub fn taxa(entry: &Entry) -> Vec<TaxonMention> {
+    let mut mentions = Vec::new();
+    let mut sense = None;
+    collect_taxa(&entry.items, &mut sense, &mut mentions);
+    mentions
+}
+
+fn collect_taxa(items: &[EntryItem], sense: &mut Option<String>, mentions: &mut Vec<TaxonMention>) {
+    for item in items {
+        if let EntryItem::Tagged { name, ref items, .. } = *item {
+            if name == TagName::Sn {
+                *sense = Some(plain_text(items).trim_end_matches('.').to_string());
+            } else if let Some(rank) = Rank::from_tag(name.as_str()) {
+                let name = plain_text(items).trim_end_matches(['.', ',']).to_string();
+                let mention = TaxonMention { rank, name, sense: sense.clone() };
+                if !mention.name.is_empty() && !mentions.contains(&mention) {
+                    mentions.push(mention);
+                }
+            } else {
+                collect_taxa(items, sense, mentions);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::EntryParser;
+
+    #[test]
+    fn species_and_family() {
+        let text = "<entry main-word=\"Aardvark\" source=\"1913 Webster\">\n<p><hw>Aard\"vark`</hw>, <pos>n.</pos> \
+                    <def>An edentate mammal (<spn>Orycteropus capensis</spn>), of the family \
+                    <fam>Orycteropodidae</fam>.</def></p>\n\n<p><sn>2.</sn> <def>Related to <spn>Orycteropus \
+                    capensis</spn> and <gen>Manis</gen>.</def></p>\n</entry>";
+        let entry = EntryParser::new(text).next().unwrap().unwrap();
+        let found: Vec<_> = taxa(&entry).into_iter()
+            .map(|t| (t.rank.as_str(), t.name, t.sense))
+            .collect();
+        assert_eq!(found, [
+            ("species", "Orycteropus capensis".to_string(), None),
+            ("family", "Orycteropodidae".to_string(), None),
+            ("species", "Orycteropus capensis".to_string(), Some("2".to_string())),
+            ("genus", "Manis".to_string(), Some("2".to_string())),
+        ]);
+    }
+}
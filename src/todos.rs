@@ -0,0 +1,196 @@
+//! Editorial work left in the sources: `<-- comments -->` and `<?/` markers
+//! for characters the transcribers could not identify.
+
+use std::collections::BTreeMap;
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+use parser::{Entry, EntryItem};
+
+/// Characters of text shown on each side of an unknown character.
+const CONTEXT_LEN: usize = 24;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TodoKind {
+    Comment,
+    UnknownEntity,
+}
+
+impl TodoKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            TodoKind::Comment => "comment",
+            TodoKind::UnknownEntity => "unknown-entity",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Todo {
+    pub kind: TodoKind,
+    /// The comment text, or the `context` of the unknown character.
+    pub text: String,
+}
+
+/// The comments and unknown characters in `entry`, in document order.
+pub fn entry_todos(entry: &Entry) -> Vec<Todo> {
+    let mut todos = Vec::new();
+    visit(&entry.items, &mut |items, idx| {
+        let todo = match items[idx] {
+            EntryItem::Comment(text) => Todo { kind: TodoKind::Comment, text: text.trim().to_string() },
+            _ => Todo { kind: TodoKind::UnknownEntity, text: UnknownChar::at(items, idx).context() },
+        };
+        todos.push(todo);
+    });
+    todos
+}
+
+/// An `<?/` marker and the plain text right before and after it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownChar<'a> {
+    pub before: &'a str,
+    pub after: &'a str,
+}
+
+impl<'a> UnknownChar<'a> {
+    fn at(items: &[EntryItem<'a>], idx: usize) -> UnknownChar<'a> {
+        UnknownChar { before: sibling_text(items[..idx].iter().rev()), after: sibling_text(items[idx + 1..].iter()) }
+    }
+
+    /// Up to `CONTEXT_LEN` characters on each side, with `<?/` between them,
+    /// e.g. `Apium grav<?/olens`. Used as the key of `Replacements`.
+    pub fn context(&self) -> String {
+        let skip = self.before.chars().count().saturating_sub(CONTEXT_LEN);
+        let before: String = self.before.chars().skip(skip).collect();
+        let after: String = self.after.chars().take(CONTEXT_LEN).collect();
+        format!("{}<?/{}", before.trim_start(), after.trim_end())
+    }
+}
+
+/// Every unknown character in `entry`, in document order.
+pub fn unknown_chars<'a>(entry: &Entry<'a>) -> Vec<UnknownChar<'a>> {
+    let mut found = Vec::new();
+    visit(&entry.items, &mut |items, idx| {
+        if items[idx] == EntryItem::EntityUnk {
+            found.push(UnknownChar::at(items, idx));
+        }
+    });
+    found
+}
+
+/// Calls `found` with the siblings and index of each comment and `<?/`.
+fn visit<'a, F: FnMut(&[EntryItem<'a>], usize)>(items: &[EntryItem<'a>], found: &mut F) {
+    for (idx, item) in items.iter().enumerate() {
+        match *item {
+            EntryItem::Comment(_) | EntryItem::EntityUnk => found(items, idx),
+            EntryItem::Tagged { ref items, .. } => visit(items, found),
+            _ => (),
+        }
+    }
+}
+
+/// The plain text next to an item, up to the nearest non-text sibling.
+fn sibling_text<'a, 'i, I: Iterator<Item = &'i EntryItem<'a>>>(mut items: I) -> &'a str where 'a: 'i {
+    match items.next() {
+        Some(&EntryItem::PlainText(text)) => text,
+        _ => "",
+    }
+}
+
+/// A table of what to put in place of unknown characters, keyed by the main
+/// word of their entry and their `UnknownChar::context`.
+#[derive(Debug, Default)]
+pub struct Replacements {
+    table: BTreeMap<(String, String), String>,
+}
+
+impl Replacements {
+    /// Reads lines of tab-separated main word, context and replacement, which
+    /// is CIDE source such as `e` or `<ae/`. A main word of `*` matches all
+    /// entries; blank lines and lines starting with `#` are skipped.
+    pub fn parse(text: &str) -> Result<Replacements, String> {
+        let mut table = BTreeMap::new();
+        for (idx, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').collect();
+            match fields[..] {
+                [word, context, replacement] if context.contains("<?/") => {
+                    table.insert((word.to_string(), context.to_string()), replacement.to_string());
+                }
+                _ => return Err(format!("line {}: expected MAIN-WORD<tab>CONTEXT<tab>REPLACEMENT", idx + 1)),
+            }
+        }
+        Ok(Replacements { table })
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    pub fn get(&self, main_word: &str, context: &str) -> Option<&str> {
+        let key = (main_word.to_string(), context.to_string());
+        self.table.get(&key)
+            .or_else(|| self.table.get(&("*".to_string(), key.1)))
+            .map(|s| &s[..])
+    }
+
+    /// Replaces the unknown characters of `entry` that are in the table and
+    /// returns how many were replaced, clearing `Entry::raw` if any were.
+    pub fn apply<'a>(&'a self, entry: &mut Entry<'a>) -> usize {
+        let main_word = entry.main_word;
+        let replaced = self.apply_items(main_word, &mut entry.items);
+        if replaced > 0 {
+            entry.raw = None;
+        }
+        replaced
+    }
+
+    fn apply_items<'a>(&'a self, main_word: &str, items: &mut [EntryItem<'a>]) -> usize {
+        let mut replaced = 0;
+        for idx in 0..items.len() {
+            if items[idx] == EntryItem::EntityUnk {
+                if let Some(replacement) = self.get(main_word, &UnknownChar::at(items, idx).context()) {
+                    items[idx] = EntryItem::PlainText(replacement);
+                    replaced += 1;
+                }
+            } else if let EntryItem::Tagged { ref mut items, .. } = items[idx] {
+                replaced += self.apply_items(main_word, items);
+            }
+        }
+        replaced
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use exporter::CIDE;
+    use parser::EntryParser;
+
+    #[test]
+    fn comments_and_unknowns() {
+        let text = "<entry main-word=\"Ach\" source=\"1913 Webster\"><p><hw>Ach</hw> <-- check etymology -->\
+                    <def>A plant (<spn>Apium grav<?/olens</spn>) of <?/gypt.</def></p></entry>";
+        let entry = EntryParser::new(text).next().unwrap().unwrap();
+        assert_eq!(entry_todos(&entry), [
+            Todo { kind: TodoKind::Comment, text: "check etymology".to_string() },
+            Todo { kind: TodoKind::UnknownEntity, text: "Apium grav<?/olens".to_string() },
+            Todo { kind: TodoKind::UnknownEntity, text: ") of <?/gypt.".to_string() },
+        ]);
+        assert_eq!(unknown_chars(&entry)[0], UnknownChar { before: "Apium grav", after: "olens" });
+
+        let replacements = Replacements::parse("# fixes\nAch\tApium grav<?/olens\te\n*\t) of <?/gypt.\tE\n").unwrap();
+        assert_eq!(replacements.len(), 2);
+        let mut entry = EntryParser::new(text).next().unwrap().unwrap();
+        entry.raw = Some(text);
+        assert_eq!(replacements.apply(&mut entry), 2);
+        assert!(CIDE(&entry).to_string().contains("Apium graveolens</spn>) of Egypt."));
+        assert!(Replacements::parse("Ach\tno marker\te").is_err());
+    }
+}
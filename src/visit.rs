@@ -0,0 +1,143 @@
+//! Recursive traversal of the item tree.
+//!
+//! Every method has a default that just keeps walking, so implementors only
+//! override the nodes they are interested in. An overriding method that still
+//! wants to descend calls the matching `walk_*` function.
+
+use parser::{Entry, EntryItem, GreekItem};
+
+pub trait Visit<'a> {
+    fn visit_entry(&mut self, entry: &Entry<'a>) {
+        walk_items(self, &entry.items);
+    }
+
+    fn visit_item(&mut self, item: &EntryItem<'a>) {
+        walk_item(self, item);
+    }
+
+    fn visit_tag(&mut self, _name: &'a str, items: &[EntryItem<'a>]) {
+        walk_items(self, items);
+    }
+
+    fn visit_text(&mut self, _text: &'a str) {}
+
+    fn visit_entity(&mut self, _name: &'a str) {}
+
+    fn visit_comment(&mut self, _text: &'a str) {}
+
+    fn visit_page_break(&mut self, _page: u32) {}
+
+    fn visit_greek(&mut self, items: &[GreekItem]) {
+        for item in items {
+            self.visit_greek_item(item);
+        }
+    }
+
+    fn visit_greek_item(&mut self, _item: &GreekItem) {}
+}
+
+pub fn walk_items<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, items: &[EntryItem<'a>]) {
+    for item in items {
+        visitor.visit_item(item);
+    }
+}
+
+pub fn walk_item<'a, V: Visit<'a> + ?Sized>(visitor: &mut V, item: &EntryItem<'a>) {
+    use parser::EntryItem::*;
+    match *item {
+        Tagged { name, ref items, .. } => visitor.visit_tag(name.as_str(), items),
+        Comment(text) => visitor.visit_comment(text),
+        PageBreak(page) => visitor.visit_page_break(page),
+        Entity(entity) => visitor.visit_entity(entity.as_str()),
+        ExternalLink(_, text) | PlainText(text) => visitor.visit_text(text),
+        Greek(ref items) => visitor.visit_greek(items),
+        EntityBr | EntityUnk | UnpairedTagOpen(..) | UnpairedTagClose(_) => (),
+    }
+}
+
+pub trait VisitMut<'a> {
+    /// Visits the items of `entry`, clearing `Entry::raw`.
+    fn visit_entry_mut(&mut self, entry: &mut Entry<'a>) {
+        entry.raw = None;
+        self.visit_items_mut(&mut entry.items);
+    }
+
+    /// Gets the whole list of siblings, so items can also be removed or inserted.
+    fn visit_items_mut(&mut self, items: &mut Vec<EntryItem<'a>>) {
+        walk_items_mut(self, items);
+    }
+
+    fn visit_item_mut(&mut self, item: &mut EntryItem<'a>) {
+        walk_item_mut(self, item);
+    }
+
+    fn visit_greek_item_mut(&mut self, _item: &mut GreekItem) {}
+}
+
+pub fn walk_items_mut<'a, V: VisitMut<'a> + ?Sized>(visitor: &mut V, items: &mut [EntryItem<'a>]) {
+    for item in items {
+        visitor.visit_item_mut(item);
+    }
+}
+
+pub fn walk_item_mut<'a, V: VisitMut<'a> + ?Sized>(visitor: &mut V, item: &mut EntryItem<'a>) {
+    match *item {
+        EntryItem::Tagged { ref mut items, .. } => visitor.visit_items_mut(items),
+        EntryItem::Greek(ref mut items) => {
+            for item in items {
+                visitor.visit_greek_item_mut(item);
+            }
+        }
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use exporter::CIDE;
+    use parser::EntryParser;
+    use tags::TagName;
+
+    const TEXT: &str = "<entry main-word=\"A\" source=\"B\"><p><hw>A</hw> <def>f<oe/tus <i>of <ae/</i></def> \
+                        <grk>a</grk></p></entry>";
+
+    #[test]
+    fn collect_entities() {
+        struct Entities<'a>(Vec<&'a str>);
+        impl<'a> Visit<'a> for Entities<'a> {
+            fn visit_entity(&mut self, name: &'a str) {
+                self.0.push(name);
+            }
+        }
+        let entry = EntryParser::new(TEXT).next().unwrap().unwrap();
+        let mut visitor = Entities(Vec::new());
+        visitor.visit_entry(&entry);
+        assert_eq!(visitor.0, ["oe", "ae"]);
+    }
+
+    #[test]
+    fn unwrap_italics() {
+        struct Unwrap;
+        impl<'a> VisitMut<'a> for Unwrap {
+            fn visit_items_mut(&mut self, items: &mut Vec<EntryItem<'a>>) {
+                walk_items_mut(self, items);
+                let old = ::std::mem::take(items);
+                for item in old {
+                    match item {
+                        EntryItem::Tagged { name: TagName::I, items: inner, .. } => items.extend(inner),
+                        item => items.push(item),
+                    }
+                }
+            }
+
+            fn visit_greek_item_mut(&mut self, item: &mut GreekItem) {
+                *item = GreekItem::Other('?');
+            }
+        }
+        let mut entry = EntryParser::new(TEXT).next().unwrap().unwrap();
+        Unwrap.visit_entry_mut(&mut entry);
+        assert_eq!(CIDE(&entry).to_string(), "<entry main-word=\"A\" source=\"B\"><p><hw>A</hw> \
+                   <def>f<oe/tus of <ae/</def> <grk>?</grk></p></entry>");
+    }
+}
@@ -0,0 +1,86 @@
+//! The format of the original `web1913` transcription of Webster's 1913
+//! dictionary, which GCIDE grew out of. It has no `<entry>` blocks: an entry
+//! is a run of paragraphs starting with one that opens with `<p><ent>WORD</ent>`,
+//! and pages of the printed edition are marked with comments like `<-- p. 1 -->`.
+
+use std::ops::Range;
+
+use parser::{Entry, EntryItem, ParserError};
+use tags::TagName;
+
+const ENTRY_START: &str = "<p><ent>";
+
+/// Iterates over the entries of a `web1913` file as GCIDE entries of source
+/// `1913 Webster`: the `<ent>` words heading the first paragraph become the
+/// main word, and are removed along with the line breaks after them. Errors are
+/// reported relative to the entry, as for `EntryItem::parse_items`.
+pub struct Web1913Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    last_span: Option<Range<usize>>,
+}
+
+impl<'a> Web1913Parser<'a> {
+    pub fn new(contents: &'a str) -> Web1913Parser<'a> {
+        Web1913Parser { input: contents, pos: 0, last_span: None }
+    }
+
+    /// Byte range of the entry last returned by `next`, without the whitespace
+    /// that follows it.
+    pub fn last_span(&self) -> Option<Range<usize>> {
+        self.last_span.clone()
+    }
+}
+
+/// The first `<ent>` word of an entry, as written in the source.
+pub fn main_word(block: &str) -> &str {
+    let heading = block.strip_prefix(ENTRY_START).unwrap_or("");
+    &heading[..heading.find("</ent>").unwrap_or(0)]
+}
+
+impl<'a> Iterator for Web1913Parser<'a> {
+    type Item = Result<Entry<'a>, ParserError<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.pos + self.input[self.pos..].find(ENTRY_START)?;
+        let end = self.input[start + 1..].find(ENTRY_START).map_or(self.input.len(), |i| start + 1 + i);
+        self.pos = end;
+        let block = self.input[start..end].trim_end();
+        self.last_span = Some(start..start + block.len());
+
+        let main_word = main_word(block);
+        Some(EntryItem::parse_items(block).map(|mut items| {
+            if let Some(&mut EntryItem::Tagged { name: TagName::P, ref mut items, .. }) = items.first_mut() {
+                let heading_len = items.iter().take_while(|item| match **item {
+                    EntryItem::Tagged { name: TagName::Other("ent"), .. } | EntryItem::EntityBr => true,
+                    EntryItem::PlainText(text) => text.trim().is_empty(),
+                    _ => false,
+                }).count();
+                items.drain(..heading_len);
+            }
+            Entry { main_word, items, source: "1913 Webster", raw: None, id: None }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use exporter::CIDE;
+
+    #[test]
+    fn entries_without_wrappers() {
+        let text = "<-- p. 1 -->\n\n<p><ent>Abacus</ent><br/\n<hw>Ab\"a*cus</hw>, <pos>n.</pos> <def>A table.</def></p>\n\n\
+                    <p><sn>2.</sn> <def>A frame.</def></p>\n\n\
+                    <p><ent>Abaft</ent><br/\n<hw>A*baft\"</hw>, <pos>adv.</pos> <def>Behind.</def> x > y</p>\n";
+        let mut parser = Web1913Parser::new(text);
+        let entry = parser.next().unwrap().unwrap();
+        assert_eq!(CIDE(&entry).to_string(),
+                   "<entry main-word=\"Abacus\" source=\"1913 Webster\"><p><hw>Ab\"a*cus</hw>, <pos>n.</pos> \
+                    <def>A table.</def></p>\n\n<p><sn>2.</sn> <def>A frame.</def></p></entry>");
+        assert_eq!(parser.last_span(), Some(14..135));
+        assert!(parser.next().unwrap().is_err());
+        assert_eq!(main_word(&text[parser.last_span().unwrap()]), "Abaft");
+        assert!(parser.next().is_none());
+    }
+}
@@ -0,0 +1,177 @@
+//! Linking senses to WordNet synsets, read from the `data.noun`, `data.verb`,
+//! `data.adj` and `data.adv` files of a WordNet database.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use model::{EntryModel, Sense};
+
+const DATA_FILES: &[&str] = &["data.noun", "data.verb", "data.adj", "data.adv"];
+
+const STEM_LEN: usize = 6;
+
+/// Words too common to tell glosses apart.
+const STOPWORDS: &[&str] = &["that", "this", "with", "from", "which", "other", "into", "used", "being", "having"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Synset {
+    /// Byte offset and part of speech, as WordNet writes them, e.g. `02084071-n`.
+    pub id: String,
+    /// `n`, `v`, `a`, `s` (adjective satellite) or `r` (adverb).
+    pub pos: char,
+    /// Lemmas with `_` for spaces, e.g. `abacus`, `calculating_board`.
+    pub words: Vec<String>,
+    pub gloss: String,
+}
+
+#[derive(Debug, Default)]
+pub struct WordNet {
+    synsets: Vec<Synset>,
+    by_lemma: HashMap<String, Vec<usize>>,
+}
+
+impl WordNet {
+    /// Reads the data files found in the WordNet `dict` directory `dir`.
+    pub fn load(dir: &Path) -> io::Result<WordNet> {
+        let mut wordnet = WordNet::default();
+        for name in DATA_FILES {
+            let path = dir.join(name);
+            if path.exists() {
+                let text = fs::read_to_string(&path)?;
+                wordnet.add_data(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            }
+        }
+        Ok(wordnet)
+    }
+
+    /// Adds the synsets of one data file. Lines starting with a space (the
+    /// license header) are skipped.
+    pub fn add_data(&mut self, text: &str) -> Result<(), String> {
+        for (idx, line) in text.lines().enumerate() {
+            if line.is_empty() || line.starts_with(' ') {
+                continue;
+            }
+            let synset = parse_synset(line).ok_or_else(|| format!("line {}: malformed synset", idx + 1))?;
+            for word in &synset.words {
+                self.by_lemma.entry(word.to_lowercase()).or_default().push(self.synsets.len());
+            }
+            self.synsets.push(synset);
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.synsets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.synsets.is_empty()
+    }
+
+    /// The synsets containing `word` (in any case, with spaces or `_`), in
+    /// the order of the data files.
+    pub fn synsets<'w>(&'w self, word: &str) -> impl Iterator<Item = &'w Synset> + 'w {
+        let key = word.trim().to_lowercase().replace(' ', "_");
+        self.by_lemma.get(&key).into_iter().flatten().map(move |&idx| &self.synsets[idx])
+    }
+
+    /// The synset of `word` whose gloss shares the most words with `sense`'s
+    /// definition, among those of the part of speech of the sense (if known).
+    /// `None` if no gloss shares any word.
+    pub fn best_match(&self, word: &str, sense: &Sense) -> Option<&Synset> {
+        let pos = sense.pos.as_ref().and_then(|pos| wordnet_pos(pos));
+        let definition = content_words(&sense.definition);
+        self.synsets(word)
+            .filter(|synset| pos.is_none_or(|pos| synset.pos == pos || (pos == 'a' && synset.pos == 's')))
+            .map(|synset| (content_words(&synset.gloss).iter().filter(|w| definition.contains(w)).count(), synset))
+            .filter(|&(score, _)| score > 0)
+            .fold(None, |best: Option<(usize, &Synset)>, (score, synset)| match best {
+                Some((best_score, _)) if best_score >= score => best,
+                _ => Some((score, synset)),
+            })
+            .map(|(_, synset)| synset)
+    }
+
+    /// Sets `Sense::synset` for the senses of `model` that match a synset of
+    /// its main word, and returns how many did.
+    pub fn annotate(&self, model: &mut EntryModel) -> usize {
+        let mut matched = 0;
+        for sense in &mut model.senses {
+            sense.synset = self.best_match(&model.main_word, sense).map(|synset| synset.id.clone());
+            matched += sense.synset.is_some() as usize;
+        }
+        matched
+    }
+}
+
+/// Parses `offset lex_filenum ss_type w_cnt word lex_id ... p_cnt ptrs... | gloss`.
+fn parse_synset(line: &str) -> Option<Synset> {
+    let (fields, gloss) = line.split_once(" | ").unwrap_or((line, ""));
+    let mut fields = fields.split(' ');
+    let offset = fields.next()?;
+    let pos = fields.nth(1)?.chars().next()?;
+    let count = usize::from_str_radix(fields.next()?, 16).ok()?;
+    let mut words = Vec::with_capacity(count);
+    for _ in 0..count {
+        let word = fields.next()?;
+        // adjective markers such as `(p)`
+        words.push(word.split('(').next().unwrap_or(word).to_string());
+        fields.next()?;
+    }
+    let id_pos = if pos == 's' { 'a' } else { pos };
+    Some(Synset { id: format!("{}-{}", offset, id_pos), pos, words, gloss: gloss.trim().to_string() })
+}
+
+/// The WordNet part of speech of a GCIDE `<pos>`.
+fn wordnet_pos(pos: &str) -> Option<char> {
+    match pos.split_whitespace().next()? {
+        "n." => Some('n'),
+        "v." => Some('v'),
+        "a." => Some('a'),
+        "adv." => Some('r'),
+        _ => None,
+    }
+}
+
+/// The longer words of `text`, cut to `STEM_LEN` characters so that e.g.
+/// `calculating` and `calculator` compare equal.
+fn content_words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphabetic())
+        .filter(|word| word.len() > 3)
+        .map(str::to_lowercase)
+        .filter(|word| !STOPWORDS.contains(&&word[..]))
+        .map(|word| word.chars().take(STEM_LEN).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use model::entry_model;
+    use parser::EntryParser;
+
+    const DATA_NOUN: &str = "  1 This software and database is being provided to you, the LICENSEE, by\n\
+        02666196 06 n 02 abacus 0 calculating_board 0 001 @ 03082979 n 0000 | a calculator that performs \
+        arithmetic functions by manually sliding counters on rods or in grooves\n\
+        02666501 06 n 01 abacus 1 001 @ 02930645 n 0000 | (architecture) the tablet on the top of a capital\n";
+
+    #[test]
+    fn senses_to_synsets() {
+        let mut wordnet = WordNet::default();
+        wordnet.add_data(DATA_NOUN).unwrap();
+        assert_eq!(wordnet.len(), 2);
+        assert_eq!(wordnet.synsets("Calculating board").next().unwrap().id, "02666196-n");
+
+        let text = "<entry main-word=\"Abacus\" source=\"1913 Webster\"><p><hw>Ab\"a*cus</hw>, <pos>n.</pos> \
+                    <sn>1.</sn> <def>A table strewn with sand.</def></p>\n<p><sn>2.</sn> <def>A frame for performing \
+                    arithmetical calculations by sliding counters.</def></p>\n<p><sn>3.</sn> <fld>(Arch.)</fld> \
+                    <def>The uppermost member of the capital of a column.</def></p></entry>";
+        let mut model = entry_model(&EntryParser::new(text).next().unwrap().unwrap());
+        assert_eq!(wordnet.annotate(&mut model), 2);
+        let synsets: Vec<_> = model.senses.iter().map(|sense| sense.synset.as_ref().map(|id| &id[..])).collect();
+        assert_eq!(synsets, [None, Some("02666196-n"), Some("02666501-n")]);
+        assert!(WordNet::default().add_data("02666196 06 n 02 abacus").is_err());
+    }
+}